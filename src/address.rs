@@ -4,12 +4,15 @@ use crate::utils::hex_str_to_bytes;
 use crate::utils::ArrayString;
 use bech32::{self, FromBase32};
 use bech32::{ToBase32, Variant};
+use sha2::{Digest, Sha256};
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::str::FromStr;
 
-/// An address that's derived from a given PublicKey
+/// An address that's derived from a given PublicKey. Serializes the prefix as a string, up to
+/// 2.4.7 the prefix was serialized as a struct of it's characters, that form is still accepted
+/// when deserializing but older versions can't read the string form
 #[derive(PartialEq, Eq, Copy, Clone, Hash, Deserialize, Serialize)]
 pub struct Address {
     bytes: [u8; 20],
@@ -36,10 +39,25 @@ impl Address {
     ) -> Result<Address, AddressError> {
         Ok(Address {
             bytes,
-            prefix: ArrayString::new(&prefix.into())?,
+            prefix: bech32_prefix(&prefix.into())?,
         })
     }
 
+    /// Derives a validator consensus address from the validators ed25519 consensus pubkey,
+    /// as used by the slashing and evidence modules. This is the first 20 bytes of the sha256
+    /// hash of the raw 32 byte pubkey (the `key` in the `consensus_pubkey` field of a validator).
+    /// The prefix is conventionally `<chain_prefix>valcons` for example `cosmosvalcons`
+    pub fn from_consensus_pubkey<T: Into<String>>(
+        pubkey: &[u8],
+        prefix: T,
+    ) -> Result<Address, AddressError> {
+        if pubkey.len() != 32 {
+            return Err(AddressError::BytesDecodeErrorWrongLength);
+        }
+        let hash = Sha256::digest(pubkey);
+        Address::from_slice(&hash[0..20], prefix)
+    }
+
     /// Returns bytes of a given Address  as a slice of bytes
     pub fn as_bytes(&self) -> &[u8] {
         &self.bytes
@@ -54,10 +72,16 @@ impl Address {
     }
 
     pub fn change_prefix<T: Into<String>>(&mut self, prefix: T) -> Result<(), AddressError> {
-        self.prefix = ArrayString::new(&prefix.into())?;
+        self.prefix = bech32_prefix(&prefix.into())?;
         Ok(())
     }
 
+    /// Returns the validator operator form of this account address, the same bytes with the
+    /// `<base_prefix>valoper` prefix, for example `cosmosvaloper` for a `cosmos` account
+    pub fn to_valoper(&self, base_prefix: &str) -> Result<Address, AddressError> {
+        Address::from_bytes(self.bytes, format!("{}valoper", base_prefix))
+    }
+
     /// Obtain a bech32 encoded address with a given prefix.
     ///
     /// * `hrp` - A prefix for bech32 encoding. The convention for addresses
@@ -74,6 +98,7 @@ impl Address {
     pub fn from_bech32(s: String) -> Result<Address, AddressError> {
         let (hrp, data, _) = match bech32::decode(&s) {
             Ok(val) => val,
+            Err(bech32::Error::MixedCase) => return Err(AddressError::PrefixMixedCase),
             Err(_e) => return Err(AddressError::Bech32InvalidEncoding),
         };
         let vec: Vec<u8> = match FromBase32::from_base32(&data) {
@@ -87,6 +112,37 @@ impl Address {
         addr.copy_from_slice(&vec);
         Address::from_bytes(addr, &hrp)
     }
+
+    /// Parses a bech32 encoded address of any chain, returning the address along with the
+    /// prefix it was encoded with, which is also kept as the prefix of the address
+    pub fn from_bech32_any(s: &str) -> Result<(Address, String), AddressError> {
+        let address = Address::from_bech32(s.to_string())?;
+        Ok((address, address.get_prefix()))
+    }
+
+    /// Parses a bech32 encoded address, failing with `PrefixMismatch` if it's prefix is not
+    /// `expected_prefix`. Useful to catch addresses of the wrong chain in multi chain code
+    pub fn from_bech32_checked(s: &str, expected_prefix: &str) -> Result<Address, AddressError> {
+        let (address, prefix) = Address::from_bech32_any(s)?;
+        if !prefix.eq_ignore_ascii_case(expected_prefix) {
+            return Err(AddressError::PrefixMismatch {
+                expected: expected_prefix.to_string(),
+                found: prefix,
+            });
+        }
+        Ok(address)
+    }
+}
+
+/// Bech32 is case insensitive but may not mix cases, prefixes are stored in lowercase so that
+/// an all uppercase prefix is the same address, it's also encoded in lowercase
+fn bech32_prefix(prefix: &str) -> Result<ArrayString, AddressError> {
+    let upper = prefix.chars().any(|c| c.is_ascii_uppercase());
+    let lower = prefix.chars().any(|c| c.is_ascii_lowercase());
+    if upper && lower {
+        return Err(AddressError::PrefixMixedCase);
+    }
+    Ok(ArrayString::new(&prefix.to_ascii_lowercase())?)
 }
 
 impl FromStr for Address {
@@ -142,6 +198,93 @@ fn test_bech32() {
         .expect("Failed to decode");
 }
 
+#[test]
+fn test_long_prefix() {
+    // consensus addresses of chains with long prefixes are still within the 90 character
+    // total allowed by BIP-173
+    let prefix = "averyveryverylongcustomchainprefixthatisvalcons";
+    let address = Address::from_consensus_pubkey(&[1u8; 32], prefix).unwrap();
+    let encoded = address.to_string();
+    assert_eq!(encoded.len(), 86);
+    assert_eq!(Address::from_bech32(encoded).unwrap(), address);
+    assert!(Address::from_bytes([0; 20], "x".repeat(84)).is_err());
+    let json = serde_json::to_string(&address).unwrap();
+    assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), address);
+}
+
+#[test]
+fn test_uppercase_prefix() {
+    // all uppercase is valid and decodes to the same address
+    let encoded = "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a";
+    let address = Address::from_bech32(encoded.to_string()).unwrap();
+    let upper = Address::from_bech32(encoded.to_uppercase()).unwrap();
+    assert_eq!(upper, address);
+    assert_eq!(upper.get_prefix(), "cosmos");
+
+    // an uppercase prefix is stored in lowercase so it's the same address
+    let upper = Address::from_bytes([0; 20], "COSMOS").unwrap();
+    assert_eq!(upper.get_prefix(), "cosmos");
+    assert_eq!(upper, address);
+    assert_eq!(upper.to_string(), encoded);
+
+    // mixed case is not valid
+    assert!(matches!(
+        Address::from_bech32("Cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a".to_string()),
+        Err(AddressError::PrefixMixedCase)
+    ));
+    assert!(matches!(
+        Address::from_bytes([0; 20], "Cosmos"),
+        Err(AddressError::PrefixMixedCase)
+    ));
+}
+
+#[test]
+fn test_address_serde() {
+    let address = Address::from_bytes([1; 20], "cosmos").unwrap();
+    let json = serde_json::to_value(address).unwrap();
+    assert_eq!(json["prefix"], "cosmos");
+    assert_eq!(serde_json::from_value::<Address>(json).unwrap(), address);
+
+    // the form serialized by earlier versions
+    let mut chars = vec![serde_json::Value::Null; 32];
+    for (i, c) in "cosmos".chars().enumerate() {
+        chars[i] = c.to_string().into();
+    }
+    let legacy = serde_json::json!({
+        "bytes": vec![1; 20],
+        "prefix": {"chars": chars, "used": 6},
+    });
+    assert_eq!(serde_json::from_value::<Address>(legacy).unwrap(), address);
+
+    // the prefix is stored inline but compactly
+    assert!(std::mem::size_of::<Address>() <= 104);
+}
+
+#[test]
+fn test_bech32_prefix_inference() {
+    let encoded = "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp";
+    let (address, prefix) = Address::from_bech32_any(encoded).unwrap();
+    assert_eq!(prefix, "cosmos");
+    assert_eq!(address.to_string(), encoded);
+
+    assert_eq!(
+        Address::from_bech32_checked(encoded, "cosmos").unwrap(),
+        address
+    );
+    match Address::from_bech32_checked(encoded, "osmo") {
+        Err(AddressError::PrefixMismatch { expected, found }) => {
+            assert_eq!(expected, "osmo");
+            assert_eq!(found, "cosmos");
+        }
+        _ => panic!("Expected prefix mismatch"),
+    }
+    // the same bytes with another prefix is still a mismatch
+    let mut osmo = address;
+    osmo.change_prefix("osmo").unwrap();
+    assert!(Address::from_bech32_checked(&osmo.to_string(), "cosmos").is_err());
+    assert!(Address::from_bech32_any("not an address").is_err());
+}
+
 #[test]
 fn test_default_prefix() {
     Address::from_bytes([0; 20], Address::DEFAULT_PREFIX).unwrap();
@@ -160,3 +303,25 @@ fn test_parse() {
         .parse()
         .unwrap();
 }
+
+#[test]
+fn test_consensus_address() {
+    let pubkey = [1u8; 32];
+    let address = Address::from_consensus_pubkey(&pubkey, "cosmosvalcons").unwrap();
+    assert_eq!(address.as_bytes(), &Sha256::digest(&pubkey)[0..20]);
+    assert!(address.to_string().starts_with("cosmosvalcons1"));
+    assert!(Address::from_consensus_pubkey(&[1u8; 33], "cosmosvalcons").is_err());
+}
+
+#[test]
+fn test_to_valoper() {
+    let account: Address = "cosmos1t0sgxmpxafdfjd3k6kgg50kdgn4muh5t0phml6"
+        .parse()
+        .unwrap();
+    let valoper = account.to_valoper("cosmos").unwrap();
+    assert_eq!(
+        valoper.to_string(),
+        "cosmosvaloper1t0sgxmpxafdfjd3k6kgg50kdgn4muh5t24rwnf"
+    );
+    assert_eq!(valoper.as_bytes(), account.as_bytes());
+}
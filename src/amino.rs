@@ -0,0 +1,265 @@
+//! Legacy Amino JSON encoding, this is what is signed when using `SIGN_MODE_LEGACY_AMINO_JSON`
+//! which is still required by hardware wallets and some chains. Every message needs a hand written
+//! Amino JSON representation so only the common Cosmos SDK messages are supported.
+
+use crate::error::PrivateKeyError;
+use crate::private_key::MessageArgs;
+use crate::Msg;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::{MsgMultiSend, MsgSend};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
+    MsgSetWithdrawAddress, MsgWithdrawDelegatorReward,
+};
+use cosmos_sdk_proto::cosmos::gov::v1beta1::{MsgDeposit, MsgVote};
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgBeginRedelegate, MsgDelegate, MsgUndelegate};
+use prost::Message;
+use serde_json::{json, Map, Value};
+
+/// Encodes a message into it's Amino JSON form `{"type": "cosmos-sdk/MsgSend", "value": {...}}`
+/// returns an error if the message type is not supported or can not be decoded
+pub fn msg_to_amino_json(msg: &Msg) -> Result<Value, PrivateKeyError> {
+    let any = &msg.0;
+    let (amino_type, value) = match any.type_url.as_str() {
+        "/cosmos.bank.v1beta1.MsgSend" => {
+            let msg: MsgSend = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgSend",
+                json!({
+                    "amount": coins_json(&msg.amount),
+                    "from_address": msg.from_address,
+                    "to_address": msg.to_address,
+                }),
+            )
+        }
+        "/cosmos.bank.v1beta1.MsgMultiSend" => {
+            let msg: MsgMultiSend = decode(&any.value)?;
+            let inputs: Vec<Value> = msg
+                .inputs
+                .iter()
+                .map(|i| json!({"address": i.address, "coins": coins_json(&i.coins)}))
+                .collect();
+            let outputs: Vec<Value> = msg
+                .outputs
+                .iter()
+                .map(|o| json!({"address": o.address, "coins": coins_json(&o.coins)}))
+                .collect();
+            (
+                "cosmos-sdk/MsgMultiSend",
+                json!({"inputs": inputs, "outputs": outputs}),
+            )
+        }
+        "/cosmos.staking.v1beta1.MsgDelegate" => {
+            let msg: MsgDelegate = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgDelegate",
+                json!({
+                    "amount": coin_json(&msg.amount),
+                    "delegator_address": msg.delegator_address,
+                    "validator_address": msg.validator_address,
+                }),
+            )
+        }
+        "/cosmos.staking.v1beta1.MsgUndelegate" => {
+            let msg: MsgUndelegate = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgUndelegate",
+                json!({
+                    "amount": coin_json(&msg.amount),
+                    "delegator_address": msg.delegator_address,
+                    "validator_address": msg.validator_address,
+                }),
+            )
+        }
+        "/cosmos.staking.v1beta1.MsgBeginRedelegate" => {
+            let msg: MsgBeginRedelegate = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgBeginRedelegate",
+                json!({
+                    "amount": coin_json(&msg.amount),
+                    "delegator_address": msg.delegator_address,
+                    "validator_dst_address": msg.validator_dst_address,
+                    "validator_src_address": msg.validator_src_address,
+                }),
+            )
+        }
+        "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward" => {
+            let msg: MsgWithdrawDelegatorReward = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgWithdrawDelegationReward",
+                json!({
+                    "delegator_address": msg.delegator_address,
+                    "validator_address": msg.validator_address,
+                }),
+            )
+        }
+        "/cosmos.distribution.v1beta1.MsgSetWithdrawAddress" => {
+            let msg: MsgSetWithdrawAddress = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgModifyWithdrawAddress",
+                json!({
+                    "delegator_address": msg.delegator_address,
+                    "withdraw_address": msg.withdraw_address,
+                }),
+            )
+        }
+        "/cosmos.gov.v1beta1.MsgVote" => {
+            let msg: MsgVote = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgVote",
+                json!({
+                    "option": msg.option,
+                    "proposal_id": msg.proposal_id.to_string(),
+                    "voter": msg.voter,
+                }),
+            )
+        }
+        "/cosmos.gov.v1beta1.MsgDeposit" => {
+            let msg: MsgDeposit = decode(&any.value)?;
+            (
+                "cosmos-sdk/MsgDeposit",
+                json!({
+                    "amount": coins_json(&msg.amount),
+                    "depositor": msg.depositor,
+                    "proposal_id": msg.proposal_id.to_string(),
+                }),
+            )
+        }
+        other => {
+            return Err(PrivateKeyError::AminoJsonError(format!(
+                "No Amino JSON representation for {}",
+                other
+            )))
+        }
+    };
+    Ok(json!({"type": amino_type, "value": value}))
+}
+
+/// Produces the canonical Amino JSON `StdSignDoc` for a transaction, this is the exact
+/// string that is hashed and signed in `SIGN_MODE_LEGACY_AMINO_JSON`. Keys are sorted, there
+/// is no whitespace, 64 bit integers are strings and the characters escaped by Go's json
+/// encoder are escaped the same way, matching `sdk.MustSortJSON` in the Cosmos SDK.
+pub fn std_sign_doc(
+    messages: &[Msg],
+    args: &MessageArgs,
+    memo: &str,
+) -> Result<String, PrivateKeyError> {
+    if args.fee.payer.is_some() || args.fee.granter.is_some() {
+        return Err(PrivateKeyError::AminoJsonError(
+            "Amino JSON signing does not support fee payers or granters".to_string(),
+        ));
+    }
+    let mut msgs = Vec::new();
+    for msg in messages {
+        msgs.push(msg_to_amino_json(msg)?);
+    }
+    let fee_coins: Vec<ProtoCoin> = args.fee.amount.iter().cloned().map(|c| c.into()).collect();
+    let mut doc = json!({
+        "account_number": args.account_number.to_string(),
+        "chain_id": args.chain_id,
+        "fee": {
+            "amount": coins_json(&fee_coins),
+            "gas": args.fee.gas_limit.to_string(),
+        },
+        "memo": memo,
+        "msgs": msgs,
+        "sequence": args.sequence.to_string(),
+    });
+    // timeout_height is tagged omitempty
+    if args.timeout_height != 0 {
+        doc["timeout_height"] = Value::String(args.timeout_height.to_string());
+    }
+    let sorted = serde_json::to_string(&sort_json(doc)).unwrap();
+    Ok(escape_like_go(sorted))
+}
+
+fn decode<T: Message + Default>(bytes: &[u8]) -> Result<T, PrivateKeyError> {
+    T::decode(bytes).map_err(|e| PrivateKeyError::AminoJsonError(e.to_string()))
+}
+
+fn coin_json(coin: &Option<ProtoCoin>) -> Value {
+    match coin {
+        Some(c) => json!({"amount": c.amount, "denom": c.denom}),
+        None => json!({"amount": "0", "denom": ""}),
+    }
+}
+
+fn coins_json(coins: &[ProtoCoin]) -> Value {
+    Value::Array(
+        coins
+            .iter()
+            .map(|c| json!({"amount": c.amount, "denom": c.denom}))
+            .collect(),
+    )
+}
+
+/// serde_json only sorts object keys when it's `preserve_order` feature is disabled, which another
+/// crate in the dependency tree may enable, so we sort explicitly
+fn sort_json(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut sorted = Map::new();
+            for (k, v) in entries {
+                sorted.insert(k, sort_json(v));
+            }
+            Value::Object(sorted)
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_json).collect()),
+        other => other,
+    }
+}
+
+/// Go's json encoder escapes these characters inside strings, they can not appear
+/// anywhere else in valid json so a plain replace is safe
+fn escape_like_go(input: String) -> String {
+    input
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coin, Fee};
+
+    #[test]
+    fn test_std_sign_doc() {
+        let send = MsgSend {
+            amount: vec![ProtoCoin {
+                denom: "uatom".to_string(),
+                amount: "1000".to_string(),
+            }],
+            from_address: "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a".to_string(),
+            to_address: "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp".to_string(),
+        };
+        let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+        let args = MessageArgs {
+            sequence: 3,
+            fee: Fee {
+                amount: vec![Coin {
+                    denom: "uatom".into(),
+                    amount: 500u64.into(),
+                }],
+                gas_limit: 200_000,
+                payer: None,
+                granter: None,
+            },
+            timeout_height: 0,
+            chain_id: "cosmoshub-4".to_string(),
+            account_number: 12,
+        };
+        let doc = std_sign_doc(&[msg], &args, "a < b & c").unwrap();
+        assert_eq!(
+            doc,
+            concat!(
+                r#"{"account_number":"12","chain_id":"cosmoshub-4","fee":{"amount":[{"amount":"500","denom":"uatom"}],"gas":"200000"},"#,
+                r#""memo":"a \u003c b \u0026 c","msgs":[{"type":"cosmos-sdk/MsgSend","value":{"amount":[{"amount":"1000","denom":"uatom"}],"#,
+                r#""from_address":"cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a","to_address":"cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp"}}],"sequence":"3"}"#
+            )
+        );
+    }
+}
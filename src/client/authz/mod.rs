@@ -0,0 +1,553 @@
+//! Contains utility functions for interacting with the Cosmos sdk authz module, which allows
+//! a granter account to authorize a grantee to execute messages on its behalf. The protobuf
+//! types required are not part of the cosmos-sdk-proto version used by this crate so they
+//! are defined here.
+
+use crate::client::rpc::unary_call;
+use crate::coin::Coin;
+use crate::error::CosmosGrpcError;
+use crate::utils::{decode_any, next_page};
+use crate::Address;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use num256::Uint256;
+use prost::Message;
+use prost_types::{Any, Timestamp};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::SystemTime;
+use tonic::Code as TonicCode;
+
+/// The type url of `SendAuthorization`
+pub const SEND_AUTHORIZATION_TYPE_URL: &str = "/cosmos.bank.v1beta1.SendAuthorization";
+/// The type url of `GenericAuthorization`
+pub const GENERIC_AUTHORIZATION_TYPE_URL: &str = "/cosmos.authz.v1beta1.GenericAuthorization";
+/// The type url of `MsgSend`
+pub const MSG_SEND_TYPE_URL: &str = "/cosmos.bank.v1beta1.MsgSend";
+
+/// The type url of `MsgExec`
+pub const MSG_EXEC_TYPE_URL: &str = "/cosmos.authz.v1beta1.MsgExec";
+
+/// MsgExec attempts to execute the provided messages using authorizations granted to the
+/// grantee. Each message should have only one signer corresponding to the granter of the
+/// authorization.
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgExec {
+    #[prost(string, tag = "1")]
+    pub grantee: String,
+    #[prost(message, repeated, tag = "2")]
+    pub msgs: Vec<Any>,
+}
+
+/// The type url of `MsgExecResponse`
+pub const MSG_EXEC_RESPONSE_TYPE_URL: &str = "/cosmos.authz.v1beta1.MsgExecResponse";
+
+/// MsgExecResponse holds the result of each message executed by a MsgExec, in the order of
+/// the messages. Each result is the encoded `Msg*Response` of the message without it's type
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgExecResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub results: Vec<Vec<u8>>,
+}
+
+/// The result of one message executed by a MsgExec, see `decode_exec_results`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecResult {
+    /// The executed message
+    pub msg: Any,
+    /// The response of the message, the type url is the type url of the message with
+    /// `Response` appended since it's not part of the encoded result
+    pub response: Any,
+    /// The results of the messages executed by `msg` if it's a MsgExec itself, in order
+    pub inner: Vec<ExecResult>,
+}
+
+/// Unwraps the response of a MsgExec into the result of each message it executed, nested
+/// MsgExec messages are unwrapped recursively. `response` is the entry of the MsgExec in
+/// the `msg_responses` of a transaction, either a `MsgExecResponse` (v0.46 and later) or
+/// the result of the message (before v0.46). Inner messages that fail fail the whole
+/// transaction, so every message of a MsgExec in a successful transaction has a result
+#[allow(clippy::result_large_err)]
+pub fn decode_exec_results(
+    exec: &MsgExec,
+    response: &Any,
+) -> Result<Vec<ExecResult>, CosmosGrpcError> {
+    let response: MsgExecResponse = decode_any(response)?;
+    decode_results(exec, response.results)
+}
+
+#[allow(clippy::result_large_err)]
+fn decode_results(
+    exec: &MsgExec,
+    results: Vec<Vec<u8>>,
+) -> Result<Vec<ExecResult>, CosmosGrpcError> {
+    if results.len() != exec.msgs.len() {
+        return Err(CosmosGrpcError::BadResponse(format!(
+            "MsgExec of {} messages has {} results",
+            exec.msgs.len(),
+            results.len()
+        )));
+    }
+    let mut decoded = Vec::new();
+    for (msg, result) in exec.msgs.iter().zip(results) {
+        let inner = if msg.type_url == MSG_EXEC_TYPE_URL {
+            let exec: MsgExec = decode_any(msg)?;
+            let response = MsgExecResponse::decode(result.as_slice())?;
+            decode_results(&exec, response.results)?
+        } else {
+            Vec::new()
+        };
+        decoded.push(ExecResult {
+            msg: msg.clone(),
+            response: Any {
+                type_url: format!("{}Response", msg.type_url),
+                value: result,
+            },
+            inner,
+        });
+    }
+    Ok(decoded)
+}
+
+/// Grant gives permissions to execute the provide method with expiration time.
+#[derive(Clone, PartialEq, Message)]
+pub struct Grant {
+    #[prost(message, optional, tag = "1")]
+    pub authorization: Option<Any>,
+    #[prost(message, optional, tag = "2")]
+    pub expiration: Option<Timestamp>,
+}
+
+/// QueryGrantsRequest is the request type for the Query/Grants RPC method.
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryGrantsRequest {
+    #[prost(string, tag = "1")]
+    pub granter: String,
+    #[prost(string, tag = "2")]
+    pub grantee: String,
+    /// Optional, msg_type_url, when set, will query only grants matching given msg type.
+    #[prost(string, tag = "3")]
+    pub msg_type_url: String,
+    /// pagination defines an pagination for the request.
+    #[prost(message, optional, tag = "4")]
+    pub pagination: Option<PageRequest>,
+}
+
+/// QueryGrantsResponse is the response type for the Query/Authorizations RPC method.
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryGrantsResponse {
+    /// authorizations is a list of grants granted for grantee by granter.
+    #[prost(message, repeated, tag = "1")]
+    pub grants: Vec<Grant>,
+    /// pagination defines an pagination for the response.
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
+
+/// SendAuthorization allows the grantee to spend up to spend_limit coins from
+/// the granter's account.
+#[derive(Clone, PartialEq, Message)]
+pub struct SendAuthorization {
+    #[prost(message, repeated, tag = "1")]
+    pub spend_limit: Vec<ProtoCoin>,
+    /// allow_list specifies an optional list of addresses to whom the grantee can send tokens
+    /// on behalf of the granter. If omitted, any recipient is allowed.
+    #[prost(string, repeated, tag = "2")]
+    pub allow_list: Vec<String>,
+}
+
+/// GenericAuthorization gives the grantee unrestricted permissions to execute
+/// the provided method on behalf of the granter's account.
+#[derive(Clone, PartialEq, Message)]
+pub struct GenericAuthorization {
+    /// Msg, identified by it's type URL, to grant unrestricted permissions to execute
+    #[prost(string, tag = "1")]
+    pub msg: String,
+}
+
+/// A decoded authorization, authorizations other than send and generic authorizations
+/// are left encoded
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authorization {
+    /// The grantee may send up to `spend_limit` in total, only to the addresses in
+    /// `allow_list` if it is not empty. The allow list is only supported since Cosmos
+    /// SDK v0.47 and is always empty on older chains
+    Send {
+        spend_limit: Vec<Coin>,
+        allow_list: Vec<String>,
+    },
+    /// The grantee may execute any message of type `msg`
+    Generic {
+        msg: String,
+    },
+    Other(Any),
+}
+
+impl Authorization {
+    /// Checks if a MsgSend executed by the grantee would be accepted under this
+    /// authorization, mirroring the checks made by the authz module so that a
+    /// transaction that would be rejected is not broadcast. Note that the message
+    /// must also come from the granter, which is checked by `AuthzGrant::check_send`
+    pub fn check_send(&self, msg: &MsgSend) -> Result<(), AuthzRejection> {
+        match self {
+            Authorization::Send {
+                spend_limit,
+                allow_list,
+            } => {
+                if !allow_list.is_empty() && !allow_list.contains(&msg.to_address) {
+                    return Err(AuthzRejection::NotInAllowList(msg.to_address.clone()));
+                }
+                let mut requested: BTreeMap<&str, Uint256> = BTreeMap::new();
+                for coin in msg.amount.iter() {
+                    let amount = Coin::try_from(coin)
+                        .map_err(|e| AuthzRejection::InvalidAmount(e.to_string()))?
+                        .amount;
+                    let total = requested.entry(&coin.denom).or_default();
+                    *total = total.clone() + amount;
+                }
+                for (denom, requested) in requested {
+                    let limit = spend_limit
+                        .iter()
+                        .filter(|c| c.denom == denom)
+                        .fold(Uint256::default(), |acc, c| acc + c.amount.clone());
+                    if requested > limit {
+                        return Err(AuthzRejection::SpendLimitExceeded {
+                            denom: denom.to_string(),
+                            requested,
+                            limit,
+                        });
+                    }
+                }
+                Ok(())
+            }
+            Authorization::Generic { msg: type_url } => {
+                if type_url == MSG_SEND_TYPE_URL {
+                    Ok(())
+                } else {
+                    Err(AuthzRejection::WrongMessageType(type_url.clone()))
+                }
+            }
+            Authorization::Other(any) => Err(AuthzRejection::UnsupportedAuthorization(
+                any.type_url.clone(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<Any> for Authorization {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: Any) -> Result<Self, Self::Error> {
+        match value.type_url.as_str() {
+            SEND_AUTHORIZATION_TYPE_URL => {
+                let send: SendAuthorization = decode_any(&value)?;
+                let mut spend_limit = Vec::new();
+                for coin in send.spend_limit {
+                    spend_limit.push(Coin::try_from(coin)?);
+                }
+                Ok(Authorization::Send {
+                    spend_limit,
+                    allow_list: send.allow_list,
+                })
+            }
+            GENERIC_AUTHORIZATION_TYPE_URL => {
+                let generic: GenericAuthorization = decode_any(&value)?;
+                Ok(Authorization::Generic { msg: generic.msg })
+            }
+            _ => Ok(Authorization::Other(value)),
+        }
+    }
+}
+
+/// A grant from `granter` to `grantee`, as returned by `Contact::get_grants`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthzGrant {
+    pub granter: String,
+    pub grantee: String,
+    pub authorization: Authorization,
+    /// The time after which the grant can no longer be used, grants without an expiration
+    /// are only supported since Cosmos SDK v0.46
+    pub expiration: Option<SystemTime>,
+}
+
+impl AuthzGrant {
+    /// Checks if a MsgSend executed by the grantee at `now` would be accepted under this
+    /// grant, see `Authorization::check_send`
+    pub fn check_send(&self, msg: &MsgSend, now: SystemTime) -> Result<(), AuthzRejection> {
+        if let Some(expiration) = self.expiration {
+            if expiration <= now {
+                return Err(AuthzRejection::Expired);
+            }
+        }
+        if msg.from_address != self.granter {
+            return Err(AuthzRejection::WrongGranter(msg.from_address.clone()));
+        }
+        self.authorization.check_send(msg)
+    }
+}
+
+/// The reason a message would be rejected by the authz module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthzRejection {
+    /// The grant has expired
+    Expired,
+    /// The message is not sent from the granters account
+    WrongGranter(String),
+    /// The recipient is not in the allow list of the authorization
+    NotInAllowList(String),
+    /// The total amount of `denom` sent is more than the remaining spend limit
+    SpendLimitExceeded {
+        denom: String,
+        requested: Uint256,
+        limit: Uint256,
+    },
+    /// A generic authorization for a different message type
+    WrongMessageType(String),
+    /// An authorization this crate can not evaluate
+    UnsupportedAuthorization(String),
+    InvalidAmount(String),
+}
+
+impl fmt::Display for AuthzRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthzRejection::Expired => write!(f, "Grant has expired"),
+            AuthzRejection::WrongGranter(a) => write!(f, "{} is not the granter", a),
+            AuthzRejection::NotInAllowList(a) => write!(f, "{} is not in the allow list", a),
+            AuthzRejection::SpendLimitExceeded {
+                denom,
+                requested,
+                limit,
+            } => write!(
+                f,
+                "Requested {}{} exceeds the spend limit of {}{}",
+                requested, denom, limit, denom
+            ),
+            AuthzRejection::WrongMessageType(t) => {
+                write!(f, "Authorization only allows {}", t)
+            }
+            AuthzRejection::UnsupportedAuthorization(t) => {
+                write!(f, "Unsupported authorization {}", t)
+            }
+            AuthzRejection::InvalidAmount(e) => write!(f, "Invalid amount {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AuthzRejection {}
+
+impl Contact {
+    /// Gets the grants from `granter` to `grantee`, optionally only those for the given
+    /// message type url, every page of results is queried. Chains without the authz
+    /// module return `ModuleNotPresent`
+    pub async fn get_grants(
+        &self,
+        granter: Address,
+        grantee: Address,
+        msg_type_url: Option<String>,
+    ) -> Result<Vec<AuthzGrant>, CosmosGrpcError> {
+        let granter = granter.to_bech32(&self.chain_prefix)?;
+        let grantee = grantee.to_bech32(&self.chain_prefix)?;
+        let mut grants = Vec::new();
+        let mut pagination = None;
+        loop {
+            let res: QueryGrantsResponse = unary_call(
+                self.channel().await?,
+                "/cosmos.authz.v1beta1.Query/Grants",
+                QueryGrantsRequest {
+                    granter: granter.clone(),
+                    grantee: grantee.clone(),
+                    msg_type_url: msg_type_url.clone().unwrap_or_default(),
+                    pagination,
+                },
+            )
+            .await
+            .map_err(|e| match e {
+                CosmosGrpcError::RequestError { error }
+                    if error.code() == TonicCode::Unimplemented =>
+                {
+                    CosmosGrpcError::ModuleNotPresent("authz".to_string())
+                }
+                e => e,
+            })?;
+            for grant in res.grants {
+                let authorization = match grant.authorization {
+                    Some(a) => Authorization::try_from(a)?,
+                    None => {
+                        return Err(CosmosGrpcError::BadResponse(
+                            "Grant without authorization".to_string(),
+                        ))
+                    }
+                };
+                grants.push(AuthzGrant {
+                    granter: granter.clone(),
+                    grantee: grantee.clone(),
+                    authorization,
+                    expiration: grant.expiration.map(SystemTime::from),
+                });
+            }
+            pagination = next_page(&res.pagination);
+            if pagination.is_none() {
+                return Ok(grants);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encode_any;
+    use std::time::Duration;
+
+    fn send(to: &str, amount: &[(u64, &str)]) -> MsgSend {
+        MsgSend {
+            from_address: "granter".to_string(),
+            to_address: to.to_string(),
+            amount: amount
+                .iter()
+                .map(|(a, d)| Coin::new((*a).into(), *d).into())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_send_authorization_spend_limit() {
+        let authorization = Authorization::try_from(encode_any(
+            SendAuthorization {
+                spend_limit: vec![Coin::new(100u8.into(), "uatom").into()],
+                allow_list: Vec::new(),
+            },
+            SEND_AUTHORIZATION_TYPE_URL.to_string(),
+        ))
+        .unwrap();
+        assert!(authorization
+            .check_send(&send("anyone", &[(100, "uatom")]))
+            .is_ok());
+        // amounts of the same denom are summed
+        assert_eq!(
+            authorization.check_send(&send("anyone", &[(60, "uatom"), (41, "uatom")])),
+            Err(AuthzRejection::SpendLimitExceeded {
+                denom: "uatom".to_string(),
+                requested: 101u8.into(),
+                limit: 100u8.into(),
+            })
+        );
+        // a denom not in the limit has a limit of zero
+        assert!(matches!(
+            authorization.check_send(&send("anyone", &[(1, "ujuno")])),
+            Err(AuthzRejection::SpendLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_send_authorization_allow_list() {
+        let grant = AuthzGrant {
+            granter: "granter".to_string(),
+            grantee: "grantee".to_string(),
+            authorization: Authorization::Send {
+                spend_limit: vec![Coin::new(100u8.into(), "uatom")],
+                allow_list: vec!["friend".to_string()],
+            },
+            expiration: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(100)),
+        };
+        let before = SystemTime::UNIX_EPOCH + Duration::from_secs(99);
+        assert!(grant
+            .check_send(&send("friend", &[(1, "uatom")]), before)
+            .is_ok());
+        assert_eq!(
+            grant.check_send(&send("stranger", &[(1, "uatom")]), before),
+            Err(AuthzRejection::NotInAllowList("stranger".to_string()))
+        );
+        let mut not_granter = send("friend", &[(1, "uatom")]);
+        not_granter.from_address = "grantee".to_string();
+        assert_eq!(
+            grant.check_send(&not_granter, before),
+            Err(AuthzRejection::WrongGranter("grantee".to_string()))
+        );
+        assert_eq!(
+            grant.check_send(
+                &send("friend", &[(1, "uatom")]),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(100)
+            ),
+            Err(AuthzRejection::Expired)
+        );
+    }
+
+    #[test]
+    fn test_decode_exec_results() {
+        let send_msg = encode_any(send("friend", &[(1, "uatom")]), MSG_SEND_TYPE_URL);
+        let inner_exec = encode_any(
+            MsgExec {
+                grantee: "grantee".to_string(),
+                msgs: vec![send_msg.clone()],
+            },
+            MSG_EXEC_TYPE_URL,
+        );
+        let exec = MsgExec {
+            grantee: "grantee".to_string(),
+            msgs: vec![send_msg.clone(), inner_exec.clone()],
+        };
+        // MsgSendResponse has no fields so it encodes to nothing
+        let mut nested = Vec::new();
+        MsgExecResponse {
+            results: vec![Vec::new()],
+        }
+        .encode(&mut nested)
+        .unwrap();
+        let response = encode_any(
+            MsgExecResponse {
+                results: vec![Vec::new(), nested.clone()],
+            },
+            MSG_EXEC_RESPONSE_TYPE_URL,
+        );
+
+        let results = decode_exec_results(&exec, &response).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].msg, send_msg);
+        assert_eq!(
+            results[0].response.type_url,
+            "/cosmos.bank.v1beta1.MsgSendResponse"
+        );
+        assert!(results[0].inner.is_empty());
+        assert_eq!(results[1].msg, inner_exec);
+        assert_eq!(results[1].response.type_url, MSG_EXEC_RESPONSE_TYPE_URL);
+        assert_eq!(results[1].response.value, nested);
+        assert_eq!(results[1].inner.len(), 1);
+        assert_eq!(results[1].inner[0].msg, send_msg);
+        assert_eq!(
+            results[1].inner[0].response.type_url,
+            "/cosmos.bank.v1beta1.MsgSendResponse"
+        );
+
+        let missing = encode_any(
+            MsgExecResponse {
+                results: vec![Vec::new()],
+            },
+            MSG_EXEC_RESPONSE_TYPE_URL,
+        );
+        assert!(matches!(
+            decode_exec_results(&exec, &missing),
+            Err(CosmosGrpcError::BadResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_generic_authorization() {
+        let authorization = Authorization::Generic {
+            msg: MSG_SEND_TYPE_URL.to_string(),
+        };
+        assert!(authorization
+            .check_send(&send("anyone", &[(1_000_000, "uatom")]))
+            .is_ok());
+        let authorization = Authorization::Generic {
+            msg: "/cosmos.gov.v1beta1.MsgVote".to_string(),
+        };
+        assert!(authorization
+            .check_send(&send("anyone", &[(1, "uatom")]))
+            .is_err());
+    }
+}
@@ -0,0 +1,471 @@
+//! Contains utility functions for interacting with the Cosmos sdk bank module
+
+use crate::client::rpc::unary_call;
+use crate::client::MEMO;
+use crate::error::AddressError;
+use crate::error::CosmosGrpcError;
+use crate::Address;
+use crate::Coin;
+use crate::Contact;
+use crate::Fee;
+use crate::MessageArgs;
+use crate::Msg;
+use crate::PrivateKey;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::Metadata;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::Params as BankParams;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::QueryDenomMetadataRequest;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::QueryParamsRequest;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::SendEnabled;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::{Input, MsgMultiSend, MsgSend, Output};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
+use num256::Uint256;
+use prost::Message;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+use tonic::Code as TonicCode;
+
+/// QuerySendEnabledRequest defines the RPC request for looking up SendEnabled entries,
+/// added in Cosmos SDK v0.47 when the send enabled entries were moved out of the params
+#[derive(Clone, PartialEq, Message)]
+pub struct QuerySendEnabledRequest {
+    /// denoms is the specific denoms you want look up. Leave empty to get all entries.
+    #[prost(string, repeated, tag = "1")]
+    pub denoms: Vec<String>,
+}
+
+/// QuerySendEnabledResponse defines the RPC response of a SendEnable query.
+#[derive(Clone, PartialEq, Message)]
+pub struct QuerySendEnabledResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub send_enabled: Vec<SendEnabled>,
+}
+
+/// QuerySpendableBalancesRequest defines the RPC request for the balances of an account that
+/// are not locked by vesting, added in Cosmos SDK v0.46
+#[derive(Clone, PartialEq, Message)]
+pub struct QuerySpendableBalancesRequest {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageRequest>,
+}
+
+/// QuerySpendableBalancesResponse defines the RPC response of a SpendableBalances query.
+#[derive(Clone, PartialEq, Message)]
+pub struct QuerySpendableBalancesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub balances: Vec<ProtoCoin>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
+
+/// Checks that `balances` cover the total of `sending` and the fee amount per denom, returns
+/// `InsufficientFunds` for the first denom that falls short
+#[allow(clippy::result_large_err)]
+fn check_affordable(balances: &[Coin], sending: &[Coin], fee: &Fee) -> Result<(), CosmosGrpcError> {
+    let mut needed: BTreeMap<&String, Uint256> = BTreeMap::new();
+    for coin in sending.iter().chain(fee.amount.iter()) {
+        let total = needed.entry(&coin.denom).or_default();
+        *total = total.clone() + coin.amount.clone();
+    }
+    for (denom, needed) in needed {
+        let available = balances
+            .iter()
+            .filter(|c| &c.denom == denom)
+            .fold(Uint256::default(), |total, c| total + c.amount.clone());
+        if available < needed {
+            return Err(CosmosGrpcError::InsufficientFunds {
+                denom: denom.to_string(),
+                needed,
+                available,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Looks up the status of `denom` in a list of send enabled entries, None if it's not listed
+fn find_send_enabled(entries: &[SendEnabled], denom: &str) -> Option<bool> {
+    entries.iter().find(|e| e.denom == denom).map(|e| e.enabled)
+}
+
+/// The result of `Contact::send_to_many`, since each chunk is a separate transaction
+/// some chunks may succeed while a later one fails
+#[derive(Debug)]
+pub struct BatchSendResult {
+    /// The responses of the chunks that were sent successfully, in order
+    pub succeeded: Vec<TxResponse>,
+    /// The index of the chunk that failed along with the error, chunks after
+    /// this one were not sent. None if every chunk was sent
+    pub failed: Option<(usize, CosmosGrpcError)>,
+}
+
+/// Splits a list of payments into messages containing at most `max_outputs` outputs each,
+/// a chunk with a single output is sent as a MsgSend, larger chunks as a MsgMultiSend with
+/// a single input from `from` covering all the outputs
+pub fn build_multi_send_chunks(
+    from: &Address,
+    prefix: &str,
+    payments: &[(Address, Coin)],
+    max_outputs: usize,
+) -> Result<Vec<Msg>, AddressError> {
+    let mut msgs = Vec::new();
+    let from = from.to_bech32(prefix)?;
+    for chunk in payments.chunks(max_outputs.max(1)) {
+        if chunk.len() == 1 {
+            let (to, coin) = &chunk[0];
+            let send = MsgSend {
+                from_address: from.clone(),
+                to_address: to.to_bech32(prefix)?,
+                amount: vec![coin.clone().into()],
+            };
+            msgs.push(Msg::new("/cosmos.bank.v1beta1.MsgSend", send));
+            continue;
+        }
+        // the input must exactly equal the sum of the outputs, coins are sorted by denom
+        let mut totals: BTreeMap<String, Uint256> = BTreeMap::new();
+        let mut outputs = Vec::new();
+        for (to, coin) in chunk {
+            let total = totals.entry(coin.denom.clone()).or_default();
+            *total = total.clone() + coin.amount.clone();
+            outputs.push(Output {
+                address: to.to_bech32(prefix)?,
+                coins: vec![coin.clone().into()],
+            });
+        }
+        let coins = totals
+            .into_iter()
+            .map(|(denom, amount)| Coin::new(amount, denom).into())
+            .collect();
+        let multi_send = MsgMultiSend {
+            inputs: vec![Input {
+                address: from.clone(),
+                coins,
+            }],
+            outputs,
+        };
+        msgs.push(Msg::new("/cosmos.bank.v1beta1.MsgMultiSend", multi_send));
+    }
+    Ok(msgs)
+}
+
+impl Contact {
+    /// Gets the metadata for a denom, this includes the base and display denoms as well
+    /// as the exponents of each denom unit, which is what you need to display amounts to
+    /// users, see `coin::format_coin`. Returns None if the chain has no metadata registered
+    /// for this denom, which is common.
+    pub async fn get_denom_metadata(
+        &self,
+        denom: String,
+    ) -> Result<Option<Metadata>, CosmosGrpcError> {
+        let mut grpc = BankQueryClient::new(self.channel().await?);
+        let res = grpc
+            .denom_metadata(QueryDenomMetadataRequest { denom })
+            .await;
+        match res {
+            Ok(res) => Ok(res.into_inner().metadata),
+            Err(e) => match e.code() {
+                TonicCode::NotFound => Ok(None),
+                _ => Err(CosmosGrpcError::RequestError { error: e }),
+            },
+        }
+    }
+
+    /// Gets the bank module params, queried from the node the first time and cached from then
+    /// on, use `refresh_bank_params` to update them. Since Cosmos SDK v0.47 the per denom send
+    /// enabled entries are no longer part of the params, see `is_send_enabled`
+    pub async fn get_bank_params(&self) -> Result<BankParams, CosmosGrpcError> {
+        if let Some(params) = self.bank_params.read().unwrap().clone() {
+            return Ok(params);
+        }
+        self.refresh_bank_params().await
+    }
+
+    /// Queries the bank module params, replacing any cached value
+    pub async fn refresh_bank_params(&self) -> Result<BankParams, CosmosGrpcError> {
+        let mut grpc = BankQueryClient::new(self.channel().await?);
+        let res = grpc.params(QueryParamsRequest {}).await?.into_inner();
+        match res.params {
+            Some(params) => {
+                *self.bank_params.write().unwrap() = Some(params.clone());
+                Ok(params)
+            }
+            None => Err(CosmosGrpcError::BadResponse(
+                "No bank params returned".to_string(),
+            )),
+        }
+    }
+
+    /// Checks if transfers of `denom` are enabled, chains may disable sending specific denoms
+    /// in which case any send of them is rejected. The entry for the denom is looked up in the
+    /// cached bank params, or with the SendEnabled query on Cosmos SDK v0.47 and later, denoms
+    /// without an entry use the default of the params. If the chain doesn't serve the bank
+    /// queries sending is assumed to be enabled.
+    pub async fn is_send_enabled(&self, denom: &str) -> Result<bool, CosmosGrpcError> {
+        let params = match self.get_bank_params().await {
+            Ok(params) => params,
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == TonicCode::Unimplemented =>
+            {
+                return Ok(true)
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(enabled) = find_send_enabled(&params.send_enabled, denom) {
+            return Ok(enabled);
+        }
+        let res: Result<QuerySendEnabledResponse, CosmosGrpcError> = unary_call(
+            self.channel().await?,
+            "/cosmos.bank.v1beta1.Query/SendEnabled",
+            QuerySendEnabledRequest {
+                denoms: vec![denom.to_string()],
+            },
+        )
+        .await;
+        match res {
+            Ok(res) => {
+                Ok(find_send_enabled(&res.send_enabled, denom)
+                    .unwrap_or(params.default_send_enabled))
+            }
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == TonicCode::Unimplemented =>
+            {
+                Ok(params.default_send_enabled)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the balances of an account that can be spent, excluding tokens still locked by a
+    /// vesting schedule. Chains before Cosmos SDK v0.46 have no query for this, on those the
+    /// full balances are returned so locked vesting tokens are included
+    pub async fn get_spendable_balances(
+        &self,
+        address: Address,
+    ) -> Result<Vec<Coin>, CosmosGrpcError> {
+        let res = self
+            .rpc
+            .spendable_balances(QuerySpendableBalancesRequest {
+                address: address.to_bech32(&self.chain_prefix)?,
+                pagination: None,
+            })
+            .await;
+        match res {
+            Ok(res) => {
+                let mut ret = Vec::new();
+                for value in res.balances {
+                    ret.push(Coin::try_from(value)?);
+                }
+                Ok(ret)
+            }
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == TonicCode::Unimplemented =>
+            {
+                self.get_balances(address).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Checks that `address` can pay for a transaction sending `sending` with `fee` before it's
+    /// signed, the amounts and the fee are added up per denom and compared to the spendable
+    /// balances of the account, see `get_spendable_balances`. Returns `InsufficientFunds` with
+    /// the first denom the account doesn't hold enough of. Balances can still change before the
+    /// transaction is executed so this doesn't guarantee it succeeds
+    pub async fn can_afford(
+        &self,
+        address: Address,
+        sending: &[Coin],
+        fee: &Fee,
+    ) -> Result<(), CosmosGrpcError> {
+        let balances = self.get_spendable_balances(address).await?;
+        check_affordable(&balances, sending, fee)
+    }
+
+    /// Pays a large number of recipients from a single account, a single MsgMultiSend with
+    /// thousands of outputs would exceed the block gas limit so the payments are split into
+    /// transactions of at most `max_outputs_per_tx` payments, see `build_multi_send_chunks`.
+    /// Each transaction uses the provided fee and sequence numbers are assigned locally so
+    /// chunks can be sent back to back. If `wait_timeout` is provided each chunk is waited
+    /// on before sending the next. Sending stops at the first failed chunk, the result
+    /// reports which chunks succeeded so the remaining payments can be retried.
+    pub async fn send_to_many(
+        &self,
+        payments: &[(Address, Coin)],
+        max_outputs_per_tx: usize,
+        fee: Fee,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<BatchSendResult, CosmosGrpcError> {
+        if max_outputs_per_tx == 0 {
+            return Err(CosmosGrpcError::BadInput(
+                "max_outputs_per_tx must be at least 1".to_string(),
+            ));
+        }
+        for (to, _) in payments {
+            self.check_address_prefix(to)?;
+        }
+        let our_address = private_key.to_address(&self.chain_prefix)?;
+        let msgs = build_multi_send_chunks(
+            &our_address,
+            &self.chain_prefix,
+            payments,
+            max_outputs_per_tx,
+        )?;
+        let mut args = self.get_message_args(our_address, fee).await?;
+
+        let mut result = BatchSendResult {
+            succeeded: Vec::new(),
+            failed: None,
+        };
+        for (idx, msg) in msgs.into_iter().enumerate() {
+            let res = self
+                .send_chunk(msg, args.clone(), &private_key, wait_timeout)
+                .await;
+            match res {
+                Ok(response) => {
+                    result.succeeded.push(response);
+                    args.sequence += 1;
+                }
+                Err(e) => {
+                    result.failed = Some((idx, e));
+                    break;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn send_chunk(
+        &self,
+        msg: Msg,
+        args: MessageArgs,
+        private_key: &PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let msg_bytes =
+            private_key.sign_std_msg_with_sign_mode(&[msg], args, MEMO, self.sign_mode)?;
+        let response = self
+            .send_transaction(msg_bytes, BroadcastMode::Sync)
+            .await?;
+        if let Some(time) = self.wait_timeout(wait_timeout) {
+            self.wait_for_tx(response, time).await
+        } else {
+            Ok(response)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::decode_any;
+    use prost_types::Any;
+
+    #[test]
+    fn test_check_affordable() {
+        let balances = vec![
+            Coin::new(100u8.into(), "stake"),
+            Coin::new(5u8.into(), "uatom"),
+        ];
+        let fee = Fee {
+            amount: vec![Coin::new(10u8.into(), "stake")],
+            gas_limit: 200_000,
+            payer: None,
+            granter: None,
+        };
+        let sending = vec![
+            Coin::new(60u8.into(), "stake"),
+            Coin::new(30u8.into(), "stake"),
+        ];
+        assert!(check_affordable(&balances, &sending, &fee).is_ok());
+
+        // the fee pushes the total of the denom over the balance
+        let sending = vec![Coin::new(91u8.into(), "stake")];
+        match check_affordable(&balances, &sending, &fee) {
+            Err(CosmosGrpcError::InsufficientFunds {
+                denom,
+                needed,
+                available,
+            }) => {
+                assert_eq!(denom, "stake");
+                assert_eq!(needed, 101u8.into());
+                assert_eq!(available, 100u8.into());
+            }
+            res => panic!("Expected insufficient funds, got {:?}", res),
+        }
+        let sending = vec![Coin::new(1u8.into(), "footoken")];
+        assert!(matches!(
+            check_affordable(&balances, &sending, &Fee::default()),
+            Err(CosmosGrpcError::InsufficientFunds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_send_enabled() {
+        let entries = vec![
+            SendEnabled {
+                denom: "uatom".to_string(),
+                enabled: true,
+            },
+            SendEnabled {
+                denom: "ulocked".to_string(),
+                enabled: false,
+            },
+        ];
+        assert_eq!(find_send_enabled(&entries, "uatom"), Some(true));
+        assert_eq!(find_send_enabled(&entries, "ulocked"), Some(false));
+        assert_eq!(find_send_enabled(&entries, "uosmo"), None);
+    }
+
+    #[test]
+    fn test_build_multi_send_chunks() {
+        let from = Address::from_bytes([0; 20], "cosmos").unwrap();
+        let payments: Vec<(Address, Coin)> = (1..=5u8)
+            .map(|i| {
+                let denom = if i % 2 == 0 { "ufoo" } else { "uatom" };
+                (
+                    Address::from_bytes([i; 20], "cosmos").unwrap(),
+                    Coin::new(u64::from(i).into(), denom),
+                )
+            })
+            .collect();
+        let msgs = build_multi_send_chunks(&from, "cosmos", &payments, 2).unwrap();
+        assert_eq!(msgs.len(), 3);
+
+        let any: Any = msgs[0].clone().into();
+        assert_eq!(any.type_url, "/cosmos.bank.v1beta1.MsgMultiSend");
+        let multi: MsgMultiSend = decode_any(&any).unwrap();
+        assert_eq!(multi.outputs.len(), 2);
+        assert_eq!(multi.inputs.len(), 1);
+        assert_eq!(multi.inputs[0].address, from.to_string());
+        let input_coins: Vec<(String, String)> = multi.inputs[0]
+            .coins
+            .iter()
+            .map(|c| (c.denom.clone(), c.amount.clone()))
+            .collect();
+        assert_eq!(
+            input_coins,
+            vec![
+                ("uatom".to_string(), "1".to_string()),
+                ("ufoo".to_string(), "2".to_string())
+            ]
+        );
+
+        let any: Any = msgs[1].clone().into();
+        let multi: MsgMultiSend = decode_any(&any).unwrap();
+        assert_eq!(multi.inputs[0].coins.len(), 2);
+
+        // the final chunk only has one payment left
+        let any: Any = msgs[2].clone().into();
+        assert_eq!(any.type_url, "/cosmos.bank.v1beta1.MsgSend");
+        let send: MsgSend = decode_any(&any).unwrap();
+        assert_eq!(send.to_address, payments[4].0.to_string());
+        assert_eq!(send.amount[0].amount, "5");
+    }
+}
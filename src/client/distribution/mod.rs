@@ -1,22 +1,66 @@
 //! Contains utility functions for interacting with and modifying the Cosmos sdk distribution module
 //! including the community pool
 
+use crate::client::MEMO;
+use crate::coin::DecCoin;
 use crate::error::CosmosGrpcError;
-use crate::{Coin, Contact};
+use crate::{Address, Coin, Contact, Fee, Msg, PrivateKey};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
 use cosmos_sdk_proto::cosmos::distribution::v1beta1::query_client::QueryClient as DistQueryClient;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::MsgSetWithdrawAddress;
 use cosmos_sdk_proto::cosmos::distribution::v1beta1::QueryCommunityPoolRequest;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::QueryDelegatorWithdrawAddressRequest;
 use num256::Uint256;
 use num_bigint::ParseBigIntError;
+use std::convert::TryFrom;
+use std::time::Duration;
 
 // required because dec coins are multiplied by 1*10^18
 const ONE_ETH: u128 = 10u128.pow(18);
 
+pub const MSG_SET_WITHDRAW_ADDRESS_TYPE_URL: &str =
+    "/cosmos.distribution.v1beta1.MsgSetWithdrawAddress";
+
+/// Builds a message sending the staking rewards of `delegator` to `withdraw_address` from now
+/// on, both addresses are encoded with `prefix`
+#[allow(clippy::result_large_err)]
+pub fn set_withdraw_address_msg(
+    delegator: &Address,
+    withdraw_address: &Address,
+    prefix: &str,
+) -> Result<Msg, CosmosGrpcError> {
+    let set = MsgSetWithdrawAddress {
+        delegator_address: delegator.to_bech32(prefix)?,
+        withdraw_address: withdraw_address.to_bech32(prefix)?,
+    };
+    Ok(Msg::new(MSG_SET_WITHDRAW_ADDRESS_TYPE_URL, set))
+}
+
 impl Contact {
+    /// Gets the coins in the community pool as DecCoins, without any loss of precision
+    pub async fn get_community_pool(&self) -> Result<Vec<DecCoin>, CosmosGrpcError> {
+        let mut grpc = DistQueryClient::new(self.channel().await?);
+        let res = grpc.community_pool(QueryCommunityPoolRequest {}).await?;
+        let mut res_coins = Vec::new();
+        for v in res.into_inner().pool {
+            match DecCoin::try_from(v) {
+                Ok(coin) => res_coins.push(coin),
+                Err(e) => {
+                    return Err(CosmosGrpcError::BadResponse(format!(
+                        "Failed to parse DecCoin {}",
+                        e
+                    )))
+                }
+            }
+        }
+        Ok(res_coins)
+    }
+
     /// Gets a list of coins in the community pool, note returned values from this endpoint
     /// are in DecCoins for precision, for the sake of ease of use this endpoint converts them
     /// into their normal form, for easy comparison against any other coin or amount.
     pub async fn get_community_pool_coins(&self) -> Result<Vec<Coin>, CosmosGrpcError> {
-        let mut grpc = DistQueryClient::connect(self.url.clone()).await?;
+        let mut grpc = DistQueryClient::new(self.channel().await?);
         let res = grpc.community_pool(QueryCommunityPoolRequest {}).await?;
         let val = res.into_inner().pool;
         let mut res = Vec::new();
@@ -32,4 +76,85 @@ impl Contact {
         }
         Ok(res)
     }
+
+    /// Gets the address the staking rewards of `delegator` are withdrawn to, this is the
+    /// delegator itself unless it was changed with `set_withdraw_address`
+    pub async fn get_withdraw_address(
+        &self,
+        delegator: Address,
+    ) -> Result<Address, CosmosGrpcError> {
+        let mut grpc = DistQueryClient::new(self.channel().await?);
+        let res = grpc
+            .delegator_withdraw_address(QueryDelegatorWithdrawAddressRequest {
+                delegator_address: delegator.to_bech32(&self.chain_prefix)?,
+            })
+            .await?
+            .into_inner();
+        if res.withdraw_address.is_empty() {
+            return Ok(delegator);
+        }
+        parse_withdraw_address(&res.withdraw_address)
+    }
+
+    /// Sets the address the staking rewards and commission of `delegator` are withdrawn to,
+    /// by default rewards go to the delegator itself. `private_key` must be the key of the
+    /// delegator. Both addresses are checked against the chain prefix, see
+    /// `check_address_prefix`. Chains can disable withdraw address changes with the
+    /// `withdraw_addr_enabled` param of the distribution module, in which case the tx fails
+    pub async fn set_withdraw_address(
+        &self,
+        delegator: Address,
+        withdraw_address: Address,
+        fee: Fee,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        self.check_address_prefix(&delegator)?;
+        self.check_address_prefix(&withdraw_address)?;
+        let msg = set_withdraw_address_msg(&delegator, &withdraw_address, &self.chain_prefix)?;
+        self.create_and_send(&[msg], fee, MEMO, private_key, wait_timeout)
+            .await
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_withdraw_address(address: &str) -> Result<Address, CosmosGrpcError> {
+    address.parse().map_err(|e| {
+        CosmosGrpcError::BadResponse(format!("Invalid withdraw address {} {}", address, e))
+    })
+}
+
+#[test]
+fn test_parse_withdraw_address() {
+    let address = Address::from_bytes([3; 20], "cosmos").unwrap();
+    assert_eq!(
+        parse_withdraw_address(&address.to_string()).unwrap(),
+        address
+    );
+    assert!(matches!(
+        parse_withdraw_address("cosmos1invalid"),
+        Err(CosmosGrpcError::BadResponse(_))
+    ));
+}
+
+#[test]
+fn test_set_withdraw_address_msg() {
+    use prost::Message;
+
+    let delegator = Address::from_bytes([1; 20], "cosmos").unwrap();
+    let withdraw = Address::from_bytes([2; 20], "althea").unwrap();
+    let msg = set_withdraw_address_msg(&delegator, &withdraw, "cosmos").unwrap();
+    assert_eq!(
+        msg.0.type_url,
+        "/cosmos.distribution.v1beta1.MsgSetWithdrawAddress"
+    );
+    let delegator = delegator.to_bech32("cosmos").unwrap();
+    let withdraw = withdraw.to_bech32("cosmos").unwrap();
+    let mut expected = vec![0x0a, delegator.len() as u8];
+    expected.extend_from_slice(delegator.as_bytes());
+    expected.extend_from_slice(&[0x12, withdraw.len() as u8]);
+    expected.extend_from_slice(withdraw.as_bytes());
+    assert_eq!(msg.0.value, expected);
+    let decoded = MsgSetWithdrawAddress::decode(msg.0.value.as_slice()).unwrap();
+    assert_eq!(decoded.withdraw_address, withdraw);
 }
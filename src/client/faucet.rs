@@ -0,0 +1,184 @@
+//! A testnet faucet, sends a fixed amount to any address that asks at most once per cooldown
+//! period. Requests may be made concurrently from many tasks, they are sent one at a time
+//! with locally tracked sequences so that none of them fail for using the same sequence.
+
+use crate::client::types::AccountOverride;
+use crate::client::Contact;
+use crate::client::MEMO;
+use crate::coin::Coin;
+use crate::coin::Fee;
+use crate::error::CosmosGrpcError;
+use crate::msg::Msg;
+use crate::private_key::PrivateKey;
+use crate::Address;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use futures_util::lock::Mutex as AsyncMutex;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Sends `amount` from the faucet key to requesting addresses, each address may receive
+/// once per `cooldown`. Cooldowns are only tracked in memory and are lost on restart
+pub struct Faucet {
+    contact: Contact,
+    key: PrivateKey,
+    amount: Coin,
+    fee: Fee,
+    cooldown: Duration,
+    /// When each address last requested, entries are removed once their cooldown passes
+    last_request: Mutex<HashMap<Address, Instant>>,
+    /// The account number and next sequence of the faucet account, None until the first
+    /// request and after any failure so that it's queried again
+    account: AsyncMutex<Option<AccountOverride>>,
+}
+
+impl Faucet {
+    pub fn new(
+        contact: Contact,
+        key: PrivateKey,
+        amount: Coin,
+        fee: Fee,
+        cooldown: Duration,
+    ) -> Faucet {
+        Faucet {
+            contact,
+            key,
+            amount,
+            fee,
+            cooldown,
+            last_request: Mutex::new(HashMap::new()),
+            account: AsyncMutex::new(None),
+        }
+    }
+
+    /// Sends the faucet amount to `recipient`, returns `CooldownActive` if the address has
+    /// already received within the cooldown period. Failed sends do not start the cooldown.
+    /// If `wait_timeout` is provided, or the Contact has a default wait timeout, this waits for
+    /// the tx to enter the chain, other requests are not held up by the wait.
+    pub async fn request(
+        &self,
+        recipient: Address,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        if let Err(retry_after) = self.start_cooldown(recipient) {
+            return Err(CosmosGrpcError::CooldownActive { retry_after });
+        }
+        let res = self.send(recipient).await;
+        let res = match (res, self.contact.wait_timeout(wait_timeout)) {
+            (Ok(response), Some(timeout)) => self.contact.wait_for_tx(response, timeout).await,
+            (res, _) => res,
+        };
+        if res.is_err() {
+            self.last_request.lock().unwrap().remove(&recipient);
+        }
+        res
+    }
+
+    /// Records the request, the slot is taken before sending so that concurrent requests
+    /// for the same address can't both be sent. Returns the remaining cooldown if active
+    fn start_cooldown(&self, recipient: Address) -> Result<(), Duration> {
+        let mut last_request = self.last_request.lock().unwrap();
+        let now = Instant::now();
+        let cooldown = self.cooldown;
+        last_request.retain(|_, requested| now.duration_since(*requested) < cooldown);
+        if let Some(requested) = last_request.get(&recipient) {
+            return Err(cooldown - now.duration_since(*requested));
+        }
+        last_request.insert(recipient, now);
+        Ok(())
+    }
+
+    async fn send(&self, recipient: Address) -> Result<TxResponse, CosmosGrpcError> {
+        let our_address = self.key.to_address(&self.contact.chain_prefix)?;
+        let msg = Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                from_address: our_address.to_string(),
+                to_address: recipient.to_string(),
+                amount: vec![self.amount.clone().into()],
+            },
+        );
+        // held until the broadcast is done so that each tx gets the next sequence
+        let mut account = self.account.lock().await;
+        let current = match *account {
+            Some(current) => current,
+            None => {
+                let info = self.contact.get_account_info(our_address).await?;
+                AccountOverride {
+                    account_number: info.account_number,
+                    sequence: info.sequence,
+                }
+            }
+        };
+        let res = self
+            .contact
+            .create_and_send_with_override(
+                &[msg],
+                self.fee.clone(),
+                MEMO,
+                self.key.clone(),
+                None,
+                Some(current),
+            )
+            .await;
+        *account = match res {
+            Ok(_) => Some(AccountOverride {
+                sequence: current.sequence + 1,
+                ..current
+            }),
+            Err(_) => None,
+        };
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::mock_contact;
+
+    #[actix_rt::test]
+    async fn test_mock_faucet() {
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxRaw};
+        use prost::Message;
+
+        let (contact, mock, key) = mock_contact();
+        let amount = Coin {
+            denom: "stake".into(),
+            amount: 10u64.into(),
+        };
+        let faucet = Faucet::new(
+            contact,
+            key,
+            amount,
+            Fee::default(),
+            Duration::from_secs(3600),
+        );
+        let first = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let second = Address::from_bytes([2; 20], "cosmos").unwrap();
+        faucet.request(first, None).await.unwrap();
+        match faucet.request(first, None).await {
+            Err(CosmosGrpcError::CooldownActive { retry_after }) => {
+                assert!(retry_after > Duration::from_secs(3500))
+            }
+            _ => panic!("Expected cooldown"),
+        }
+        // the second tx uses the next sequence even though the chain has not moved
+        faucet.request(second, None).await.unwrap();
+        assert_eq!(mock.broadcast_count(), 2);
+        let sequences: Vec<u64> = mock
+            .broadcasts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|b| {
+                let tx = TxRaw::decode(b.tx_bytes.as_slice()).unwrap();
+                let auth_info = AuthInfo::decode(tx.auth_info_bytes.as_slice()).unwrap();
+                auth_info.signer_infos[0].sequence
+            })
+            .collect();
+        assert_eq!(sequences, vec![0, 1]);
+    }
+}
@@ -0,0 +1,80 @@
+//! Contains utility functions for querying the dynamic gas price of chains running the
+//! `x/feemarket` module, where the base gas price rises and falls with block usage in the
+//! style of EIP-1559. The module is not part of the Cosmos SDK so it's types are defined here.
+
+use crate::client::rpc::unary_call_at_height;
+use crate::client::types::ChainStatus;
+use crate::coin::DecCoin;
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::base::v1beta1::DecCoin as ProtoDecCoin;
+use prost::Message;
+use std::convert::TryFrom;
+use tonic::Code as TonicCode;
+
+/// GasPriceRequest is the request type for the Query/GasPrice RPC method.
+#[derive(Clone, PartialEq, Message)]
+pub struct GasPriceRequest {
+    /// denom we are querying gas price in
+    #[prost(string, tag = "1")]
+    pub denom: String,
+}
+
+/// GasPriceResponse is the response type for the Query/GasPrice RPC method.
+/// Returns a gas price in specified denom.
+#[derive(Clone, PartialEq, Message)]
+pub struct GasPriceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub price: Option<ProtoDecCoin>,
+}
+
+impl Contact {
+    /// Gets the base gas price in `denom` of the fee market for each of the last `blocks`
+    /// blocks, oldest first, by querying the state at each height. The last entry is the
+    /// price the next transaction pays at least, the trend of the rest helps predict where
+    /// it's going. Returns an empty list on chains without a fee market. Like all historical
+    /// queries this fails if the node has pruned the state of the older blocks.
+    pub async fn get_base_fee_history(
+        &self,
+        blocks: u32,
+        denom: &str,
+    ) -> Result<Vec<DecCoin>, CosmosGrpcError> {
+        let latest = match self.get_chain_status().await? {
+            ChainStatus::Moving { block_height } => block_height,
+            ChainStatus::Syncing => return Err(CosmosGrpcError::NodeNotSynced),
+            ChainStatus::WaitingToStart => return Err(CosmosGrpcError::ChainNotRunning),
+        };
+        let first = latest.saturating_sub(u64::from(blocks)) + 1;
+        let mut history = Vec::new();
+        for height in first..=latest {
+            let res: Result<GasPriceResponse, CosmosGrpcError> = unary_call_at_height(
+                self.channel().await?,
+                "/feemarket.feemarket.v1.Query/GasPrice",
+                GasPriceRequest {
+                    denom: denom.to_string(),
+                },
+                Some(height),
+            )
+            .await;
+            let price = match res {
+                Ok(res) => res.price.unwrap_or_default(),
+                Err(CosmosGrpcError::RequestError { error })
+                    if error.code() == TonicCode::Unimplemented =>
+                {
+                    return Ok(Vec::new())
+                }
+                Err(e) => return Err(e),
+            };
+            match DecCoin::try_from(price) {
+                Ok(price) => history.push(price),
+                Err(e) => {
+                    return Err(CosmosGrpcError::BadResponse(format!(
+                        "Failed to parse DecCoin {}",
+                        e
+                    )))
+                }
+            }
+        }
+        Ok(history)
+    }
+}
@@ -1,38 +1,79 @@
+use crate::client::rpc::{unary_call, TracedChannel};
 use crate::client::types::*;
 use crate::coin::Coin;
 use crate::coin::Fee;
+use crate::coin::GasPrice;
+use crate::utils::decode_any;
+use crate::utils::next_page;
+use crate::utils::normalize_tx_hash;
+use crate::utils::tx_hash;
 use crate::{address::Address, private_key::MessageArgs};
 use crate::{client::Contact, error::CosmosGrpcError};
 use bytes::BytesMut;
 use cosmos_sdk_proto::cosmos::auth::v1beta1::{
-    query_client::QueryClient as AuthQueryClient, BaseAccount, QueryAccountRequest,
+    BaseAccount, Params as AuthParams, QueryAccountRequest, QueryParamsRequest as AuthParamsRequest,
 };
-use cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
 use cosmos_sdk_proto::cosmos::bank::v1beta1::QueryAllBalancesRequest;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
+use cosmos_sdk_proto::cosmos::base::reflection::v1beta1::ListImplementationsRequest;
+use cosmos_sdk_proto::cosmos::base::reflection::v1beta1::ListImplementationsResponse;
 use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetBlockByHeightRequest;
 use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetLatestBlockRequest;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetLatestValidatorSetRequest;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetNodeInfoRequest;
 use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetSyncingRequest;
-use cosmos_sdk_proto::cosmos::tx::v1beta1::service_client::ServiceClient as TxServiceClient;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetValidatorSetByHeightRequest;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::GetTxRequest;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::GetTxResponse;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::GetTxsEventRequest;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::OrderBy;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::Tx;
 use prost::Message;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::time::Duration;
 use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tendermint_proto::types::Block;
 use tokio::time::sleep;
+use tokio::time::timeout;
+use tonic::Code as TonicCode;
+
+/// `GetTxResponse` with only the events of the response decoded
+#[derive(Clone, PartialEq, Message)]
+struct GetTxEventsResponse {
+    #[prost(message, optional, tag = "2")]
+    tx_response: Option<TxResponseEvents>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Bech32PrefixRequest {}
+
+#[derive(Clone, PartialEq, Message)]
+struct Bech32PrefixResponse {
+    #[prost(string, tag = "1")]
+    bech32_prefix: String,
+}
 
 impl Contact {
     /// Gets the current chain status, returns an enum taking into account the various possible states
     /// of the chain and the requesting full node. In the common case this provides the block number
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url))
+    )]
     pub async fn get_chain_status(&self) -> Result<ChainStatus, CosmosGrpcError> {
-        let mut grpc = TendermintServiceClient::connect(self.url.clone()).await?;
-        let syncing = grpc.get_syncing(GetSyncingRequest {}).await?.into_inner();
+        let syncing = self.rpc.get_syncing(GetSyncingRequest {}).await?;
 
         if syncing.syncing {
             Ok(ChainStatus::Syncing)
         } else {
-            let block = grpc.get_latest_block(GetLatestBlockRequest {}).await;
+            let block = self.rpc.get_latest_block(GetLatestBlockRequest {}).await;
             match block {
-                Ok(block) => match block.into_inner().block {
+                Ok(block) => match block.block {
                     Some(block) => match block.last_commit {
                         // for some reason the block height can be negative, we cast it to a u64 for the sake
                         // of logical bounds checking
@@ -47,29 +88,27 @@ impl Contact {
                 },
                 // if get syncing succeeded and this fails, it means there's 'no block' and
                 // we're waiting to start
-                Err(e) => {
-                    if e.message().contains("nil Block") {
-                        Ok(ChainStatus::WaitingToStart)
-                    } else {
-                        Err(e.into())
-                    }
+                Err(CosmosGrpcError::RequestError { error })
+                    if error.message().contains("nil Block") =>
+                {
+                    Ok(ChainStatus::WaitingToStart)
                 }
+                Err(e) => Err(e),
             }
         }
     }
 
     /// Gets the latest block from the node, taking into account the possibility that the chain is halted
     /// and also the possibility that the node is syncing
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url))
+    )]
     pub async fn get_latest_block(&self) -> Result<LatestBlock, CosmosGrpcError> {
-        let mut grpc = TendermintServiceClient::connect(self.url.clone()).await?;
-        let syncing = grpc
-            .get_syncing(GetSyncingRequest {})
-            .await?
-            .into_inner()
-            .syncing;
+        let syncing = self.rpc.get_syncing(GetSyncingRequest {}).await?.syncing;
 
-        let block = grpc.get_latest_block(GetLatestBlockRequest {}).await?;
-        let block = block.into_inner().block;
+        let block = self.rpc.get_latest_block(GetLatestBlockRequest {}).await?;
+        let block = block.block;
         match block {
             Some(block) => {
                 if syncing {
@@ -82,80 +121,466 @@ impl Contact {
         }
     }
 
+    /// Gets the versions of the node and the application it runs, along with the messages the
+    /// application accepts so that callers can detect which modules are available. Nodes with
+    /// the reflection service disabled still return the versions but no messages.
+    pub async fn get_app_version(&self) -> Result<NodeInfo, CosmosGrpcError> {
+        let mut grpc = TendermintServiceClient::new(self.channel().await?);
+        let res = grpc
+            .get_node_info(GetNodeInfoRequest {})
+            .await?
+            .into_inner();
+        let node_info = res.default_node_info.unwrap_or_default();
+        let app_version = res.application_version.unwrap_or_default();
+
+        let msg_type_urls =
+            match list_implementations(self.channel().await?, "cosmos.base.v1beta1.Msg").await {
+                Ok(names) => Some(names),
+                Err(e) => {
+                    debug!("Reflection is not available {:?}", e);
+                    None
+                }
+            };
+
+        Ok(NodeInfo {
+            network: node_info.network,
+            moniker: node_info.moniker,
+            tendermint_version: node_info.version,
+            app_name: app_version.app_name,
+            app_version: app_version.version,
+            cosmos_sdk_version: cosmos_sdk_version(&app_version.build_deps),
+            msg_type_urls,
+        })
+    }
+
+    /// Fetches everything about the chain that is normally fetched on demand in one go, for
+    /// applications that adapt to the chain on startup. The chain id and gas price are set on
+    /// this Contact unless they were already configured, the gas price is the node's minimum
+    /// gas price in the bond denom, or the first of them. The auth params are cached as by
+    /// `refresh_auth_params`. After this, sending transactions needs no queries beyond the
+    /// account and latest block. The result is also kept, see `get_chain_info`.
+    pub async fn bootstrap(&mut self) -> Result<ChainInfo, CosmosGrpcError> {
+        let node = self.get_app_version().await?;
+        let bond_denom = match self.get_bond_denom().await {
+            Ok(denom) => Some(denom),
+            Err(CosmosGrpcError::ModuleNotPresent(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let min_gas_prices = match self.get_min_gas_prices().await {
+            Ok(prices) => Some(prices),
+            Err(CosmosGrpcError::ModuleNotPresent(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let auth_params = self.refresh_auth_params().await?;
+
+        let info = ChainInfo {
+            chain_id: node.network.clone(),
+            node,
+            bond_denom,
+            min_gas_prices,
+            max_memo_characters: auth_params.max_memo_characters,
+        };
+        if self.chain_id.is_none() {
+            self.chain_id = Some(info.chain_id.clone());
+        }
+        if self.gas_price.is_none() {
+            self.gas_price = default_gas_price(&info);
+        }
+        self.chain_info = Some(info.clone());
+        Ok(info)
+    }
+
+    /// Connects to the node and checks that it's usable, so that a misconfigured endpoint is
+    /// reported on startup rather than by the first transaction. Creating a Contact never
+    /// connects, every call connects on demand, this is an optional check on top of that.
+    /// Returns an error if the node can't be reached within the timeout of this Contact, if
+    /// it's status can't be queried or if the chain reports an address prefix other than the
+    /// one configured. The prefix can only be checked on chains serving the `Bech32Prefix`
+    /// query of the auth module, Cosmos SDK v0.46 or later.
+    pub async fn connect(&self) -> Result<ChainStatus, CosmosGrpcError> {
+        let channel = match timeout(self.timeout, self.channel()).await {
+            Ok(channel) => channel?,
+            Err(_) => {
+                return Err(CosmosGrpcError::Timeout {
+                    time: self.timeout,
+                    tx_hash: None,
+                })
+            }
+        };
+        let status = self.get_chain_status().await?;
+
+        let res: Result<Bech32PrefixResponse, CosmosGrpcError> = unary_call(
+            channel,
+            "/cosmos.auth.v1beta1.Query/Bech32Prefix",
+            Bech32PrefixRequest {},
+        )
+        .await;
+        match res {
+            Ok(res) if res.bech32_prefix != self.chain_prefix => {
+                Err(CosmosGrpcError::PrefixMismatch {
+                    expected: self.chain_prefix.clone(),
+                    found: res.bech32_prefix,
+                })
+            }
+            Ok(_) => Ok(status),
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == TonicCode::Unimplemented =>
+            {
+                debug!("Chain does not serve it's address prefix, not checked");
+                Ok(status)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     /// Gets account info for the provided Cosmos account using the accounts endpoint
     /// accounts do not have any info if they have no tokens or are otherwise never seen
     /// before in this case we return the special error NoToken
     pub async fn get_account_info(&self, address: Address) -> Result<BaseAccount, CosmosGrpcError> {
-        let mut agrpc = AuthQueryClient::connect(self.url.clone()).await?;
-        let res = agrpc
+        self.get_account_info_at_height(address, None).await
+    }
+
+    /// Compares a locally tracked sequence of an account with the one on chain, returning the
+    /// on chain sequence minus `local_sequence`. A positive gap means the local counter is
+    /// behind, something else signed for the account, a negative gap that it was incremented
+    /// for transactions that never made it on chain. Either way the next broadcast with the
+    /// local sequence will fail. Accounts not yet on chain have sequence zero.
+    pub async fn sequence_gap(
+        &self,
+        address: Address,
+        local_sequence: u64,
+    ) -> Result<i64, CosmosGrpcError> {
+        let sequence = match self.get_account_info(address).await {
+            Ok(account) => account.sequence,
+            Err(CosmosGrpcError::NoToken) => 0,
+            Err(e) => return Err(e),
+        };
+        let gap = i128::from(sequence) - i128::from(local_sequence);
+        Ok(gap.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64)
+    }
+
+    /// Identical to `get_account_info` but queries the account as it was at the provided block
+    /// height, or the latest state if None. Returns `HeightNotAvailable` if the node has pruned
+    /// the state at that height or has not reached it yet.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, address = %address, height = ?height))
+    )]
+    pub async fn get_account_info_at_height(
+        &self,
+        address: Address,
+        height: Option<u64>,
+    ) -> Result<BaseAccount, CosmosGrpcError> {
+        let res = self
+            .rpc
             // todo detect chain prefix here
-            .account(QueryAccountRequest {
-                address: address.to_bech32(&self.chain_prefix).unwrap(),
-            })
+            .account(
+                QueryAccountRequest {
+                    address: address.to_bech32(&self.chain_prefix)?,
+                },
+                height,
+            )
             .await;
         match res {
-            Ok(account) => {
-                // null pointer if this fails to unwrap
-                let value = account.into_inner().account.unwrap();
-                let mut buf = BytesMut::with_capacity(value.value.len());
-                buf.extend_from_slice(&value.value);
-                let decoded: BaseAccount = BaseAccount::decode(buf)?;
-                Ok(decoded)
-            }
-            Err(e) => match e.code() {
-                _ => Err(CosmosGrpcError::RequestError { error: e }),
+            Ok(account) => match account.account {
+                Some(value) => {
+                    let mut buf = BytesMut::with_capacity(value.value.len());
+                    buf.extend_from_slice(&value.value);
+                    let decoded: BaseAccount = BaseAccount::decode(buf)?;
+                    Ok(decoded)
+                }
+                None => Err(CosmosGrpcError::NoToken),
             },
+            Err(CosmosGrpcError::RequestError { error }) if error.code() == TonicCode::NotFound => {
+                Err(CosmosGrpcError::NoToken)
+            }
+            Err(e) => Err(height_error(e, height)),
         }
     }
 
-    // Gets a transaction using it's hash value, TODO should fail if the transaction isn't found
-    pub async fn get_tx_by_hash(&self, txhash: String) -> Result<GetTxResponse, CosmosGrpcError> {
-        let mut txrpc = TxServiceClient::connect(self.url.clone()).await?;
-        let res = txrpc
-            .get_tx(GetTxRequest { hash: txhash })
-            .await?
-            .into_inner();
-        Ok(res)
+    /// Gets the public key `address` signs with along with it's algorithm, for example to
+    /// verify it's signatures or to build a multisig that includes it. Accounts only record
+    /// their key on chain with their first transaction, None is returned for accounts that
+    /// never signed one and for accounts that don't exist.
+    pub async fn get_account_pubkey(
+        &self,
+        address: Address,
+    ) -> Result<Option<AccountPubKey>, CosmosGrpcError> {
+        let res = self
+            .rpc
+            .account(
+                QueryAccountRequest {
+                    address: address.to_bech32(&self.chain_prefix)?,
+                },
+                None,
+            )
+            .await;
+        let account = match res {
+            Ok(res) => res.account,
+            Err(CosmosGrpcError::RequestError { error }) if error.code() == TonicCode::NotFound => {
+                None
+            }
+            Err(e) => return Err(e),
+        };
+        let prefix = format!("{}pub", self.chain_prefix);
+        match account {
+            Some(account) => {
+                Ok(account_pub_key(&account)?.map(|key| AccountPubKey::from_any(key, &prefix)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the auth module params, such as the maximum memo length, queried from the
+    /// node the first time and cached from then on, use `refresh_auth_params` to update them.
+    pub async fn get_auth_params(&self) -> Result<AuthParams, CosmosGrpcError> {
+        if let Some(params) = self.auth_params.read().unwrap().clone() {
+            return Ok(params);
+        }
+        self.refresh_auth_params().await
+    }
+
+    /// Queries the auth module params, replacing any cached value
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url))
+    )]
+    pub async fn refresh_auth_params(&self) -> Result<AuthParams, CosmosGrpcError> {
+        let res = self.rpc.auth_params(AuthParamsRequest {}).await?;
+        match res.params {
+            Some(params) => {
+                *self.auth_params.write().unwrap() = Some(params.clone());
+                Ok(params)
+            }
+            None => Err(CosmosGrpcError::BadResponse(
+                "No auth params returned".to_string(),
+            )),
+        }
+    }
+
+    /// Gets a transaction using it's hash value as the protobuf response of the Cosmos SDK
+    /// version this crate is built against, fields added in later versions are dropped.
+    /// The hash is normalized as in `get_tx_by_hash`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, tx_hash = %txhash))
+    )]
+    pub async fn get_raw_tx_by_hash(
+        &self,
+        txhash: String,
+    ) -> Result<GetTxResponse, CosmosGrpcError> {
+        let hash = match normalize_tx_hash(&txhash) {
+            Some(hash) => hash,
+            None => return Err(CosmosGrpcError::InvalidTxHash(txhash)),
+        };
+        self.rpc.get_tx(GetTxRequest { hash }).await
+    }
+
+    /// Gets a transaction using it's hash value, hashes copied from elsewhere may be lowercase
+    /// or `0x` prefixed and are normalized first, returns `InvalidTxHash` if the hash is not 64
+    /// hex characters. The response is decoded into a `TxRecord` which is the same for every
+    /// Cosmos SDK version, so chain upgrades don't change what is returned
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, tx_hash = %txhash))
+    )]
+    pub async fn get_tx_by_hash(&self, txhash: String) -> Result<TxRecord, CosmosGrpcError> {
+        let hash = match normalize_tx_hash(&txhash) {
+            Some(hash) => hash,
+            None => return Err(CosmosGrpcError::InvalidTxHash(txhash)),
+        };
+        let res = self.rpc.get_tx_raw(GetTxRequest { hash }).await?;
+        let res = GetTxResponseCompat::decode(res.as_slice()).map_err(|e| {
+            CosmosGrpcError::BadResponse(format!("Failed to decode tx response {}", e))
+        })?;
+        TxRecord::try_from(res)
+    }
+
+    /// Gets a transaction by it's hash along with it's result in decoded form, including it's
+    /// messages, fee, gas usage, events and the reason it failed if it did. Returns `NotFound`
+    /// if the transaction is not on chain. Use `get_raw_tx_by_hash` for the protobuf response
+    pub async fn get_tx_summary(&self, txhash: String) -> Result<TxSummary, CosmosGrpcError> {
+        match self.get_raw_tx_by_hash(txhash.clone()).await {
+            Ok(res) => TxSummary::try_from(res),
+            Err(CosmosGrpcError::RequestError { error }) if error.code() == TonicCode::NotFound => {
+                Err(CosmosGrpcError::NotFound(format!("tx {}", txhash)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the events emitted by the messages of a transaction in the same form for every Cosmos
+    /// SDK version, see `TxResponseEvents`. Unlike `get_tx_summary` this also works on v0.50 and
+    /// later chains which no longer return `logs`. Returns `NotFound` if the transaction is not
+    /// on chain
+    pub async fn get_tx_events(&self, txhash: String) -> Result<Vec<TxEvent>, CosmosGrpcError> {
+        let hash = match normalize_tx_hash(&txhash) {
+            Some(hash) => hash,
+            None => return Err(CosmosGrpcError::InvalidTxHash(txhash)),
+        };
+        let res: Result<GetTxEventsResponse, CosmosGrpcError> = unary_call(
+            self.channel().await?,
+            "/cosmos.tx.v1beta1.Service/GetTx",
+            GetTxRequest { hash },
+        )
+        .await;
+        match res {
+            Ok(res) => Ok(res.tx_response.unwrap_or_default().tx_events()),
+            Err(CosmosGrpcError::RequestError { error }) if error.code() == TonicCode::NotFound => {
+                Err(CosmosGrpcError::NotFound(format!("tx {}", txhash)))
+            }
+            Err(e) => Err(e),
+        }
     }
 
     pub async fn get_balances(&self, address: Address) -> Result<Vec<Coin>, CosmosGrpcError> {
-        let mut bankrpc = BankQueryClient::connect(self.url.clone()).await?;
-        let res = bankrpc
-            .all_balances(QueryAllBalancesRequest {
-                // chain prefix is validated as part of this client, so this can't
-                // panic
-                address: address.to_bech32(&self.chain_prefix).unwrap(),
-                pagination: None,
-            })
-            .await?
-            .into_inner();
+        self.get_balances_at_height(address, None).await
+    }
+
+    /// Identical to `get_balances` but queries the balances as they were at the provided block
+    /// height, or the latest state if None. Returns `HeightNotAvailable` if the node has pruned
+    /// the state at that height or has not reached it yet.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, address = %address, height = ?height))
+    )]
+    pub async fn get_balances_at_height(
+        &self,
+        address: Address,
+        height: Option<u64>,
+    ) -> Result<Vec<Coin>, CosmosGrpcError> {
+        let res = self
+            .rpc
+            .all_balances(
+                QueryAllBalancesRequest {
+                    address: address.to_bech32(&self.chain_prefix)?,
+                    pagination: None,
+                },
+                height,
+            )
+            .await
+            .map_err(|e| height_error(e, height))?;
         let balances = res.balances;
         let mut ret = Vec::new();
         for value in balances {
-            ret.push(value.into());
+            ret.push(Coin::try_from(value)?);
         }
         Ok(ret)
     }
 
+    /// Gets the balance of `denom` held by `address` as it was at the block `height`, for
+    /// example to reconcile accounts at a point in time. A zero balance is returned if the
+    /// address held none of `denom`. Nodes prune old state by default and so can only answer
+    /// for recent heights, querying further back requires an archive node (`pruning = "nothing"`).
+    /// Returns `HeightPruned` if the node no longer has the state at `height` and
+    /// `HeightNotAvailable` if it has not reached `height` yet.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, address = %address, denom, height))
+    )]
+    pub async fn get_balance_at_height(
+        &self,
+        address: Address,
+        denom: &str,
+        height: u64,
+    ) -> Result<Coin, CosmosGrpcError> {
+        let balances = match self.get_balances_at_height(address, Some(height)).await {
+            Ok(balances) => balances,
+            Err(CosmosGrpcError::HeightNotAvailable { height, message })
+                if message.contains("failed to load state at height") =>
+            {
+                return Err(CosmosGrpcError::HeightPruned { height, message })
+            }
+            Err(e) => return Err(e),
+        };
+        match balances.into_iter().find(|coin| coin.denom == denom) {
+            Some(coin) => Ok(coin),
+            None => Ok(Coin::new(0u8.into(), denom)),
+        }
+    }
+
     /// Grabs an up to date MessageArgs structure for an address,
     /// provided a fee value to insert into the structure. The goal of
     /// this function is to be very minimal and make a lot of choices for
     /// the user. Like how to handle changes in chain-id or timeout heights
+    ///
+    /// This is the building block for custom signing flows, the returned args can be
+    /// passed directly to `PrivateKey::sign_std_msg` or `PrivateKey::build_tx`. The
+    /// account number and sequence are fetched for `our_address`, the chain id is read
+    /// from the latest block header and the timeout height is set 100 blocks in the future.
+    /// Returns `NoToken` if the account does not exist on chain yet, which is the case until
+    /// it has received tokens, `NodeNotSynced` or `ChainNotRunning` if the node can't
+    /// provide a current block.
     pub async fn get_message_args(
         &self,
         our_address: Address,
         fee: Fee,
     ) -> Result<MessageArgs, CosmosGrpcError> {
-        let account_info = self.get_account_info(our_address).await?;
-        let latest_block = self.get_latest_block().await?;
+        self.get_message_args_with_override(our_address, fee, None)
+            .await
+    }
+
+    /// Identical to `get_message_args` except that if `account_override` is provided it's
+    /// account number and sequence are used verbatim and the account is not queried at all.
+    /// This is a recovery tool for when the sequence on chain is not the one you need, for
+    /// example to replace a stuck transaction or to sign ahead of a transaction sent elsewhere.
+    /// Nothing is validated, a wrong sequence is rejected by the node with an `incorrect account
+    /// sequence` error and a wrong account number fails signature verification.
+    pub async fn get_message_args_with_override(
+        &self,
+        our_address: Address,
+        fee: Fee,
+        account_override: Option<AccountOverride>,
+    ) -> Result<MessageArgs, CosmosGrpcError> {
+        let mut fee = fee;
+        if !self.fees_required {
+            fee.amount.clear();
+        }
+        let account = match account_override {
+            Some(account) => account,
+            None => {
+                let account_info = self.get_account_info(our_address).await?;
+                AccountOverride {
+                    account_number: account_info.account_number,
+                    sequence: account_info.sequence,
+                }
+            }
+        };
+        let latest_block = match (self.get_latest_block().await, &self.chain_id) {
+            (Ok(latest_block), _) => latest_block,
+            (Err(e), Some(chain_id)) => {
+                warn!(
+                    "Failed to get the latest block {}, signing for {} without a timeout height",
+                    e, chain_id
+                );
+                return Ok(MessageArgs {
+                    sequence: account.sequence,
+                    account_number: account.account_number,
+                    chain_id: chain_id.clone(),
+                    fee,
+                    timeout_height: 0,
+                });
+            }
+            (Err(e), None) => return Err(e),
+        };
 
         match latest_block {
             LatestBlock::Latest { block } => {
                 if let Some(header) = block.header {
+                    let chain_id = match &self.chain_id {
+                        Some(chain_id) if *chain_id != header.chain_id => {
+                            warn!(
+                                "Configured chain id {} does not match the node's chain id {}",
+                                chain_id, header.chain_id
+                            );
+                            chain_id.clone()
+                        }
+                        _ => header.chain_id,
+                    };
                     Ok(MessageArgs {
-                        sequence: account_info.sequence,
-                        account_number: account_info.account_number,
-                        chain_id: header.chain_id,
+                        sequence: account.sequence,
+                        account_number: account.account_number,
+                        chain_id,
                         fee,
                         timeout_height: header.height as u64 + 100,
                     })
@@ -196,4 +621,694 @@ impl Contact {
         }
         Err(CosmosGrpcError::NoBlockProduced { time: timeout })
     }
+
+    /// Waits until the chain reaches the target height, for example the end of an unbonding or
+    /// a governance voting period, returns `HeightTimeout` if it is not reached in time. The
+    /// chain is polled at half the recent average block time, between `MIN_HEIGHT_POLL_INTERVAL`
+    /// and `MAX_HEIGHT_POLL_INTERVAL`, or every second if the block time can't be estimated.
+    pub async fn wait_for_height(
+        &self,
+        target: u64,
+        timeout: Duration,
+    ) -> Result<(), CosmosGrpcError> {
+        let start = Instant::now();
+        let mut interval = None;
+        let mut last_height = None;
+        while Instant::now() - start < timeout {
+            match self.get_chain_status().await {
+                Ok(ChainStatus::Moving { block_height }) => {
+                    if block_height >= target {
+                        return Ok(());
+                    }
+                    last_height = Some(block_height);
+                }
+                Ok(ChainStatus::Syncing) => return Err(CosmosGrpcError::NodeNotSynced),
+                Ok(ChainStatus::WaitingToStart) => return Err(CosmosGrpcError::ChainNotRunning),
+                // we don't want a single error to exit this loop early
+                Err(_) => {}
+            }
+            if interval.is_none() {
+                interval = Some(self.height_poll_interval(last_height).await);
+            }
+            let remaining = timeout.saturating_sub(Instant::now() - start);
+            sleep(interval.unwrap_or(HEIGHT_POLL_FALLBACK).min(remaining)).await;
+        }
+        Err(CosmosGrpcError::HeightTimeout {
+            target,
+            height: last_height,
+            time: timeout,
+        })
+    }
+
+    async fn height_poll_interval(&self, height: Option<u64>) -> Duration {
+        let samples = match height {
+            Some(height) if height > 1 => u32::try_from(height - 1)
+                .unwrap_or(u32::MAX)
+                .min(HEIGHT_POLL_SAMPLES),
+            _ => return HEIGHT_POLL_FALLBACK,
+        };
+        match self.average_block_time(samples).await {
+            Ok(average) => (average / 2).clamp(MIN_HEIGHT_POLL_INTERVAL, MAX_HEIGHT_POLL_INTERVAL),
+            Err(_) => HEIGHT_POLL_FALLBACK,
+        }
+    }
+
+    /// Gets the block at the provided height, returns None if the node has no block at that
+    /// height. Nodes that prune their block store, which is most of them, return an error for
+    /// heights older than the oldest block they have kept.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, height = height))
+    )]
+    pub async fn get_block(&self, height: u64) -> Result<Option<Block>, CosmosGrpcError> {
+        let res = self
+            .rpc
+            .get_block_by_height(GetBlockByHeightRequest {
+                height: height as i64,
+            })
+            .await?;
+        Ok(res.block)
+    }
+
+    /// Gets the consensus validator set, the validators that sign blocks along with their
+    /// consensus keys and voting power, at the provided height or the latest height if None.
+    /// This is the view of the consensus engine, use `get_validators_list` for the staking
+    /// module's view of validator operators. Every page of results is queried at the same height.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, height = ?height))
+    )]
+    pub async fn get_validator_set(
+        &self,
+        height: Option<u64>,
+    ) -> Result<ValidatorSet, CosmosGrpcError> {
+        let mut grpc = TendermintServiceClient::new(self.channel().await?);
+        let (block_height, first_page, mut pagination) = match height {
+            Some(height) => {
+                let res = grpc
+                    .get_validator_set_by_height(GetValidatorSetByHeightRequest {
+                        height: height as i64,
+                        pagination: None,
+                    })
+                    .await?
+                    .into_inner();
+                (res.block_height, res.validators, next_page(&res.pagination))
+            }
+            None => {
+                let res = grpc
+                    .get_latest_validator_set(GetLatestValidatorSetRequest { pagination: None })
+                    .await?
+                    .into_inner();
+                (res.block_height, res.validators, next_page(&res.pagination))
+            }
+        };
+        let mut validators = Vec::new();
+        for validator in first_page {
+            validators.push(ConsensusValidator::try_from(validator)?);
+        }
+        while pagination.is_some() {
+            let res = grpc
+                .get_validator_set_by_height(GetValidatorSetByHeightRequest {
+                    height: block_height,
+                    pagination,
+                })
+                .await?
+                .into_inner();
+            for validator in res.validators {
+                validators.push(ConsensusValidator::try_from(validator)?);
+            }
+            pagination = next_page(&res.pagination);
+        }
+        Ok(ValidatorSet {
+            block_height: block_height as u64,
+            validators,
+        })
+    }
+
+    /// Gets the block at the provided height with each of it's transactions paired with it's
+    /// execution result, queried from the tx index of the node. Results are matched to the
+    /// transactions in the block by hash rather than by position, so transactions that fail
+    /// to decode or were not indexed get a `None` result instead of shifting the results of
+    /// the transactions after them. The results are searched by height, if the search fails,
+    /// for example because the node can't decode one of the transactions, they are queried
+    /// one transaction at a time instead. Returns the error of the block query if the node
+    /// has no block at that height.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, height = height))
+    )]
+    pub async fn get_block_with_results(
+        &self,
+        height: u64,
+    ) -> Result<BlockWithResults, CosmosGrpcError> {
+        let block = match self.get_block(height).await? {
+            Some(block) => block,
+            None => return Err(CosmosGrpcError::NotFound(format!("block {}", height))),
+        };
+        let raw_txs = match &block.data {
+            Some(data) => data.txs.clone(),
+            None => Vec::new(),
+        };
+
+        let mut results = HashMap::new();
+        if !raw_txs.is_empty() {
+            results = match self.search_block_results(height).await {
+                Ok(results) => results,
+                Err(e) => {
+                    warn!(
+                        "Tx search for block {} failed {:?}, querying each tx",
+                        height, e
+                    );
+                    self.query_block_results(&raw_txs).await?
+                }
+            };
+        }
+
+        let txs = raw_txs
+            .into_iter()
+            .map(|raw| {
+                let hash = tx_hash(&raw);
+                BlockTx {
+                    result: results.remove(&hash),
+                    tx: Tx::decode(raw.as_slice()).ok(),
+                    hash,
+                    raw,
+                }
+            })
+            .collect();
+        Ok(BlockWithResults { block, txs })
+    }
+
+    /// The results of the txs at `height` by hash, from a tx search by height
+    async fn search_block_results(
+        &self,
+        height: u64,
+    ) -> Result<HashMap<String, TxResponse>, CosmosGrpcError> {
+        let mut results = HashMap::new();
+        // the tx search used by this endpoint only supports offset pagination
+        let mut offset = 0;
+        loop {
+            let res = self
+                .rpc
+                .get_txs_event(GetTxsEventRequest {
+                    events: vec![format!("tx.height={}", height)],
+                    pagination: Some(PageRequest {
+                        key: Vec::new(),
+                        offset,
+                        limit: TX_SEARCH_PAGE_SIZE,
+                        count_total: true,
+                    }),
+                    order_by: OrderBy::Asc.into(),
+                })
+                .await?;
+            let count = res.tx_responses.len() as u64;
+            for response in res.tx_responses {
+                results.insert(response.txhash.to_uppercase(), response);
+            }
+            offset += count;
+            let total = res.pagination.map(|p| p.total).unwrap_or(0);
+            if count == 0 || offset >= total {
+                return Ok(results);
+            }
+        }
+    }
+
+    /// The results of `raw_txs` by hash, queried one tx at a time. Txs that are not indexed
+    /// or that the node fails to decode have no result
+    async fn query_block_results(
+        &self,
+        raw_txs: &[Vec<u8>],
+    ) -> Result<HashMap<String, TxResponse>, CosmosGrpcError> {
+        let mut results = HashMap::new();
+        for raw in raw_txs {
+            let hash = tx_hash(raw);
+            match self.rpc.get_tx(GetTxRequest { hash: hash.clone() }).await {
+                Ok(res) => {
+                    if let Some(response) = res.tx_response {
+                        results.insert(hash, response);
+                    }
+                }
+                Err(CosmosGrpcError::RequestError { error })
+                    if error.code() == TonicCode::NotFound
+                        || Tx::decode(raw.as_slice()).is_err() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Searches the `limit` most recent transactions sent by `sender`, found by their
+    /// `message.sender` event, for a successful one with exactly `memo` as it's memo. Failed
+    /// transactions are skipped since none of their messages were executed. This relies on the
+    /// tx index of the node, nodes with indexing disabled return an error and nodes that prune
+    /// old transactions from the index won't find them
+    pub async fn find_tx_with_memo(
+        &self,
+        sender: Address,
+        memo: &str,
+        limit: u64,
+    ) -> Result<Option<TxResponse>, CosmosGrpcError> {
+        let mut offset = 0;
+        while offset < limit {
+            let res = self
+                .rpc
+                .get_txs_event(GetTxsEventRequest {
+                    events: vec![format!("message.sender='{}'", sender)],
+                    pagination: Some(PageRequest {
+                        key: Vec::new(),
+                        offset,
+                        // full pages, v0.50 nodes can only page by multiples of the limit
+                        limit: TX_SEARCH_PAGE_SIZE,
+                        count_total: true,
+                    }),
+                    order_by: OrderBy::Desc.into(),
+                })
+                .await?;
+            let count = res.tx_responses.len() as u64;
+            let remaining = (limit - offset) as usize;
+            for (index, response) in res.tx_responses.into_iter().enumerate().take(remaining) {
+                let tx_memo = res
+                    .txs
+                    .get(index)
+                    .cloned()
+                    .or_else(|| response.tx.as_ref().and_then(|tx| decode_any(tx).ok()))
+                    .and_then(|tx: Tx| tx.body)
+                    .map(|body| body.memo);
+                if response.code == 0 && tx_memo.as_deref() == Some(memo) {
+                    return Ok(Some(response));
+                }
+            }
+            offset += count;
+            let total = res.pagination.map(|p| p.total).unwrap_or(0);
+            if count == 0 || offset >= total {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Gets the number of transactions, the total gas used, the total fees paid and the
+    /// proposer of the block at the provided height, see `get_block_with_results` for how the
+    /// results are queried. Returns the error of the block query if the node has no block at
+    /// that height.
+    pub async fn get_block_stats(&self, height: u64) -> Result<BlockStats, CosmosGrpcError> {
+        let block = self.get_block_with_results(height).await?;
+        BlockStats::new(&block, &format!("{}valcons", self.chain_prefix))
+            .map_err(CosmosGrpcError::BadResponse)
+    }
+
+    /// Estimates the average time between blocks using the timestamps of the latest block and
+    /// the block `samples` blocks before it. Block times vary with validator performance, the
+    /// number of transactions and the chain's consensus params, so this is only an estimate of
+    /// the recent past. More samples smooth out individual slow blocks but respond slower to
+    /// changes, and the sampled block must not have been pruned by the node.
+    pub async fn average_block_time(&self, samples: u32) -> Result<Duration, CosmosGrpcError> {
+        let (latest_height, latest_time) = self.latest_block_height_and_time().await?;
+        if samples == 0 || u64::from(samples) >= latest_height {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "Can not sample {} blocks at height {}",
+                samples, latest_height
+            )));
+        }
+        let earlier_height = latest_height - u64::from(samples);
+        let earlier_time = match self.get_block(earlier_height).await? {
+            Some(block) => block_time(&block).ok_or_else(no_block_time)?,
+            None => {
+                return Err(CosmosGrpcError::NotFound(format!(
+                    "block {}",
+                    earlier_height
+                )))
+            }
+        };
+        match latest_time.duration_since(earlier_time) {
+            Ok(elapsed) => Ok(elapsed / samples),
+            Err(_) => Err(CosmosGrpcError::BadResponse(
+                "Block times are not increasing".to_string(),
+            )),
+        }
+    }
+
+    /// Estimates the time at which the chain will reach the target height, for example to
+    /// choose a timeout height for a transaction or to know when an unbonding completes. For
+    /// heights that have already passed the actual block time is returned. Future heights are
+    /// extrapolated from the latest block using `average_block_time` over the last
+    /// `DEFAULT_BLOCK_TIME_SAMPLES` blocks, so the further away the target the less accurate
+    /// the estimate, see `average_block_time`.
+    pub async fn estimate_time_at_height(
+        &self,
+        target: u64,
+    ) -> Result<SystemTime, CosmosGrpcError> {
+        let (latest_height, latest_time) = self.latest_block_height_and_time().await?;
+        if target <= latest_height {
+            return match self.get_block(target).await? {
+                Some(block) => block_time(&block).ok_or_else(no_block_time),
+                None => Err(CosmosGrpcError::NotFound(format!("block {}", target))),
+            };
+        }
+        let max_samples = u32::try_from(latest_height - 1).unwrap_or(u32::MAX);
+        let average = self
+            .average_block_time(DEFAULT_BLOCK_TIME_SAMPLES.min(max_samples))
+            .await?;
+        let remaining = average.as_secs_f64() * (target - latest_height) as f64;
+        Ok(latest_time + Duration::from_secs_f64(remaining))
+    }
+
+    async fn latest_block_height_and_time(&self) -> Result<(u64, SystemTime), CosmosGrpcError> {
+        match self.get_latest_block().await? {
+            LatestBlock::Latest { block } => match &block.header {
+                Some(header) => {
+                    let time = block_time(&block).ok_or_else(no_block_time)?;
+                    Ok((header.height as u64, time))
+                }
+                None => Err(CosmosGrpcError::BadResponse(
+                    "Null block header?".to_string(),
+                )),
+            },
+            LatestBlock::Syncing { .. } => Err(CosmosGrpcError::NodeNotSynced),
+            LatestBlock::WaitingToStart => Err(CosmosGrpcError::ChainNotRunning),
+        }
+    }
+}
+
+/// The gas price `Contact::bootstrap` configures, the node's price in the bond denom or the
+/// first price of the node
+fn default_gas_price(info: &ChainInfo) -> Option<GasPrice> {
+    let prices = info.min_gas_prices.as_ref()?;
+    info.bond_denom
+        .as_deref()
+        .and_then(|denom| prices.for_denom(denom))
+        .or_else(|| prices.0.first())
+        .cloned()
+}
+
+/// Lists the registered implementations of an interface using the sdk reflection service,
+/// cosmos-sdk-proto does not generate a client for this service so the call is made directly
+async fn list_implementations(
+    channel: TracedChannel,
+    interface_name: &str,
+) -> Result<Vec<String>, CosmosGrpcError> {
+    let res: ListImplementationsResponse = unary_call(
+        channel,
+        "/cosmos.base.reflection.v1beta1.ReflectionService/ListImplementations",
+        ListImplementationsRequest {
+            interface_name: interface_name.to_string(),
+        },
+    )
+    .await?;
+    Ok(res.implementation_message_names)
+}
+
+/// Converts the errors returned by the sdk for a query at a height it has no state for into
+/// `HeightNotAvailable`, these are InvalidArgument errors distinguished only by their message
+fn height_error(e: CosmosGrpcError, height: Option<u64>) -> CosmosGrpcError {
+    match (e, height) {
+        (CosmosGrpcError::RequestError { error }, Some(height))
+            if error.code() == TonicCode::InvalidArgument
+                && (error.message().contains("failed to load state at height")
+                    || error
+                        .message()
+                        .contains("cannot query with height in the future")) =>
+        {
+            CosmosGrpcError::HeightNotAvailable {
+                height,
+                message: error.message().to_string(),
+            }
+        }
+        (e, _) => e,
+    }
+}
+
+/// The number of results requested per page when searching for the txs of a block
+const TX_SEARCH_PAGE_SIZE: u64 = 100;
+
+/// The number of blocks sampled by `Contact::estimate_time_at_height`
+pub const DEFAULT_BLOCK_TIME_SAMPLES: u32 = 100;
+
+/// The shortest interval at which `Contact::wait_for_height` polls the chain
+pub const MIN_HEIGHT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// The longest interval at which `Contact::wait_for_height` polls the chain, so a slow
+/// estimate doesn't delay noticing the target height
+pub const MAX_HEIGHT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const HEIGHT_POLL_FALLBACK: Duration = Duration::from_secs(1);
+/// Few samples are used as they are queried on every wait
+const HEIGHT_POLL_SAMPLES: u32 = 10;
+
+/// Reads the timestamp from the header of a block
+fn block_time(block: &Block) -> Option<SystemTime> {
+    let time = block.header.as_ref()?.time.as_ref()?;
+    if time.seconds < 0 || time.nanos < 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::new(time.seconds as u64, time.nanos as u32))
+}
+
+fn no_block_time() -> CosmosGrpcError {
+    CosmosGrpcError::BadResponse("Block has no valid time".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::mock_contact;
+    use crate::client::rpc::{request_at_height, BLOCK_HEIGHT_HEADER};
+    use crate::private_key::PrivateKey;
+
+    #[actix_rt::test]
+    async fn test_mock_get_tx_by_hash() {
+        let (contact, mock, _key) = mock_contact();
+        let hash = "AB".repeat(32);
+        mock.include_tx(TxResponse {
+            txhash: hash.clone(),
+            height: 7,
+            gas_used: 1_000,
+            ..Default::default()
+        });
+        let record = contact
+            .get_tx_by_hash(format!("0x{}", hash.to_lowercase()))
+            .await
+            .unwrap();
+        assert_eq!(record.hash, hash);
+        assert_eq!(record.height, 7);
+        assert_eq!(record.gas_used, 1_000);
+        assert!(contact.get_tx_by_hash("CD".repeat(32)).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_wait_for_height() {
+        let (contact, mock, _) = mock_contact();
+        contact
+            .wait_for_height(10, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        match contact.wait_for_height(12, Duration::from_secs(1)).await {
+            Err(CosmosGrpcError::HeightTimeout { target, height, .. }) => {
+                assert_eq!(target, 12);
+                assert_eq!(height, Some(10));
+            }
+            _ => panic!("Expected height timeout"),
+        }
+
+        let producer = mock.clone();
+        actix_rt::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            producer.set_latest_block("testing", 12);
+        });
+        contact
+            .wait_for_height(12, Duration::from_secs(5))
+            .await
+            .unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_sequence_gap() {
+        let (contact, mock, key) = mock_contact();
+        let address = key.to_address("cosmos").unwrap();
+        mock.add_account(&address, 6, 5);
+        assert_eq!(contact.sequence_gap(address, 5).await.unwrap(), 0);
+        assert_eq!(contact.sequence_gap(address, 3).await.unwrap(), 2);
+        assert_eq!(contact.sequence_gap(address, 8).await.unwrap(), -3);
+
+        let unknown = Address::from_bytes([9; 20], "cosmos").unwrap();
+        assert_eq!(contact.sequence_gap(unknown, 1).await.unwrap(), -1);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_block_time() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let (contact, mock, _) = mock_contact();
+        let start = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let time_at = |height: u64| start + Duration::from_secs(5 * height);
+        for height in (1..=11).filter(|h| *h != 6) {
+            mock.add_block("testing", height as i64, time_at(height));
+        }
+
+        assert_eq!(
+            contact.average_block_time(10).await.unwrap(),
+            Duration::from_secs(5)
+        );
+        assert!(matches!(
+            contact.average_block_time(11).await,
+            Err(CosmosGrpcError::BadInput(_))
+        ));
+        // the sampled block was pruned
+        assert!(matches!(
+            contact.average_block_time(5).await,
+            Err(CosmosGrpcError::RequestError { .. })
+        ));
+        let estimate: SystemTime = contact.estimate_time_at_height(21).await.unwrap();
+        assert_eq!(estimate, time_at(21));
+        assert_eq!(
+            contact.estimate_time_at_height(3).await.unwrap(),
+            time_at(3)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_query_at_height() {
+        let (contact, mock, key) = mock_contact();
+        let address = key.to_address("cosmos").unwrap();
+        *mock.earliest_state_height.lock().unwrap() = 5;
+
+        contact.get_balances(address).await.unwrap();
+        contact
+            .get_balances_at_height(address, Some(7))
+            .await
+            .unwrap();
+        let account = contact
+            .get_account_info_at_height(address, Some(10))
+            .await
+            .unwrap();
+        assert_eq!(account.account_number, 5);
+        for height in [4, 11] {
+            match contact.get_balances_at_height(address, Some(height)).await {
+                Err(CosmosGrpcError::HeightNotAvailable { height: h, .. }) => assert_eq!(h, height),
+                _ => panic!("Expected height not available"),
+            }
+        }
+        assert!(matches!(
+            contact.get_account_info_at_height(address, Some(4)).await,
+            Err(CosmosGrpcError::HeightNotAvailable { height: 4, .. })
+        ));
+        assert_eq!(
+            *mock.query_heights.lock().unwrap(),
+            vec![None, Some(7), Some(10), Some(4), Some(11), Some(4)]
+        );
+
+        let request = request_at_height((), Some(42));
+        assert_eq!(request.metadata().get(BLOCK_HEIGHT_HEADER).unwrap(), "42");
+        assert!(request_at_height((), None).metadata().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_balance_at_height() {
+        let (contact, mock, key) = mock_contact();
+        let address = key.to_address("cosmos").unwrap();
+        *mock.earliest_state_height.lock().unwrap() = 5;
+        mock.balances.lock().unwrap().insert(
+            address.to_bech32("cosmos").unwrap(),
+            vec![cosmos_sdk_proto::cosmos::base::v1beta1::Coin {
+                denom: "stake".to_string(),
+                amount: "100".to_string(),
+            }],
+        );
+
+        let balance = contact
+            .get_balance_at_height(address, "stake", 7)
+            .await
+            .unwrap();
+        assert_eq!(balance, Coin::new(100u8.into(), "stake"));
+        let balance = contact
+            .get_balance_at_height(address, "footoken", 7)
+            .await
+            .unwrap();
+        assert_eq!(balance, Coin::new(0u8.into(), "footoken"));
+        assert!(matches!(
+            contact.get_balance_at_height(address, "stake", 4).await,
+            Err(CosmosGrpcError::HeightPruned { height: 4, .. })
+        ));
+        assert!(matches!(
+            contact.get_balance_at_height(address, "stake", 11).await,
+            Err(CosmosGrpcError::HeightNotAvailable { height: 11, .. })
+        ));
+        assert_eq!(
+            *mock.query_heights.lock().unwrap(),
+            vec![Some(7), Some(7), Some(4), Some(11)]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_block_with_results() {
+        use crate::utils::tx_hash;
+        use std::time::SystemTime;
+
+        let (contact, mock, key) = mock_contact();
+        let fee = Fee::default();
+        let args = contact
+            .get_message_args(key.to_address("cosmos").unwrap(), fee)
+            .await
+            .unwrap();
+        let valid = key.sign_std_msg(&[], args.clone(), "one").unwrap();
+        let unindexed = key.sign_std_msg(&[], args, "two").unwrap();
+        let garbage = vec![0xff, 0xff];
+        mock.add_block("testing", 12, SystemTime::now());
+        mock.set_block_txs(12, vec![garbage.clone(), valid.clone(), unindexed.clone()]);
+        mock.include_tx(TxResponse {
+            txhash: tx_hash(&valid),
+            height: 12,
+            gas_used: 1234,
+            ..Default::default()
+        });
+
+        let block = contact.get_block_with_results(12).await.unwrap();
+        assert_eq!(block.txs.len(), 3);
+        assert!(block.txs[0].tx.is_none());
+        assert!(block.txs[0].result.is_none());
+        assert_eq!(block.txs[1].raw, valid);
+        assert!(block.txs[1].tx.is_some());
+        assert_eq!(block.txs[1].result.as_ref().unwrap().gas_used, 1234);
+        assert_eq!(block.txs[2].hash, tx_hash(&unindexed));
+        assert!(block.txs[2].result.is_none());
+
+        // the same results are queried per tx when the search fails
+        *mock.tx_search_error.lock().unwrap() = Some(tonic::Status::internal("decode failed"));
+        let fallback = contact.get_block_with_results(12).await.unwrap();
+        let results = |block: &BlockWithResults| -> Vec<_> {
+            block.txs.iter().map(|tx| tx.result.clone()).collect()
+        };
+        assert_eq!(results(&fallback), results(&block));
+        assert!(matches!(
+            contact.get_block_with_results(13).await,
+            Err(CosmosGrpcError::RequestError { .. })
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_account_override() {
+        let (contact, mock, _) = mock_contact();
+        // not on chain, so only usable with an override
+        let key = PrivateKey::from_secret(b"mock rpc unknown key");
+        let address = key.to_address("cosmos").unwrap();
+        assert!(matches!(
+            contact.get_message_args(address, Fee::default()).await,
+            Err(CosmosGrpcError::NoToken)
+        ));
+        let account = AccountOverride {
+            account_number: 9,
+            sequence: 42,
+        };
+        let args = contact
+            .get_message_args_with_override(address, Fee::default(), Some(account))
+            .await
+            .unwrap();
+        assert_eq!((args.account_number, args.sequence), (9, 42));
+        assert_eq!(args.chain_id, "testing");
+
+        contact
+            .create_and_send_with_override(&[], Fee::default(), "", key, None, Some(account))
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 1);
+    }
 }
@@ -1,23 +1,446 @@
 //! Contains utility functions for interacting with and modifying Cosmos validator staking status
 
+use crate::client::rpc::unary_call;
+use crate::client::types::{ProposalStatus, VoteOption};
 use crate::client::MEMO;
+use crate::decimal::Decimal;
 use crate::error::CosmosGrpcError;
+use crate::utils::{decode_any, next_page};
 use crate::Coin;
 use crate::Contact;
 use crate::Fee;
 use crate::Msg;
 use crate::PrivateKey;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageResponse;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::CommunityPoolSpendProposal;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::query_client::QueryClient as GovQueryClient;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::MsgSubmitProposal;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::MsgVote;
-use cosmos_sdk_proto::cosmos::gov::v1beta1::ProposalStatus;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::Proposal as ProtoProposal;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryProposalRequest;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryProposalsRequest;
 use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryProposalsResponse;
-use cosmos_sdk_proto::cosmos::gov::v1beta1::VoteOption;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryTallyResultRequest;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::QueryVotesRequest;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::TallyResult;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::TextProposal;
+use cosmos_sdk_proto::cosmos::params::v1beta1::ParameterChangeProposal;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
-use prost_types::Any;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::CancelSoftwareUpgradeProposal;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::Plan;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::SoftwareUpgradeProposal;
+use prost::Message;
+use prost_types::{Any, Timestamp};
+use std::convert::TryFrom;
 use std::time::Duration;
+use std::time::SystemTime;
+use tonic::Code as TonicCode;
+use tonic::Status;
+
+/// The type url of `MsgVoteWeighted`
+pub const MSG_VOTE_WEIGHTED_TYPE_URL: &str = "/cosmos.gov.v1beta1.MsgVoteWeighted";
+
+/// WeightedVoteOption defines a unit of vote for vote split, introduced in Cosmos SDK v0.43
+/// and not part of the cosmos-sdk-proto version used by this crate
+#[derive(Clone, PartialEq, Message)]
+pub struct WeightedVoteOption {
+    #[prost(int32, tag = "1")]
+    pub option: i32,
+    /// An `sdk.Dec` in it's protobuf encoding, see `Decimal::to_sdk_dec_string`
+    #[prost(string, tag = "2")]
+    pub weight: String,
+}
+
+/// MsgVoteWeighted defines a message to cast a vote split between several options
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgVoteWeighted {
+    #[prost(uint64, tag = "1")]
+    pub proposal_id: u64,
+    #[prost(string, tag = "2")]
+    pub voter: String,
+    #[prost(message, repeated, tag = "3")]
+    pub options: Vec<WeightedVoteOption>,
+}
+
+/// Vote defines a vote on a governance proposal, with the weighted `options` field added in
+/// Cosmos SDK v0.43 that the cosmos-sdk-proto version used by this crate does not decode
+#[derive(Clone, PartialEq, Message)]
+pub struct Vote {
+    #[prost(uint64, tag = "1")]
+    pub proposal_id: u64,
+    #[prost(string, tag = "2")]
+    pub voter: String,
+    /// Deprecated, unspecified for weighted votes
+    #[prost(int32, tag = "3")]
+    pub option: i32,
+    #[prost(message, repeated, tag = "4")]
+    pub options: Vec<WeightedVoteOption>,
+}
+
+/// QueryVotesResponse is the response type for the Query/Votes RPC method, decoding the
+/// weighted options of the votes
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryVotesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub votes: Vec<Vote>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
+
+/// Checks a weighted vote the same way the chain does, every weight must be positive,
+/// no option may appear twice and the weights must sum to exactly one
+fn weighted_vote_options(
+    votes: &[(VoteOption, Decimal)],
+) -> Result<Vec<WeightedVoteOption>, String> {
+    if votes.is_empty() {
+        return Err("A weighted vote needs at least one option".to_string());
+    }
+    let zero = Decimal::from(0u8);
+    let mut total = zero;
+    let mut options = Vec::new();
+    for (i, (option, weight)) in votes.iter().enumerate() {
+        if *weight <= zero {
+            return Err(format!(
+                "Weight {} for {:?} is not positive",
+                weight, option
+            ));
+        }
+        if votes[..i].iter().any(|(o, _)| o == option) {
+            return Err(format!("{:?} appears more than once", option));
+        }
+        total = total
+            .checked_add(weight)
+            .ok_or_else(|| "Weights overflow".to_string())?;
+        options.push(WeightedVoteOption {
+            option: (*option).into(),
+            weight: weight.to_sdk_dec_string(),
+        });
+    }
+    if total != Decimal::from(1u8) {
+        return Err(format!("Weights sum to {} not 1", total));
+    }
+    Ok(options)
+}
+
+/// The content of a governance proposal decoded from it's Any encoding, proposal
+/// types this crate does not know about are returned as the raw Any
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProposalContent {
+    Text(TextProposal),
+    ParameterChange(ParameterChangeProposal),
+    SoftwareUpgrade(SoftwareUpgradeProposal),
+    CancelSoftwareUpgrade(CancelSoftwareUpgradeProposal),
+    CommunityPoolSpend(CommunityPoolSpendProposal),
+    Unknown(Any),
+}
+
+impl ProposalContent {
+    /// Decodes proposal content, an error is only returned if the type url is
+    /// known but the value does not decode as that type
+    pub fn decode(any: &Any) -> Result<ProposalContent, prost::DecodeError> {
+        Ok(match any.type_url.as_str() {
+            "/cosmos.gov.v1beta1.TextProposal" => ProposalContent::Text(decode_any(any)?),
+            "/cosmos.params.v1beta1.ParameterChangeProposal" => {
+                ProposalContent::ParameterChange(decode_any(any)?)
+            }
+            "/cosmos.upgrade.v1beta1.SoftwareUpgradeProposal" => {
+                ProposalContent::SoftwareUpgrade(decode_any(any)?)
+            }
+            "/cosmos.upgrade.v1beta1.CancelSoftwareUpgradeProposal" => {
+                ProposalContent::CancelSoftwareUpgrade(decode_any(any)?)
+            }
+            "/cosmos.distribution.v1beta1.CommunityPoolSpendProposal" => {
+                ProposalContent::CommunityPoolSpend(decode_any(any)?)
+            }
+            _ => ProposalContent::Unknown(any.clone()),
+        })
+    }
+}
+
+/// MsgExecLegacyContent wraps a v1beta1 proposal content in a gov v1 proposal, introduced in
+/// Cosmos SDK v0.46 along with the other gov v1 types below
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgExecLegacyContent {
+    #[prost(message, optional, tag = "1")]
+    pub content: Option<Any>,
+    #[prost(string, tag = "2")]
+    pub authority: String,
+}
+
+/// MsgCommunityPoolSpend spends from the community pool, replacing
+/// `CommunityPoolSpendProposal` in gov v1 proposals
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgCommunityPoolSpend {
+    #[prost(string, tag = "1")]
+    pub authority: String,
+    #[prost(string, tag = "2")]
+    pub recipient: String,
+    #[prost(message, repeated, tag = "3")]
+    pub amount: Vec<ProtoCoin>,
+}
+
+/// MsgSoftwareUpgrade schedules an upgrade, replacing `SoftwareUpgradeProposal` in gov v1
+/// proposals
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgSoftwareUpgrade {
+    #[prost(string, tag = "1")]
+    pub authority: String,
+    #[prost(message, optional, tag = "2")]
+    pub plan: Option<Plan>,
+}
+
+/// MsgCancelUpgrade cancels the scheduled upgrade, replacing `CancelSoftwareUpgradeProposal`
+/// in gov v1 proposals
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgCancelUpgrade {
+    #[prost(string, tag = "1")]
+    pub authority: String,
+}
+
+/// A gov v1 proposal, the tally result has the same encoding as the v1beta1 tally
+#[derive(Clone, PartialEq, Message)]
+pub struct ProposalV1 {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub messages: Vec<Any>,
+    #[prost(int32, tag = "3")]
+    pub status: i32,
+    #[prost(message, optional, tag = "4")]
+    pub final_tally_result: Option<TallyResult>,
+    #[prost(message, optional, tag = "5")]
+    pub submit_time: Option<Timestamp>,
+    #[prost(message, optional, tag = "6")]
+    pub deposit_end_time: Option<Timestamp>,
+    #[prost(message, repeated, tag = "7")]
+    pub total_deposit: Vec<ProtoCoin>,
+    #[prost(message, optional, tag = "8")]
+    pub voting_start_time: Option<Timestamp>,
+    #[prost(message, optional, tag = "9")]
+    pub voting_end_time: Option<Timestamp>,
+    #[prost(string, tag = "10")]
+    pub metadata: String,
+    /// Only set since Cosmos SDK v0.47
+    #[prost(string, tag = "11")]
+    pub title: String,
+    /// Only set since Cosmos SDK v0.47
+    #[prost(string, tag = "12")]
+    pub summary: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryProposalV1Request {
+    #[prost(uint64, tag = "1")]
+    pub proposal_id: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryProposalV1Response {
+    #[prost(message, optional, tag = "1")]
+    pub proposal: Option<ProposalV1>,
+}
+
+/// A message of a gov v1 proposal decoded from it's Any encoding, executed by the gov module
+/// if the proposal passes. Messages this crate does not know about, such as the
+/// `MsgUpdateParams` of each module, are returned as the raw Any
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProposalMessage {
+    /// A v1beta1 proposal content submitted as a v1 proposal
+    ExecLegacyContent {
+        content: Option<ProposalContent>,
+        authority: String,
+    },
+    CommunityPoolSpend(MsgCommunityPoolSpend),
+    SoftwareUpgrade(MsgSoftwareUpgrade),
+    CancelUpgrade(MsgCancelUpgrade),
+    Unknown(Any),
+}
+
+impl ProposalMessage {
+    /// Decodes a proposal message, an error is only returned if the type url is known but
+    /// the value does not decode as that type
+    pub fn decode(any: &Any) -> Result<ProposalMessage, prost::DecodeError> {
+        Ok(match any.type_url.as_str() {
+            "/cosmos.gov.v1.MsgExecLegacyContent" => {
+                let msg: MsgExecLegacyContent = decode_any(any)?;
+                ProposalMessage::ExecLegacyContent {
+                    content: match msg.content {
+                        Some(content) => Some(ProposalContent::decode(&content)?),
+                        None => None,
+                    },
+                    authority: msg.authority,
+                }
+            }
+            "/cosmos.distribution.v1beta1.MsgCommunityPoolSpend" => {
+                ProposalMessage::CommunityPoolSpend(decode_any(any)?)
+            }
+            "/cosmos.upgrade.v1beta1.MsgSoftwareUpgrade" => {
+                ProposalMessage::SoftwareUpgrade(decode_any(any)?)
+            }
+            "/cosmos.upgrade.v1beta1.MsgCancelUpgrade" => {
+                ProposalMessage::CancelUpgrade(decode_any(any)?)
+            }
+            _ => ProposalMessage::Unknown(any.clone()),
+        })
+    }
+}
+
+/// A governance proposal with it's content decoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    pub proposal_id: u64,
+    /// The content of a v1beta1 proposal, for a v1 proposal the content of it's message if
+    /// it's only message is a `MsgExecLegacyContent`. None if there is no content
+    pub content: Option<ProposalContent>,
+    /// The messages of a gov v1 proposal in order, empty for v1beta1 proposals
+    pub messages: Vec<ProposalMessage>,
+    pub status: ProposalStatus,
+    /// The result of the vote, only populated once voting has ended, use
+    /// `Contact::get_tally` for the current tally during the voting period
+    pub final_tally_result: Option<TallyResult>,
+    pub submit_time: Option<SystemTime>,
+    /// The time at which the proposal is removed if it has not reached it's min deposit
+    pub deposit_end_time: Option<SystemTime>,
+    pub total_deposit: Vec<Coin>,
+    pub voting_start_time: Option<SystemTime>,
+    pub voting_end_time: Option<SystemTime>,
+}
+
+impl TryFrom<ProtoProposal> for Proposal {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: ProtoProposal) -> Result<Self, Self::Error> {
+        let content = match value.content {
+            Some(any) => Some(ProposalContent::decode(&any)?),
+            None => None,
+        };
+        let status = match ProposalStatus::try_from(value.status) {
+            Ok(status) => status,
+            Err(v) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Invalid proposal status {}",
+                    v
+                )))
+            }
+        };
+        Ok(Proposal {
+            proposal_id: value.proposal_id,
+            content,
+            messages: Vec::new(),
+            status,
+            final_tally_result: value.final_tally_result,
+            submit_time: value.submit_time.map(|t| t.into()),
+            deposit_end_time: value.deposit_end_time.map(|t| t.into()),
+            total_deposit: value
+                .total_deposit
+                .into_iter()
+                .map(Coin::try_from)
+                .collect::<Result<_, _>>()?,
+            voting_start_time: value.voting_start_time.map(|t| t.into()),
+            voting_end_time: value.voting_end_time.map(|t| t.into()),
+        })
+    }
+}
+
+impl TryFrom<ProposalV1> for Proposal {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: ProposalV1) -> Result<Self, Self::Error> {
+        let messages = value
+            .messages
+            .iter()
+            .map(ProposalMessage::decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        let content = match messages.as_slice() {
+            [ProposalMessage::ExecLegacyContent { content, .. }] => content.clone(),
+            _ => None,
+        };
+        let status = match ProposalStatus::try_from(value.status) {
+            Ok(status) => status,
+            Err(v) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Invalid proposal status {}",
+                    v
+                )))
+            }
+        };
+        Ok(Proposal {
+            proposal_id: value.id,
+            content,
+            messages,
+            status,
+            final_tally_result: value.final_tally_result,
+            submit_time: value.submit_time.map(|t| t.into()),
+            deposit_end_time: value.deposit_end_time.map(|t| t.into()),
+            total_deposit: value
+                .total_deposit
+                .into_iter()
+                .map(Coin::try_from)
+                .collect::<Result<_, _>>()?,
+            voting_start_time: value.voting_start_time.map(|t| t.into()),
+            voting_end_time: value.voting_end_time.map(|t| t.into()),
+        })
+    }
+}
+
+/// A vote cast on a governance proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposalVote {
+    pub proposal_id: u64,
+    pub voter: String,
+    /// The options voted for with their weight, a vote for a single option is that option
+    /// with a weight of one
+    pub options: Vec<(VoteOption, Decimal)>,
+}
+
+impl TryFrom<Vote> for ProposalVote {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: Vote) -> Result<Self, Self::Error> {
+        let invalid = |v| CosmosGrpcError::BadResponse(format!("Invalid vote option {}", v));
+        // weighted votes leave the deprecated option unspecified, votes from before weighted
+        // votes existed only have the option
+        let options = if value.options.is_empty() {
+            vec![(
+                VoteOption::try_from(value.option).map_err(invalid)?,
+                Decimal::from(1u8),
+            )]
+        } else {
+            let mut options = Vec::new();
+            for option in value.options {
+                let weight = Decimal::from_sdk_dec_str(&option.weight).map_err(|_| {
+                    CosmosGrpcError::BadResponse(format!("Invalid vote weight {}", option.weight))
+                })?;
+                options.push((
+                    VoteOption::try_from(option.option).map_err(invalid)?,
+                    weight,
+                ));
+            }
+            options
+        };
+        Ok(ProposalVote {
+            proposal_id: value.proposal_id,
+            voter: value.voter,
+            options,
+        })
+    }
+}
+
+/// Maps the errors returned by the gov module for a proposal that does not exist
+fn proposal_error(e: Status, proposal_id: u64) -> CosmosGrpcError {
+    match e.code() {
+        TonicCode::NotFound => {
+            CosmosGrpcError::NotFound(format!("Governance proposal {}", proposal_id))
+        }
+        // older versions of the gov module return invalid argument instead
+        TonicCode::InvalidArgument if e.message().contains("doesn't exist") => {
+            CosmosGrpcError::NotFound(format!("Governance proposal {}", proposal_id))
+        }
+        _ => CosmosGrpcError::RequestError { error: e },
+    }
+}
 
 impl Contact {
     /// Gets a list of governance proposals, user provides filter items
@@ -25,71 +448,182 @@ impl Contact {
         &self,
         filters: QueryProposalsRequest,
     ) -> Result<QueryProposalsResponse, CosmosGrpcError> {
-        let mut grpc = GovQueryClient::connect(self.url.clone()).await?;
+        let mut grpc = GovQueryClient::new(self.channel().await?);
         let res = grpc.proposals(filters).await?.into_inner();
         Ok(res)
     }
 
-    /// Gets a list of all active governance proposals currently in the voting period
-    pub async fn get_governance_proposals_in_voting_period(
+    /// Gets a single governance proposal by id, returns `NotFound` if there is no
+    /// proposal with that id. The gov v1 query is used where available so the messages of
+    /// proposals submitted with the v1 model are decoded, chains before Cosmos SDK v0.46 are
+    /// queried with v1beta1
+    pub async fn get_proposal(&self, proposal_id: u64) -> Result<Proposal, CosmosGrpcError> {
+        // gov v1 proposals with several messages can't be returned by the v1beta1 query
+        let res: Result<QueryProposalV1Response, CosmosGrpcError> = unary_call(
+            self.channel().await?,
+            "/cosmos.gov.v1.Query/Proposal",
+            QueryProposalV1Request { proposal_id },
+        )
+        .await;
+        match res {
+            Ok(QueryProposalV1Response {
+                proposal: Some(proposal),
+            }) => return Proposal::try_from(proposal),
+            Ok(QueryProposalV1Response { proposal: None }) => {
+                return Err(CosmosGrpcError::NotFound(format!(
+                    "Governance proposal {}",
+                    proposal_id
+                )))
+            }
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == TonicCode::Unimplemented => {}
+            Err(CosmosGrpcError::RequestError { error }) => {
+                return Err(proposal_error(error, proposal_id))
+            }
+            Err(e) => return Err(e),
+        }
+        let mut grpc = GovQueryClient::new(self.channel().await?);
+        let res = grpc
+            .proposal(QueryProposalRequest { proposal_id })
+            .await
+            .map_err(|e| proposal_error(e, proposal_id))?
+            .into_inner();
+        match res.proposal {
+            Some(proposal) => Proposal::try_from(proposal),
+            None => Err(CosmosGrpcError::NotFound(format!(
+                "Governance proposal {}",
+                proposal_id
+            ))),
+        }
+    }
+
+    /// Gets all governance proposals, optionally only those with the provided status,
+    /// every page of results is queried
+    pub async fn get_proposals(
+        &self,
+        status_filter: Option<ProposalStatus>,
+    ) -> Result<Vec<Proposal>, CosmosGrpcError> {
+        let mut grpc = GovQueryClient::new(self.channel().await?);
+        let mut proposals = Vec::new();
+        let mut pagination = None;
+        loop {
+            let res = grpc
+                .proposals(QueryProposalsRequest {
+                    proposal_status: status_filter.map(|s| s.into()).unwrap_or_default(),
+                    voter: String::new(),
+                    depositor: String::new(),
+                    pagination,
+                })
+                .await?
+                .into_inner();
+            for proposal in res.proposals {
+                proposals.push(Proposal::try_from(proposal)?);
+            }
+            pagination = next_page(&res.pagination);
+            if pagination.is_none() {
+                return Ok(proposals);
+            }
+        }
+    }
+
+    /// Gets the current tally of a governance proposal in it's voting period, returns
+    /// `NotFound` if there is no proposal with that id
+    pub async fn get_tally(&self, proposal_id: u64) -> Result<TallyResult, CosmosGrpcError> {
+        let mut grpc = GovQueryClient::new(self.channel().await?);
+        let res = grpc
+            .tally_result(QueryTallyResultRequest { proposal_id })
+            .await
+            .map_err(|e| proposal_error(e, proposal_id))?
+            .into_inner();
+        match res.tally {
+            Some(tally) => Ok(tally),
+            None => Err(CosmosGrpcError::BadResponse(
+                "No tally returned".to_string(),
+            )),
+        }
+    }
+
+    /// Gets all the votes cast on a governance proposal, note that votes are removed from
+    /// state once the voting period ends so this is only useful for active proposals
+    pub async fn get_votes(&self, proposal_id: u64) -> Result<Vec<ProposalVote>, CosmosGrpcError> {
+        let mut votes = Vec::new();
+        let mut pagination = None;
+        loop {
+            let res: Result<QueryVotesResponse, CosmosGrpcError> = unary_call(
+                self.channel().await?,
+                "/cosmos.gov.v1beta1.Query/Votes",
+                QueryVotesRequest {
+                    proposal_id,
+                    pagination,
+                },
+            )
+            .await;
+            let res = match res {
+                Ok(res) => res,
+                Err(CosmosGrpcError::RequestError { error }) => {
+                    return Err(proposal_error(error, proposal_id))
+                }
+                Err(e) => return Err(e),
+            };
+            for vote in res.votes {
+                votes.push(ProposalVote::try_from(vote)?);
+            }
+            pagination = next_page(&res.pagination);
+            if pagination.is_none() {
+                return Ok(votes);
+            }
+        }
+    }
+
+    /// Gets a list of all governance proposals with the given status
+    pub async fn get_governance_proposals_by_status(
         &self,
+        status: ProposalStatus,
     ) -> Result<QueryProposalsResponse, CosmosGrpcError> {
         let req = QueryProposalsRequest {
             // Go default values indicate that this search param is not
             // being used
             depositor: String::new(),
-            proposal_status: ProposalStatus::VotingPeriod.into(),
+            proposal_status: status.into(),
             voter: String::new(),
             pagination: None,
         };
         self.get_governance_proposals(req).await
     }
 
+    /// Gets a list of all active governance proposals currently in the voting period
+    pub async fn get_governance_proposals_in_voting_period(
+        &self,
+    ) -> Result<QueryProposalsResponse, CosmosGrpcError> {
+        self.get_governance_proposals_by_status(ProposalStatus::VotingPeriod)
+            .await
+    }
+
     /// Gets a list of all governance proposals that have passed
     pub async fn get_passed_governance_proposals(
         &self,
     ) -> Result<QueryProposalsResponse, CosmosGrpcError> {
-        let req = QueryProposalsRequest {
-            // Go default values indicate that this search param is not
-            // being used
-            depositor: String::new(),
-            proposal_status: ProposalStatus::Passed.into(),
-            voter: String::new(),
-            pagination: None,
-        };
-        self.get_governance_proposals(req).await
+        self.get_governance_proposals_by_status(ProposalStatus::Passed)
+            .await
     }
 
     /// Gets a list of all governance proposals that have failed
     pub async fn get_failed_governance_proposals(
         &self,
     ) -> Result<QueryProposalsResponse, CosmosGrpcError> {
-        let req = QueryProposalsRequest {
-            // Go default values indicate that this search param is not
-            // being used
-            depositor: String::new(),
-            proposal_status: ProposalStatus::Failed.into(),
-            voter: String::new(),
-            pagination: None,
-        };
-        self.get_governance_proposals(req).await
+        self.get_governance_proposals_by_status(ProposalStatus::Failed)
+            .await
     }
 
     /// Gets a list of all governance proposals that have been rejected
     pub async fn get_rejected_governance_proposals(
         &self,
     ) -> Result<QueryProposalsResponse, CosmosGrpcError> {
-        let req = QueryProposalsRequest {
-            // Go default values indicate that this search param is not
-            // being used
-            depositor: String::new(),
-            proposal_status: ProposalStatus::Rejected.into(),
-            voter: String::new(),
-            pagination: None,
-        };
-        self.get_governance_proposals(req).await
+        self.get_governance_proposals_by_status(ProposalStatus::Rejected)
+            .await
     }
 
+    /// Votes on a governance proposal, the vote is cast by the account of the provided private key
     pub async fn vote_on_gov_proposal(
         &self,
         proposal_id: u64,
@@ -98,7 +632,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.chain_prefix)?;
         let vote = MsgVote {
             proposal_id,
             voter: our_address.to_string(),
@@ -117,14 +651,61 @@ impl Contact {
         let args = self.get_message_args(our_address, fee).await?;
         trace!("got optional tx info");
 
-        let msg_bytes = private_key.sign_std_msg(&[msg], args, MEMO)?;
+        let msg_bytes =
+            private_key.sign_std_msg_with_sign_mode(&[msg], args, MEMO, self.sign_mode)?;
+
+        let response = self
+            .send_transaction(msg_bytes, BroadcastMode::Sync)
+            .await?;
+
+        trace!("broadcasted! with response {:?}", response);
+        if let Some(time) = self.wait_timeout(wait_timeout) {
+            self.wait_for_tx(response, time).await
+        } else {
+            Ok(response)
+        }
+    }
+
+    /// Votes on a governance proposal with the voting power split between several options,
+    /// for example 70% Yes and 30% Abstain. The weights must each be positive and sum to
+    /// exactly one, otherwise `BadInput` is returned without anything being sent
+    pub async fn vote_weighted(
+        &self,
+        proposal_id: u64,
+        votes: Vec<(VoteOption, Decimal)>,
+        fee: Coin,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let options = weighted_vote_options(&votes).map_err(CosmosGrpcError::BadInput)?;
+        let our_address = private_key.to_address(&self.chain_prefix)?;
+        let vote = MsgVoteWeighted {
+            proposal_id,
+            voter: our_address.to_string(),
+            options,
+        };
+
+        let fee = Fee {
+            amount: vec![fee],
+            gas_limit: 500_000u64,
+            granter: None,
+            payer: None,
+        };
+
+        let msg = Msg::new(MSG_VOTE_WEIGHTED_TYPE_URL, vote);
+
+        let args = self.get_message_args(our_address, fee).await?;
+        trace!("got optional tx info");
+
+        let msg_bytes =
+            private_key.sign_std_msg_with_sign_mode(&[msg], args, MEMO, self.sign_mode)?;
 
         let response = self
             .send_transaction(msg_bytes, BroadcastMode::Sync)
             .await?;
 
         trace!("broadcasted! with response {:?}", response);
-        if let Some(time) = wait_timeout {
+        if let Some(time) = self.wait_timeout(wait_timeout) {
             self.wait_for_tx(response, time).await
         } else {
             Ok(response)
@@ -140,7 +721,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.chain_prefix)?;
         let proposal = MsgSubmitProposal {
             proposer: our_address.to_string(),
             content: Some(content),
@@ -159,17 +740,263 @@ impl Contact {
         let args = self.get_message_args(our_address, fee).await?;
         trace!("got optional tx info");
 
-        let msg_bytes = private_key.sign_std_msg(&[msg], args, MEMO)?;
+        let msg_bytes =
+            private_key.sign_std_msg_with_sign_mode(&[msg], args, MEMO, self.sign_mode)?;
 
         let response = self
             .send_transaction(msg_bytes, BroadcastMode::Sync)
             .await?;
 
         trace!("broadcasted! with response {:?}", response);
-        if let Some(time) = wait_timeout {
+        if let Some(time) = self.wait_timeout(wait_timeout) {
             self.wait_for_tx(response, time).await
         } else {
             Ok(response)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encode_any;
+    use prost_types::Timestamp;
+
+    #[test]
+    fn test_weighted_vote_options() {
+        let dec = |s| Decimal::from_str_lenient(s).unwrap();
+        let options = weighted_vote_options(&[
+            (VoteOption::Yes, dec("0.7")),
+            (VoteOption::Abstain, dec("0.3")),
+        ])
+        .unwrap();
+        assert_eq!(options[0].option, 1);
+        assert_eq!(options[0].weight, "700000000000000000");
+        assert_eq!(options[1].weight, "300000000000000000");
+
+        assert!(weighted_vote_options(&[]).is_err());
+        assert!(weighted_vote_options(&[(VoteOption::Yes, dec("0.5"))]).is_err());
+        assert!(weighted_vote_options(&[
+            (VoteOption::Yes, dec("1.5")),
+            (VoteOption::No, dec("-0.5")),
+        ])
+        .is_err());
+        assert!(weighted_vote_options(&[
+            (VoteOption::Yes, dec("0.5")),
+            (VoteOption::Yes, dec("0.5")),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_decode_vote() {
+        let vote = Vote {
+            proposal_id: 3,
+            voter: "cosmos1voter".to_string(),
+            option: 3,
+            options: vec![],
+        };
+        let one = Decimal::from(1u8);
+        assert_eq!(
+            ProposalVote::try_from(vote.clone()).unwrap().options,
+            vec![(VoteOption::No, one)]
+        );
+
+        // weighted votes leave the option unspecified
+        let options = weighted_vote_options(&[
+            (VoteOption::Yes, Decimal::from_str_lenient("0.7").unwrap()),
+            (
+                VoteOption::Abstain,
+                Decimal::from_str_lenient("0.3").unwrap(),
+            ),
+        ])
+        .unwrap();
+        let weighted = ProposalVote::try_from(Vote {
+            option: 0,
+            options,
+            ..vote.clone()
+        })
+        .unwrap();
+        assert_eq!(
+            weighted.options,
+            vec![
+                (VoteOption::Yes, Decimal::from_str_lenient("0.7").unwrap()),
+                (
+                    VoteOption::Abstain,
+                    Decimal::from_str_lenient("0.3").unwrap()
+                ),
+            ]
+        );
+
+        assert!(ProposalVote::try_from(Vote { option: 0, ..vote }).is_err());
+    }
+
+    #[test]
+    fn test_decode_proposal() {
+        let text = TextProposal {
+            title: "title".to_string(),
+            description: "description".to_string(),
+        };
+        let proto = ProtoProposal {
+            proposal_id: 4,
+            content: Some(encode_any(text.clone(), "/cosmos.gov.v1beta1.TextProposal")),
+            status: 2,
+            final_tally_result: None,
+            submit_time: Some(Timestamp {
+                seconds: 100,
+                nanos: 0,
+            }),
+            deposit_end_time: None,
+            total_deposit: vec![],
+            voting_start_time: None,
+            voting_end_time: Some(Timestamp {
+                seconds: 200,
+                nanos: 0,
+            }),
+        };
+        let proposal = Proposal::try_from(proto.clone()).unwrap();
+        assert_eq!(proposal.content, Some(ProposalContent::Text(text)));
+        assert_eq!(proposal.status, ProposalStatus::VotingPeriod);
+        assert_eq!(
+            proposal.voting_end_time,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(200))
+        );
+
+        let unknown = Any {
+            type_url: "/custom.v1.SomeProposal".to_string(),
+            value: vec![1, 2],
+        };
+        let proposal = Proposal::try_from(ProtoProposal {
+            content: Some(unknown.clone()),
+            ..proto
+        })
+        .unwrap();
+        assert_eq!(proposal.content, Some(ProposalContent::Unknown(unknown)));
+    }
+
+    #[test]
+    fn test_decode_proposal_content() {
+        let coins = vec![Coin::new(1_000u64.into(), "uatom").into()];
+        let params = ParameterChangeProposal {
+            title: "params".to_string(),
+            description: "raise the max validators".to_string(),
+            changes: vec![cosmos_sdk_proto::cosmos::params::v1beta1::ParamChange {
+                subspace: "staking".to_string(),
+                key: "MaxValidators".to_string(),
+                value: "180".to_string(),
+            }],
+        };
+        let spend = CommunityPoolSpendProposal {
+            title: "spend".to_string(),
+            description: "fund the team".to_string(),
+            recipient: "cosmos1recipient".to_string(),
+            amount: coins.clone(),
+        };
+        let plan = Plan {
+            name: "v2".to_string(),
+            height: 1_000,
+            ..Default::default()
+        };
+        let upgrade = SoftwareUpgradeProposal {
+            title: "upgrade".to_string(),
+            description: "to v2".to_string(),
+            plan: Some(plan.clone()),
+        };
+        assert_eq!(
+            ProposalContent::decode(&encode_any(
+                params.clone(),
+                "/cosmos.params.v1beta1.ParameterChangeProposal"
+            ))
+            .unwrap(),
+            ProposalContent::ParameterChange(params)
+        );
+        assert_eq!(
+            ProposalContent::decode(&encode_any(
+                spend.clone(),
+                "/cosmos.distribution.v1beta1.CommunityPoolSpendProposal"
+            ))
+            .unwrap(),
+            ProposalContent::CommunityPoolSpend(spend)
+        );
+        assert_eq!(
+            ProposalContent::decode(&encode_any(
+                upgrade.clone(),
+                "/cosmos.upgrade.v1beta1.SoftwareUpgradeProposal"
+            ))
+            .unwrap(),
+            ProposalContent::SoftwareUpgrade(upgrade)
+        );
+        // a known type url with a value of another type
+        assert!(ProposalContent::decode(&Any {
+            type_url: "/cosmos.gov.v1beta1.TextProposal".to_string(),
+            value: vec![0xff],
+        })
+        .is_err());
+
+        // gov v1 proposals hold messages instead
+        let text = TextProposal {
+            title: "title".to_string(),
+            description: "description".to_string(),
+        };
+        let legacy = MsgExecLegacyContent {
+            content: Some(encode_any(text.clone(), "/cosmos.gov.v1beta1.TextProposal")),
+            authority: "cosmos1gov".to_string(),
+        };
+        let v1 = ProposalV1 {
+            id: 9,
+            messages: vec![encode_any(legacy, "/cosmos.gov.v1.MsgExecLegacyContent")],
+            status: 3,
+            title: "title".to_string(),
+            ..Default::default()
+        };
+        let proposal = Proposal::try_from(v1.clone()).unwrap();
+        assert_eq!(proposal.proposal_id, 9);
+        assert_eq!(proposal.status, ProposalStatus::Passed);
+        assert_eq!(proposal.content, Some(ProposalContent::Text(text.clone())));
+        assert_eq!(
+            proposal.messages,
+            vec![ProposalMessage::ExecLegacyContent {
+                content: Some(ProposalContent::Text(text)),
+                authority: "cosmos1gov".to_string(),
+            }]
+        );
+
+        let pool_spend = MsgCommunityPoolSpend {
+            authority: "cosmos1gov".to_string(),
+            recipient: "cosmos1recipient".to_string(),
+            amount: coins,
+        };
+        let software_upgrade = MsgSoftwareUpgrade {
+            authority: "cosmos1gov".to_string(),
+            plan: Some(plan),
+        };
+        let update_params = Any {
+            type_url: "/cosmos.staking.v1beta1.MsgUpdateParams".to_string(),
+            value: vec![10, 2, 1, 2],
+        };
+        let proposal = Proposal::try_from(ProposalV1 {
+            messages: vec![
+                encode_any(
+                    pool_spend.clone(),
+                    "/cosmos.distribution.v1beta1.MsgCommunityPoolSpend",
+                ),
+                encode_any(
+                    software_upgrade.clone(),
+                    "/cosmos.upgrade.v1beta1.MsgSoftwareUpgrade",
+                ),
+                update_params.clone(),
+            ],
+            ..v1
+        })
+        .unwrap();
+        assert_eq!(proposal.content, None);
+        assert_eq!(
+            proposal.messages,
+            vec![
+                ProposalMessage::CommunityPoolSpend(pool_spend),
+                ProposalMessage::SoftwareUpgrade(software_upgrade),
+                ProposalMessage::Unknown(update_params),
+            ]
+        );
+    }
+}
@@ -0,0 +1,562 @@
+//! Contains utility functions for querying the state of IBC channels and connections, useful
+//! for relaying and for checking a channel is open before sending tokens over it. The
+//! cosmos-sdk-proto version used by this crate only includes the IBC message types, not the
+//! gRPC clients of the channel, connection and transfer query services, so the calls are made
+//! directly with `unary_call`.
+
+use crate::client::rpc::unary_call;
+use crate::client::types::{
+    ChannelOrder, ChannelState, ConnectionState, TxEvent, TxResponseEvents,
+};
+use crate::error::CosmosGrpcError;
+use crate::utils::next_page;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{GetTxsEventRequest, OrderBy};
+use cosmos_sdk_proto::ibc::applications::transfer::v1::{
+    DenomTrace, QueryDenomTraceRequest, QueryDenomTraceResponse,
+};
+use cosmos_sdk_proto::ibc::core::channel::v1::{
+    IdentifiedChannel, QueryChannelRequest, QueryChannelResponse, QueryChannelsRequest,
+    QueryChannelsResponse,
+};
+use cosmos_sdk_proto::ibc::core::connection::v1::{
+    ConnectionEnd, QueryConnectionRequest, QueryConnectionResponse, Version,
+};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tonic::Code as TonicCode;
+
+/// How often `Contact::wait_for_ibc_packet` searches for the acknowledgement of a packet
+const IBC_PACKET_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An IBC channel end on this chain along with the channel end it is connected to
+/// on the counterparty chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IbcChannel {
+    pub port_id: String,
+    pub channel_id: String,
+    pub state: ChannelState,
+    pub ordering: ChannelOrder,
+    pub counterparty_port_id: String,
+    /// Empty until the handshake has reached the counterparty chain
+    pub counterparty_channel_id: String,
+    /// The connections packets on this channel travel over, currently always
+    /// a single connection
+    pub connection_hops: Vec<String>,
+    /// The application version negotiated during the handshake, for example `ics20-1`
+    pub version: String,
+}
+
+impl TryFrom<IdentifiedChannel> for IbcChannel {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: IdentifiedChannel) -> Result<Self, Self::Error> {
+        let state = ChannelState::try_from(value.state)
+            .map_err(|v| CosmosGrpcError::BadResponse(format!("Invalid channel state {}", v)))?;
+        let ordering = ChannelOrder::try_from(value.ordering)
+            .map_err(|v| CosmosGrpcError::BadResponse(format!("Invalid channel ordering {}", v)))?;
+        let counterparty = value.counterparty.unwrap_or_default();
+        Ok(IbcChannel {
+            port_id: value.port_id,
+            channel_id: value.channel_id,
+            state,
+            ordering,
+            counterparty_port_id: counterparty.port_id,
+            counterparty_channel_id: counterparty.channel_id,
+            connection_hops: value.connection_hops,
+            version: value.version,
+        })
+    }
+}
+
+/// An IBC connection end on this chain along with the connection end it is connected
+/// to on the counterparty chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct IbcConnection {
+    pub connection_id: String,
+    /// The light client on this chain tracking the counterparty chain
+    pub client_id: String,
+    pub state: ConnectionState,
+    pub counterparty_client_id: String,
+    /// Empty until the handshake has reached the counterparty chain
+    pub counterparty_connection_id: String,
+    /// The versions supported by this connection, once open only the negotiated version remains
+    pub versions: Vec<Version>,
+    /// The delay in nanoseconds before a packet can be processed after the client is updated
+    pub delay_period: u64,
+}
+
+impl IbcConnection {
+    fn from_end(connection_id: String, end: ConnectionEnd) -> Result<Self, String> {
+        let state = ConnectionState::try_from(end.state)
+            .map_err(|v| format!("Invalid connection state {}", v))?;
+        let counterparty = end.counterparty.unwrap_or_default();
+        Ok(IbcConnection {
+            connection_id,
+            client_id: end.client_id,
+            state,
+            counterparty_client_id: counterparty.client_id,
+            counterparty_connection_id: counterparty.connection_id,
+            versions: end.versions,
+            delay_period: end.delay_period,
+        })
+    }
+}
+
+/// An IBC packet sent by a transaction on this chain, as reported by it's `send_packet` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IbcPacket {
+    pub sequence: u64,
+    pub src_port: String,
+    pub src_channel: String,
+    pub dst_port: String,
+    pub dst_channel: String,
+}
+
+impl IbcPacket {
+    /// Finds the first packet sent by the events of a transaction, None if it sent no packet
+    pub fn from_events(events: &[TxEvent]) -> Option<IbcPacket> {
+        let event = events.iter().find(|e| e.kind == "send_packet")?;
+        Some(IbcPacket {
+            sequence: event.attribute("packet_sequence")?.parse().ok()?,
+            src_port: event.attribute("packet_src_port")?.to_string(),
+            src_channel: event.attribute("packet_src_channel")?.to_string(),
+            dst_port: event.attribute("packet_dst_port")?.to_string(),
+            dst_channel: event.attribute("packet_dst_channel")?.to_string(),
+        })
+    }
+
+    /// Returns true if an event of this chain is about this packet
+    fn matches(&self, event: &TxEvent) -> bool {
+        event.attribute("packet_sequence") == Some(self.sequence.to_string().as_str())
+            && event.attribute("packet_src_port") == Some(self.src_port.as_str())
+            && event.attribute("packet_src_channel") == Some(self.src_channel.as_str())
+    }
+
+    /// The tx search query for the transaction on this chain that emitted `kind` for this
+    /// packet, relayers submit the acknowledgement or timeout of a packet on the chain it was
+    /// sent from
+    fn search_events(&self, kind: &str) -> Vec<String> {
+        vec![
+            format!("{}.packet_sequence='{}'", kind, self.sequence),
+            format!("{}.packet_src_port='{}'", kind, self.src_port),
+            format!("{}.packet_src_channel='{}'", kind, self.src_channel),
+        ]
+    }
+}
+
+/// The outcome of an IBC transfer, see `Contact::wait_for_ibc_transfer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IbcTransferStatus {
+    /// The packet was received by the counterparty chain and it's acknowledgement was relayed
+    /// back in the transaction `tx_hash`. `error` is the error the transfer application of the
+    /// counterparty acknowledged the packet with, in which case the tokens were refunded. It is
+    /// only known from the logs of the transaction, which Cosmos SDK v0.50 and later no longer
+    /// return, so it's always None on those chains
+    Acknowledged {
+        tx_hash: String,
+        height: u64,
+        error: Option<String>,
+    },
+    /// The packet timed out before it was received and the tokens were refunded in `tx_hash`
+    TimedOut { tx_hash: String, height: u64 },
+    /// Neither an acknowledgement nor a timeout was relayed in time
+    Pending,
+}
+
+/// The error the transfer application acknowledged `packet` with, from the
+/// `fungible_token_packet` event emitted by the same message as the acknowledgement
+fn acknowledgement_error(packet: &IbcPacket, events: &[TxEvent]) -> Option<String> {
+    let ack = events
+        .iter()
+        .find(|e| e.kind == "acknowledge_packet" && packet.matches(e))?;
+    events
+        .iter()
+        .filter(|e| e.kind == "fungible_token_packet" && e.msg_index == ack.msg_index)
+        .find_map(|e| e.attribute("error"))
+        .map(|e| e.to_string())
+}
+
+/// Chains without IBC return `ModuleNotPresent`
+fn ibc_error(e: CosmosGrpcError) -> CosmosGrpcError {
+    match e {
+        CosmosGrpcError::RequestError { error } if error.code() == TonicCode::Unimplemented => {
+            CosmosGrpcError::ModuleNotPresent("ibc".to_string())
+        }
+        e => e,
+    }
+}
+
+fn is_not_found(e: &CosmosGrpcError) -> bool {
+    matches!(e, CosmosGrpcError::RequestError { error } if error.code() == TonicCode::NotFound)
+}
+
+impl Contact {
+    /// Gets an IBC channel by it's port and channel id, for example `transfer` and `channel-0`,
+    /// returns None if there is no such channel
+    pub async fn get_channel(
+        &self,
+        port_id: String,
+        channel_id: String,
+    ) -> Result<Option<IbcChannel>, CosmosGrpcError> {
+        let res: Result<QueryChannelResponse, CosmosGrpcError> = unary_call(
+            self.channel().await?,
+            "/ibc.core.channel.v1.Query/Channel",
+            QueryChannelRequest {
+                port_id: port_id.clone(),
+                channel_id: channel_id.clone(),
+            },
+        )
+        .await;
+        let channel = match res {
+            Ok(res) => match res.channel {
+                Some(channel) => channel,
+                None => return Ok(None),
+            },
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(ibc_error(e)),
+        };
+        IbcChannel::try_from(IdentifiedChannel {
+            state: channel.state,
+            ordering: channel.ordering,
+            counterparty: channel.counterparty,
+            connection_hops: channel.connection_hops,
+            version: channel.version,
+            port_id,
+            channel_id,
+        })
+        .map(Some)
+    }
+
+    /// Gets every IBC channel on this chain, every page of results is queried
+    pub async fn get_channels(&self) -> Result<Vec<IbcChannel>, CosmosGrpcError> {
+        let mut channels = Vec::new();
+        let mut pagination = None;
+        loop {
+            let res: QueryChannelsResponse = unary_call(
+                self.channel().await?,
+                "/ibc.core.channel.v1.Query/Channels",
+                QueryChannelsRequest { pagination },
+            )
+            .await
+            .map_err(ibc_error)?;
+            for channel in res.channels {
+                channels.push(IbcChannel::try_from(channel)?);
+            }
+            pagination = next_page(&res.pagination);
+            if pagination.is_none() {
+                return Ok(channels);
+            }
+        }
+    }
+
+    /// Gets an IBC connection by it's id, for example `connection-0`, returns None if there
+    /// is no such connection. The connection used by a channel is the first of it's
+    /// `connection_hops`
+    pub async fn get_connection(
+        &self,
+        connection_id: String,
+    ) -> Result<Option<IbcConnection>, CosmosGrpcError> {
+        let res: Result<QueryConnectionResponse, CosmosGrpcError> = unary_call(
+            self.channel().await?,
+            "/ibc.core.connection.v1.Query/Connection",
+            QueryConnectionRequest {
+                connection_id: connection_id.clone(),
+            },
+        )
+        .await;
+        let end = match res {
+            Ok(res) => match res.connection {
+                Some(end) => end,
+                None => return Ok(None),
+            },
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(ibc_error(e)),
+        };
+        match IbcConnection::from_end(connection_id, end) {
+            Ok(connection) => Ok(Some(connection)),
+            Err(e) => Err(CosmosGrpcError::BadResponse(e)),
+        }
+    }
+
+    /// Resolves an IBC voucher denom to the path it was transferred over and it's base
+    /// denom on the source chain, accepts either `ibc/<HASH>` or just the hash. Returns None
+    /// if this chain has never received the denom. The reverse is `utils::ibc_denom_hash`
+    pub async fn get_denom_trace(
+        &self,
+        hash: String,
+    ) -> Result<Option<DenomTrace>, CosmosGrpcError> {
+        let hash = hash.strip_prefix("ibc/").unwrap_or(&hash).to_string();
+        let res: Result<QueryDenomTraceResponse, CosmosGrpcError> = unary_call(
+            self.channel().await?,
+            "/ibc.applications.transfer.v1.Query/DenomTrace",
+            QueryDenomTraceRequest { hash },
+        )
+        .await;
+        match res {
+            Ok(res) => Ok(res.denom_trace),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(ibc_error(e)),
+        }
+    }
+
+    /// Waits for the IBC transfer sent by the transaction `response` to complete, meaning the
+    /// acknowledgement or the timeout of it's packet was relayed back to this chain, polling the
+    /// tx index of the node until `timeout` has passed. Returns `Pending` if neither happened in
+    /// time, transfers depend on a relayer so this can take much longer than a block. The send
+    /// transaction must be on chain, see `wait_for_tx`, returns `BadInput` if it sent no packet
+    pub async fn wait_for_ibc_transfer(
+        &self,
+        response: &TxResponse,
+        timeout: Duration,
+    ) -> Result<IbcTransferStatus, CosmosGrpcError> {
+        let events = TxResponseEvents {
+            logs: response.logs.clone(),
+            events: Vec::new(),
+        }
+        .tx_events();
+        let packet = match IbcPacket::from_events(&events) {
+            Some(packet) => packet,
+            // v0.50 and later chains only return events when the tx is queried
+            None => {
+                match IbcPacket::from_events(&self.get_tx_events(response.txhash.clone()).await?) {
+                    Some(packet) => packet,
+                    None => {
+                        return Err(CosmosGrpcError::BadInput(format!(
+                            "Transaction {} sent no IBC packet",
+                            response.txhash
+                        )))
+                    }
+                }
+            }
+        };
+        self.wait_for_ibc_packet(&packet, timeout).await
+    }
+
+    /// Waits for the acknowledgement or timeout of a packet sent by this chain, see
+    /// `wait_for_ibc_transfer`. Failed searches of the tx index are logged and retried until
+    /// `timeout` has passed
+    pub async fn wait_for_ibc_packet(
+        &self,
+        packet: &IbcPacket,
+        timeout: Duration,
+    ) -> Result<IbcTransferStatus, CosmosGrpcError> {
+        let start = Instant::now();
+        loop {
+            match self.search_packet_tx(packet, "acknowledge_packet").await {
+                Ok(Some(ack)) => {
+                    let events = TxResponseEvents {
+                        logs: ack.logs,
+                        events: Vec::new(),
+                    }
+                    .tx_events();
+                    return Ok(IbcTransferStatus::Acknowledged {
+                        error: acknowledgement_error(packet, &events),
+                        tx_hash: ack.txhash,
+                        height: ack.height.max(0) as u64,
+                    });
+                }
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "Failed to search for the acknowledgement of packet {} {:?}",
+                    packet.sequence, e
+                ),
+            }
+            match self.search_packet_tx(packet, "timeout_packet").await {
+                Ok(Some(refund)) => {
+                    return Ok(IbcTransferStatus::TimedOut {
+                        tx_hash: refund.txhash,
+                        height: refund.height.max(0) as u64,
+                    })
+                }
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "Failed to search for the timeout of packet {} {:?}",
+                    packet.sequence, e
+                ),
+            }
+            let elapsed = Instant::now() - start;
+            if elapsed >= timeout {
+                return Ok(IbcTransferStatus::Pending);
+            }
+            sleep(IBC_PACKET_POLL_INTERVAL.min(timeout - elapsed)).await;
+        }
+    }
+
+    /// Finds the transaction on this chain that emitted `kind` for `packet`
+    async fn search_packet_tx(
+        &self,
+        packet: &IbcPacket,
+        kind: &str,
+    ) -> Result<Option<TxResponse>, CosmosGrpcError> {
+        let res = self
+            .rpc
+            .get_txs_event(GetTxsEventRequest {
+                events: packet.search_events(kind),
+                pagination: Some(PageRequest {
+                    key: Vec::new(),
+                    offset: 0,
+                    limit: 1,
+                    count_total: false,
+                }),
+                order_by: OrderBy::Asc.into(),
+            })
+            .await?;
+        Ok(res.tx_responses.into_iter().next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::mock_contact;
+    use cosmos_sdk_proto::ibc::core::channel::v1::Counterparty;
+
+    #[test]
+    fn test_channel_conversion() {
+        let proto = IdentifiedChannel {
+            state: 3,
+            ordering: 1,
+            counterparty: Some(Counterparty {
+                port_id: "transfer".to_string(),
+                channel_id: "channel-141".to_string(),
+            }),
+            connection_hops: vec!["connection-257".to_string()],
+            version: "ics20-1".to_string(),
+            port_id: "transfer".to_string(),
+            channel_id: "channel-0".to_string(),
+        };
+        let channel = IbcChannel::try_from(proto.clone()).unwrap();
+        assert_eq!(channel.state, ChannelState::Open);
+        assert_eq!(channel.ordering, ChannelOrder::Unordered);
+        assert_eq!(channel.counterparty_channel_id, "channel-141");
+        assert_eq!(channel.state.to_string(), "STATE_OPEN");
+
+        // ibc-go v8 channel upgrades
+        let flushing = IdentifiedChannel {
+            state: 5,
+            ..proto.clone()
+        };
+        let channel = IbcChannel::try_from(flushing).unwrap();
+        assert_eq!(channel.state, ChannelState::Unknown(5));
+        assert_eq!(channel.state.to_string(), "STATE_UNKNOWN(5)");
+
+        let uninitialized = IdentifiedChannel { state: 0, ..proto };
+        assert!(IbcChannel::try_from(uninitialized).is_err());
+    }
+
+    #[test]
+    fn test_connection_conversion() {
+        let end = ConnectionEnd {
+            client_id: "07-tendermint-0".to_string(),
+            versions: Vec::new(),
+            state: 2,
+            counterparty: None,
+            delay_period: 0,
+        };
+        let connection = IbcConnection::from_end("connection-0".to_string(), end.clone()).unwrap();
+        assert_eq!(connection.state, ConnectionState::TryOpen);
+        assert!(connection.counterparty_connection_id.is_empty());
+        // connections have no closed state
+        assert!(IbcConnection::from_end(
+            "connection-0".to_string(),
+            ConnectionEnd { state: 4, ..end }
+        )
+        .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_wait_for_ibc_transfer() {
+        use crate::client::ibc::IbcTransferStatus;
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{
+            AbciMessageLog, Attribute, StringEvent,
+        };
+
+        let (contact, mock, _key) = mock_contact();
+        let event = |kind: &str, attributes: &[(&str, &str)]| StringEvent {
+            r#type: kind.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| Attribute {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+        };
+        let packet = [
+            ("packet_sequence", "7"),
+            ("packet_src_port", "transfer"),
+            ("packet_src_channel", "channel-0"),
+            ("packet_dst_port", "transfer"),
+            ("packet_dst_channel", "channel-141"),
+        ];
+        let sent = TxResponse {
+            txhash: "AA".to_string(),
+            height: 10,
+            logs: vec![AbciMessageLog {
+                msg_index: 0,
+                log: String::new(),
+                events: vec![event("send_packet", &packet)],
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            contact
+                .wait_for_ibc_transfer(&sent, Duration::from_millis(100))
+                .await
+                .unwrap(),
+            IbcTransferStatus::Pending
+        );
+        // a failing tx search is retried until the timeout
+        *mock.tx_search_error.lock().unwrap() = Some(tonic::Status::internal("unavailable"));
+        assert_eq!(
+            contact
+                .wait_for_ibc_transfer(&sent, Duration::from_millis(100))
+                .await
+                .unwrap(),
+            IbcTransferStatus::Pending
+        );
+        *mock.tx_search_error.lock().unwrap() = None;
+
+        // the relayer acknowledged another packet in the same tx first
+        let mut other = packet;
+        other[0] = ("packet_sequence", "6");
+        mock.include_tx(TxResponse {
+            txhash: "BB".to_string(),
+            height: 12,
+            logs: vec![
+                AbciMessageLog {
+                    msg_index: 1,
+                    log: String::new(),
+                    events: vec![
+                        event("acknowledge_packet", &other),
+                        event("fungible_token_packet", &[("success", "\u{1}")]),
+                    ],
+                },
+                AbciMessageLog {
+                    msg_index: 2,
+                    log: String::new(),
+                    events: vec![
+                        event("acknowledge_packet", &packet),
+                        event("fungible_token_packet", &[("error", "invalid receiver")]),
+                    ],
+                },
+            ],
+            ..Default::default()
+        });
+        assert_eq!(
+            contact
+                .wait_for_ibc_transfer(&sent, Duration::from_secs(1))
+                .await
+                .unwrap(),
+            IbcTransferStatus::Acknowledged {
+                tx_hash: "BB".to_string(),
+                height: 12,
+                error: Some("invalid receiver".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,523 @@
+//! Queries of the mempool of a node, the transactions it has accepted but that are not in a
+//! block yet, and broadcasting through it with `broadcast_tx_commit`. The mempool is not served
+//! over gRPC so these use the Tendermint RPC of the node, which must be set with
+//! `Contact::set_tendermint_rpc_url`. Only plain http Tendermint RPC urls are supported.
+
+use crate::client::rpc::traced;
+use crate::error::CosmosGrpcError;
+use crate::utils::{normalize_tx_hash, tx_hash};
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::Tx;
+use hyper::body::to_bytes;
+use hyper::{Body, Client, Method, Request, Uri};
+use prost::Message;
+use std::time::Duration;
+use tokio::time::timeout;
+use tonic::Code as TonicCode;
+
+/// A transaction waiting in the mempool
+#[derive(Debug, Clone, PartialEq)]
+pub struct MempoolTx {
+    /// The hash of the transaction, as returned by the broadcast of the tx
+    pub hash: String,
+    /// The decoded transaction, None if the bytes are not a Cosmos transaction
+    pub tx: Option<Tx>,
+    pub bytes: Vec<u8>,
+}
+
+/// The contents of the mempool, which may be only part of it if a limit was requested
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnconfirmedTxs {
+    /// The number of transactions in the mempool
+    pub total: u64,
+    /// The total size of the transactions in the mempool in bytes
+    pub total_bytes: u64,
+    pub txs: Vec<MempoolTx>,
+}
+
+/// Where a broadcast transaction is, see `Contact::tx_status`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxStatus {
+    /// In the mempool of the node waiting to be included in a block
+    Pending,
+    /// Included in a block, the response holds the result of executing it
+    Committed(Box<TxResponse>),
+    /// Neither on chain nor in the mempool, the tx was dropped or never reached this node
+    Unknown,
+}
+
+/// The result of running a transaction, either in CheckTx on admission to the mempool or
+/// when executing it in a block, see `CommitResult`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TxExecResult {
+    /// Zero on success, otherwise an error code of `codespace`
+    pub code: u32,
+    pub codespace: String,
+    pub log: String,
+    pub gas_wanted: u64,
+    pub gas_used: u64,
+    /// The encoded `TxMsgData` of the transaction, empty for CheckTx
+    pub data: Vec<u8>,
+}
+
+impl TxExecResult {
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+}
+
+/// The result of `Contact::broadcast_commit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitResult {
+    pub hash: String,
+    /// The height of the block the transaction is in, zero if it failed CheckTx
+    pub height: u64,
+    /// The result of CheckTx, the transaction was not added to the mempool if it failed
+    pub check_tx: TxExecResult,
+    /// The result of executing the transaction in a block, None if it failed CheckTx
+    pub deliver_tx: Option<TxExecResult>,
+}
+
+impl CommitResult {
+    /// True if the transaction passed CheckTx and executed successfully in a block
+    pub fn is_success(&self) -> bool {
+        self.check_tx.is_success() && matches!(&self.deliver_tx, Some(r) if r.is_success())
+    }
+}
+
+/// How long `broadcast_commit` waits at least, Tendermint waits up to it's
+/// `timeout_broadcast_tx_commit` (10 seconds by default) for the tx to be included
+const BROADCAST_COMMIT_WAIT: Duration = Duration::from_secs(15);
+
+/// The largest page of the mempool Tendermint returns
+const MEMPOOL_PAGE: u32 = 100;
+
+/// The `result` of an `unconfirmed_txs` response, numbers are strings in Tendermint JSON
+#[derive(Deserialize)]
+struct UnconfirmedTxsResult {
+    total: String,
+    total_bytes: String,
+    txs: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    result: Option<UnconfirmedTxsResult>,
+    error: Option<serde_json::Value>,
+}
+
+/// A `check_tx` or `deliver_tx` result in Tendermint JSON, numbers are strings
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct JsonExecResult {
+    code: u32,
+    codespace: String,
+    log: String,
+    gas_wanted: String,
+    gas_used: String,
+    data: Option<String>,
+}
+
+/// The `result` of a `broadcast_tx_commit` response, CometBFT v0.38 renamed `deliver_tx`
+/// to `tx_result`
+#[derive(Deserialize)]
+struct BroadcastCommitResult {
+    check_tx: JsonExecResult,
+    #[serde(alias = "tx_result", default)]
+    deliver_tx: JsonExecResult,
+    hash: String,
+    height: String,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcCommitResponse {
+    result: Option<BroadcastCommitResult>,
+    error: Option<serde_json::Value>,
+}
+
+impl Contact {
+    /// Gets up to `limit` transactions from the mempool of the node, along with the total
+    /// number of transactions in it. A tx that was broadcast successfully but that is neither
+    /// on chain nor in the mempool has been dropped and will not be included without being
+    /// broadcast again. Each node has it's own mempool so this only reflects the node queried.
+    /// Requires the Tendermint RPC url to be set, see `Contact::set_tendermint_rpc_url`,
+    /// returns `BadInput` if it is not.
+    pub async fn get_unconfirmed_txs(&self, limit: u32) -> Result<UnconfirmedTxs, CosmosGrpcError> {
+        let base = match &self.tendermint_rpc_url {
+            Some(url) => url,
+            None => return Err(CosmosGrpcError::BadInput(
+                "Querying the mempool requires a Tendermint RPC url, see set_tendermint_rpc_url"
+                    .to_string(),
+            )),
+        };
+        let uri: Uri = format!("{}/unconfirmed_txs?limit={}", base, limit)
+            .parse()
+            .map_err(|e| CosmosGrpcError::BadInput(format!("Invalid Tendermint RPC url {}", e)))?;
+        let request = async {
+            let response = Client::new().get(uri).await?;
+            to_bytes(response.into_body()).await
+        };
+        match timeout(self.timeout, traced("unconfirmed_txs", base, request)).await {
+            Ok(Ok(body)) => parse_unconfirmed_txs(&body).map_err(CosmosGrpcError::BadResponse),
+            Ok(Err(e)) => Err(CosmosGrpcError::BadResponse(format!(
+                "Tendermint RPC request failed {}",
+                e
+            ))),
+            Err(_) => Err(CosmosGrpcError::Timeout {
+                time: self.timeout,
+                tx_hash: None,
+            }),
+        }
+    }
+
+    /// Broadcasts a signed transaction with the Tendermint RPC `broadcast_tx_commit`, which
+    /// returns once the transaction is in a block (or failed CheckTx) with both the CheckTx
+    /// and the block execution result. On chains with fast blocks this confirms sooner than
+    /// broadcasting over gRPC and polling with `wait_for_tx`. Requires the Tendermint RPC url
+    /// to be set, see `Contact::set_tendermint_rpc_url`, returns `BadInput` if it is not. The
+    /// node gives up waiting after it's `timeout_broadcast_tx_commit`, in which case an error
+    /// is returned even though the tx may still be included, and some public nodes disable
+    /// this endpoint entirely. Like `send_transaction` the confirm callback, if set, has to
+    /// approve the tx before it's posted.
+    pub async fn broadcast_commit(
+        &self,
+        tx_bytes: Vec<u8>,
+    ) -> Result<CommitResult, CosmosGrpcError> {
+        let base = match &self.tendermint_rpc_url {
+            Some(url) => url,
+            None => return Err(CosmosGrpcError::BadInput(
+                "Broadcasting with broadcast_tx_commit requires a Tendermint RPC url, see set_tendermint_rpc_url"
+                    .to_string(),
+            )),
+        };
+        self.confirm_tx(&tx_bytes).await?;
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "broadcast_tx_commit",
+            "params": {"tx": base64::encode(&tx_bytes)},
+        });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(base.as_str())
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .map_err(|e| CosmosGrpcError::BadInput(format!("Invalid Tendermint RPC url {}", e)))?;
+        let request = async {
+            let response = Client::new().request(request).await?;
+            to_bytes(response.into_body()).await
+        };
+        let request = traced("broadcast_tx_commit", base, request);
+        match timeout(self.timeout.max(BROADCAST_COMMIT_WAIT), request).await {
+            Ok(Ok(body)) => parse_broadcast_commit(&body).map_err(CosmosGrpcError::BadResponse),
+            Ok(Err(e)) => Err(CosmosGrpcError::BadResponse(format!(
+                "Tendermint RPC request failed {}",
+                e
+            ))),
+            Err(_) => Err(CosmosGrpcError::Timeout {
+                time: self.timeout.max(BROADCAST_COMMIT_WAIT),
+                tx_hash: Some(tx_hash(&tx_bytes)),
+            }),
+        }
+    }
+
+    /// Finds out if a broadcast transaction was included, is still waiting in the mempool or
+    /// was dropped, for example after being broadcast in async mode. The chain is queried
+    /// first so committed transactions are found without a Tendermint RPC url, telling pending
+    /// and dropped transactions apart requires one, see `get_unconfirmed_txs`. Tendermint only
+    /// returns the first 100 transactions of the mempool, if the tx isn't among them but the
+    /// mempool holds more it's reported as `Pending` rather than risk reporting it dropped.
+    pub async fn tx_status(&self, txhash: String) -> Result<TxStatus, CosmosGrpcError> {
+        let hash = match normalize_tx_hash(&txhash) {
+            Some(hash) => hash,
+            None => return Err(CosmosGrpcError::InvalidTxHash(txhash)),
+        };
+        if let Some(response) = self.committed_tx(&hash).await? {
+            return Ok(TxStatus::Committed(Box::new(response)));
+        }
+        let mempool = self.get_unconfirmed_txs(MEMPOOL_PAGE).await?;
+        if mempool.txs.iter().any(|tx| tx.hash == hash) || mempool.total > mempool.txs.len() as u64
+        {
+            return Ok(TxStatus::Pending);
+        }
+        // the tx may have left the mempool for a block between the two queries
+        match self.committed_tx(&hash).await? {
+            Some(response) => Ok(TxStatus::Committed(Box::new(response))),
+            None => Ok(TxStatus::Unknown),
+        }
+    }
+
+    pub(crate) async fn committed_tx(
+        &self,
+        hash: &str,
+    ) -> Result<Option<TxResponse>, CosmosGrpcError> {
+        match self.get_raw_tx_by_hash(hash.to_string()).await {
+            Ok(res) => Ok(res.tx_response),
+            // like `wait_for_tx`, some nodes report missing txs as unknown or invalid
+            Err(CosmosGrpcError::RequestError { error })
+                if matches!(
+                    error.code(),
+                    TonicCode::NotFound | TonicCode::Unknown | TonicCode::InvalidArgument
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn parse_unconfirmed_txs(body: &[u8]) -> Result<UnconfirmedTxs, String> {
+    let response: JsonRpcResponse = serde_json::from_slice(body)
+        .map_err(|e| format!("Invalid unconfirmed_txs response {}", e))?;
+    let result = match (response.result, response.error) {
+        (Some(result), None) => result,
+        (_, error) => return Err(format!("unconfirmed_txs failed {:?}", error)),
+    };
+    let number = |s: &str| {
+        s.parse::<u64>()
+            .map_err(|_| format!("Invalid number in unconfirmed_txs {}", s))
+    };
+    let mut txs = Vec::new();
+    for encoded in result.txs.unwrap_or_default() {
+        let bytes = base64::decode(&encoded)
+            .map_err(|e| format!("Invalid base64 tx in unconfirmed_txs {}", e))?;
+        txs.push(MempoolTx {
+            hash: tx_hash(&bytes),
+            tx: Tx::decode(bytes.as_slice()).ok(),
+            bytes,
+        });
+    }
+    Ok(UnconfirmedTxs {
+        total: number(&result.total)?,
+        total_bytes: number(&result.total_bytes)?,
+        txs,
+    })
+}
+
+fn parse_broadcast_commit(body: &[u8]) -> Result<CommitResult, String> {
+    let response: JsonRpcCommitResponse = serde_json::from_slice(body)
+        .map_err(|e| format!("Invalid broadcast_tx_commit response {}", e))?;
+    let result = match (response.result, response.error) {
+        (Some(result), None) => result,
+        (_, error) => return Err(format!("broadcast_tx_commit failed {:?}", error)),
+    };
+    let number = |s: &str| match s {
+        "" => Ok(0),
+        s => s
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid number in broadcast_tx_commit {}", s)),
+    };
+    let exec_result = |r: JsonExecResult| -> Result<TxExecResult, String> {
+        let data = match r.data {
+            Some(data) => base64::decode(&data)
+                .map_err(|e| format!("Invalid base64 data in broadcast_tx_commit {}", e))?,
+            None => Vec::new(),
+        };
+        Ok(TxExecResult {
+            code: r.code,
+            codespace: r.codespace,
+            log: r.log,
+            gas_wanted: number(&r.gas_wanted)?,
+            gas_used: number(&r.gas_used)?,
+            data,
+        })
+    };
+    let height = number(&result.height)?;
+    Ok(CommitResult {
+        hash: result.hash.to_uppercase(),
+        height,
+        check_tx: exec_result(result.check_tx)?,
+        deliver_tx: match height {
+            0 => None,
+            _ => Some(exec_result(result.deliver_tx)?),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::mock_contact;
+    use crate::client::types::{ConfirmTx, DryRunResult};
+    use crate::coin::Fee;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_broadcast_commit() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{"check_tx":{"code":0,"data":null,"log":"[]","info":"","gas_wanted":"200000","gas_used":"45000","events":[],"codespace":""},"deliver_tx":{"code":0,"data":"EiYKJC9jb3Ntb3MuYmFuay52MWJldGExLk1zZ1NlbmRSZXNwb25zZQ==","log":"[]","info":"","gas_wanted":"200000","gas_used":"81234","events":[],"codespace":""},"hash":"a1b2","height":"42"}}"#;
+        let res = parse_broadcast_commit(body.as_bytes()).unwrap();
+        assert!(res.is_success());
+        assert_eq!(res.hash, "A1B2");
+        assert_eq!(res.height, 42);
+        assert_eq!(res.check_tx.gas_used, 45_000);
+        let deliver_tx = res.deliver_tx.unwrap();
+        assert_eq!(deliver_tx.gas_used, 81_234);
+        assert!(!deliver_tx.data.is_empty());
+
+        // CometBFT v0.38, the tx failed during execution
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{"check_tx":{"code":0,"gas_wanted":"1000","gas_used":"900"},"tx_result":{"code":11,"codespace":"sdk","log":"out of gas","gas_wanted":"1000","gas_used":"1001"},"hash":"A1B2","height":"43"}}"#;
+        let res = parse_broadcast_commit(body.as_bytes()).unwrap();
+        assert!(!res.is_success());
+        assert_eq!(res.deliver_tx.unwrap().code, 11);
+
+        // rejected in CheckTx, never included
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{"check_tx":{"code":13,"codespace":"sdk","log":"insufficient fee","gas_wanted":"1000","gas_used":"0"},"deliver_tx":{},"hash":"A1B2","height":"0"}}"#;
+        let res = parse_broadcast_commit(body.as_bytes()).unwrap();
+        assert!(!res.is_success());
+        assert_eq!(res.check_tx.code, 13);
+        assert_eq!(res.deliver_tx, None);
+
+        let error = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":"timed out waiting for tx to be included in a block"}}"#;
+        assert!(parse_broadcast_commit(error.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_unconfirmed_txs() {
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::TxRaw;
+
+        let raw = TxRaw {
+            body_bytes: vec![],
+            auth_info_bytes: vec![],
+            signatures: vec![vec![1; 64]],
+        };
+        let mut bytes = Vec::new();
+        raw.encode(&mut bytes).unwrap();
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":-1,"result":{{"n_txs":"2","total":"5","total_bytes":"900","txs":["{}","/w=="]}}}}"#,
+            base64::encode(&bytes)
+        );
+        let res = parse_unconfirmed_txs(body.as_bytes()).unwrap();
+        assert_eq!(res.total, 5);
+        assert_eq!(res.total_bytes, 900);
+        assert_eq!(res.txs.len(), 2);
+        assert_eq!(res.txs[0].hash, tx_hash(&bytes));
+        assert_eq!(res.txs[0].tx.as_ref().unwrap().signatures, raw.signatures);
+        // not a Cosmos tx, but still reported
+        assert_eq!(res.txs[1].tx, None);
+        assert_eq!(res.txs[1].bytes, vec![0xff]);
+
+        let empty = r#"{"jsonrpc":"2.0","id":-1,"result":{"n_txs":"0","total":"0","total_bytes":"0","txs":null}}"#;
+        assert!(parse_unconfirmed_txs(empty.as_bytes())
+            .unwrap()
+            .txs
+            .is_empty());
+        let error =
+            r#"{"jsonrpc":"2.0","id":-1,"error":{"code":-32603,"message":"Internal error"}}"#;
+        assert!(parse_unconfirmed_txs(error.as_bytes()).is_err());
+    }
+
+    /// Serves `body` as the response to every request, standing in for the Tendermint RPC
+    fn serve_tendermint_rpc(body: String) -> String {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        url
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_tx_status() {
+        use crate::client::mempool::TxStatus;
+
+        let (mut contact, mock, _) = mock_contact();
+        let committed = "AB".repeat(32);
+        let pending = "CD".repeat(32);
+        mock.include_tx(TxResponse {
+            txhash: committed.clone(),
+            height: 9,
+            ..Default::default()
+        });
+        match contact.tx_status(committed.to_lowercase()).await.unwrap() {
+            TxStatus::Committed(res) => assert_eq!(res.height, 9),
+            other => panic!("Expected committed, got {:?}", other),
+        }
+        // without the Tendermint RPC pending and dropped txs can't be told apart
+        assert!(matches!(
+            contact.tx_status(pending.clone()).await,
+            Err(CosmosGrpcError::BadInput(_))
+        ));
+
+        // the mempool holds a tx with the `pending` hash
+        let tx = vec![0u8; 3];
+        let pending = crate::utils::tx_hash(&tx);
+        let url = serve_tendermint_rpc(format!(
+            r#"{{"jsonrpc":"2.0","id":-1,"result":{{"n_txs":"1","total":"1","total_bytes":"3","txs":["{}"]}}}}"#,
+            base64::encode(&tx)
+        ));
+        assert!(matches!(
+            contact.set_tendermint_rpc_url(Some("https://rpc.cosmos.network")),
+            Err(CosmosGrpcError::BadInput(_))
+        ));
+        contact.set_tendermint_rpc_url(Some(&url)).unwrap();
+        assert_eq!(contact.tx_status(pending).await.unwrap(), TxStatus::Pending);
+        assert_eq!(
+            contact.tx_status("EF".repeat(32)).await.unwrap(),
+            TxStatus::Unknown
+        );
+
+        let response = TxResponse {
+            txhash: "EF".repeat(32),
+            ..Default::default()
+        };
+        // dropped once two consecutive mempool checks, every third poll, don't find it
+        let mut polls = 0;
+        let res = contact
+            .wait_for_tx_with_progress(response, Duration::from_secs(20), |_| polls += 1)
+            .await;
+        match res {
+            Err(CosmosGrpcError::TxDropped { tx_hash }) => assert_eq!(tx_hash, "EF".repeat(32)),
+            other => panic!("Expected dropped tx, got {:?}", other),
+        }
+        assert_eq!(polls, 5);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_broadcast_commit_confirm() {
+        struct Confirm(bool);
+
+        #[async_trait::async_trait]
+        impl ConfirmTx for Confirm {
+            async fn confirm(&self, _tx: &DryRunResult) -> bool {
+                self.0
+            }
+        }
+
+        let (mut contact, _, key) = mock_contact();
+        let args = contact
+            .get_message_args(key.to_address("cosmos").unwrap(), Fee::default())
+            .await
+            .unwrap();
+        let tx = key.sign_std_msg(&[], args, "").unwrap();
+        let url = serve_tendermint_rpc(
+            r#"{"jsonrpc":"2.0","id":1,"result":{"check_tx":{"code":0,"gas_wanted":"1000","gas_used":"900"},"tx_result":{"code":0,"gas_wanted":"1000","gas_used":"950"},"hash":"A1B2","height":"11"}}"#
+                .to_string(),
+        );
+        contact.set_tendermint_rpc_url(Some(&url)).unwrap();
+
+        contact.set_confirm_callback(Some(Arc::new(Confirm(false))));
+        assert!(matches!(
+            contact.broadcast_commit(tx.clone()).await,
+            Err(CosmosGrpcError::UserRejected)
+        ));
+        contact.set_confirm_callback(Some(Arc::new(Confirm(true))));
+        let res = contact.broadcast_commit(tx).await.unwrap();
+        assert!(res.is_success());
+        assert_eq!(res.height, 11);
+    }
+}
@@ -0,0 +1,49 @@
+//! Contains utility functions for querying the Cosmos sdk mint module
+
+use crate::decimal::Decimal;
+use crate::decimal::DecimalError;
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::query_client::QueryClient as MintQueryClient;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::QueryAnnualProvisionsRequest;
+use cosmos_sdk_proto::cosmos::mint::v1beta1::QueryInflationRequest;
+use tonic::Code as TonicCode;
+use tonic::Status;
+
+impl Contact {
+    /// Gets the current inflation rate of the chain, for example 0.13 is 13% annual inflation.
+    /// Chains without the mint module return `ModuleNotPresent`
+    pub async fn get_inflation(&self) -> Result<Decimal, CosmosGrpcError> {
+        let mut grpc = MintQueryClient::new(self.channel().await?);
+        let res = grpc
+            .inflation(QueryInflationRequest {})
+            .await
+            .map_err(mint_error)?
+            .into_inner();
+        Decimal::from_sdk_dec_bytes(&res.inflation).map_err(dec_parse_error)
+    }
+
+    /// Gets the current annual provisions of the chain, meaning the amount of the mint denom
+    /// that will be minted over the next year at the current inflation rate. Chains without the
+    /// mint module return `ModuleNotPresent`
+    pub async fn get_annual_provisions(&self) -> Result<Decimal, CosmosGrpcError> {
+        let mut grpc = MintQueryClient::new(self.channel().await?);
+        let res = grpc
+            .annual_provisions(QueryAnnualProvisionsRequest {})
+            .await
+            .map_err(mint_error)?
+            .into_inner();
+        Decimal::from_sdk_dec_bytes(&res.annual_provisions).map_err(dec_parse_error)
+    }
+}
+
+fn mint_error(e: Status) -> CosmosGrpcError {
+    match e.code() {
+        TonicCode::Unimplemented => CosmosGrpcError::ModuleNotPresent("mint".to_string()),
+        _ => CosmosGrpcError::RequestError { error: e },
+    }
+}
+
+fn dec_parse_error(e: DecimalError) -> CosmosGrpcError {
+    CosmosGrpcError::BadResponse(format!("Failed to parse decimal {}", e))
+}
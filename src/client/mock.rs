@@ -0,0 +1,497 @@
+//! A mock implementation of `CosmosRpc` for unit testing code built on `Contact` without a
+//! running chain, available with the `mock` feature. Responses are configured ahead of time
+//! and every broadcast and simulation is recorded for inspection.
+//!
+//! ```ignore
+//! let mock = Arc::new(MockRpc::new("testing", 10));
+//! mock.add_account(&address, 5, 0);
+//! let mut contact = Contact::new("http://localhost:9090", timeout, "cosmos")?;
+//! contact.set_rpc(mock.clone());
+//! ```
+
+use crate::client::bank::{QuerySpendableBalancesRequest, QuerySpendableBalancesResponse};
+use crate::client::rpc::{ConfigRequest, ConfigResponse, CosmosRpc};
+use crate::error::CosmosGrpcError;
+use crate::utils::{encode_any, tx_hash};
+use crate::Address;
+use async_trait::async_trait;
+use cosmos_sdk_proto::cosmos::auth::v1beta1::{
+    BaseAccount, Params as AuthParams, QueryAccountRequest, QueryAccountResponse,
+    QueryParamsRequest as AuthParamsRequest, QueryParamsResponse as AuthParamsResponse,
+};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::{QueryAllBalancesRequest, QueryAllBalancesResponse};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{GasInfo, Result as AbciResult, TxResponse};
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageResponse;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::{
+    GetBlockByHeightRequest, GetBlockByHeightResponse, GetLatestBlockRequest,
+    GetLatestBlockResponse, GetSyncingRequest, GetSyncingResponse,
+};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    BroadcastTxRequest, BroadcastTxResponse, GetTxRequest, GetTxResponse, GetTxsEventRequest,
+    GetTxsEventResponse, SimulateRequest, SimulateResponse,
+};
+use prost::Message;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tendermint_proto::google::protobuf::Timestamp;
+use tendermint_proto::types::{Block, Commit, Data, Header};
+use tonic::Status;
+
+/// A configurable in memory stand in for a node, see the module docs
+#[derive(Debug, Default)]
+pub struct MockRpc {
+    /// The value returned by the syncing query
+    pub syncing: Mutex<bool>,
+    /// If the block and syncing queries fail with Unimplemented, like a node with it's
+    /// Tendermint service disabled
+    pub tendermint_disabled: Mutex<bool>,
+    /// The block returned as the latest block, None if the chain has not started
+    pub latest_block: Mutex<Option<Block>>,
+    /// Historical blocks by height, missing blocks return InvalidArgument like a pruned node
+    pub blocks: Mutex<HashMap<i64, Block>>,
+    /// The oldest height the mock has state for, queries at lower heights or above the
+    /// latest block fail like they would on a pruned node
+    pub earliest_state_height: Mutex<u64>,
+    /// The height of every account and balance query received, in order
+    pub query_heights: Mutex<Vec<Option<u64>>>,
+    /// Accounts by bech32 address, missing accounts return NotFound
+    pub accounts: Mutex<HashMap<String, BaseAccount>>,
+    pub auth_params: Mutex<AuthParams>,
+    /// The minimum gas prices of the node, None for a node that doesn't serve the config query
+    pub minimum_gas_price: Mutex<Option<String>>,
+    /// Balances by bech32 address
+    pub balances: Mutex<HashMap<String, Vec<ProtoCoin>>>,
+    /// Spendable balances by bech32 address, addresses without an entry can spend their
+    /// full balances
+    pub spendable_balances: Mutex<HashMap<String, Vec<ProtoCoin>>>,
+    /// Transactions that are included on chain by hash, missing txs return NotFound
+    pub txs: Mutex<HashMap<String, TxResponse>>,
+    /// Returned by every tx search if set, like a node that fails to decode a matched tx
+    pub tx_search_error: Mutex<Option<Status>>,
+    /// Results returned by successive broadcasts, once empty a broadcast succeeds
+    /// with a response containing the hash of the tx
+    pub broadcast_results: Mutex<VecDeque<Result<TxResponse, Status>>>,
+    /// Results returned by successive simulations, once empty simulations fail
+    pub simulate_results: Mutex<VecDeque<Result<GasInfo, Status>>>,
+    /// The execution result included in every successful simulation
+    pub simulate_abci_result: Mutex<Option<AbciResult>>,
+    /// Every broadcast request received, in order
+    pub broadcasts: Mutex<Vec<BroadcastTxRequest>>,
+    /// Every simulate request received, in order
+    pub simulations: Mutex<Vec<SimulateRequest>>,
+}
+
+impl MockRpc {
+    /// Creates a mock of a running chain with the given chain id at the given height
+    pub fn new(chain_id: &str, height: i64) -> MockRpc {
+        let mock = MockRpc::default();
+        mock.set_latest_block(chain_id, height);
+        *mock.auth_params.lock().unwrap() = AuthParams {
+            max_memo_characters: 256,
+            tx_sig_limit: 7,
+            tx_size_cost_per_byte: 10,
+            sig_verify_cost_ed25519: 590,
+            sig_verify_cost_secp256k1: 1000,
+        };
+        mock
+    }
+
+    /// Sets the latest block to an empty block with the given chain id and height
+    pub fn set_latest_block(&self, chain_id: &str, height: i64) {
+        *self.latest_block.lock().unwrap() = Some(empty_block(chain_id, height, None));
+    }
+
+    /// Adds an empty block with the given time to the historical blocks, it also becomes the
+    /// latest block if it is at least as high as the current latest block
+    pub fn add_block(&self, chain_id: &str, height: i64, time: SystemTime) {
+        let block = empty_block(chain_id, height, Some(time));
+        let mut latest = self.latest_block.lock().unwrap();
+        let latest_height = latest
+            .as_ref()
+            .and_then(|b| b.header.as_ref())
+            .map(|h| h.height);
+        match latest_height {
+            Some(latest_height) if latest_height > height => {}
+            _ => *latest = Some(block.clone()),
+        }
+        self.blocks.lock().unwrap().insert(height, block);
+    }
+    /// Sets the raw transactions of a block previously added with `add_block`
+    pub fn set_block_txs(&self, height: i64, txs: Vec<Vec<u8>>) {
+        let mut blocks = self.blocks.lock().unwrap();
+        let block = blocks.get_mut(&height).expect("block not added");
+        block.data = Some(Data { txs });
+        let mut latest = self.latest_block.lock().unwrap();
+        if latest
+            .as_ref()
+            .and_then(|b| b.header.as_ref())
+            .map(|h| h.height)
+            == Some(height)
+        {
+            *latest = Some(block.clone());
+        }
+    }
+
+    /// Adds or replaces an account
+    pub fn add_account(&self, address: &Address, account_number: u64, sequence: u64) {
+        let account = BaseAccount {
+            address: address.to_string(),
+            pub_key: None,
+            account_number,
+            sequence,
+        };
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert(address.to_string(), account);
+    }
+
+    /// The error returned by Tendermint service queries if it's disabled
+    fn tendermint_error(&self) -> Option<CosmosGrpcError> {
+        if *self.tendermint_disabled.lock().unwrap() {
+            return Some(
+                Status::unimplemented("unknown service cosmos.base.tendermint.v1beta1.Service")
+                    .into(),
+            );
+        }
+        None
+    }
+
+    /// Queues the result of the next broadcast
+    pub fn push_broadcast_result(&self, result: Result<TxResponse, Status>) {
+        self.broadcast_results.lock().unwrap().push_back(result);
+    }
+
+    /// Queues the result of the next simulation
+    pub fn push_simulate_result(&self, result: Result<GasInfo, Status>) {
+        self.simulate_results.lock().unwrap().push_back(result);
+    }
+
+    /// Marks a transaction as included on chain
+    pub fn include_tx(&self, response: TxResponse) {
+        self.txs
+            .lock()
+            .unwrap()
+            .insert(response.txhash.clone(), response);
+    }
+
+    /// Records the height of a query and returns the error of a node with no state at
+    /// that height, if there is no state at that height
+    fn height_unavailable(&self, height: Option<u64>) -> Option<Status> {
+        self.query_heights.lock().unwrap().push(height);
+        let height = height?;
+        let latest = self
+            .latest_block
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|b| b.header.as_ref())
+            .map(|h| h.height as u64)
+            .unwrap_or(0);
+        if height > latest {
+            Some(Status::invalid_argument(
+                "cannot query with height in the future; please provide a valid height",
+            ))
+        } else if height < *self.earliest_state_height.lock().unwrap() {
+            Some(Status::invalid_argument(format!(
+                "failed to load state at height {}; version does not exist (latest height: {})",
+                height, latest
+            )))
+        } else {
+            None
+        }
+    }
+
+    /// The number of broadcasts received so far
+    pub fn broadcast_count(&self) -> usize {
+        self.broadcasts.lock().unwrap().len()
+    }
+}
+
+fn empty_block(chain_id: &str, height: i64, time: Option<SystemTime>) -> Block {
+    let time = time.map(|time| {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap();
+        Timestamp {
+            seconds: since_epoch.as_secs() as i64,
+            nanos: since_epoch.subsec_nanos() as i32,
+        }
+    });
+    Block {
+        header: Some(Header {
+            chain_id: chain_id.to_string(),
+            height,
+            time,
+            ..Default::default()
+        }),
+        data: None,
+        evidence: None,
+        last_commit: Some(Commit {
+            height,
+            ..Default::default()
+        }),
+    }
+}
+
+#[async_trait]
+impl CosmosRpc for MockRpc {
+    async fn get_syncing(
+        &self,
+        _req: GetSyncingRequest,
+    ) -> Result<GetSyncingResponse, CosmosGrpcError> {
+        if let Some(e) = self.tendermint_error() {
+            return Err(e);
+        }
+        Ok(GetSyncingResponse {
+            syncing: *self.syncing.lock().unwrap(),
+        })
+    }
+
+    async fn get_latest_block(
+        &self,
+        _req: GetLatestBlockRequest,
+    ) -> Result<GetLatestBlockResponse, CosmosGrpcError> {
+        if let Some(e) = self.tendermint_error() {
+            return Err(e);
+        }
+        Ok(GetLatestBlockResponse {
+            block_id: None,
+            block: self.latest_block.lock().unwrap().clone(),
+        })
+    }
+
+    async fn get_block_by_height(
+        &self,
+        req: GetBlockByHeightRequest,
+    ) -> Result<GetBlockByHeightResponse, CosmosGrpcError> {
+        if let Some(e) = self.tendermint_error() {
+            return Err(e);
+        }
+        match self.blocks.lock().unwrap().get(&req.height) {
+            Some(block) => Ok(GetBlockByHeightResponse {
+                block_id: None,
+                block: Some(block.clone()),
+            }),
+            None => Err(Status::invalid_argument(format!(
+                "height {} is not available",
+                req.height
+            ))
+            .into()),
+        }
+    }
+
+    async fn account(
+        &self,
+        req: QueryAccountRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAccountResponse, CosmosGrpcError> {
+        if let Some(e) = self.height_unavailable(height) {
+            return Err(e.into());
+        }
+        match self.accounts.lock().unwrap().get(&req.address) {
+            Some(account) => Ok(QueryAccountResponse {
+                account: Some(encode_any(
+                    account.clone(),
+                    "/cosmos.auth.v1beta1.BaseAccount",
+                )),
+            }),
+            None => Err(Status::not_found(format!("account {} not found", req.address)).into()),
+        }
+    }
+
+    async fn auth_params(
+        &self,
+        _req: AuthParamsRequest,
+    ) -> Result<AuthParamsResponse, CosmosGrpcError> {
+        Ok(AuthParamsResponse {
+            params: Some(self.auth_params.lock().unwrap().clone()),
+        })
+    }
+
+    async fn all_balances(
+        &self,
+        req: QueryAllBalancesRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAllBalancesResponse, CosmosGrpcError> {
+        if let Some(e) = self.height_unavailable(height) {
+            return Err(e.into());
+        }
+        Ok(QueryAllBalancesResponse {
+            balances: self
+                .balances
+                .lock()
+                .unwrap()
+                .get(&req.address)
+                .cloned()
+                .unwrap_or_default(),
+            pagination: None,
+        })
+    }
+
+    async fn spendable_balances(
+        &self,
+        req: QuerySpendableBalancesRequest,
+    ) -> Result<QuerySpendableBalancesResponse, CosmosGrpcError> {
+        let balances = match self.spendable_balances.lock().unwrap().get(&req.address) {
+            Some(balances) => balances.clone(),
+            None => self
+                .balances
+                .lock()
+                .unwrap()
+                .get(&req.address)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        Ok(QuerySpendableBalancesResponse {
+            balances,
+            pagination: None,
+        })
+    }
+
+    async fn get_tx(&self, req: GetTxRequest) -> Result<GetTxResponse, CosmosGrpcError> {
+        match self.txs.lock().unwrap().get(&req.hash) {
+            Some(response) => Ok(GetTxResponse {
+                tx: None,
+                tx_response: Some(response.clone()),
+            }),
+            None => Err(Status::not_found(format!("tx not found: {}", req.hash)).into()),
+        }
+    }
+
+    async fn get_tx_raw(&self, req: GetTxRequest) -> Result<Vec<u8>, CosmosGrpcError> {
+        let res = self.get_tx(req).await?;
+        let mut buf = Vec::new();
+        res.encode(&mut buf).unwrap();
+        Ok(buf)
+    }
+
+    /// Supports the `tx.height=<height>` event and `<type>.<key>='<value>'` events, which are
+    /// matched against the logs of the included txs. Matching txs are returned in hash order
+    /// using offset pagination like the sdk
+    async fn get_txs_event(
+        &self,
+        req: GetTxsEventRequest,
+    ) -> Result<GetTxsEventResponse, CosmosGrpcError> {
+        if let Some(error) = self.tx_search_error.lock().unwrap().clone() {
+            return Err(error.into());
+        }
+        let mut conditions = Vec::new();
+        for event in req.events.iter() {
+            match event
+                .split_once('=')
+                .and_then(|(k, v)| Some((k.split_once('.')?, v)))
+            {
+                Some(((kind, key), value)) => {
+                    conditions.push((kind, key, value.trim_matches('\'')));
+                }
+                None => return Err(Status::invalid_argument("unsupported events").into()),
+            }
+        }
+        let matches = |response: &TxResponse| {
+            conditions
+                .iter()
+                .all(|(kind, key, value)| match (*kind, *key) {
+                    ("tx", "height") => response.height.to_string() == *value,
+                    _ => response.logs.iter().any(|log| {
+                        log.events.iter().any(|e| {
+                            e.r#type == *kind
+                                && e.attributes
+                                    .iter()
+                                    .any(|a| a.key == *key && a.value == *value)
+                        })
+                    }),
+                })
+        };
+        let mut matching: Vec<TxResponse> = self
+            .txs
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|r| matches(r))
+            .cloned()
+            .collect();
+        matching.sort_by(|a, b| a.txhash.cmp(&b.txhash));
+        let total = matching.len() as u64;
+        let (offset, limit) = match &req.pagination {
+            Some(page) if page.limit > 0 => (page.offset as usize, page.limit as usize),
+            Some(page) => (page.offset as usize, 100),
+            None => (0, 100),
+        };
+        let tx_responses: Vec<TxResponse> = matching.into_iter().skip(offset).take(limit).collect();
+        Ok(GetTxsEventResponse {
+            txs: tx_responses
+                .iter()
+                .map(|r| {
+                    r.tx.as_ref()
+                        .and_then(|tx| crate::utils::decode_any(tx).ok())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            tx_responses,
+            pagination: Some(PageResponse {
+                next_key: Vec::new(),
+                total,
+            }),
+        })
+    }
+
+    async fn broadcast_tx(
+        &self,
+        req: BroadcastTxRequest,
+    ) -> Result<BroadcastTxResponse, CosmosGrpcError> {
+        let hash = tx_hash(&req.tx_bytes);
+        self.broadcasts.lock().unwrap().push(req);
+        let result = self.broadcast_results.lock().unwrap().pop_front();
+        match result {
+            Some(Ok(tx_response)) => Ok(BroadcastTxResponse {
+                tx_response: Some(tx_response),
+            }),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(BroadcastTxResponse {
+                tx_response: Some(TxResponse {
+                    txhash: hash,
+                    ..Default::default()
+                }),
+            }),
+        }
+    }
+
+    async fn simulate(&self, req: SimulateRequest) -> Result<SimulateResponse, CosmosGrpcError> {
+        self.simulations.lock().unwrap().push(req);
+        let result = self.simulate_results.lock().unwrap().pop_front();
+        match result {
+            Some(Ok(gas_info)) => Ok(SimulateResponse {
+                gas_info: Some(gas_info),
+                result: self.simulate_abci_result.lock().unwrap().clone(),
+            }),
+            Some(Err(e)) => Err(e.into()),
+            None => Err(Status::internal("no simulation result configured").into()),
+        }
+    }
+
+    async fn node_config(&self, _req: ConfigRequest) -> Result<ConfigResponse, CosmosGrpcError> {
+        match self.minimum_gas_price.lock().unwrap().clone() {
+            Some(minimum_gas_price) => Ok(ConfigResponse { minimum_gas_price }),
+            None => Err(
+                Status::unimplemented("unknown service cosmos.base.node.v1beta1.Service").into(),
+            ),
+        }
+    }
+}
+
+/// A `Contact` for the prefix `cosmos` backed by a mock at height 10 of the chain `testing`,
+/// along with the key of the one account it holds, shared by the unit tests of the modules
+#[cfg(test)]
+pub(crate) fn mock_contact() -> (crate::Contact, std::sync::Arc<MockRpc>, crate::PrivateKey) {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let key = crate::PrivateKey::from_secret(b"mock rpc test key");
+    let mock = Arc::new(MockRpc::new("testing", 10));
+    mock.add_account(&key.to_address("cosmos").unwrap(), 5, 0);
+    let mut contact =
+        crate::Contact::new("http://localhost:9090", Duration::from_secs(60), "cosmos").unwrap();
+    contact.set_rpc(mock.clone());
+    (contact, mock, key)
+}
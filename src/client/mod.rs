@@ -1,30 +1,165 @@
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+pub mod authz;
+pub mod bank;
 pub mod distribution;
+pub mod faucet;
+pub mod feemarket;
 pub mod get;
 pub mod gov;
+pub mod ibc;
+pub mod mempool;
+pub mod mint;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod params;
+pub mod pool;
+pub mod rpc;
 pub mod send;
+pub mod slashing;
 pub mod staking;
+#[cfg(feature = "osmosis")]
+pub mod txfees;
 pub mod types;
+pub mod upgrade;
 
+pub use types::AccountOverride;
+pub use types::AccountPubKey;
+pub use types::BlockStats;
+pub use types::BondStatus;
+pub use types::ChainInfo;
 pub use types::ChainStatus;
+pub use types::ChannelOrder;
+pub use types::ChannelState;
+pub use types::ConfirmTx;
+pub use types::ConnectionState;
+pub use types::ConsensusPubKey;
+pub use types::ConsensusValidator;
+pub use types::DryRunResult;
+pub use types::FeeStrategy;
+pub use types::GasEstimate;
+pub use types::ProposalStatus;
+pub use types::Receipt;
+pub use types::SendOptions;
+pub use types::SimulateResult;
+pub use types::SimulatedEvent;
+pub use types::TxEvent;
+pub use types::TxRecord;
+pub use types::TxResponseEvents;
+pub use types::TxSummary;
+pub use types::ValidatorSet;
+pub use types::VoteOption;
 
-use crate::{error::CosmosGrpcError, utils::ArrayString};
+use crate::type_registry::TypeRegistry;
+use crate::{address::Address, coin::GasPrice, error::CosmosGrpcError, utils::ArrayString};
+use cosmos_sdk_proto::cosmos::auth::v1beta1::Params as AuthParams;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::Params as BankParams;
+use cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
+use prost_types::Any;
+use rpc::{connect, connect_endpoint, CosmosRpc, GrpcRpc, KeepAlive, TracedChannel};
+use tonic::transport::Endpoint;
 
 pub const MEMO: &str = "Sent with Deep Space";
 
+/// The default multiplier applied to the simulated gas usage of a transaction
+/// to produce it's gas limit, simulation is not perfectly accurate so some
+/// margin is required
+pub const DEFAULT_GAS_ADJUSTMENT: f64 = 1.3;
+
+/// The default multiplier applied to fees computed from the gas price, no buffer
+pub const DEFAULT_FEE_BUFFER: f64 = 1.0;
+
+/// The default gas `Contact::estimate_gas_offline` adds to the ante handler costs of a
+/// transaction for executing it's messages, enough for a few bank sends
+pub const DEFAULT_OFFLINE_BASE_GAS: u64 = 80_000;
+
+/// The default time `Contact::send_and_confirm` waits for a transaction to be included and
+/// confirmed, see `SendOptions`
+pub const DEFAULT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The default number of times a broadcast is retried after a transport
+/// failure, see `Contact::send_transaction` for when a retry is performed
+pub const DEFAULT_BROADCAST_RETRIES: u32 = 2;
+
 /// An instance of Contact Cosmos RPC Client.
+///
+/// Contact is `Send + Sync` and cheap to clone, so the usual way to share it between tasks
+/// is to clone it into each of them. Clones share the transport and everything cached about
+/// the chain, currently the auth and bank module params, so a value queried by one clone is reused by
+/// all of them and a `refresh_auth_params` on any clone is seen by every clone. Configuration
+/// such as the gas price or sign mode is copied on clone, changing it afterwards only
+/// affects that clone. No connection is held between calls, each call connects anew so that a
+/// load balanced url fails over, so there is no connection state for clones to share or reset.
+/// Contact does not track account sequences, transactions sent concurrently from the same key
+/// will race for the same sequence, sign them in order and use `send_all` or send them from
+/// a single task instead.
 #[derive(Clone)]
 pub struct Contact {
     /// The GRPC server url, we connect to this address
     /// with a new instance for each call to ensure
     /// proper failover
     url: String,
+    /// The Tendermint RPC url of the same node, only needed for the few
+    /// queries that are not served over gRPC such as the mempool
+    tendermint_rpc_url: Option<String>,
     /// The maximum amount of wall time any action taken
     /// will wait for.
     timeout: Duration,
+    /// HTTP/2 keep alive settings of the connections made to the node
+    keep_alive: KeepAlive,
+    /// A caller configured endpoint, when set it's used to connect instead
+    /// of the url and keep alive settings
+    endpoint: Option<Endpoint>,
     /// The prefix being used by this node / chain for Addresses
     chain_prefix: String,
+    /// The chain id to sign transactions for, if None it's taken from the
+    /// latest block
+    chain_id: Option<String>,
+    /// The multiplier applied to simulated gas usage when estimating
+    /// the gas limit of a transaction
+    gas_adjustment: f64,
+    /// The multiplier applied to fees computed from the gas price, on top of
+    /// the gas adjustment
+    fee_buffer: f64,
+    /// The execution gas `estimate_gas_offline` adds to the ante handler costs
+    offline_base_gas: u64,
+    /// The gas price used to compute fees for estimated transactions, if
+    /// this is not set the node is asked for the fee it requires
+    gas_price: Option<GasPrice>,
+    /// If addresses with a prefix other than chain_prefix should be rejected
+    /// rather than just logging a warning
+    strict_prefix: bool,
+    /// The number of times a broadcast that failed in transit is retried
+    broadcast_retries: u32,
+    /// If transactions pay fees, when false every transaction is signed with an
+    /// empty fee amount
+    fees_required: bool,
+    /// If a transaction rejected for insufficient fees should be signed again
+    /// with the fee the node requires and sent once more
+    auto_bump_fees: bool,
+    /// The auth module params of the chain, these change rarely so they are
+    /// queried once and shared between clones of this Contact
+    auth_params: Arc<RwLock<Option<AuthParams>>>,
+    /// The bank module params of the chain, cached and shared like the auth params
+    bank_params: Arc<RwLock<Option<BankParams>>>,
+    /// What was fetched about the chain by the last `bootstrap`
+    chain_info: Option<ChainInfo>,
+    /// How long the send helpers wait for a transaction to enter the chain when
+    /// no wait timeout is passed to them, None to not wait
+    default_wait_timeout: Option<Duration>,
+    /// The sign mode transactions are signed with by the send helpers
+    sign_mode: SignMode,
+    /// The denoms fees are paid in by preference, empty to use the gas price
+    fee_denom_preference: Vec<String>,
+    /// The message types of the chain's own modules, for the JSON form of messages
+    type_registry: TypeRegistry,
+    /// Approves every transaction before it's broadcast, None to broadcast without asking
+    confirm_callback: Option<Arc<dyn ConfirmTx>>,
+    /// The transport used for block, account and transaction calls
+    rpc: Arc<dyn CosmosRpc>,
+    /// If the transport was replaced with `set_rpc`, it's then kept by `set_keep_alive`
+    custom_rpc: bool,
 }
 
 impl Contact {
@@ -36,11 +171,49 @@ impl Contact {
         ArrayString::new(chain_prefix)?;
         Ok(Self {
             url: url.to_string(),
+            tendermint_rpc_url: None,
             timeout,
+            keep_alive: KeepAlive::default(),
+            endpoint: None,
             chain_prefix: chain_prefix.to_string(),
+            chain_id: None,
+            gas_adjustment: DEFAULT_GAS_ADJUSTMENT,
+            fee_buffer: DEFAULT_FEE_BUFFER,
+            offline_base_gas: DEFAULT_OFFLINE_BASE_GAS,
+            gas_price: None,
+            strict_prefix: false,
+            broadcast_retries: DEFAULT_BROADCAST_RETRIES,
+            fees_required: true,
+            auto_bump_fees: false,
+            auth_params: Arc::new(RwLock::new(None)),
+            bank_params: Arc::new(RwLock::new(None)),
+            chain_info: None,
+            default_wait_timeout: None,
+            sign_mode: SignMode::Direct,
+            fee_denom_preference: Vec::new(),
+            type_registry: TypeRegistry::default(),
+            confirm_callback: None,
+            rpc: Arc::new(GrpcRpc::new(url.to_string())),
+            custom_rpc: false,
         })
     }
 
+    /// Creates a Contact that connects using the given endpoint for every call, for transport
+    /// settings the url of `new` can't express such as TLS, HTTP/2 window sizes or connect and
+    /// request timeouts. The endpoint's settings are used as is, so `set_keep_alive` has no
+    /// effect on the returned Contact.
+    #[allow(clippy::result_large_err)]
+    pub fn from_endpoint(
+        endpoint: Endpoint,
+        timeout: Duration,
+        chain_prefix: &str,
+    ) -> Result<Self, CosmosGrpcError> {
+        let mut contact = Contact::new(&endpoint.uri().to_string(), timeout, chain_prefix)?;
+        contact.rpc = Arc::new(GrpcRpc::from_endpoint(endpoint.clone()));
+        contact.endpoint = Some(endpoint);
+        Ok(contact)
+    }
+
     pub fn get_prefix(&self) -> String {
         self.chain_prefix.clone()
     }
@@ -49,19 +222,478 @@ impl Contact {
         self.url.clone()
     }
 
+    pub fn get_chain_id(&self) -> Option<String> {
+        self.chain_id.clone()
+    }
+
+    /// Sets the chain id transactions are signed for. By default the chain id is taken from
+    /// the header of the latest block when signing, a configured chain id takes precedence
+    /// over it and also allows signing when the node does not serve blocks, for example
+    /// when it's Tendermint service is disabled, in which case transactions are signed without
+    /// a timeout height. If there is neither a configured chain id nor a block from the node
+    /// the error of the block query is returned.
+    pub fn set_chain_id(&mut self, chain_id: Option<String>) {
+        self.chain_id = chain_id;
+    }
+
+    /// The chain info fetched by the last call to `bootstrap`, None if it was never called
+    pub fn get_chain_info(&self) -> Option<&ChainInfo> {
+        self.chain_info.as_ref()
+    }
+
+    pub fn get_tendermint_rpc_url(&self) -> Option<String> {
+        self.tendermint_rpc_url.clone()
+    }
+
+    /// Sets the Tendermint RPC url of the node, for example `http://localhost:26657`. This is
+    /// a different port, and often a different host, than the gRPC url and is required by
+    /// queries that are only served by Tendermint such as `get_unconfirmed_txs`. Only plain
+    /// `http` is supported, `https` urls are rejected with `BadInput`, use a local proxy that
+    /// terminates TLS for nodes only served over https
+    #[allow(clippy::result_large_err)]
+    pub fn set_tendermint_rpc_url(&mut self, url: Option<&str>) -> Result<(), CosmosGrpcError> {
+        if let Some(url) = url {
+            if url.trim().to_ascii_lowercase().starts_with("https://") {
+                return Err(CosmosGrpcError::BadInput(format!(
+                    "Tendermint RPC url {} uses https which is not supported, use http",
+                    url
+                )));
+            }
+        }
+        self.tendermint_rpc_url = url.map(|url| url.trim_end_matches('/').to_string());
+        Ok(())
+    }
+
+    /// Replaces the transport used for block, account and transaction calls, see the
+    /// `rpc` module. Mostly useful for testing with `mock::MockRpc`
+    pub fn set_rpc(&mut self, rpc: Arc<dyn CosmosRpc>) {
+        self.rpc = rpc;
+        self.custom_rpc = true;
+    }
+
     pub fn get_timeout(&self) -> Duration {
         self.timeout
     }
+
+    pub fn get_keep_alive(&self) -> KeepAlive {
+        self.keep_alive
+    }
+
+    /// Sets the HTTP/2 keep alive settings of the connections made to the node, by default a
+    /// ping is sent every 30 seconds. A new connection is made for each call so connections are
+    /// not left idle between calls, the pings detect connections dropped during long calls such
+    /// as a broadcast in `Block` mode. A transport set with `set_rpc` is kept, the settings
+    /// then only apply to the calls made over gRPC directly. Ignored if this Contact was made
+    /// with `from_endpoint`
+    pub fn set_keep_alive(&mut self, keep_alive: KeepAlive) {
+        if self.endpoint.is_some() {
+            return;
+        }
+        self.keep_alive = keep_alive;
+        if !self.custom_rpc {
+            self.rpc = Arc::new(GrpcRpc::with_keep_alive(self.url.clone(), keep_alive));
+        }
+    }
+
+    /// Connects to the gRPC server of the node
+    pub(crate) async fn channel(&self) -> Result<TracedChannel, CosmosGrpcError> {
+        match &self.endpoint {
+            Some(endpoint) => connect_endpoint(endpoint).await,
+            None => connect(&self.url, self.keep_alive).await,
+        }
+    }
+
+    pub fn get_gas_adjustment(&self) -> f64 {
+        self.gas_adjustment
+    }
+
+    /// Sets the multiplier applied to simulated gas usage, values below 1.0 will
+    /// produce gas limits that are too low and are rejected
+    #[allow(clippy::result_large_err)]
+    pub fn set_gas_adjustment(&mut self, gas_adjustment: f64) -> Result<(), CosmosGrpcError> {
+        if !gas_adjustment.is_finite() || gas_adjustment < 1.0 {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "Gas adjustment must be at least 1.0, got {}",
+                gas_adjustment
+            )));
+        }
+        self.gas_adjustment = gas_adjustment;
+        Ok(())
+    }
+
+    pub fn get_fee_buffer(&self) -> f64 {
+        self.fee_buffer
+    }
+
+    /// Sets the multiplier applied to the fee amount `estimate_fee` computes from the gas price,
+    /// so that a transaction still pays enough if the minimum gas price rises between estimation
+    /// and broadcast. This is independent of the gas adjustment, which sets the margin on the
+    /// `gas_limit` of the fee, the buffer only scales the amount paid and leaves the limit as is.
+    /// With a gas adjustment of 1.3 and a buffer of 1.2 a tx simulated at 100k gas gets a limit
+    /// of 130k and pays for 156k gas at the gas price. Values below 1.0 are rejected.
+    #[allow(clippy::result_large_err)]
+    pub fn set_fee_buffer(&mut self, fee_buffer: f64) -> Result<(), CosmosGrpcError> {
+        if !fee_buffer.is_finite() || fee_buffer < 1.0 {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "Fee buffer must be at least 1.0, got {}",
+                fee_buffer
+            )));
+        }
+        self.fee_buffer = fee_buffer;
+        Ok(())
+    }
+
+    pub fn get_offline_base_gas(&self) -> u64 {
+        self.offline_base_gas
+    }
+
+    /// Sets the gas `estimate_gas_offline` adds for executing the messages of a transaction,
+    /// `DEFAULT_OFFLINE_BASE_GAS` by default. Raise it for messages heavier than bank sends,
+    /// such as contract calls
+    pub fn set_offline_base_gas(&mut self, base_gas: u64) {
+        self.offline_base_gas = base_gas;
+    }
+
+    pub fn get_gas_price(&self) -> Option<GasPrice> {
+        self.gas_price.clone()
+    }
+
+    /// Sets the gas price used to compute fees for estimated transactions
+    pub fn set_gas_price(&mut self, gas_price: Option<GasPrice>) {
+        self.gas_price = gas_price;
+    }
+
+    /// Sets a callback that is asked to approve every transaction before it's broadcast, with
+    /// the messages, fee and hash of the signed transaction. If it returns false nothing is
+    /// broadcast and `UserRejected` is returned. By default transactions are broadcast without
+    /// asking. A transaction re-signed with a new fee, see `set_auto_bump_fees`, is asked
+    /// for again, broadcast retries of the same transaction are not
+    pub fn set_confirm_callback(&mut self, confirm_callback: Option<Arc<dyn ConfirmTx>>) {
+        self.confirm_callback = confirm_callback;
+    }
+
+    pub fn get_fees_required(&self) -> bool {
+        self.fees_required
+    }
+
+    /// Disabling fees is for chains that accept zero fee transactions, such as testnets and
+    /// some app specific chains. Every transaction is then signed with an empty fee amount
+    /// regardless of the fee passed in, `estimate_fee` skips the gas price and `send_transaction`
+    /// no longer interprets rejections as `InsufficientFees`. Gas is still metered on these chains
+    /// so the gas limit of the fee is kept as is and `estimate_fee` still simulates to produce it
+    pub fn set_fees_required(&mut self, fees_required: bool) {
+        self.fees_required = fees_required;
+    }
+
+    pub fn get_auto_bump_fees(&self) -> bool {
+        self.auto_bump_fees
+    }
+
+    /// When enabled a transaction sent by `create_and_send`, or any of the helpers built on it,
+    /// that the node rejects with `InsufficientFees` is signed again with the fee amount the
+    /// node states it requires and broadcast once more, the gas limit is unchanged. If that fails
+    /// too the error is returned. Disabled by default, in which case `InsufficientFees` is returned
+    /// so that the fee paid is always the fee requested
+    pub fn set_auto_bump_fees(&mut self, auto_bump_fees: bool) {
+        self.auto_bump_fees = auto_bump_fees;
+    }
+
+    pub fn get_strict_prefix(&self) -> bool {
+        self.strict_prefix
+    }
+
+    /// When enabled addresses passed to utility functions like `send_tokens` must have
+    /// the same prefix as this chain, otherwise a `PrefixMismatch` error is returned. When
+    /// disabled (the default) a warning is logged instead.
+    pub fn set_strict_prefix(&mut self, strict_prefix: bool) {
+        self.strict_prefix = strict_prefix;
+    }
+
+    pub fn get_broadcast_retries(&self) -> u32 {
+        self.broadcast_retries
+    }
+
+    /// Sets the number of times `send_transaction` will retry a broadcast that failed
+    /// in transit, zero disables retries
+    pub fn set_broadcast_retries(&mut self, broadcast_retries: u32) {
+        self.broadcast_retries = broadcast_retries;
+    }
+
+    pub fn get_default_wait_timeout(&self) -> Option<Duration> {
+        self.default_wait_timeout
+    }
+
+    /// Sets how long `send_tokens`, `create_and_send` and the other send helpers wait for a
+    /// transaction to enter the chain when they are passed a `wait_timeout` of None, a timeout
+    /// passed to the helper takes precedence. With the default of None they don't wait
+    pub fn set_default_wait_timeout(&mut self, default_wait_timeout: Option<Duration>) {
+        self.default_wait_timeout = default_wait_timeout;
+    }
+
+    pub fn get_sign_mode(&self) -> SignMode {
+        self.sign_mode
+    }
+
+    /// Sets the sign mode `create_and_send`, `send_tokens` and the other send helpers sign
+    /// transactions with, `Direct` by default. Some chains and hardware signers only accept
+    /// `LegacyAminoJson`, which can only sign the messages supported by the `amino` module.
+    /// These are the two modes supported for the secp256k1 keys of this crate, any other mode
+    /// is rejected with `BadInput`. Transactions with more than one signer are always signed
+    /// with `Direct`
+    #[allow(clippy::result_large_err)]
+    pub fn set_sign_mode(&mut self, sign_mode: SignMode) -> Result<(), CosmosGrpcError> {
+        match sign_mode {
+            SignMode::Direct | SignMode::LegacyAminoJson => {
+                self.sign_mode = sign_mode;
+                Ok(())
+            }
+            other => Err(CosmosGrpcError::BadInput(format!(
+                "Sign mode {:?} is not supported for secp256k1 keys",
+                other
+            ))),
+        }
+    }
+
+    pub fn get_fee_denom_preference(&self) -> &[String] {
+        &self.fee_denom_preference
+    }
+
+    /// Sets the denoms `estimate_fee` and `simulate_and_send` pay fees in, most preferred first.
+    /// The first denom the node has a minimum gas price in and the paying account can spend
+    /// enough of for the fee is used, tokens locked by vesting don't count and the spendable
+    /// balances are queried for every fee estimate. This takes the place
+    /// of the configured gas price for the fee amount, an empty list restores it
+    pub fn set_fee_denom_preference(&mut self, denoms: Vec<String>) {
+        self.fee_denom_preference = denoms;
+    }
+
+    pub fn get_type_registry(&self) -> &TypeRegistry {
+        &self.type_registry
+    }
+
+    /// Sets the message types of the chain's own modules that `any_to_json` and
+    /// `decode_any_readable` show the fields of, see `type_registry` for how to register
+    /// prost generated types
+    pub fn set_type_registry(&mut self, registry: TypeRegistry) {
+        self.type_registry = registry;
+    }
+
+    /// Encodes an Any as proto3 JSON, see `proto_json::any_to_json`, with the types registered
+    /// with `set_type_registry`. Use this to show messages, message responses or proposal
+    /// contents of chains with custom modules
+    pub fn any_to_json(&self, any: &Any) -> serde_json::Value {
+        self.type_registry.any_to_json(any)
+    }
+
+    /// A human readable form of an Any, see `proto_json::decode_any_readable`, with the types
+    /// registered with `set_type_registry`
+    pub fn decode_any_readable(&self, any: &Any) -> (String, serde_json::Value) {
+        self.type_registry.decode_any_readable(any)
+    }
+
+    /// The wait timeout of a send helper, falling back to the default wait timeout
+    pub(crate) fn wait_timeout(&self, wait_timeout: Option<Duration>) -> Option<Duration> {
+        wait_timeout.or(self.default_wait_timeout)
+    }
+
+    /// Checks that an address was decoded with the prefix of this chain, addresses are always
+    /// re-encoded with the chain prefix before being sent so a mismatch usually means an address
+    /// for another chain was provided by mistake. Note addresses parsed from hex are given the
+    /// default `cosmos` prefix, use `Address::change_prefix` before passing them in strict mode.
+    #[allow(clippy::result_large_err)]
+    pub fn check_address_prefix(&self, address: &Address) -> Result<(), CosmosGrpcError> {
+        let found = address.get_prefix();
+        if found == self.chain_prefix {
+            Ok(())
+        } else if self.strict_prefix {
+            Err(CosmosGrpcError::PrefixMismatch {
+                expected: self.chain_prefix.clone(),
+                found,
+            })
+        } else {
+            warn!(
+                "Address {} has prefix {} but this chain uses {}, it will be re-encoded!",
+                address, found, self.chain_prefix
+            );
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::mock::mock_contact;
     use crate::private_key::PrivateKey;
     use crate::Coin;
+    use crate::Fee;
 
     const TIMEOUT: Duration = Duration::from_secs(60);
 
+    #[test]
+    fn test_check_address_prefix() {
+        let mut contact = Contact::new("http://localhost:9090", TIMEOUT, "althea").unwrap();
+        let althea: Address = "althea1ezyy5y8a4pzv9jgaeh4gd2c4kmhfn4pmpjc8np"
+            .parse()
+            .unwrap();
+        let cosmos: Address = "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp"
+            .parse()
+            .unwrap();
+        assert!(contact.check_address_prefix(&althea).is_ok());
+        assert!(contact.check_address_prefix(&cosmos).is_ok());
+        contact.set_strict_prefix(true);
+        assert!(contact.check_address_prefix(&althea).is_ok());
+        match contact.check_address_prefix(&cosmos) {
+            Err(CosmosGrpcError::PrefixMismatch { expected, found }) => {
+                assert_eq!(expected, "althea");
+                assert_eq!(found, "cosmos");
+            }
+            _ => panic!("Expected prefix mismatch"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_clones_share_cache() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<Contact>();
+
+        let (contact, _, _) = mock_contact();
+        let mut clone = contact.clone();
+        clone.set_strict_prefix(true);
+        assert!(!contact.get_strict_prefix());
+        contact.get_auth_params().await.unwrap();
+        assert!(clone.auth_params.read().unwrap().is_some());
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_default_wait_timeout() {
+        let (mut contact, _mock, key) = mock_contact();
+        let destination = key.to_address("cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 1u64.into(),
+        };
+        // the mock never includes the tx, so only a send that waits times out
+        contact
+            .send_tokens(coin.clone(), None, destination, key.clone(), None, None)
+            .await
+            .unwrap();
+        contact.set_default_wait_timeout(Some(Duration::from_millis(100)));
+        assert!(matches!(
+            contact
+                .send_tokens(coin, None, destination, key, None, None)
+                .await,
+            Err(CosmosGrpcError::TransactionFailed { .. })
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_sign_mode() {
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{mode_info, AuthInfo, TxRaw};
+        use prost::Message;
+
+        let (mut contact, mock, key) = mock_contact();
+        assert_eq!(contact.get_sign_mode(), SignMode::Direct);
+        assert!(contact.set_sign_mode(SignMode::Textual).is_err());
+        contact.set_sign_mode(SignMode::LegacyAminoJson).unwrap();
+        let destination = key.to_address("cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 1u64.into(),
+        };
+        contact
+            .send_tokens(coin, None, destination, key, None, None)
+            .await
+            .unwrap();
+        let sent = mock.broadcasts.lock().unwrap()[0].tx_bytes.clone();
+        let raw = TxRaw::decode(sent.as_slice()).unwrap();
+        let auth_info = AuthInfo::decode(raw.auth_info_bytes.as_slice()).unwrap();
+        let mode = auth_info.signer_infos[0].mode_info.clone().unwrap().sum;
+        assert!(matches!(
+            mode,
+            Some(mode_info::Sum::Single(single)) if single.mode == SignMode::LegacyAminoJson as i32
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_configured_chain_id() {
+        let (mut contact, mock, key) = mock_contact();
+        let address = key.to_address("cosmos").unwrap();
+        *mock.tendermint_disabled.lock().unwrap() = true;
+        assert!(contact
+            .get_message_args(address, Fee::default())
+            .await
+            .is_err());
+
+        contact.set_chain_id(Some("configured".to_string()));
+        let args = contact
+            .get_message_args(address, Fee::default())
+            .await
+            .unwrap();
+        assert_eq!(args.chain_id, "configured");
+        assert_eq!(args.timeout_height, 0);
+
+        // the configured chain id takes precedence over the fetched one
+        *mock.tendermint_disabled.lock().unwrap() = false;
+        let args = contact
+            .get_message_args(address, Fee::default())
+            .await
+            .unwrap();
+        assert_eq!(args.chain_id, "configured");
+        assert!(args.timeout_height > 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_keep_alive_connect() {
+        let mut contact = Contact::new("http://127.0.0.1:1", TIMEOUT, "cosmos").unwrap();
+        assert_eq!(contact.get_keep_alive(), KeepAlive::default());
+        // endpoints are connected lazily per call, an unreachable node fails on connect
+        contact.set_keep_alive(KeepAlive::disabled());
+        assert_eq!(contact.get_keep_alive().interval, None);
+        assert!(contact.channel().await.is_err());
+        contact.set_keep_alive(KeepAlive::default());
+        assert!(contact.channel().await.is_err());
+
+        // a transport set with set_rpc is kept
+        let (mut contact, _mock, _key) = mock_contact();
+        contact.set_keep_alive(KeepAlive::disabled());
+        assert!(matches!(
+            contact.get_chain_status().await,
+            Ok(ChainStatus::Moving { block_height: 10 })
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_connect_unreachable() {
+        // creating the contact does not connect, connect fails right away
+        let contact = Contact::new("http://127.0.0.1:1", TIMEOUT, "cosmos").unwrap();
+        match contact.connect().await {
+            Err(CosmosGrpcError::ConnectionError { .. }) => {}
+            Err(CosmosGrpcError::Timeout { .. }) => {}
+            Err(e) => panic!("Expected connection error, got {:?}", e),
+            Ok(_) => panic!("Expected connection error"),
+        }
+    }
+
+    #[test]
+    fn test_from_endpoint() {
+        let endpoint = Endpoint::from_static("http://localhost:9090")
+            .timeout(Duration::from_secs(3))
+            .concurrency_limit(4);
+        let mut contact = Contact::from_endpoint(endpoint, TIMEOUT, "cosmos").unwrap();
+        assert!(contact.get_url().starts_with("http://localhost:9090"));
+        assert_eq!(contact.get_prefix(), "cosmos");
+        // the endpoint's own settings are kept
+        contact.set_keep_alive(KeepAlive::disabled());
+        assert_eq!(contact.get_keep_alive(), KeepAlive::default());
+        assert!(Contact::from_endpoint(
+            Endpoint::from_static("http://localhost:9090"),
+            TIMEOUT,
+            &"x".repeat(84)
+        )
+        .is_err());
+    }
+
     /// If you run the start-chains.sh script in the Gravity repo it will pass
     /// port 9090 on localhost and allow you to debug things quickly
     /// then be used to run this test and debug things quickly. You will need
@@ -104,11 +736,18 @@ mod tests {
         }
 
         let send = Coin {
-            denom: token_name,
+            denom: token_name.as_str().into(),
             amount: 100u64.into(),
         };
         contact
-            .send_tokens(send.clone(), Some(send), destination, key, Some(TIMEOUT))
+            .send_tokens(
+                send.clone(),
+                Some(send),
+                destination,
+                key,
+                Some(TIMEOUT),
+                None,
+            )
             .await
             .unwrap();
     }
@@ -0,0 +1,45 @@
+//! Contains utility functions for querying the legacy Cosmos sdk params module
+
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::params::v1beta1::query_client::QueryClient as ParamsQueryClient;
+use cosmos_sdk_proto::cosmos::params::v1beta1::QueryParamsRequest;
+use tonic::Code as TonicCode;
+use tonic::Status;
+
+impl Contact {
+    /// Gets a single parameter from the params module by it's subspace (usually the module name)
+    /// and key, for example `get_subspace_param("staking", "UnbondingTime")`. This covers params
+    /// that have no dedicated query in their own module. The value is returned as the raw string
+    /// stored by the chain, this is usually JSON so strings and integers will be quoted, parsing
+    /// is left to the caller. An unknown key returns an empty string while an unknown subspace
+    /// is an error from the node.
+    pub async fn get_subspace_param(
+        &self,
+        subspace: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Result<String, CosmosGrpcError> {
+        let mut grpc = ParamsQueryClient::new(self.channel().await?);
+        let res = grpc
+            .params(QueryParamsRequest {
+                subspace: subspace.into(),
+                key: key.into(),
+            })
+            .await
+            .map_err(params_error)?
+            .into_inner();
+        match res.param {
+            Some(param) => Ok(param.value),
+            None => Err(CosmosGrpcError::BadResponse(
+                "No param in params response".to_string(),
+            )),
+        }
+    }
+}
+
+fn params_error(e: Status) -> CosmosGrpcError {
+    match e.code() {
+        TonicCode::Unimplemented => CosmosGrpcError::ModuleNotPresent("params".to_string()),
+        _ => CosmosGrpcError::RequestError { error: e },
+    }
+}
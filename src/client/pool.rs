@@ -0,0 +1,437 @@
+//! A `CosmosRpc` that spreads calls over a pool of endpoints, for example a list of public
+//! nodes of varying quality. By default endpoints are used in turn, with
+//! `Selection::Weighted` the latency and error rate of every call are tracked per endpoint
+//! and faster, healthier endpoints are used more often. A call that fails to connect is
+//! retried on the next endpoint. Set the pool on a Contact with `Contact::set_rpc`, keep a
+//! clone of the Arc to inspect the scores. Like any `CosmosRpc` this only covers the block,
+//! account and transaction calls, module queries go to the url of the Contact.
+
+use crate::client::bank::{QuerySpendableBalancesRequest, QuerySpendableBalancesResponse};
+use crate::client::rpc::{ConfigRequest, ConfigResponse, CosmosRpc, GrpcRpc};
+use crate::error::CosmosGrpcError;
+use async_trait::async_trait;
+use cosmos_sdk_proto::cosmos::auth::v1beta1::{
+    QueryAccountRequest, QueryAccountResponse, QueryParamsRequest as AuthParamsRequest,
+    QueryParamsResponse as AuthParamsResponse,
+};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::{QueryAllBalancesRequest, QueryAllBalancesResponse};
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::{
+    GetBlockByHeightRequest, GetBlockByHeightResponse, GetLatestBlockRequest,
+    GetLatestBlockResponse, GetSyncingRequest, GetSyncingResponse,
+};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    BroadcastTxRequest, BroadcastTxResponse, GetTxRequest, GetTxResponse, GetTxsEventRequest,
+    GetTxsEventResponse, SimulateRequest, SimulateResponse,
+};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tonic::Code as TonicCode;
+
+/// How much each call moves the tracked latency and error rate of an endpoint
+const SMOOTHING: f64 = 0.2;
+/// The lowest health an endpoint is weighted with, so that failing endpoints are still
+/// tried now and then and can recover
+const MIN_HEALTH: f64 = 0.05;
+
+/// How the pool picks the endpoint for each call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Selection {
+    /// Every endpoint in turn
+    #[default]
+    RoundRobin,
+    /// Endpoints in proportion to their `EndpointScore::weight`, spread evenly over time
+    Weighted,
+}
+
+/// What the pool has seen of an endpoint so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointScore {
+    pub url: String,
+    /// The smoothed latency of successful calls, None until a call succeeded
+    pub latency: Option<Duration>,
+    /// The smoothed share of calls that failed, between 0 and 1
+    pub error_rate: f64,
+    pub successes: u64,
+    pub failures: u64,
+}
+
+impl EndpointScore {
+    fn new(url: String) -> EndpointScore {
+        EndpointScore {
+            url,
+            latency: None,
+            error_rate: 0.0,
+            successes: 0,
+            failures: 0,
+        }
+    }
+
+    /// The relative weight of the endpoint in weighted selection, calls per second of latency
+    /// scaled by the share of calls that succeed. Endpoints without a measured latency are
+    /// weighted as if they had `default_latency`
+    pub fn weight(&self, default_latency: Duration) -> f64 {
+        let latency = self.latency.unwrap_or(default_latency).as_secs_f64();
+        let health = (1.0 - self.error_rate).max(MIN_HEALTH);
+        health / latency.max(0.001)
+    }
+
+    fn record(&mut self, elapsed: Duration, success: bool) {
+        let failed = if success { 0.0 } else { 1.0 };
+        self.error_rate += SMOOTHING * (failed - self.error_rate);
+        if success {
+            self.successes += 1;
+            self.latency = Some(match self.latency {
+                Some(latency) => latency.mul_f64(1.0 - SMOOTHING) + elapsed.mul_f64(SMOOTHING),
+                None => elapsed,
+            });
+        } else {
+            self.failures += 1;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    scores: Vec<EndpointScore>,
+    /// The running weights of smooth weighted round robin, see `PoolState::pick`
+    current: Vec<f64>,
+    next: usize,
+}
+
+impl PoolState {
+    fn pick(&mut self, selection: Selection) -> usize {
+        match selection {
+            Selection::RoundRobin => {
+                let index = self.next % self.scores.len();
+                self.next = index + 1;
+                index
+            }
+            Selection::Weighted => {
+                // new endpoints are weighted like the fastest known one so they get tried
+                let default_latency = self
+                    .scores
+                    .iter()
+                    .filter_map(|s| s.latency)
+                    .min()
+                    .unwrap_or_else(|| Duration::from_secs(1));
+                let mut total = 0.0;
+                let mut best = 0;
+                for (index, score) in self.scores.iter().enumerate() {
+                    let weight = score.weight(default_latency);
+                    total += weight;
+                    self.current[index] += weight;
+                    if self.current[index] > self.current[best] {
+                        best = index;
+                    }
+                }
+                self.current[best] -= total;
+                best
+            }
+        }
+    }
+}
+
+/// A pool of endpoints used as one `CosmosRpc`, see the module docs
+pub struct PoolRpc {
+    endpoints: Vec<Arc<dyn CosmosRpc>>,
+    selection: Selection,
+    state: Mutex<PoolState>,
+}
+
+impl PoolRpc {
+    /// A pool of the gRPC servers at `urls` using round robin selection, returns `BadInput`
+    /// if no url is provided
+    #[allow(clippy::result_large_err)]
+    pub fn new(urls: &[&str]) -> Result<PoolRpc, CosmosGrpcError> {
+        PoolRpc::from_rpcs(
+            urls.iter()
+                .map(|url| {
+                    let rpc: Arc<dyn CosmosRpc> = Arc::new(GrpcRpc::new(url.to_string()));
+                    (url.to_string(), rpc)
+                })
+                .collect(),
+        )
+    }
+
+    /// A pool of any `CosmosRpc` implementations, each named by the url in it's score
+    #[allow(clippy::result_large_err)]
+    pub fn from_rpcs(rpcs: Vec<(String, Arc<dyn CosmosRpc>)>) -> Result<PoolRpc, CosmosGrpcError> {
+        if rpcs.is_empty() {
+            return Err(CosmosGrpcError::BadInput(
+                "A pool needs at least one endpoint".to_string(),
+            ));
+        }
+        let (urls, endpoints): (Vec<String>, Vec<Arc<dyn CosmosRpc>>) = rpcs.into_iter().unzip();
+        Ok(PoolRpc {
+            state: Mutex::new(PoolState {
+                current: vec![0.0; urls.len()],
+                scores: urls.into_iter().map(EndpointScore::new).collect(),
+                next: 0,
+            }),
+            endpoints,
+            selection: Selection::default(),
+        })
+    }
+
+    /// Sets how the endpoint of each call is picked, `Selection::RoundRobin` by default
+    pub fn with_selection(mut self, selection: Selection) -> PoolRpc {
+        self.selection = selection;
+        self
+    }
+
+    pub fn get_selection(&self) -> Selection {
+        self.selection
+    }
+
+    /// The current score of every endpoint, in the order they were provided
+    pub fn scores(&self) -> Vec<EndpointScore> {
+        self.state.lock().unwrap().scores.clone()
+    }
+
+    /// Makes `call` on the picked endpoint, trying the others in turn if it can't connect
+    async fn call<T, F, Fut>(&self, call: F) -> Result<T, CosmosGrpcError>
+    where
+        F: Fn(Arc<dyn CosmosRpc>) -> Fut,
+        Fut: Future<Output = Result<T, CosmosGrpcError>>,
+    {
+        let first = self.state.lock().unwrap().pick(self.selection);
+        let mut result = None;
+        for attempt in 0..self.endpoints.len() {
+            let index = (first + attempt) % self.endpoints.len();
+            let start = Instant::now();
+            let res = call(self.endpoints[index].clone()).await;
+            let elapsed = start.elapsed();
+            self.state.lock().unwrap().scores[index].record(elapsed, !is_failure(&res));
+            match res {
+                Err(CosmosGrpcError::ConnectionError { error }) => {
+                    debug!("Endpoint {} failed to connect, trying the next", index);
+                    result = Some(Err(CosmosGrpcError::ConnectionError { error }));
+                }
+                res => return res,
+            }
+        }
+        // the loop runs at least once as the pool is never empty
+        result.unwrap()
+    }
+}
+
+/// If a call result counts against the endpoint, errors returned by a working node such as
+/// a missing account are answers and count as successes
+fn is_failure<T>(res: &Result<T, CosmosGrpcError>) -> bool {
+    match res {
+        Ok(_) => false,
+        Err(CosmosGrpcError::ConnectionError { .. }) => true,
+        Err(CosmosGrpcError::RequestError { error }) => matches!(
+            error.code(),
+            TonicCode::Unavailable
+                | TonicCode::DeadlineExceeded
+                | TonicCode::ResourceExhausted
+                | TonicCode::Internal
+                | TonicCode::Unknown
+        ),
+        Err(_) => false,
+    }
+}
+
+#[async_trait]
+impl CosmosRpc for PoolRpc {
+    async fn get_syncing(
+        &self,
+        req: GetSyncingRequest,
+    ) -> Result<GetSyncingResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.get_syncing(req).await }
+        })
+        .await
+    }
+
+    async fn get_latest_block(
+        &self,
+        req: GetLatestBlockRequest,
+    ) -> Result<GetLatestBlockResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.get_latest_block(req).await }
+        })
+        .await
+    }
+
+    async fn get_block_by_height(
+        &self,
+        req: GetBlockByHeightRequest,
+    ) -> Result<GetBlockByHeightResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.get_block_by_height(req).await }
+        })
+        .await
+    }
+
+    async fn account(
+        &self,
+        req: QueryAccountRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAccountResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.account(req, height).await }
+        })
+        .await
+    }
+
+    async fn auth_params(
+        &self,
+        req: AuthParamsRequest,
+    ) -> Result<AuthParamsResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.auth_params(req).await }
+        })
+        .await
+    }
+
+    async fn all_balances(
+        &self,
+        req: QueryAllBalancesRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAllBalancesResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.all_balances(req, height).await }
+        })
+        .await
+    }
+
+    async fn spendable_balances(
+        &self,
+        req: QuerySpendableBalancesRequest,
+    ) -> Result<QuerySpendableBalancesResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.spendable_balances(req).await }
+        })
+        .await
+    }
+
+    async fn get_tx(&self, req: GetTxRequest) -> Result<GetTxResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.get_tx(req).await }
+        })
+        .await
+    }
+
+    async fn get_tx_raw(&self, req: GetTxRequest) -> Result<Vec<u8>, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.get_tx_raw(req).await }
+        })
+        .await
+    }
+
+    async fn get_txs_event(
+        &self,
+        req: GetTxsEventRequest,
+    ) -> Result<GetTxsEventResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.get_txs_event(req).await }
+        })
+        .await
+    }
+
+    async fn broadcast_tx(
+        &self,
+        req: BroadcastTxRequest,
+    ) -> Result<BroadcastTxResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.broadcast_tx(req).await }
+        })
+        .await
+    }
+
+    async fn simulate(&self, req: SimulateRequest) -> Result<SimulateResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.simulate(req).await }
+        })
+        .await
+    }
+
+    async fn node_config(&self, req: ConfigRequest) -> Result<ConfigResponse, CosmosGrpcError> {
+        self.call(|rpc| {
+            let req = req.clone();
+            async move { rpc.node_config(req).await }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::MockRpc;
+
+    fn state(urls: &[&str]) -> PoolState {
+        PoolState {
+            scores: urls
+                .iter()
+                .map(|url| EndpointScore::new(url.to_string()))
+                .collect(),
+            current: vec![0.0; urls.len()],
+            next: 0,
+        }
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut pool = state(&["a", "b", "c"]);
+        let picks: Vec<usize> = (0..6).map(|_| pool.pick(Selection::RoundRobin)).collect();
+        assert_eq!(picks, vec![0, 1, 2, 0, 1, 2]);
+
+        // a is twice as fast as b, c fails every call
+        let mut pool = state(&["a", "b", "c"]);
+        for _ in 0..20 {
+            pool.scores[0].record(Duration::from_millis(50), true);
+            pool.scores[1].record(Duration::from_millis(100), true);
+            pool.scores[2].record(Duration::from_millis(50), false);
+        }
+        let mut counts = [0; 3];
+        for _ in 0..1000 {
+            counts[pool.pick(Selection::Weighted)] += 1;
+        }
+        assert!(counts[0] > counts[1] && counts[1] > counts[2]);
+        assert!((counts[0] as f64 / counts[1] as f64 - 2.0).abs() < 0.1);
+        // failing endpoints are still probed
+        assert!(counts[2] > 0);
+        assert_eq!(pool.scores[2].failures, 20);
+        assert_eq!(pool.scores[2].latency, None);
+        assert!(pool.scores[2].error_rate > 0.95);
+    }
+
+    #[actix_rt::test]
+    async fn test_pool_failover() {
+        let mock: Arc<dyn CosmosRpc> = Arc::new(MockRpc::new("testing", 10));
+        let unreachable: Arc<dyn CosmosRpc> =
+            Arc::new(GrpcRpc::new("http://127.0.0.1:1".to_string()));
+        let pool = PoolRpc::from_rpcs(vec![
+            ("unreachable".to_string(), unreachable),
+            ("mock".to_string(), mock),
+        ])
+        .unwrap()
+        .with_selection(Selection::Weighted);
+        for _ in 0..3 {
+            pool.get_syncing(GetSyncingRequest {}).await.unwrap();
+        }
+        let scores = pool.scores();
+        assert_eq!(scores[0].successes, 0);
+        assert!(scores[0].failures > 0);
+        assert_eq!(scores[1].successes, 3);
+        assert!(scores[1].latency.is_some());
+
+        assert!(PoolRpc::new(&[]).is_err());
+    }
+}
@@ -0,0 +1,613 @@
+//! The transport used by `Contact` for the core block, account and transaction calls. By
+//! default these are made over gRPC to the node at the url provided to `Contact::new`, a
+//! different implementation can be provided with `Contact::set_rpc`, for example
+//! `client::mock::MockRpc` (behind the `mock` feature) to unit test code built on `Contact`
+//! without a running chain, or `client::pool::PoolRpc` to spread calls over several nodes.
+//! Queries for individual modules such as staking or governance are always made over gRPC.
+
+use crate::client::bank::{QuerySpendableBalancesRequest, QuerySpendableBalancesResponse};
+use crate::error::CosmosGrpcError;
+use async_trait::async_trait;
+use bytes::{Buf, BufMut};
+use cosmos_sdk_proto::cosmos::auth::v1beta1::query_client::QueryClient as AuthQueryClient;
+use cosmos_sdk_proto::cosmos::auth::v1beta1::{
+    QueryAccountRequest, QueryAccountResponse, QueryParamsRequest as AuthParamsRequest,
+    QueryParamsResponse as AuthParamsResponse,
+};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::{QueryAllBalancesRequest, QueryAllBalancesResponse};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::{
+    GetBlockByHeightRequest, GetBlockByHeightResponse, GetLatestBlockRequest,
+    GetLatestBlockResponse, GetSyncingRequest, GetSyncingResponse,
+};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::service_client::ServiceClient as TxServiceClient;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    BroadcastTxRequest, BroadcastTxResponse, GetTxRequest, GetTxResponse, GetTxsEventRequest,
+    GetTxsEventResponse, OrderBy, SimulateRequest, SimulateResponse, Tx,
+};
+use prost::Message;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+use tonic::body::BoxBody;
+use tonic::client::Grpc;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder, ProstCodec};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::http::{Request as HttpRequest, Response as HttpResponse};
+use tonic::codegen::{BoxFuture, Service};
+use tonic::transport::{Body as TransportBody, Error as TransportError};
+use tonic::transport::{Channel, Endpoint};
+use tonic::Request;
+use tonic::Status;
+
+/// The gRPC metadata header used to query the state of the chain at a past block height
+pub const BLOCK_HEIGHT_HEADER: &str = "x-cosmos-block-height";
+
+/// Wraps a query in a tonic request, setting the block height header if a height is
+/// provided so that the node answers from the state at that height
+pub fn request_at_height<T>(req: T, height: Option<u64>) -> Request<T> {
+    let mut request = Request::new(req);
+    if let Some(height) = height {
+        // the string form of an integer is always valid metadata
+        request
+            .metadata_mut()
+            .insert(BLOCK_HEIGHT_HEADER, height.to_string().parse().unwrap());
+    }
+    request
+}
+
+/// HTTP/2 keep alive settings for the connections made to the node, see `Contact::set_keep_alive`.
+/// Pings are sent every `interval` and the connection is dropped if one is not answered within
+/// `timeout`, a connection that was silently dropped by a load balancer then fails fast rather
+/// than hanging until the request times out. When `interval` is None no pings are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepAlive {
+    pub interval: Option<Duration>,
+    pub timeout: Duration,
+    /// If pings are also sent while no request is in progress
+    pub while_idle: bool,
+}
+
+impl KeepAlive {
+    /// Keep alive pings disabled, as was the default before keep alive settings were added
+    pub fn disabled() -> KeepAlive {
+        KeepAlive {
+            interval: None,
+            ..KeepAlive::default()
+        }
+    }
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        KeepAlive {
+            interval: Some(Duration::from_secs(30)),
+            timeout: Duration::from_secs(20),
+            while_idle: true,
+        }
+    }
+}
+
+/// Connects to the gRPC server at `url` with the given keep alive settings
+pub(crate) async fn connect(
+    url: &str,
+    keep_alive: KeepAlive,
+) -> Result<TracedChannel, CosmosGrpcError> {
+    let mut endpoint = Endpoint::new(url.to_string())?;
+    if let Some(interval) = keep_alive.interval {
+        endpoint = endpoint
+            .http2_keep_alive_interval(interval)
+            .keep_alive_timeout(keep_alive.timeout)
+            .keep_alive_while_idle(keep_alive.while_idle);
+    }
+    connect_endpoint(&endpoint).await
+}
+
+/// Connects to the gRPC server of `endpoint` with all of it's settings
+pub(crate) async fn connect_endpoint(
+    endpoint: &Endpoint,
+) -> Result<TracedChannel, CosmosGrpcError> {
+    Ok(TracedChannel {
+        channel: endpoint.connect().await?,
+        endpoint: endpoint.uri().to_string().into(),
+    })
+}
+
+/// A gRPC channel to a node, every call made over it is one of the methods of the node's
+/// gRPC services. With the `tracing` feature each call is wrapped in an `rpc` span recording
+/// the method, for example `/cosmos.bank.v1beta1.Query/AllBalances`, the endpoint and the
+/// milliseconds until the response arrived, see `traced`
+#[derive(Debug, Clone)]
+pub(crate) struct TracedChannel {
+    channel: Channel,
+    endpoint: Arc<str>,
+}
+
+impl Service<HttpRequest<BoxBody>> for TracedChannel {
+    type Response = HttpResponse<TransportBody>;
+    type Error = TransportError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.channel.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest<BoxBody>) -> Self::Future {
+        let method = request.uri().path().to_string();
+        Box::pin(traced(method, &self.endpoint, self.channel.call(request)))
+    }
+}
+
+/// Runs `future` in an `rpc` span recording `method`, `endpoint` and the milliseconds it took
+/// to complete in `elapsed_ms`, used for every network call to a node
+#[cfg(feature = "tracing")]
+pub(crate) fn traced<F: Future>(
+    method: impl fmt::Display,
+    endpoint: &str,
+    future: F,
+) -> impl Future<Output = F::Output> {
+    use tracing::Instrument;
+
+    let span = tracing::info_span!(
+        "rpc",
+        method = %method,
+        endpoint = %endpoint,
+        elapsed_ms = tracing::field::Empty
+    );
+    async move {
+        let start = Instant::now();
+        let output = future.await;
+        tracing::Span::current().record("elapsed_ms", start.elapsed().as_millis() as u64);
+        output
+    }
+    .instrument(span)
+}
+
+/// Without the `tracing` feature `future` is run as is
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn traced<F: Future>(
+    _method: impl fmt::Display,
+    _endpoint: &str,
+    future: F,
+) -> impl Future<Output = F::Output> {
+    future
+}
+
+/// Makes a unary gRPC call to the method at `path`, for example `/cosmos.authz.v1beta1.Query/Grants`,
+/// this is used for services that cosmos-sdk-proto does not generate a client for
+pub(crate) async fn unary_call<Req, Res>(
+    channel: TracedChannel,
+    path: &'static str,
+    req: Req,
+) -> Result<Res, CosmosGrpcError>
+where
+    Req: prost::Message + Send + Sync + 'static,
+    Res: prost::Message + Default + Send + Sync + 'static,
+{
+    unary_call_at_height(channel, path, req, None).await
+}
+
+/// Identical to `unary_call` but answered from the state at `height` if provided,
+/// see `request_at_height`
+pub(crate) async fn unary_call_at_height<Req, Res>(
+    channel: TracedChannel,
+    path: &'static str,
+    req: Req,
+    height: Option<u64>,
+) -> Result<Res, CosmosGrpcError>
+where
+    Req: prost::Message + Send + Sync + 'static,
+    Res: prost::Message + Default + Send + Sync + 'static,
+{
+    let mut grpc = Grpc::new(channel);
+    grpc.ready().await?;
+    let res: tonic::Response<Res> = grpc
+        .unary(
+            request_at_height(req, height),
+            PathAndQuery::from_static(path),
+            ProstCodec::default(),
+        )
+        .await?;
+    Ok(res.into_inner())
+}
+
+/// Identical to `unary_call` but returns the response in it's protobuf encoding
+pub(crate) async fn unary_call_raw<Req>(
+    channel: TracedChannel,
+    path: &'static str,
+    req: Req,
+) -> Result<Vec<u8>, CosmosGrpcError>
+where
+    Req: prost::Message + Send + Sync + 'static,
+{
+    let mut buf = Vec::new();
+    req.encode(&mut buf).unwrap();
+    let mut grpc = Grpc::new(channel);
+    grpc.ready().await?;
+    let res: tonic::Response<Vec<u8>> = grpc
+        .unary(Request::new(buf), PathAndQuery::from_static(path), RawCodec)
+        .await?;
+    Ok(res.into_inner())
+}
+
+/// A codec passing messages through in their protobuf encoding, see `unary_call_raw`
+#[derive(Debug, Clone, Copy, Default)]
+struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Vec<u8>;
+    type Decode = Vec<u8>;
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawCodec
+    }
+}
+
+impl Encoder for RawCodec {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn encode(&mut self, item: Vec<u8>, buf: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        buf.put_slice(&item);
+        Ok(())
+    }
+}
+
+impl Decoder for RawCodec {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Vec<u8>>, Status> {
+        Ok(Some(buf.copy_to_bytes(buf.remaining()).to_vec()))
+    }
+}
+
+/// GetTxsEventRequest with the `page` and `limit` fields of Cosmos SDK v0.46 and the `query`
+/// field of v0.50, which replaces `events` and is required from then on. Older nodes ignore
+/// the fields they don't know, so every field is set
+#[derive(Clone, PartialEq, prost::Message)]
+struct TxSearchRequest {
+    #[prost(string, repeated, tag = "1")]
+    events: Vec<String>,
+    #[prost(message, optional, tag = "2")]
+    pagination: Option<PageRequest>,
+    #[prost(enumeration = "OrderBy", tag = "3")]
+    order_by: i32,
+    #[prost(uint64, tag = "4")]
+    page: u64,
+    #[prost(uint64, tag = "5")]
+    limit: u64,
+    #[prost(string, tag = "6")]
+    query: String,
+}
+
+impl From<GetTxsEventRequest> for TxSearchRequest {
+    fn from(value: GetTxsEventRequest) -> Self {
+        // pages start at one, the offset must be a multiple of the limit to be expressed
+        let (page, limit) = match &value.pagination {
+            Some(p) if p.limit > 0 => (p.offset / p.limit + 1, p.limit),
+            _ => (0, 0),
+        };
+        TxSearchRequest {
+            query: value.events.join(" AND "),
+            events: value.events,
+            pagination: value.pagination,
+            order_by: value.order_by,
+            page,
+            limit,
+        }
+    }
+}
+
+/// GetTxsEventResponse with the `total` field that replaces the deprecated pagination from
+/// Cosmos SDK v0.46
+#[derive(Clone, PartialEq, prost::Message)]
+struct TxSearchResponse {
+    #[prost(message, repeated, tag = "1")]
+    txs: Vec<Tx>,
+    #[prost(message, repeated, tag = "2")]
+    tx_responses: Vec<TxResponse>,
+    #[prost(message, optional, tag = "3")]
+    pagination: Option<PageResponse>,
+    #[prost(uint64, tag = "4")]
+    total: u64,
+}
+
+impl From<TxSearchResponse> for GetTxsEventResponse {
+    fn from(value: TxSearchResponse) -> Self {
+        let pagination = match value.pagination {
+            Some(pagination) => Some(pagination),
+            None if value.total > 0 => Some(PageResponse {
+                next_key: Vec::new(),
+                total: value.total,
+            }),
+            None => None,
+        };
+        GetTxsEventResponse {
+            txs: value.txs,
+            tx_responses: value.tx_responses,
+            pagination,
+        }
+    }
+}
+
+/// ConfigRequest defines the request structure for the Config gRPC query of the node
+/// service, added in Cosmos SDK v0.46 and so not part of the cosmos-sdk-proto version used
+/// by this crate
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConfigRequest {}
+
+/// ConfigResponse defines the response structure for the Config gRPC query.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConfigResponse {
+    /// The `minimum-gas-prices` setting of the node, for example `0.025uatom,0.1stake`
+    #[prost(string, tag = "1")]
+    pub minimum_gas_price: String,
+}
+
+/// The calls `Contact` makes to a node to query blocks and accounts, and to simulate
+/// and broadcast transactions. Errors returned by the node should be returned as
+/// `CosmosGrpcError::RequestError` as callers inspect the status code. Queries that take
+/// a height should be answered from the state at that height, or the latest state if None.
+#[async_trait]
+pub trait CosmosRpc: Send + Sync {
+    async fn get_syncing(
+        &self,
+        req: GetSyncingRequest,
+    ) -> Result<GetSyncingResponse, CosmosGrpcError>;
+    async fn get_latest_block(
+        &self,
+        req: GetLatestBlockRequest,
+    ) -> Result<GetLatestBlockResponse, CosmosGrpcError>;
+    async fn get_block_by_height(
+        &self,
+        req: GetBlockByHeightRequest,
+    ) -> Result<GetBlockByHeightResponse, CosmosGrpcError>;
+    async fn account(
+        &self,
+        req: QueryAccountRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAccountResponse, CosmosGrpcError>;
+    async fn auth_params(
+        &self,
+        req: AuthParamsRequest,
+    ) -> Result<AuthParamsResponse, CosmosGrpcError>;
+    async fn all_balances(
+        &self,
+        req: QueryAllBalancesRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAllBalancesResponse, CosmosGrpcError>;
+    /// The balances not locked by vesting, added in Cosmos SDK v0.46. By default this fails
+    /// with Unimplemented like an older node, so the full balances are used instead
+    async fn spendable_balances(
+        &self,
+        _req: QuerySpendableBalancesRequest,
+    ) -> Result<QuerySpendableBalancesResponse, CosmosGrpcError> {
+        Err(Status::unimplemented("SpendableBalances is not supported").into())
+    }
+    async fn get_tx(&self, req: GetTxRequest) -> Result<GetTxResponse, CosmosGrpcError>;
+    /// The `GetTxResponse` in it's protobuf encoding, for decoding with types that keep the
+    /// fields of newer Cosmos SDK versions. By default `get_tx` encoded, which drops them
+    async fn get_tx_raw(&self, req: GetTxRequest) -> Result<Vec<u8>, CosmosGrpcError> {
+        let res = self.get_tx(req).await?;
+        let mut buf = Vec::new();
+        res.encode(&mut buf).unwrap();
+        Ok(buf)
+    }
+    async fn get_txs_event(
+        &self,
+        req: GetTxsEventRequest,
+    ) -> Result<GetTxsEventResponse, CosmosGrpcError>;
+    async fn broadcast_tx(
+        &self,
+        req: BroadcastTxRequest,
+    ) -> Result<BroadcastTxResponse, CosmosGrpcError>;
+    async fn simulate(&self, req: SimulateRequest) -> Result<SimulateResponse, CosmosGrpcError>;
+    async fn node_config(&self, req: ConfigRequest) -> Result<ConfigResponse, CosmosGrpcError>;
+}
+
+/// The default implementation of `CosmosRpc`, a new connection is made to
+/// the gRPC server for each call to ensure proper failover
+#[derive(Debug, Clone)]
+pub struct GrpcRpc {
+    url: String,
+    keep_alive: KeepAlive,
+    /// Used instead of the url and keep alive settings if set
+    endpoint: Option<Endpoint>,
+}
+
+impl GrpcRpc {
+    pub fn new(url: String) -> GrpcRpc {
+        GrpcRpc::with_keep_alive(url, KeepAlive::default())
+    }
+
+    pub fn with_keep_alive(url: String, keep_alive: KeepAlive) -> GrpcRpc {
+        GrpcRpc {
+            url,
+            keep_alive,
+            endpoint: None,
+        }
+    }
+
+    /// Connects using the given endpoint and all of it's settings, see `Contact::from_endpoint`
+    pub fn from_endpoint(endpoint: Endpoint) -> GrpcRpc {
+        GrpcRpc {
+            url: endpoint.uri().to_string(),
+            keep_alive: KeepAlive::disabled(),
+            endpoint: Some(endpoint),
+        }
+    }
+
+    async fn channel(&self) -> Result<TracedChannel, CosmosGrpcError> {
+        match &self.endpoint {
+            Some(endpoint) => connect_endpoint(endpoint).await,
+            None => connect(&self.url, self.keep_alive).await,
+        }
+    }
+}
+
+#[async_trait]
+impl CosmosRpc for GrpcRpc {
+    async fn get_syncing(
+        &self,
+        req: GetSyncingRequest,
+    ) -> Result<GetSyncingResponse, CosmosGrpcError> {
+        let mut grpc = TendermintServiceClient::new(self.channel().await?);
+        Ok(grpc.get_syncing(req).await?.into_inner())
+    }
+
+    async fn get_latest_block(
+        &self,
+        req: GetLatestBlockRequest,
+    ) -> Result<GetLatestBlockResponse, CosmosGrpcError> {
+        let mut grpc = TendermintServiceClient::new(self.channel().await?);
+        Ok(grpc.get_latest_block(req).await?.into_inner())
+    }
+
+    async fn get_block_by_height(
+        &self,
+        req: GetBlockByHeightRequest,
+    ) -> Result<GetBlockByHeightResponse, CosmosGrpcError> {
+        let mut grpc = TendermintServiceClient::new(self.channel().await?);
+        Ok(grpc.get_block_by_height(req).await?.into_inner())
+    }
+
+    async fn account(
+        &self,
+        req: QueryAccountRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAccountResponse, CosmosGrpcError> {
+        let mut grpc = AuthQueryClient::new(self.channel().await?);
+        Ok(grpc
+            .account(request_at_height(req, height))
+            .await?
+            .into_inner())
+    }
+
+    async fn auth_params(
+        &self,
+        req: AuthParamsRequest,
+    ) -> Result<AuthParamsResponse, CosmosGrpcError> {
+        let mut grpc = AuthQueryClient::new(self.channel().await?);
+        Ok(grpc.params(req).await?.into_inner())
+    }
+
+    async fn all_balances(
+        &self,
+        req: QueryAllBalancesRequest,
+        height: Option<u64>,
+    ) -> Result<QueryAllBalancesResponse, CosmosGrpcError> {
+        let mut grpc = BankQueryClient::new(self.channel().await?);
+        Ok(grpc
+            .all_balances(request_at_height(req, height))
+            .await?
+            .into_inner())
+    }
+
+    async fn spendable_balances(
+        &self,
+        req: QuerySpendableBalancesRequest,
+    ) -> Result<QuerySpendableBalancesResponse, CosmosGrpcError> {
+        unary_call(
+            self.channel().await?,
+            "/cosmos.bank.v1beta1.Query/SpendableBalances",
+            req,
+        )
+        .await
+    }
+
+    async fn get_tx(&self, req: GetTxRequest) -> Result<GetTxResponse, CosmosGrpcError> {
+        let mut grpc = TxServiceClient::new(self.channel().await?);
+        Ok(grpc.get_tx(req).await?.into_inner())
+    }
+
+    async fn get_tx_raw(&self, req: GetTxRequest) -> Result<Vec<u8>, CosmosGrpcError> {
+        unary_call_raw(
+            self.channel().await?,
+            "/cosmos.tx.v1beta1.Service/GetTx",
+            req,
+        )
+        .await
+    }
+
+    async fn get_txs_event(
+        &self,
+        req: GetTxsEventRequest,
+    ) -> Result<GetTxsEventResponse, CosmosGrpcError> {
+        let res: TxSearchResponse = unary_call(
+            self.channel().await?,
+            "/cosmos.tx.v1beta1.Service/GetTxsEvent",
+            TxSearchRequest::from(req),
+        )
+        .await?;
+        Ok(res.into())
+    }
+
+    async fn broadcast_tx(
+        &self,
+        req: BroadcastTxRequest,
+    ) -> Result<BroadcastTxResponse, CosmosGrpcError> {
+        let mut grpc = TxServiceClient::new(self.channel().await?);
+        Ok(grpc.broadcast_tx(req).await?.into_inner())
+    }
+
+    async fn simulate(&self, req: SimulateRequest) -> Result<SimulateResponse, CosmosGrpcError> {
+        let mut grpc = TxServiceClient::new(self.channel().await?);
+        Ok(grpc.simulate(req).await?.into_inner())
+    }
+
+    async fn node_config(&self, req: ConfigRequest) -> Result<ConfigResponse, CosmosGrpcError> {
+        unary_call(
+            self.channel().await?,
+            "/cosmos.base.node.v1beta1.Service/Config",
+            req,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tx_search_request() {
+        let req = TxSearchRequest::from(GetTxsEventRequest {
+            events: vec![
+                "tx.height=5".to_string(),
+                "message.action='send'".to_string(),
+            ],
+            pagination: Some(PageRequest {
+                key: Vec::new(),
+                offset: 200,
+                limit: 100,
+                count_total: true,
+            }),
+            order_by: OrderBy::Desc.into(),
+        });
+        assert_eq!(req.query, "tx.height=5 AND message.action='send'");
+        assert_eq!(req.events.len(), 2);
+        assert_eq!((req.page, req.limit), (3, 100));
+        assert_eq!(req.order_by, i32::from(OrderBy::Desc));
+
+        // v0.50 nodes return the total instead of the pagination
+        let res = GetTxsEventResponse::from(TxSearchResponse {
+            total: 7,
+            ..Default::default()
+        });
+        assert_eq!(res.pagination.unwrap().total, 7);
+    }
+}
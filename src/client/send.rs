@@ -1,50 +1,189 @@
 use crate::address::Address;
+use crate::client::mempool::TxStatus;
+use crate::client::rpc::ConfigRequest;
+use crate::client::types::AccountOverride;
+use crate::client::types::ChainStatus;
+use crate::client::types::DryRunResult;
+use crate::client::types::FeeStrategy;
+use crate::client::types::GasEstimate;
+use crate::client::types::Receipt;
+use crate::client::types::SendOptions;
+use crate::client::types::SimulateResult;
+use crate::client::types::TxResponseEvents;
+use crate::client::types::TxWaitProgress;
 use crate::client::Contact;
 use crate::client::MEMO;
 use crate::coin::Coin;
 use crate::coin::Fee;
+use crate::coin::GasPrices;
 use crate::error::CosmosGrpcError;
+use crate::memo::Memo;
 use crate::msg::Msg;
+use crate::msg::{required_signers, DecodedMsg};
+use crate::private_key::build_multi_signer_tx;
 use crate::private_key::PrivateKey;
+use crate::private_key::SignedTx;
 use crate::private_key::TxParts;
+use crate::private_key::TxSigner;
 use crate::utils::check_tx_response;
 use crate::utils::determine_min_fees_and_gas;
+use crate::utils::determine_out_of_gas;
+use crate::utils::encode_any;
+use crate::utils::tx_hash;
+use crate::utils::FeeInfo;
+use crate::utils::SDK_CODESPACE;
+use crate::utils::TX_IN_MEMPOOL_CACHE_CODE;
 use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::GasInfo;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as ProtoSecp256k1Pubkey;
+use cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastTxRequest;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::Fee as ProtoFee;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::GetTxResponse;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::SimulateRequest;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::Tx;
-use cosmos_sdk_proto::cosmos::{
-    base::abci::v1beta1::TxResponse, tx::v1beta1::service_client::ServiceClient as TxServiceClient,
-};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::TxRaw;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{mode_info, AuthInfo, ModeInfo, SignerInfo, TxBody};
+use futures_util::stream::{self, StreamExt};
+use num256::Uint256;
+use prost::Message;
+use std::convert::TryFrom;
 use std::time::Instant;
 use std::{clone::Clone, time::Duration};
 use tokio::time::sleep;
+use tokio::time::timeout;
 use tonic::Code as TonicCode;
+use tonic::Status;
+
+/// How many times `wait_for_tx` polls for a tx between checks of the mempool
+const MEMPOOL_CHECK_POLLS: u32 = 3;
+/// How many consecutive mempool checks must not find a tx before `wait_for_tx` considers it
+/// dropped, the gRPC and Tendermint RPC urls may be different nodes that are not in sync
+const DROPPED_TX_CHECKS: u32 = 2;
+
+/// The most transactions `wait_for_txs` polls for at once
+pub const WAIT_FOR_TXS_CONCURRENCY: usize = 16;
+
+/// The number of the most recent transactions of a key `send_idempotent` searches for an
+/// earlier transaction with the same client id
+pub const IDEMPOTENT_SEARCH_LIMIT: u64 = 100;
+
+/// Returns true if a broadcast request failing with this code may have
+/// been received by the node regardless
+fn broadcast_failure_is_ambiguous(code: TonicCode) -> bool {
+    matches!(
+        code,
+        TonicCode::Unavailable
+            | TonicCode::Unknown
+            | TonicCode::Cancelled
+            | TonicCode::DeadlineExceeded
+            | TonicCode::Aborted
+            | TonicCode::Internal
+    )
+}
+
+/// Returns true if the memo is within the limit, the limit is in bytes
+fn memo_within_limit(memo: &str, limit: u64) -> bool {
+    memo.len() as u64 <= limit
+}
+
+/// Returns true if a simulate request failed because the node doesn't allow simulation,
+/// either the tx service is not served at all or the node reports simulation as disabled
+fn simulation_disabled(status: &Status) -> bool {
+    let message = status.message().to_lowercase();
+    status.code() == TonicCode::Unimplemented
+        || (message.contains("simulat")
+            && (message.contains("disabled") || message.contains("not supported")))
+}
+
+/// The distinct signers required by a set of messages in signing order, None if
+/// any of the messages is of a type we don't know the signers of
+fn messages_signers(messages: &[Msg]) -> Option<Vec<String>> {
+    let decoded: Vec<DecodedMsg> = messages.iter().map(Msg::decode).collect();
+    required_signers(&decoded)
+}
+
+/// Matches each required signer to one of the available addresses, returning the indexes
+/// of the available addresses in signing order or the first required signer that was not
+/// found. Addresses are compared by their bytes so the prefix does not matter.
+fn order_signers(required: &[String], available: &[Address]) -> Result<Vec<usize>, String> {
+    let mut order = Vec::new();
+    for signer in required {
+        let position = signer.parse::<Address>().ok().and_then(|signer| {
+            available
+                .iter()
+                .position(|a| a.as_bytes() == signer.as_bytes())
+        });
+        match position {
+            Some(position) => order.push(position),
+            None => return Err(signer.clone()),
+        }
+    }
+    Ok(order)
+}
+
+/// Appends the fee payer to the signers required by the messages if it's set and not already
+/// one of them, the sdk requires the signature of the payer last
+fn with_fee_payer(mut required: Vec<String>, fee: &Fee) -> Vec<String> {
+    if let Some(payer) = &fee.payer {
+        let listed = required.iter().any(|signer| {
+            signer
+                .parse::<Address>()
+                .map(|signer| signer.as_bytes() == payer.as_bytes())
+                .unwrap_or(false)
+        });
+        if !listed {
+            required.push(payer.to_string());
+        }
+    }
+    required
+}
 
 impl Contact {
     /// The advanced version of create_and_send transaction that expects you to
     /// perform your own signing and prep first. This is used by all message sending
     /// functions in deep_space and I suggest you use it that way as well. It provides
     /// validation for various failure conditions that is non-trivial to perform correctly.
+    ///
+    /// The broadcast is limited to the timeout configured on this Contact, if it expires
+    /// `CosmosGrpcError::Timeout` is returned containing the locally computed hash of the tx
+    /// so that it's inclusion can be checked later. This mostly matters for `BroadcastMode::Block`
+    /// which will not return until the tx is in a block, if the chain is halted this may never
+    /// happen. `Block` mode is deprecated and removed in newer Cosmos SDK versions, prefer
+    /// `BroadcastMode::Sync` followed by `wait_for_tx`
+    ///
+    /// Retries, up to the number set with `Contact::set_broadcast_retries`, never cause a
+    /// transaction to be included twice. If connecting to the node fails the tx was never sent
+    /// and the broadcast is simply retried. If the request fails in transit we can not know if
+    /// the node received it, so before re-broadcasting the node is asked for the tx by it's
+    /// locally computed hash, if it was included that result is returned. If the node reports
+    /// the re-broadcast tx is already in it's mempool we treat the original broadcast as a
+    /// success. Any other failure, including failure of the inclusion check itself, is
+    /// returned without re-broadcasting. Timeouts are never retried.
+    ///
+    /// Responses with a nonzero code are converted into errors such as `InsufficientFees` or
+    /// `TransactionFailed`, use `send_transaction_raw` to inspect the `TxResponse` yourself.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, tx_hash = %tx_hash(&msg), mode = ?mode))
+    )]
     pub async fn send_transaction(
         &self,
         // proto serialized message for us to turn into an 'any' object
         msg: Vec<u8>,
         mode: BroadcastMode,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let mut txrpc = TxServiceClient::connect(self.get_url()).await?;
-        let response = txrpc
-            .broadcast_tx(BroadcastTxRequest {
-                tx_bytes: msg,
-                mode: mode.into(),
-            })
-            .await?
-            .into_inner()
-            .tx_response
-            .unwrap();
-        if let Some(v) = determine_min_fees_and_gas(&response) {
+        let response = self.send_transaction_raw(msg, mode).await?;
+        if let Some((gas_wanted, gas_used)) = determine_out_of_gas(&response) {
+            return Err(CosmosGrpcError::OutOfGas {
+                gas_wanted,
+                gas_used,
+            });
+        } else if let Some(v) = determine_min_fees_and_gas(&response).filter(|_| self.fees_required)
+        {
             return Err(CosmosGrpcError::InsufficientFees { fee_info: v });
         } else if !check_tx_response(&response) {
             return Err(CosmosGrpcError::TransactionFailed {
@@ -55,33 +194,169 @@ impl Contact {
         Ok(response)
     }
 
+    /// Asks the confirm callback, if one is set, to approve the signed tx bytes before they
+    /// are broadcast, returns `UserRejected` if it does not
+    pub(crate) async fn confirm_tx(&self, tx_bytes: &[u8]) -> Result<(), CosmosGrpcError> {
+        if let Some(confirm) = &self.confirm_callback {
+            let tx = match DryRunResult::from_tx_bytes(tx_bytes) {
+                Some(tx) => tx,
+                None => {
+                    return Err(CosmosGrpcError::BadInput(
+                        "Can not confirm invalid tx bytes".to_string(),
+                    ))
+                }
+            };
+            if !confirm.confirm(&tx).await {
+                return Err(CosmosGrpcError::UserRejected);
+            }
+        }
+        Ok(())
+    }
+
+    /// Identical to `send_transaction`, including timeouts and retries, but the `TxResponse`
+    /// returned by the node is returned as is even if it has a nonzero code. Only transport
+    /// failures are errors, checking the code of the response is left to the caller.
+    pub async fn send_transaction_raw(
+        &self,
+        // proto serialized message for us to turn into an 'any' object
+        msg: Vec<u8>,
+        mode: BroadcastMode,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        self.confirm_tx(&msg).await?;
+        let predicted_hash = tx_hash(&msg);
+        let mut attempt = 0;
+        let response = loop {
+            let result = self
+                .broadcast_tx_once(msg.clone(), mode, &predicted_hash)
+                .await;
+            match result {
+                Ok(response) => {
+                    if attempt > 0
+                        && response.codespace == SDK_CODESPACE
+                        && response.code == TX_IN_MEMPOOL_CACHE_CODE
+                    {
+                        info!("Tx {} was already in the mempool", predicted_hash);
+                        break TxResponse {
+                            txhash: predicted_hash,
+                            ..Default::default()
+                        };
+                    }
+                    break response;
+                }
+                Err(e) if attempt < self.broadcast_retries => match e {
+                    CosmosGrpcError::ConnectionError { .. } => {}
+                    CosmosGrpcError::RequestError { ref error }
+                        if broadcast_failure_is_ambiguous(error.code()) =>
+                    {
+                        sleep(Duration::from_secs(1)).await;
+                        match self.get_raw_tx_by_hash(predicted_hash.clone()).await {
+                            Ok(GetTxResponse {
+                                tx_response: Some(response),
+                                ..
+                            }) => break response,
+                            Err(CosmosGrpcError::RequestError { error })
+                                if error.code() == TonicCode::NotFound => {}
+                            // we can't be sure the tx was not included
+                            _ => return Err(e),
+                        }
+                    }
+                    _ => return Err(e),
+                },
+                Err(e) => return Err(e),
+            }
+            attempt += 1;
+            warn!(
+                "Broadcast of tx {} failed, retrying attempt {}",
+                predicted_hash, attempt
+            );
+        };
+        Ok(response)
+    }
+
+    /// Performs a single broadcast attempt of a tx, limited to the timeout of this Contact
+    async fn broadcast_tx_once(
+        &self,
+        msg: Vec<u8>,
+        mode: BroadcastMode,
+        predicted_hash: &str,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let broadcast = self.rpc.broadcast_tx(BroadcastTxRequest {
+            tx_bytes: msg,
+            mode: mode.into(),
+        });
+        match timeout(self.timeout, broadcast).await {
+            Ok(response) => match response?.tx_response {
+                Some(tx_response) => Ok(tx_response),
+                None => Err(CosmosGrpcError::BadResponse(format!(
+                    "Broadcast of tx {} returned no tx_response",
+                    predicted_hash
+                ))),
+            },
+            Err(_) => Err(CosmosGrpcError::Timeout {
+                time: self.timeout,
+                tx_hash: Some(predicted_hash.to_string()),
+            }),
+        }
+    }
+
+    /// Simulates a signed transaction returning it's gas usage, convert the result into a
+    /// `GasEstimate` to compute a gas limit with `GasEstimate::recommended_gas_limit`. The
+    /// simulation is limited to the timeout of this Contact, returning `Timeout` if it takes
+    /// longer. Nodes with simulation disabled return `SimulationUnsupported`, in which case
+    /// a fixed gas limit must be used instead
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url))
+    )]
     pub async fn simulate_tx(
         &self,
         // proto serialized message for us to turn into an 'any' object
         tx_parts: TxParts,
     ) -> Result<GasInfo, CosmosGrpcError> {
-        let mut txrpc = TxServiceClient::connect(self.get_url()).await?;
+        Ok(self.simulate_tx_full(tx_parts).await?.gas_info)
+    }
 
+    /// Simulates a signed transaction like `simulate_tx`, returning the message responses and
+    /// events it would produce along with it's gas usage. Useful to dry run a transaction, for
+    /// example to see what a contract call would do without executing it
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url))
+    )]
+    pub async fn simulate_tx_full(
+        &self,
+        tx_parts: TxParts,
+    ) -> Result<SimulateResult, CosmosGrpcError> {
         let tx = Tx {
             body: Some(tx_parts.body),
             auth_info: Some(tx_parts.auth_info),
             signatures: tx_parts.signatures,
         };
 
-        let response = txrpc
-            .simulate(SimulateRequest { tx: Some(tx) })
-            .await?
-            .into_inner()
-            .gas_info
-            .unwrap();
-
-        Ok(response)
+        let simulate = self.rpc.simulate(SimulateRequest { tx: Some(tx) });
+        let response = match timeout(self.timeout, simulate).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(CosmosGrpcError::RequestError { error })) if simulation_disabled(&error) => {
+                return Err(CosmosGrpcError::SimulationUnsupported)
+            }
+            Ok(Err(e)) => return Err(e),
+            Err(_) => {
+                return Err(CosmosGrpcError::Timeout {
+                    time: self.timeout,
+                    tx_hash: None,
+                })
+            }
+        };
+        SimulateResult::try_from(response)
     }
 
     /// A utility function that creates a one to one simple transaction
     /// and sends it from the provided private key, waiting the configured
     /// amount of time for the tx to enter the chain, if you do not specify
-    /// a fee the smallest working amount will be selected.
+    /// a fee the smallest working amount will be selected using `min_fee_for_gas`, if that
+    /// fails the tx is sent without a fee. A zero `fee` is rejected, see `Fee::new`. The tx is
+    /// broadcast with `mode`, `Sync` by default, `wait_timeout` is ignored for `Async` and
+    /// `Block` broadcasts.
     pub async fn send_tokens(
         &self,
         coin: Coin,
@@ -89,70 +364,1033 @@ impl Contact {
         destination: Address,
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
+        mode: Option<BroadcastMode>,
     ) -> Result<TxResponse, CosmosGrpcError> {
+        let (msg, fee) = self
+            .tokens_msg_and_fee(coin, fee, destination, &private_key)
+            .await?;
+        self.create_and_send_with_mode(
+            &[msg],
+            fee,
+            MEMO,
+            private_key,
+            wait_timeout,
+            None,
+            mode.unwrap_or(BroadcastMode::Sync),
+        )
+        .await
+    }
+
+    /// Signs the transaction `send_tokens` would send and returns it decoded without
+    /// broadcasting it, to review exactly what automation would send. The account is queried
+    /// and the transaction validated as usual
+    pub async fn dry_run_send_tokens(
+        &self,
+        coin: Coin,
+        fee: Option<Coin>,
+        destination: Address,
+        private_key: PrivateKey,
+    ) -> Result<DryRunResult, CosmosGrpcError> {
+        let (msg, fee) = self
+            .tokens_msg_and_fee(coin, fee, destination, &private_key)
+            .await?;
+        self.dry_run_create_and_send(&[msg], fee, MEMO, private_key)
+            .await
+    }
+
+    /// The message and fee of a `send_tokens` transaction
+    async fn tokens_msg_and_fee(
+        &self,
+        coin: Coin,
+        fee: Option<Coin>,
+        destination: Address,
+        private_key: &PrivateKey,
+    ) -> Result<(Msg, Fee), CosmosGrpcError> {
         trace!("Creating transaction");
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        self.check_address_prefix(&destination)?;
+        let our_address = private_key.to_address(&self.chain_prefix)?;
 
         let send = MsgSend {
             amount: vec![coin.into()],
-            from_address: our_address.to_bech32(&self.chain_prefix).unwrap(),
-            to_address: destination.to_bech32(&self.chain_prefix).unwrap(),
+            from_address: our_address.to_bech32(&self.chain_prefix)?,
+            to_address: destination.to_bech32(&self.chain_prefix)?,
         };
         let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
 
-        let fee_obj = if let Some(fee) = fee {
-            Fee {
-                amount: vec![fee],
-                gas_limit: 500_000,
-                granter: None,
-                payer: None,
+        let amount = match fee {
+            Some(fee) => vec![fee],
+            None => match self.min_fee_for_gas(500_000, None).await {
+                Ok(amount) => amount,
+                Err(e) => {
+                    warn!(
+                        "Could not determine the minimum fee {}, sending without a fee",
+                        e
+                    );
+                    Vec::new()
+                }
+            },
+        };
+        Ok((msg, Fee::new(amount, 500_000)?))
+    }
+
+    /// The generic version of utility functions like `send_tokens`, signs a transaction
+    /// containing the provided messages using the provided private key and broadcasts it,
+    /// waiting for the tx to enter the chain if `wait_timeout` is provided. Note that a
+    /// transaction that runs out of gas during execution is only detected as `OutOfGas`
+    /// if we wait for it.
+    pub async fn create_and_send(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        self.create_and_send_with_override(messages, fee, memo, private_key, wait_timeout, None)
+            .await
+    }
+
+    /// Signs the transaction `create_and_send` would send and returns it decoded without
+    /// broadcasting it, to review exactly what automation would send. The account is queried
+    /// and the transaction validated as usual, the signed transaction is also logged
+    pub async fn dry_run_create_and_send(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+    ) -> Result<DryRunResult, CosmosGrpcError> {
+        let msg_bytes = self
+            .sign_checked(messages, &fee, &memo.into(), &private_key, None)
+            .await?;
+        match DryRunResult::from_tx_bytes(&msg_bytes) {
+            Some(result) => {
+                info!("Dry run, not broadcasting tx {:?}", result);
+                Ok(result)
             }
-        } else {
-            Fee {
-                amount: vec![],
-                gas_limit: 500_000,
-                granter: None,
-                payer: None,
+            None => Err(CosmosGrpcError::BadInput(
+                "Dry run of invalid tx bytes".to_string(),
+            )),
+        }
+    }
+
+    /// Identical to `create_and_send` but signs with the account number and sequence from
+    /// `account_override` if provided rather than querying them, see
+    /// `Contact::get_message_args_with_override` for when this is useful and it's risks.
+    pub async fn create_and_send_with_override(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+        account_override: Option<AccountOverride>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        self.create_and_send_with_mode(
+            messages,
+            fee,
+            memo,
+            private_key,
+            wait_timeout,
+            account_override,
+            BroadcastMode::Sync,
+        )
+        .await
+    }
+
+    /// Identical to `create_and_send_with_override` but broadcast with `mode`. The tx is only
+    /// waited for in `Sync` mode, an `Async` broadcast returns as soon as the node has received
+    /// the tx without checking it, and a `Block` broadcast has already waited for the block
+    #[allow(clippy::too_many_arguments)]
+    async fn create_and_send_with_mode(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+        account_override: Option<AccountOverride>,
+        mode: BroadcastMode,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let (response, _) = self
+            .sign_and_broadcast(messages, fee, memo, private_key, account_override, mode)
+            .await?;
+        match self.wait_timeout(wait_timeout) {
+            Some(time) if mode == BroadcastMode::Sync => self.wait_for_tx(response, time).await,
+            _ => Ok(response),
+        }
+    }
+
+    /// Signs `messages` and broadcasts them with `mode` without waiting for the tx, returns the
+    /// broadcast response and the fee the tx was signed with, which differs from `fee` if
+    /// the node required more and the fee was bumped, see `Contact::set_auto_bump_fees`
+    async fn sign_and_broadcast(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+        account_override: Option<AccountOverride>,
+        mode: BroadcastMode,
+    ) -> Result<(TxResponse, Fee), CosmosGrpcError> {
+        let memo = memo.into();
+        let msg_bytes = self
+            .sign_checked(messages, &fee, &memo, &private_key, account_override)
+            .await?;
+
+        let (response, fee) = match self.send_transaction(msg_bytes, mode).await {
+            Err(CosmosGrpcError::InsufficientFees {
+                fee_info: FeeInfo { required, .. },
+            }) if self.auto_bump_fees && !required.is_empty() => {
+                info!(
+                    "Node requires a fee of {}, resending",
+                    Coin::display_list(&required)
+                );
+                // the rejected tx did not consume the sequence, so it's signed again as is
+                let fee = Fee {
+                    amount: required,
+                    ..fee
+                };
+                let msg_bytes = self
+                    .sign_checked(messages, &fee, &memo, &private_key, account_override)
+                    .await?;
+                (self.send_transaction(msg_bytes, mode).await?, fee)
             }
+            res => (res?, fee),
         };
 
-        let args = self.get_message_args(our_address, fee_obj).await?;
+        trace!("broadcasted! with response {:?}", response);
+        Ok((response, fee))
+    }
 
-        let msg_bytes = private_key.sign_std_msg(&[msg], args, MEMO)?;
+    /// Validates the memo, fee payer and signers of `messages` and signs them with `private_key`
+    async fn sign_checked(
+        &self,
+        messages: &[Msg],
+        fee: &Fee,
+        memo: &str,
+        private_key: &PrivateKey,
+        account_override: Option<AccountOverride>,
+    ) -> Result<Vec<u8>, CosmosGrpcError> {
+        self.check_memo_length(memo).await?;
+        let our_address = private_key.to_address(&self.chain_prefix)?;
+        // a payer other than the signer must co-sign, see `create_and_send_with_payer`
+        if let Some(payer) = &fee.payer {
+            if payer.as_bytes() != our_address.as_bytes() {
+                return Err(CosmosGrpcError::MissingSigner {
+                    address: payer.to_string(),
+                });
+            }
+        }
+        // messages of unknown type are left for the chain to validate
+        if let Some(required) = messages_signers(messages) {
+            if let Err(address) = order_signers(&required, &[our_address]) {
+                return Err(CosmosGrpcError::MissingSigner { address });
+            }
+        }
+
+        let args = self
+            .get_message_args_with_override(our_address, fee.clone(), account_override)
+            .await?;
+        trace!("got optional tx info");
+
+        let msg_bytes =
+            private_key.sign_std_msg_with_sign_mode(messages, args, memo, self.sign_mode)?;
         trace!("{}", msg_bytes.len());
+        Ok(msg_bytes)
+    }
+
+    /// Signs a transaction containing messages from more than one signer and broadcasts it,
+    /// waiting for the tx to enter the chain if `wait_timeout` is provided. The signers are
+    /// determined from the messages and ordered as the sdk requires, so the keys may be provided
+    /// in any order, `MissingSigner` is returned if no key is provided for one of them. Exactly
+    /// one key per signer must be provided, the first signer in the messages pays the fee unless
+    /// `fee.payer` is set, in which case the payer is also a signer and signs last. Only
+    /// messages of types known to `DecodedMsg` are supported as their signers must be known.
+    pub async fn create_and_send_multi_signer(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_keys: &[PrivateKey],
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let memo = memo.into();
+        self.check_memo_length(&memo).await?;
+        let required = match messages_signers(messages) {
+            Some(required) if !required.is_empty() => required,
+            Some(_) => return Err(CosmosGrpcError::BadInput("No messages to sign".to_string())),
+            None => {
+                return Err(CosmosGrpcError::BadInput(
+                    "Can not determine the signers of messages of unknown type".to_string(),
+                ))
+            }
+        };
+        let required = with_fee_payer(required, &fee);
+        let mut addresses = Vec::new();
+        for key in private_keys {
+            addresses.push(key.to_address(&self.chain_prefix)?);
+        }
+        let order = order_signers(&required, &addresses)
+            .map_err(|address| CosmosGrpcError::MissingSigner { address })?;
+        // the sdk rejects transactions with more signatures than signers
+        if order.len() != private_keys.len() {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "{} keys provided for {} signers",
+                private_keys.len(),
+                order.len()
+            )));
+        }
+        let sig_limit = self.get_auth_params().await?.tx_sig_limit;
+        if order.len() as u64 > sig_limit {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "{} signers is above the chain limit of {} signatures",
+                order.len(),
+                sig_limit
+            )));
+        }
+
+        let mut signers = Vec::new();
+        let mut chain_id = String::new();
+        let mut timeout_height = 0;
+        for index in order {
+            let args = self.get_message_args(addresses[index], fee.clone()).await?;
+            signers.push(TxSigner {
+                key: private_keys[index].clone(),
+                account_number: args.account_number,
+                sequence: args.sequence,
+            });
+            chain_id = args.chain_id;
+            timeout_height = args.timeout_height;
+        }
+        let parts =
+            build_multi_signer_tx(messages, &signers, fee, timeout_height, &chain_id, memo)?;
+        let tx_raw = TxRaw {
+            body_bytes: parts.body_buf,
+            auth_info_bytes: parts.auth_buf,
+            signatures: parts.signatures,
+        };
+        let mut msg_bytes = Vec::new();
+        tx_raw.encode(&mut msg_bytes).unwrap();
 
         let response = self
             .send_transaction(msg_bytes, BroadcastMode::Sync)
             .await?;
-
         trace!("broadcasted! with response {:?}", response);
-        if let Some(time) = wait_timeout {
+        if let Some(time) = self.wait_timeout(wait_timeout) {
             self.wait_for_tx(response, time).await
         } else {
             Ok(response)
         }
     }
 
+    /// Identical to `create_and_send` except that the fee is paid by the account of `payer`
+    /// rather than by the signer of the messages, the payer is set as `fee.payer` and co-signs
+    /// the transaction. This is different from a fee grant, see `Fee::granter`, where the
+    /// granter doesn't sign but must have granted the signer an allowance with the feegrant
+    /// module beforehand. Useful for meta transactions where a service pays for the
+    /// transactions of it's users. Only messages of types known to `DecodedMsg` are supported
+    pub async fn create_and_send_with_payer(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+        payer: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let fee = Fee {
+            payer: Some(payer.to_address(&self.chain_prefix)?),
+            ..fee
+        };
+        let signer = private_key.to_address(&self.chain_prefix)?;
+        let keys = if fee.payer.map(|p| p.as_bytes() == signer.as_bytes()) == Some(true) {
+            vec![private_key]
+        } else {
+            vec![private_key, payer]
+        };
+        self.create_and_send_multi_signer(messages, fee, memo, &keys, wait_timeout)
+            .await
+    }
+
+    /// Gets the `minimum-gas-prices` setting of the node, which is the lowest gas price it
+    /// accepts transactions at, an empty list if it accepts transactions without fees. This
+    /// is a setting of each node rather than of the chain, other nodes may require more.
+    /// Only nodes since Cosmos SDK v0.46 serve this query, older nodes return `ModuleNotPresent`
+    pub async fn get_min_gas_prices(&self) -> Result<GasPrices, CosmosGrpcError> {
+        match self.rpc.node_config(ConfigRequest {}).await {
+            Ok(config) => config.minimum_gas_price.parse().map_err(|e| {
+                CosmosGrpcError::BadResponse(format!("Invalid minimum gas price {}", e))
+            }),
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == TonicCode::Unimplemented =>
+            {
+                Err(CosmosGrpcError::ModuleNotPresent(
+                    "cosmos.base.node".to_string(),
+                ))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Computes the minimum fee for a transaction with `gas_limit`, rounded up to whole base
+    /// units. The gas price configured with `set_gas_price` is used if set and in `denom`,
+    /// otherwise the minimum gas prices of the node are fetched and the price in `denom` is
+    /// used, or the first of them if `denom` is None. Returns an empty list if the node or
+    /// this Contact doesn't require fees, and `BadInput` if the node has no price in `denom`
+    pub async fn min_fee_for_gas(
+        &self,
+        gas_limit: u64,
+        denom: Option<&str>,
+    ) -> Result<Vec<Coin>, CosmosGrpcError> {
+        if !self.fees_required {
+            return Ok(Vec::new());
+        }
+        let price = match (&self.gas_price, denom) {
+            (Some(price), None) => price.clone(),
+            (Some(price), Some(denom)) if price.denom == denom => price.clone(),
+            _ => {
+                let prices = self.get_min_gas_prices().await?;
+                let price = match denom {
+                    Some(denom) => prices.for_denom(denom),
+                    None => prices.0.first(),
+                };
+                match (price, denom) {
+                    (Some(price), _) => price.clone(),
+                    (None, _) if prices.0.is_empty() => return Ok(Vec::new()),
+                    (None, denom) => {
+                        return Err(CosmosGrpcError::BadInput(format!(
+                            "The node has no gas price in {}, it accepts {}",
+                            denom.unwrap_or_default(),
+                            prices
+                        )))
+                    }
+                }
+            }
+        };
+        match price.fee_for_gas(gas_limit) {
+            Some(fee) if fee.amount == 0u8.into() => Ok(Vec::new()),
+            Some(fee) => Ok(vec![fee]),
+            None => Err(CosmosGrpcError::BadInput(format!(
+                "Fee for {} gas at {} overflows",
+                gas_limit, price
+            ))),
+        }
+    }
+
+    /// Checks a memo against the maximum memo length of the chain, returns `MemoTooLong`
+    /// if it would be rejected. The limit is read from the cached auth params. Note that
+    /// despite the name of the `max_memo_characters` param the limit is in bytes.
+    pub async fn check_memo_length(&self, memo: &str) -> Result<(), CosmosGrpcError> {
+        if memo.is_empty() {
+            return Ok(());
+        }
+        let limit = self.get_auth_params().await?.max_memo_characters;
+        if memo_within_limit(memo, limit) {
+            Ok(())
+        } else {
+            Err(CosmosGrpcError::MemoTooLong { limit })
+        }
+    }
+
+    /// Serializes a memo and checks it against the maximum memo length of the chain like
+    /// `check_memo_length`, returns the memo string to include in the transaction
+    pub async fn check_memo(&self, memo: impl Into<Memo>) -> Result<String, CosmosGrpcError> {
+        let memo = memo.into().to_string();
+        self.check_memo_length(&memo).await?;
+        Ok(memo)
+    }
+
+    /// The gas charged by the ante handler of the auth module before any message is executed,
+    /// for the size of the signed transaction and for verifying each of it's secp256k1
+    /// signatures, using the cached auth params. Simulation already includes this, it's useful
+    /// when computing gas limits by hand from the known cost of the messages
+    pub async fn estimate_ante_gas(&self, tx_bytes: &[u8]) -> Result<u64, CosmosGrpcError> {
+        let tx = TxRaw::decode(tx_bytes)?;
+        let params = self.get_auth_params().await?;
+        Ok(params.tx_size_cost_per_byte * tx_bytes.len() as u64
+            + params.sig_verify_cost_secp256k1 * tx.signatures.len() as u64)
+    }
+
+    /// Estimates the gas used by a transaction of `messages` signed by `num_signatures` keys
+    /// without simulating it, a fallback for offline signing flows where `estimate_fee` can't
+    /// reach a node. The ante handler costs are computed like the node does from the cached auth
+    /// params, `tx_size_cost_per_byte` for every byte of the estimated size of the signed tx and
+    /// `sig_verify_cost_secp256k1` per signature, and the base gas of `set_offline_base_gas` is
+    /// added for executing the messages. The ante costs are usually within a few hundred gas of
+    /// the real cost, execution varies widely by message and module so expect the estimate to
+    /// be off by 20 percent or more and apply a gas adjustment on top. The memo is not counted,
+    /// add `tx_size_cost_per_byte` per byte of it. The auth params are only queried if they were
+    /// not cached yet, call `get_auth_params` while online to estimate without network access.
+    pub async fn estimate_gas_offline(
+        &self,
+        messages: &[Msg],
+        num_signatures: u32,
+    ) -> Result<u64, CosmosGrpcError> {
+        let params = self.get_auth_params().await?;
+        Ok(self.offline_base_gas
+            + params.tx_size_cost_per_byte * offline_tx_size(messages, num_signatures)
+            + params.sig_verify_cost_secp256k1 * u64::from(num_signatures))
+    }
+
+    /// Simulates a transaction containing the provided messages to estimate it's gas
+    /// usage and produces a fee for it. The gas limit is the simulated gas usage multiplied
+    /// by the configured gas adjustment and rounded up, the fee amount is computed from the
+    /// configured gas price and scaled by the fee buffer, see `Contact::set_fee_buffer`, if
+    /// there is no configured gas price the fee amount is left empty. With a fee denom
+    /// preference the fee is in the first preferred denom that can pay for it instead, see
+    /// `Contact::set_fee_denom_preference`
+    pub async fn estimate_fee(
+        &self,
+        messages: &[Msg],
+        memo: impl Into<String>,
+        private_key: &PrivateKey,
+    ) -> Result<Fee, CosmosGrpcError> {
+        self.estimate_fee_with_granter(messages, memo, private_key, None)
+            .await
+    }
+
+    /// Identical to `estimate_fee` for a transaction whose fee is paid by `granter` through a
+    /// fee grant, the granter is included in the simulated fee so that the gas used by the
+    /// feegrant module is accounted for, and in the returned fee
+    pub async fn estimate_fee_with_granter(
+        &self,
+        messages: &[Msg],
+        memo: impl Into<String>,
+        private_key: &PrivateKey,
+        granter: Option<String>,
+    ) -> Result<Fee, CosmosGrpcError> {
+        let memo = memo.into();
+        self.check_memo_length(&memo).await?;
+        let our_address = private_key.to_address(&self.chain_prefix)?;
+        // simulations run with an unlimited gas meter and do not check the fee
+        // amount against the min gas price so an empty fee is fine here
+        let sim_fee = Fee {
+            amount: vec![],
+            gas_limit: 0,
+            granter: granter.clone(),
+            payer: None,
+        };
+        let args = self.get_message_args(our_address, sim_fee).await?;
+        let tx_parts = private_key.build_tx_with_sign_mode(messages, args, memo, self.sign_mode)?;
+        let gas_info = self.simulate_tx(tx_parts).await?;
+        trace!("simulated gas info {:?}", gas_info);
+
+        let gas_limit = GasEstimate::from(gas_info).recommended_gas_limit(self.gas_adjustment);
+        let amount = match &self.gas_price {
+            _ if !self.fee_denom_preference.is_empty() => {
+                // with a granter the allowance pays rather than our balance
+                let payer = match granter {
+                    Some(_) => None,
+                    None => Some(our_address),
+                };
+                self.preferred_fee(payer, (gas_limit as f64 * self.fee_buffer).ceil() as u64)
+                    .await?
+            }
+            Some(_) if !self.fees_required => vec![],
+            // the buffer is applied as extra gas paid for rather than to the amount so
+            // that the fee is still rounded up the same way
+            Some(price) => {
+                match price.fee_for_gas((gas_limit as f64 * self.fee_buffer).ceil() as u64) {
+                    Some(fee) => vec![fee],
+                    None => {
+                        return Err(CosmosGrpcError::BadInput(format!(
+                            "Could not compute fee for {} gas at {}",
+                            gas_limit, price
+                        )))
+                    }
+                }
+            }
+            None => vec![],
+        };
+        Ok(Fee {
+            amount,
+            gas_limit,
+            granter,
+            payer: None,
+        })
+    }
+
+    /// The fee for `gas_limit` in the first denom of the fee denom preference the node accepts
+    /// and `payer` can spend enough of, any accepted denom if there is no payer to check
+    async fn preferred_fee(
+        &self,
+        payer: Option<Address>,
+        gas_limit: u64,
+    ) -> Result<Vec<Coin>, CosmosGrpcError> {
+        let mut balances = None;
+        let mut rejected = Vec::new();
+        for denom in self.fee_denom_preference.iter() {
+            let fee = match self.min_fee_for_gas(gas_limit, Some(denom)).await {
+                Ok(fee) => fee,
+                Err(CosmosGrpcError::BadInput(_)) => {
+                    rejected.push(format!("{} is not accepted by the node", denom));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let (required, payer) = match (fee.first(), payer) {
+                (Some(required), Some(payer)) => (required, payer),
+                _ => return Ok(fee),
+            };
+            if balances.is_none() {
+                balances = Some(self.get_spendable_balances(payer).await?);
+            }
+            let held = balances
+                .iter()
+                .flatten()
+                .find(|coin| coin.denom == *denom)
+                .map(|coin| coin.amount.clone())
+                .unwrap_or_default();
+            if held >= required.amount {
+                return Ok(fee);
+            }
+            rejected.push(format!("{} holds {}{} of {}", payer, held, denom, required));
+        }
+        Err(CosmosGrpcError::BadInput(format!(
+            "No preferred fee denom can pay for {} gas: {}",
+            gas_limit,
+            rejected.join(", ")
+        )))
+    }
+
+    /// Simulates each bundle of messages as a transaction from `private_key` to compare the gas
+    /// used by alternative transactions, at most `max_concurrent` simulations run at once. Every
+    /// bundle is signed with the account's current sequence as if it were the next transaction.
+    /// Results are returned in the order of `bundles`, each simulation succeeds or fails on it's
+    /// own, the outer error is returned if the account can not be queried.
+    pub async fn simulate_many(
+        &self,
+        bundles: Vec<Vec<Msg>>,
+        private_key: &PrivateKey,
+        max_concurrent: usize,
+    ) -> Result<Vec<Result<GasInfo, CosmosGrpcError>>, CosmosGrpcError> {
+        let our_address = private_key.to_address(&self.chain_prefix)?;
+        let args = self.get_message_args(our_address, Fee::default()).await?;
+        Ok(stream::iter(bundles)
+            .map(|messages| {
+                let args = args.clone();
+                async move {
+                    let tx_parts = private_key.build_tx_with_sign_mode(
+                        &messages,
+                        args,
+                        MEMO,
+                        self.sign_mode,
+                    )?;
+                    self.simulate_tx(tx_parts).await
+                }
+            })
+            .buffered(max_concurrent.max(1))
+            .collect()
+            .await)
+    }
+
+    /// The fully automated version of `create_and_send`, simulates the transaction to determine
+    /// the gas limit (see `estimate_fee`), computes the fee from the configured gas price, signs,
+    /// broadcasts in sync mode and waits for the transaction to enter the chain if `wait_timeout`
+    /// is provided. If no gas price is configured the transaction is first sent with no fee,
+    /// nodes with a minimum gas price reject this during CheckTx (without consuming the sequence)
+    /// stating the exact fee required for the gas limit, in which case the transaction is re-signed
+    /// and sent once more with that fee. Errors from each stage are returned as they occur, a failed
+    /// simulation normally indicates a transaction that would fail on chain.
+    pub async fn simulate_and_send(
+        &self,
+        messages: &[Msg],
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        self.simulate_and_send_with_granter(messages, None, private_key, wait_timeout)
+            .await
+    }
+
+    /// Identical to `simulate_and_send` with the fee paid by `granter` through a fee grant,
+    /// the granter is set on the fee both when simulating and when sending, see
+    /// `estimate_fee_with_granter`
+    pub async fn simulate_and_send_with_granter(
+        &self,
+        messages: &[Msg],
+        granter: Option<String>,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let mut fee = self
+            .estimate_fee_with_granter(messages, MEMO, &private_key, granter)
+            .await?;
+        let res = self
+            .create_and_send(
+                messages,
+                fee.clone(),
+                MEMO,
+                private_key.clone(),
+                wait_timeout,
+            )
+            .await;
+        match res {
+            Err(CosmosGrpcError::InsufficientFees {
+                fee_info: FeeInfo { required, .. },
+            }) if self.fees_required && self.gas_price.is_none() && !required.is_empty() => {
+                info!(
+                    "Node requires a fee of {}, resending",
+                    Coin::display_list(&required)
+                );
+                fee.amount = required;
+                self.create_and_send(messages, fee, MEMO, private_key, wait_timeout)
+                    .await
+            }
+            res => res,
+        }
+    }
+
+    /// Identical to `create_and_send` except that if the transaction fails with `OutOfGas`
+    /// the gas limit is multiplied by `gas_multiplier` (starting from the larger of the limit
+    /// and the gas actually used) and the transaction is re-signed with a freshly queried
+    /// sequence and sent again, at most `max_retries` times. If all retries are exhausted the
+    /// last `OutOfGas` error is returned. You should provide a `wait_timeout`, most out of gas
+    /// failures happen during execution and can only be detected once the tx is in a block.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_and_send_with_gas_retry(
+        &self,
+        messages: &[Msg],
+        mut fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+        gas_multiplier: f64,
+        max_retries: u32,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let memo = memo.into();
+        let mut retries = 0;
+        loop {
+            let res = self
+                .create_and_send(
+                    messages,
+                    fee.clone(),
+                    memo.clone(),
+                    private_key.clone(),
+                    wait_timeout,
+                )
+                .await;
+            match res {
+                Err(CosmosGrpcError::OutOfGas { gas_used, .. }) if retries < max_retries => {
+                    let base = std::cmp::max(fee.gas_limit, gas_used);
+                    fee.gas_limit = (base as f64 * gas_multiplier).ceil() as u64;
+                    retries += 1;
+                    info!(
+                        "Transaction ran out of gas, retrying with gas limit {}",
+                        fee.gas_limit
+                    );
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Signs and sends `messages` again with the gas limit raised to `new_gas_limit`, for
+    /// recovering from `OutOfGas` without rebuilding the messages. The fee amount is scaled
+    /// with the gas limit so the gas price paid stays the same, rounded up. The sequence is
+    /// queried again rather than reused: a tx that ran out of gas in CheckTx never consumed it,
+    /// while one that ran out of gas in a block did, as failed txs still increment the sequence
+    pub async fn rebroadcast_with_gas(
+        &self,
+        messages: &[Msg],
+        fee: Fee,
+        memo: impl Into<String>,
+        private_key: PrivateKey,
+        new_gas_limit: u64,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let fee = Fee {
+            amount: scale_fee_amount(&fee, new_gas_limit),
+            gas_limit: new_gas_limit,
+            ..fee
+        };
+        self.create_and_send(messages, fee, memo, private_key, wait_timeout)
+            .await
+    }
+
+    /// Sends `messages` with `client_id` as the memo unless a successful transaction from the
+    /// same key with that memo is already on chain, in which case it's response is returned and
+    /// nothing is broadcast. Use a unique id per payment so retrying after a crash or restart
+    /// can't pay twice. Only the `IDEMPOTENT_SEARCH_LIMIT` most recent transactions of the key
+    /// are searched, see `find_tx_with_memo` for what this needs from the node's tx index.
+    /// There is a race window: a transaction that was broadcast but is not yet in a block is
+    /// not found and sending again pays twice, so only retry once an earlier attempt could
+    /// have been included, for example after it's `wait_timeout` has passed
+    pub async fn send_idempotent(
+        &self,
+        client_id: &str,
+        messages: &[Msg],
+        fee: Fee,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        if client_id.is_empty() {
+            return Err(CosmosGrpcError::BadInput(
+                "Empty client id for idempotent send".to_string(),
+            ));
+        }
+        self.check_memo_length(client_id).await?;
+        let sender = private_key.to_address(&self.chain_prefix)?;
+        if let Some(response) = self
+            .find_tx_with_memo(sender, client_id, IDEMPOTENT_SEARCH_LIMIT)
+            .await?
+        {
+            return Ok(response);
+        }
+        self.create_and_send(messages, fee, client_id, private_key, wait_timeout)
+            .await
+    }
+
+    /// Signs and broadcasts `messages` and waits for the transaction to be executed, the one
+    /// call most applications need, see `SendOptions` for what can be configured. The lower
+    /// level functions this is built on remain available for anything else. Errors are those
+    /// of the phase that failed: fee estimation (a failed simulation normally means the tx would
+    /// fail on chain), the broadcast (CheckTx errors such as `InsufficientFees`), waiting
+    /// (`TransactionFailed`, `TxDropped` or `Timeout`), or execution, `OutOfGas` or
+    /// `ExecutionFailed` for a transaction that was included but failed. The fee of a
+    /// transaction that failed during execution was still paid. The events are queried again
+    /// on Cosmos SDK v0.50 and later chains, if that fails the receipt has no events rather
+    /// than returning an error for a transaction that was executed
+    pub async fn send_and_confirm(
+        &self,
+        messages: &[Msg],
+        private_key: PrivateKey,
+        opts: SendOptions,
+    ) -> Result<Receipt, CosmosGrpcError> {
+        let memo = opts.memo.to_string();
+        let (fee, simulated) = match opts.fee {
+            FeeStrategy::Simulate => (
+                self.estimate_fee(messages, memo.clone(), &private_key)
+                    .await?,
+                true,
+            ),
+            FeeStrategy::SimulateWithGranter(granter) => (
+                self.estimate_fee_with_granter(messages, memo.clone(), &private_key, Some(granter))
+                    .await?,
+                true,
+            ),
+            FeeStrategy::Fixed(fee) => (fee, false),
+        };
+        let res = self
+            .sign_and_broadcast(
+                messages,
+                fee.clone(),
+                memo.clone(),
+                private_key.clone(),
+                None,
+                opts.mode,
+            )
+            .await;
+        let (response, fee) = match res {
+            // see `simulate_and_send_with_granter`
+            Err(CosmosGrpcError::InsufficientFees {
+                fee_info: FeeInfo { required, .. },
+            }) if simulated && self.fees_required && !required.is_empty() => {
+                info!(
+                    "Node requires a fee of {}, resending",
+                    Coin::display_list(&required)
+                );
+                let fee = Fee {
+                    amount: required,
+                    ..fee
+                };
+                self.sign_and_broadcast(messages, fee, memo, private_key, None, opts.mode)
+                    .await?
+            }
+            res => res?,
+        };
+
+        let response = if opts.confirmations == 0 {
+            self.wait_for_tx(response, opts.timeout).await?
+        } else {
+            self.wait_for_confirmations(response, opts.confirmations, opts.timeout)
+                .await?
+        };
+        if response.code != 0 {
+            return Err(CosmosGrpcError::ExecutionFailed { tx: response });
+        }
+
+        let mut events = TxResponseEvents {
+            logs: response.logs.clone(),
+            events: Vec::new(),
+        }
+        .tx_events();
+        // v0.50 and later chains only return events when the tx is queried
+        if events.is_empty() {
+            match self.get_tx_events(response.txhash.clone()).await {
+                Ok(queried) => events = queried,
+                Err(e) => warn!("Failed to get the events of {} {:?}", response.txhash, e),
+            }
+        }
+        Ok(Receipt::new(response, events, fee))
+    }
+
+    /// Broadcasts independent signed transactions, usually from different keys, in async mode
+    /// and waits for each to enter the chain, at most `max_concurrent` transactions are in flight
+    /// at once. Results are returned in the order of `txs`, each transaction succeeds or fails
+    /// on it's own. Transactions from the same key must be signed with consecutive sequences
+    /// and may still be rejected if they reach a node out of order, prefer `send_to_many` or
+    /// `create_and_send` for those.
+    pub async fn send_all(
+        &self,
+        txs: Vec<SignedTx>,
+        wait_timeout: Duration,
+        max_concurrent: usize,
+    ) -> Vec<Result<TxResponse, CosmosGrpcError>> {
+        stream::iter(txs)
+            .map(|tx| async move {
+                let response = self
+                    .send_transaction(tx.tx_raw_bytes, BroadcastMode::Async)
+                    .await?;
+                self.wait_for_tx(response, wait_timeout).await
+            })
+            .buffered(max_concurrent.max(1))
+            .collect()
+            .await
+    }
+
+    /// Waits for each of the transactions in `hashes` to enter the chain like `wait_for_tx`, for
+    /// example after broadcasting a batch in async mode. At most `WAIT_FOR_TXS_CONCURRENCY`
+    /// transactions are polled for at once. `timeout` applies to the whole call rather than to
+    /// each transaction, a transaction that only gets it's turn after the timeout has passed is
+    /// checked once. Results are returned in the order of `hashes`.
+    pub async fn wait_for_txs(
+        &self,
+        hashes: Vec<String>,
+        timeout: Duration,
+    ) -> Vec<Result<TxResponse, CosmosGrpcError>> {
+        let start = Instant::now();
+        stream::iter(hashes)
+            .map(|hash| async move {
+                let response = TxResponse {
+                    txhash: hash,
+                    ..Default::default()
+                };
+                let remaining = timeout.saturating_sub(Instant::now() - start);
+                if remaining > Duration::ZERO {
+                    return self.wait_for_tx(response, remaining).await;
+                }
+                match self.committed_tx(&response.txhash).await? {
+                    Some(res) => match determine_out_of_gas(&res) {
+                        Some((gas_wanted, gas_used)) => Err(CosmosGrpcError::OutOfGas {
+                            gas_wanted,
+                            gas_used,
+                        }),
+                        None => Ok(res),
+                    },
+                    None => Err(CosmosGrpcError::TransactionFailed {
+                        tx: response,
+                        time: timeout,
+                    }),
+                }
+            })
+            .buffered(WAIT_FOR_TXS_CONCURRENCY)
+            .collect()
+            .await
+    }
+
     /// Utility function that waits for a tx to enter the chain by querying
     /// it's txid, will not exit for timeout time unless the error is known
-    /// and unrecoverable
+    /// and unrecoverable. If a Tendermint RPC url is set the mempool is also checked every
+    /// few polls and `TxDropped` is returned once the tx is neither on chain nor in the
+    /// mempool on two consecutive checks, see `tx_status`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(endpoint = %self.url, tx_hash = %response.txhash, timeout_ms = timeout.as_millis() as u64))
+    )]
     pub async fn wait_for_tx(
         &self,
         response: TxResponse,
         timeout: Duration,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        self.wait_for_tx_with_progress(response, timeout, |_| {})
+            .await
+    }
+
+    /// Waits for a tx to enter the chain, see `wait_for_tx`, and then for `confirmations` more
+    /// blocks to be produced on top of the block it was included in, `timeout` covers both.
+    /// Returns `Timeout` if the chain does not reach that height in time. Cosmos chains have
+    /// instant finality, a tx in a committed block can't be reverted, so this is only useful
+    /// to guard against a node that is reporting something other than the canonical chain
+    /// and 0 or 1 confirmations is normally sufficient.
+    pub async fn wait_for_confirmations(
+        &self,
+        response: TxResponse,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        let start = Instant::now();
+        let included = self.wait_for_tx(response, timeout).await?;
+        let target = included.height as u64 + confirmations;
+        while Instant::now() - start < timeout {
+            match self.get_chain_status().await {
+                Ok(ChainStatus::Moving { block_height }) if block_height >= target => {
+                    return Ok(included)
+                }
+                Ok(ChainStatus::Syncing) => return Err(CosmosGrpcError::NodeNotSynced),
+                Ok(ChainStatus::WaitingToStart) => return Err(CosmosGrpcError::ChainNotRunning),
+                // we don't want a single error to exit this loop early
+                _ => {}
+            }
+            sleep(Duration::from_secs(1)).await;
+        }
+        Err(CosmosGrpcError::Timeout {
+            time: timeout,
+            tx_hash: Some(included.txhash),
+        })
+    }
+
+    /// Identical to `wait_for_tx` but calls `on_poll` each time the transaction is not found,
+    /// with the number of queries made so far and the time elapsed, and once more when the
+    /// transaction is found. Useful to show progress in a user interface during long waits.
+    pub async fn wait_for_tx_with_progress(
+        &self,
+        response: TxResponse,
+        timeout: Duration,
+        mut on_poll: impl FnMut(TxWaitProgress),
     ) -> Result<TxResponse, CosmosGrpcError> {
         let start = Instant::now();
+        let mut attempt = 0;
+        let mut unknown_checks = 0;
         while Instant::now() - start < timeout {
             // TODO what actually determines when the tx is in the chain?
-            let status = self.get_tx_by_hash(response.txhash.clone()).await;
+            let status = self.get_raw_tx_by_hash(response.txhash.clone()).await;
+            attempt += 1;
             match status {
                 Ok(status) => {
                     if let Some(res) = status.tx_response {
+                        on_poll(TxWaitProgress::Included {
+                            attempt,
+                            elapsed: Instant::now() - start,
+                            response: &res,
+                        });
+                        if let Some((gas_wanted, gas_used)) = determine_out_of_gas(&res) {
+                            return Err(CosmosGrpcError::OutOfGas {
+                                gas_wanted,
+                                gas_used,
+                            });
+                        }
                         return Ok(res);
                     }
                 }
                 Err(CosmosGrpcError::RequestError { error }) => match error.code() {
-                    TonicCode::NotFound | TonicCode::Unknown | TonicCode::InvalidArgument => {}
+                    TonicCode::NotFound | TonicCode::Unknown | TonicCode::InvalidArgument => {
+                        if self.tendermint_rpc_url.is_some() && attempt % MEMPOOL_CHECK_POLLS == 0 {
+                            match self.tx_status(response.txhash.clone()).await {
+                                Ok(TxStatus::Unknown) => {
+                                    unknown_checks += 1;
+                                    if unknown_checks >= DROPPED_TX_CHECKS {
+                                        return Err(CosmosGrpcError::TxDropped {
+                                            tx_hash: response.txhash,
+                                        });
+                                    }
+                                }
+                                Ok(_) => unknown_checks = 0,
+                                // the mempool check is best effort, keep waiting
+                                Err(e) => debug!("Failed to check the mempool {:?}", e),
+                            }
+                        }
+                    }
                     _ => {
                         return Err(CosmosGrpcError::TransactionFailed {
                             tx: response,
@@ -162,6 +1400,10 @@ impl Contact {
                 },
                 Err(e) => return Err(e),
             }
+            on_poll(TxWaitProgress::Polling {
+                attempt,
+                elapsed: Instant::now() - start,
+            });
             sleep(Duration::from_secs(1)).await;
         }
         Err(CosmosGrpcError::TransactionFailed {
@@ -170,3 +1412,1162 @@ impl Contact {
         })
     }
 }
+
+/// An upper bound on the size of a signed tx holding `messages` signed by `num_signatures`
+/// secp256k1 keys with an empty memo, every signer and the fee take their largest size
+fn offline_tx_size(messages: &[Msg], num_signatures: u32) -> u64 {
+    let body = TxBody {
+        messages: messages.iter().map(|msg| msg.0.clone()).collect(),
+        ..Default::default()
+    };
+    let signer = SignerInfo {
+        public_key: Some(encode_any(
+            ProtoSecp256k1Pubkey { key: vec![0; 33] },
+            "/cosmos.crypto.secp256k1.PubKey",
+        )),
+        mode_info: Some(ModeInfo {
+            sum: Some(mode_info::Sum::Single(mode_info::Single {
+                mode: SignMode::LegacyAminoJson.into(),
+            })),
+        }),
+        sequence: u64::MAX,
+    };
+    let fee = ProtoFee {
+        amount: vec![ProtoCoin {
+            denom: format!("ibc/{}", "0".repeat(64)),
+            amount: u64::MAX.to_string(),
+        }],
+        gas_limit: u64::MAX,
+        ..Default::default()
+    };
+    let auth_info = AuthInfo {
+        signer_infos: vec![signer; num_signatures as usize],
+        fee: Some(fee),
+    };
+    // only the lengths of the fields matter for the size
+    let raw = TxRaw {
+        body_bytes: vec![0; body.encoded_len()],
+        auth_info_bytes: vec![0; auth_info.encoded_len()],
+        signatures: vec![vec![0; 64]; num_signatures as usize],
+    };
+    raw.encoded_len() as u64
+}
+
+/// The fee amount for `gas_limit` at the gas price `fee` pays, rounded up. The amount is kept
+/// as is for a fee without a gas limit
+fn scale_fee_amount(fee: &Fee, gas_limit: u64) -> Vec<Coin> {
+    if fee.gas_limit == 0 {
+        return fee.amount.clone();
+    }
+    let old_limit: Uint256 = fee.gas_limit.into();
+    let new_limit: Uint256 = gas_limit.into();
+    fee.amount
+        .iter()
+        .map(|coin| Coin {
+            amount: (coin.amount.clone() * new_limit.clone() + old_limit.clone() - 1u8.into())
+                / old_limit.clone(),
+            denom: coin.denom.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::mock_contact;
+    use crate::client::types::ConfirmTx;
+    use crate::client::DEFAULT_OFFLINE_BASE_GAS;
+    use crate::coin::GasPrice;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scale_fee_amount() {
+        let fee = Fee {
+            amount: vec![Coin {
+                amount: 1000u64.into(),
+                denom: "stake".into(),
+            }],
+            gas_limit: 300_000,
+            ..Default::default()
+        };
+        let scaled = scale_fee_amount(&fee, 450_000);
+        assert_eq!(scaled[0].amount, 1500u64.into());
+        assert_eq!(scaled[0].denom, "stake");
+        // rounded up so the gas price never drops
+        assert_eq!(scale_fee_amount(&fee, 300_001)[0].amount, 1001u64.into());
+        let no_limit = Fee {
+            gas_limit: 0,
+            ..fee.clone()
+        };
+        assert_eq!(scale_fee_amount(&no_limit, 450_000), fee.amount);
+    }
+
+    #[test]
+    fn test_offline_tx_size() {
+        use crate::private_key::MessageArgs;
+
+        let key = PrivateKey::from_secret(b"offline size key");
+        let address = key.to_address("cosmos").unwrap();
+        let send = Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                from_address: address.to_string(),
+                to_address: address.to_string(),
+                amount: vec![Coin::new(1_000_000u64.into(), "uatom").into()],
+            },
+        );
+        let messages = vec![send.clone(), send];
+        let args = MessageArgs {
+            sequence: 12,
+            fee: Fee {
+                amount: vec![Coin::new(5000u64.into(), "uatom")],
+                gas_limit: 200_000,
+                ..Default::default()
+            },
+            timeout_height: 0,
+            chain_id: "cosmoshub-4".to_string(),
+            account_number: 100,
+        };
+        let signed = key.sign_std_msg(&messages, args, "").unwrap().len() as u64;
+        let estimate = offline_tx_size(&messages, 1);
+        assert!(estimate >= signed);
+        assert!(estimate - signed < 100);
+        // each signature adds at least it's 64 bytes
+        assert!(offline_tx_size(&messages, 2) > estimate + 64);
+    }
+
+    #[test]
+    fn test_order_signers() {
+        let a = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let b = Address::from_bytes([2; 20], "cosmos").unwrap();
+        let c = Address::from_bytes([3; 20], "cosmos").unwrap();
+        // the prefix of required signers does not need to match
+        let required = vec![
+            b.to_bech32("althea").unwrap(),
+            a.to_bech32("cosmos").unwrap(),
+        ];
+        assert_eq!(order_signers(&required, &[a, c, b]), Ok(vec![2, 0]));
+        assert_eq!(order_signers(&[], &[a]), Ok(vec![]));
+        assert_eq!(order_signers(&required, &[a, c]), Err(required[0].clone()));
+        assert_eq!(
+            order_signers(&["not an address".to_string()], &[a]),
+            Err("not an address".to_string())
+        );
+    }
+
+    #[test]
+    fn test_memo_within_limit() {
+        assert!(memo_within_limit("", 0));
+        assert!(memo_within_limit(MEMO, 256));
+        assert!(memo_within_limit(&"a".repeat(256), 256));
+        assert!(!memo_within_limit(&"a".repeat(257), 256));
+        // the limit is in bytes, not characters
+        assert!(!memo_within_limit(&"é".repeat(200), 256));
+    }
+
+    #[test]
+    fn test_simulation_disabled() {
+        assert!(simulation_disabled(&Status::unimplemented(
+            "unknown service cosmos.tx.v1beta1.Service"
+        )));
+        assert!(simulation_disabled(&Status::unknown(
+            "Simulation is disabled on this node"
+        )));
+        assert!(!simulation_disabled(&Status::unknown(
+            "out of gas in location: ReadFlat"
+        )));
+        assert!(!simulation_disabled(&Status::unavailable("disabled")));
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_ante_gas() {
+        let (contact, mock, key) = mock_contact();
+        let args = contact
+            .get_message_args(key.to_address("cosmos").unwrap(), Fee::default())
+            .await
+            .unwrap();
+        let tx = key.sign_std_msg(&[], args, "").unwrap();
+        // 10 gas per byte and 1000 per signature
+        let gas = contact.estimate_ante_gas(&tx).await.unwrap();
+        assert_eq!(gas, tx.len() as u64 * 10 + 1000);
+
+        // the params are cached until refreshed
+        mock.auth_params.lock().unwrap().tx_size_cost_per_byte = 20;
+        assert_eq!(contact.estimate_ante_gas(&tx).await.unwrap(), gas);
+        contact.refresh_auth_params().await.unwrap();
+        assert_eq!(
+            contact.estimate_ante_gas(&tx).await.unwrap(),
+            tx.len() as u64 * 20 + 1000
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_broadcast_retry() {
+        let (contact, mock, key) = mock_contact();
+        let destination = key.to_address("cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 1u64.into(),
+        };
+
+        // the broadcast is not found on chain after failing, so it's sent again
+        mock.push_broadcast_result(Err(tonic::Status::unavailable("node overloaded")));
+        let response = contact
+            .send_tokens(coin.clone(), None, destination, key.clone(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 2);
+        let sent = mock.broadcasts.lock().unwrap()[1].tx_bytes.clone();
+        assert_eq!(response.txhash, crate::utils::tx_hash(&sent));
+
+        // the broadcast reached the chain despite failing, so it's not sent again
+        mock.push_broadcast_result(Err(tonic::Status::unavailable("node overloaded")));
+        mock.include_tx(TxResponse {
+            txhash: response.txhash.clone(),
+            height: 11,
+            ..Default::default()
+        });
+        let response = contact
+            .send_tokens(coin, None, destination, key, None, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 3);
+        assert_eq!(response.height, 11);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_dry_run() {
+        let (contact, mock, key) = mock_contact();
+        let destination = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 7u64.into(),
+        };
+        let result = contact
+            .dry_run_send_tokens(coin, None, destination, key)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 0);
+        assert_eq!(result.messages.len(), 1);
+        match &result.messages[0].value {
+            crate::msg::DecodedMsgValue::Send(send) => {
+                assert_eq!(send.to_address, destination.to_string());
+            }
+            _ => panic!("Expected MsgSend"),
+        }
+        assert_eq!(result.gas, result.fee.gas_limit);
+        assert_eq!(result.tx_hash.len(), 64);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_confirm_callback() {
+        use std::sync::Mutex;
+
+        struct Confirm {
+            approve: bool,
+            seen: Mutex<Vec<DryRunResult>>,
+        }
+
+        #[async_trait::async_trait]
+        impl ConfirmTx for Confirm {
+            async fn confirm(&self, tx: &DryRunResult) -> bool {
+                self.seen.lock().unwrap().push(tx.clone());
+                self.approve
+            }
+        }
+
+        let (mut contact, mock, key) = mock_contact();
+        let destination = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 7u64.into(),
+        };
+        let reject = Arc::new(Confirm {
+            approve: false,
+            seen: Mutex::new(Vec::new()),
+        });
+        contact.set_confirm_callback(Some(reject.clone()));
+        let res = contact
+            .send_tokens(coin.clone(), None, destination, key.clone(), None, None)
+            .await;
+        assert!(matches!(res, Err(CosmosGrpcError::UserRejected)));
+        assert_eq!(mock.broadcast_count(), 0);
+        let seen = reject.seen.lock().unwrap()[0].clone();
+        assert_eq!(seen.messages.len(), 1);
+        assert_eq!(seen.tx_hash.len(), 64);
+
+        let approve = Arc::new(Confirm {
+            approve: true,
+            seen: Mutex::new(Vec::new()),
+        });
+        contact.set_confirm_callback(Some(approve.clone()));
+        let res = contact
+            .send_tokens(coin, None, destination, key, None, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 1);
+        assert_eq!(res.txhash, approve.seen.lock().unwrap()[0].tx_hash);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_min_fee_for_gas() {
+        use crate::decimal::Decimal;
+
+        let (mut contact, mock, key) = mock_contact();
+        let stake = |amount: u64| Coin {
+            denom: "stake".into(),
+            amount: amount.into(),
+        };
+        // old nodes don't report their gas prices
+        assert!(matches!(
+            contact.min_fee_for_gas(200_000, None).await,
+            Err(CosmosGrpcError::ModuleNotPresent(_))
+        ));
+
+        *mock.minimum_gas_price.lock().unwrap() = Some("".to_string());
+        assert_eq!(
+            contact.min_fee_for_gas(200_000, None).await.unwrap(),
+            vec![]
+        );
+
+        *mock.minimum_gas_price.lock().unwrap() =
+            Some("0.025000000000000000uatom,0.100000000000000000stake".to_string());
+        let fee = contact
+            .min_fee_for_gas(200_001, Some("stake"))
+            .await
+            .unwrap();
+        assert_eq!(fee, vec![stake(20_001)]);
+        let fee = contact.min_fee_for_gas(200_000, None).await.unwrap();
+        assert_eq!(fee[0].denom, "uatom");
+        assert!(contact
+            .min_fee_for_gas(200_000, Some("uosmo"))
+            .await
+            .is_err());
+
+        // the configured price takes precedence
+        contact.set_gas_price(Some(GasPrice::new(
+            Decimal::from_str_lenient("0.5").unwrap(),
+            "stake".to_string(),
+        )));
+        let fee = contact.min_fee_for_gas(200_000, None).await.unwrap();
+        assert_eq!(fee, vec![stake(100_000)]);
+        let fee = contact
+            .min_fee_for_gas(200_000, Some("uatom"))
+            .await
+            .unwrap();
+        assert_eq!(fee[0].amount, 5_000u64.into());
+
+        // and is the default fee of send_tokens
+        let destination = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let result = contact
+            .dry_run_send_tokens(stake(7), None, destination, key)
+            .await
+            .unwrap();
+        assert_eq!(result.fee.amount, vec![stake(250_000)]);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_send_tokens_mode() {
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
+
+        let (contact, mock, key) = mock_contact();
+        let destination = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 7u64.into(),
+        };
+        // the tx is never included, an async send must return without waiting for it
+        let response = contact
+            .send_tokens(
+                coin.clone(),
+                Some(coin),
+                destination,
+                key,
+                Some(Duration::from_secs(60)),
+                Some(BroadcastMode::Async),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.txhash.len(), 64);
+        let broadcasts = mock.broadcasts.lock().unwrap();
+        assert_eq!(broadcasts[0].mode, BroadcastMode::Async as i32);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_no_fees() {
+        let (mut contact, _, key) = mock_contact();
+        contact.set_fees_required(false);
+        let destination = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 7u64.into(),
+        };
+        let result = contact
+            .dry_run_send_tokens(coin.clone(), Some(coin), destination, key)
+            .await
+            .unwrap();
+        assert!(result.fee.amount.is_empty());
+        assert!(result.fee.gas_limit > 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_auto_bump_fees() {
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxRaw};
+        use prost::Message;
+        let (mut contact, mock, key) = mock_contact();
+        let destination = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let coin = Coin {
+            denom: "stake".into(),
+            amount: 7u64.into(),
+        };
+        let rejected = TxResponse {
+            code: 13,
+            codespace: "sdk".to_string(),
+            raw_log: "insufficient fees; got: 1stake required: 100stake: insufficient fee"
+                .to_string(),
+            ..Default::default()
+        };
+        let fee = Coin {
+            denom: "stake".into(),
+            amount: 1u64.into(),
+        };
+        mock.push_broadcast_result(Ok(rejected.clone()));
+        let res = contact
+            .send_tokens(
+                coin.clone(),
+                Some(fee.clone()),
+                destination,
+                key.clone(),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(CosmosGrpcError::InsufficientFees { .. })));
+        assert_eq!(mock.broadcast_count(), 1);
+
+        contact.set_auto_bump_fees(true);
+        mock.push_broadcast_result(Ok(rejected));
+        contact
+            .send_tokens(coin, Some(fee), destination, key, None, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 3);
+        let sent = mock.broadcasts.lock().unwrap()[2].tx_bytes.clone();
+        let tx = TxRaw::decode(sent.as_slice()).unwrap();
+        let auth_info = AuthInfo::decode(tx.auth_info_bytes.as_slice()).unwrap();
+        assert_eq!(auth_info.fee.unwrap().amount[0].amount, "100");
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_send_all() {
+        use crate::private_key::MessageArgs;
+        use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+        let (contact, mock, _) = mock_contact();
+        let chain_id = "testing".to_string();
+        let mut txs = Vec::new();
+        for i in 0..3u8 {
+            let key = PrivateKey::from_secret(&[i; 4]);
+            let address = key.to_address("cosmos").unwrap();
+            let send = MsgSend {
+                from_address: address.to_string(),
+                to_address: address.to_string(),
+                amount: vec![],
+            };
+            let args = MessageArgs {
+                sequence: 0,
+                fee: Fee {
+                    amount: Vec::new(),
+                    gas_limit: 100_000,
+                    payer: None,
+                    granter: None,
+                },
+                timeout_height: 100,
+                chain_id: chain_id.clone(),
+                account_number: i as u64,
+            };
+            let msg = crate::Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+            txs.push(key.sign_std_msg_detailed(&[msg], args, "").unwrap());
+        }
+        let hashes: Vec<String> = txs.iter().map(|tx| tx.tx_hash.clone()).collect();
+        // the second tx is never included
+        for (i, hash) in hashes.iter().enumerate() {
+            if i != 1 {
+                mock.include_tx(TxResponse {
+                    txhash: hash.clone(),
+                    height: 11 + i as i64,
+                    ..Default::default()
+                });
+            }
+        }
+        let results = contact.send_all(txs, Duration::from_secs(2), 2).await;
+        assert_eq!(mock.broadcast_count(), 3);
+        assert_eq!(results[0].as_ref().unwrap().height, 11);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().txhash, hashes[2]);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_wait_for_txs() {
+        let (contact, mock, _) = mock_contact();
+        let hashes: Vec<String> = (0..4).map(|i| format!("{:X}", i + 10).repeat(64)).collect();
+        // only the first and the last tx are included
+        for i in [0, 3] {
+            mock.include_tx(TxResponse {
+                txhash: hashes[i].clone(),
+                height: 20 + i as i64,
+                ..Default::default()
+            });
+        }
+        let start = std::time::Instant::now();
+        let results = contact
+            .wait_for_txs(hashes.clone(), Duration::from_secs(2))
+            .await;
+        // the missing txs are waited for at the same time, not one after the other
+        assert!(start.elapsed() < Duration::from_millis(3500));
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap().height, 20);
+        assert!(results[1].is_err());
+        assert!(results[2].is_err());
+        assert_eq!(results[3].as_ref().unwrap().txhash, hashes[3]);
+
+        // once the timeout passed each tx is still checked once
+        let results = contact.wait_for_txs(hashes, Duration::ZERO).await;
+        assert_eq!(results[0].as_ref().unwrap().height, 20);
+        assert!(matches!(
+            results[1],
+            Err(CosmosGrpcError::TransactionFailed { .. })
+        ));
+        assert_eq!(results[3].as_ref().unwrap().height, 23);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_wait_for_confirmations() {
+        let (contact, mock, _) = mock_contact();
+        let hash = "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string();
+        let included = TxResponse {
+            txhash: hash.clone(),
+            height: 10,
+            ..Default::default()
+        };
+        mock.include_tx(included.clone());
+        mock.set_latest_block("testing", 12);
+
+        let res = contact
+            .wait_for_confirmations(included.clone(), 2, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(res.height, 10);
+
+        match contact
+            .wait_for_confirmations(included, 5, Duration::from_secs(2))
+            .await
+        {
+            Err(CosmosGrpcError::Timeout { tx_hash, .. }) => assert_eq!(tx_hash, Some(hash)),
+            _ => panic!("Expected timeout"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_simulate_many() {
+        let (contact, mock, key) = mock_contact();
+        let destination = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let bundle = |count: usize| -> Vec<crate::Msg> {
+            (0..count)
+                .map(|_| {
+                    crate::Msg::new(
+                        "/cosmos.bank.v1beta1.MsgSend",
+                        cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend {
+                            from_address: key.to_address("cosmos").unwrap().to_string(),
+                            to_address: destination.to_string(),
+                            amount: vec![],
+                        },
+                    )
+                })
+                .collect()
+        };
+        mock.simulate_results.lock().unwrap().extend(vec![
+            Ok(GasInfo {
+                gas_wanted: 0,
+                gas_used: 100,
+            }),
+            Err(tonic::Status::invalid_argument("insufficient funds")),
+            Ok(GasInfo {
+                gas_wanted: 0,
+                gas_used: 300,
+            }),
+        ]);
+        let results = contact
+            .simulate_many(vec![bundle(1), bundle(2), bundle(3)], &key, 2)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().gas_used, 100);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().gas_used, 300);
+        let simulations = mock.simulations.lock().unwrap();
+        assert_eq!(simulations.len(), 3);
+        assert_eq!(
+            simulations[2]
+                .tx
+                .as_ref()
+                .unwrap()
+                .body
+                .as_ref()
+                .unwrap()
+                .messages
+                .len(),
+            3
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_multi_signer() {
+        use crate::msg::Msg;
+        use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::TxRaw;
+        use prost::Message;
+
+        let (contact, mock, key) = mock_contact();
+        let other = PrivateKey::from_secret(b"mock rpc other key");
+        let ours = key.to_address("cosmos").unwrap();
+        let theirs = other.to_address("cosmos").unwrap();
+        mock.add_account(&theirs, 6, 3);
+        let send = |from: Address, to: Address| {
+            Msg::new(
+                "/cosmos.bank.v1beta1.MsgSend",
+                MsgSend {
+                    from_address: from.to_string(),
+                    to_address: to.to_string(),
+                    amount: vec![],
+                },
+            )
+        };
+        let messages = [send(theirs, ours), send(ours, theirs)];
+
+        match contact
+            .create_and_send(&messages, Fee::default(), "", key.clone(), None)
+            .await
+        {
+            Err(CosmosGrpcError::MissingSigner { address }) => {
+                assert_eq!(address, theirs.to_string())
+            }
+            _ => panic!("Expected missing signer"),
+        }
+        assert_eq!(mock.broadcast_count(), 0);
+
+        // keys are reordered to match the messages
+        contact
+            .create_and_send_multi_signer(&messages, Fee::default(), "", &[key, other], None)
+            .await
+            .unwrap();
+        let sent = mock.broadcasts.lock().unwrap()[0].tx_bytes.clone();
+        let tx = TxRaw::decode(sent.as_slice()).unwrap();
+        assert_eq!(tx.signatures.len(), 2);
+        let auth_info =
+            cosmos_sdk_proto::cosmos::tx::v1beta1::AuthInfo::decode(tx.auth_info_bytes.as_slice())
+                .unwrap();
+        assert_eq!(auth_info.signer_infos[0].sequence, 3);
+        assert_eq!(auth_info.signer_infos[1].sequence, 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_send_idempotent() {
+        use crate::msg::Msg;
+        use crate::utils::encode_any;
+        use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{
+            AbciMessageLog, Attribute, StringEvent,
+        };
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, Tx, TxBody, TxRaw};
+        use prost::Message;
+
+        let (contact, mock, key) = mock_contact();
+        let ours = key.to_address("cosmos").unwrap();
+        let messages = [Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                from_address: ours.to_string(),
+                to_address: ours.to_string(),
+                amount: vec![],
+            },
+        )];
+        let landed = |code: u32| {
+            let sent = mock
+                .broadcasts
+                .lock()
+                .unwrap()
+                .last()
+                .unwrap()
+                .tx_bytes
+                .clone();
+            let raw = TxRaw::decode(sent.as_slice()).unwrap();
+            let tx = Tx {
+                body: Some(TxBody::decode(raw.body_bytes.as_slice()).unwrap()),
+                auth_info: Some(AuthInfo::decode(raw.auth_info_bytes.as_slice()).unwrap()),
+                signatures: raw.signatures,
+            };
+            TxResponse {
+                txhash: crate::utils::tx_hash(&sent),
+                code,
+                height: 10,
+                logs: vec![AbciMessageLog {
+                    msg_index: 0,
+                    log: String::new(),
+                    events: vec![StringEvent {
+                        r#type: "message".to_string(),
+                        attributes: vec![Attribute {
+                            key: "sender".to_string(),
+                            value: ours.to_string(),
+                        }],
+                    }],
+                }],
+                tx: Some(encode_any(tx, "/cosmos.tx.v1beta1.Tx")),
+                ..Default::default()
+            }
+        };
+
+        contact
+            .send_idempotent("payment-1", &messages, Fee::default(), key.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 1);
+        // the first attempt failed on chain, so it's sent again
+        mock.include_tx(landed(5));
+        contact
+            .send_idempotent("payment-1", &messages, Fee::default(), key.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 2);
+
+        let response = landed(0);
+        mock.include_tx(response.clone());
+        let found = contact
+            .send_idempotent("payment-1", &messages, Fee::default(), key.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(found.txhash, response.txhash);
+        assert_eq!(mock.broadcast_count(), 2);
+        // a different id is a different payment
+        contact
+            .send_idempotent("payment-2", &messages, Fee::default(), key, None)
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_fee_payer() {
+        use crate::msg::Msg;
+        use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxRaw};
+        use prost::Message;
+
+        let (contact, mock, key) = mock_contact();
+        let payer = PrivateKey::from_secret(b"mock rpc payer key");
+        let ours = key.to_address("cosmos").unwrap();
+        let theirs = payer.to_address("cosmos").unwrap();
+        mock.add_account(&theirs, 6, 3);
+        let messages = [Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                from_address: ours.to_string(),
+                to_address: ours.to_string(),
+                amount: vec![],
+            },
+        )];
+
+        // the payer co-signs and can't be set without it's key
+        let fee = Fee {
+            payer: Some(theirs),
+            ..Default::default()
+        };
+        match contact
+            .create_and_send(&messages, fee, "", key.clone(), None)
+            .await
+        {
+            Err(CosmosGrpcError::MissingSigner { address }) => {
+                assert_eq!(address, theirs.to_string())
+            }
+            _ => panic!("Expected missing signer"),
+        }
+        assert_eq!(mock.broadcast_count(), 0);
+
+        contact
+            .create_and_send_with_payer(&messages, Fee::default(), "", key, payer, None)
+            .await
+            .unwrap();
+        let sent = mock.broadcasts.lock().unwrap()[0].tx_bytes.clone();
+        let tx = TxRaw::decode(sent.as_slice()).unwrap();
+        assert_eq!(tx.signatures.len(), 2);
+        let auth_info = AuthInfo::decode(tx.auth_info_bytes.as_slice()).unwrap();
+        // the payer signs last
+        assert_eq!(auth_info.signer_infos[0].sequence, 0);
+        assert_eq!(auth_info.signer_infos[1].sequence, 3);
+        assert_eq!(auth_info.fee.unwrap().payer, theirs.to_string());
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_send_transaction_raw() {
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
+
+        let (contact, mock, _) = mock_contact();
+        let failed = TxResponse {
+            code: 5,
+            codespace: "sdk".to_string(),
+            raw_log: "insufficient funds".to_string(),
+            ..Default::default()
+        };
+        mock.push_broadcast_result(Ok(failed.clone()));
+        match contact
+            .send_transaction(vec![1, 2, 3], BroadcastMode::Sync)
+            .await
+        {
+            Err(CosmosGrpcError::TransactionFailed { tx, .. }) => assert_eq!(tx, failed),
+            _ => panic!("Expected transaction failure"),
+        }
+        mock.push_broadcast_result(Ok(failed.clone()));
+        let response = contact
+            .send_transaction_raw(vec![1, 2, 3], BroadcastMode::Sync)
+            .await
+            .unwrap();
+        assert_eq!(response, failed);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_estimate_fee() {
+        let (contact, mock, key) = mock_contact();
+        mock.push_simulate_result(Ok(GasInfo {
+            gas_wanted: 0,
+            gas_used: 100_000,
+        }));
+        let fee = contact.estimate_fee(&[], "", &key).await.unwrap();
+        assert_eq!(fee.gas_limit, 130_000);
+        assert!(fee.amount.is_empty());
+        assert_eq!(mock.simulations.lock().unwrap().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_estimate_gas_offline() {
+        let (mut contact, _mock, _key) = mock_contact();
+        // the mock charges 10 gas per byte and 1000 per signature
+        let one = contact.estimate_gas_offline(&[], 1).await.unwrap();
+        let two = contact.estimate_gas_offline(&[], 2).await.unwrap();
+        assert!(one > DEFAULT_OFFLINE_BASE_GAS + 1000);
+        assert!(two > one + 1000 + 640);
+        contact.set_offline_base_gas(0);
+        assert_eq!(
+            contact.estimate_gas_offline(&[], 1).await.unwrap(),
+            one - DEFAULT_OFFLINE_BASE_GAS
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_send_and_confirm() {
+        use crate::msg::Msg;
+        use crate::utils::bytes_to_hex_str;
+        use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{
+            AbciMessageLog, Attribute, GasInfo, MsgData, StringEvent, TxMsgData,
+        };
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxRaw};
+        use prost::Message;
+
+        let (contact, mock, key) = mock_contact();
+        let ours = key.to_address("cosmos").unwrap();
+        let messages = [Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                from_address: ours.to_string(),
+                to_address: ours.to_string(),
+                amount: vec![],
+            },
+        )];
+        let hash = "AB".repeat(32);
+        let mut data = Vec::new();
+        TxMsgData {
+            data: vec![MsgData {
+                msg_type: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                data: vec![],
+            }],
+        }
+        .encode(&mut data)
+        .unwrap();
+        let included = TxResponse {
+            txhash: hash.clone(),
+            height: 10,
+            gas_wanted: 130_000,
+            gas_used: 90_000,
+            data: bytes_to_hex_str(&data),
+            logs: vec![AbciMessageLog {
+                msg_index: 0,
+                log: String::new(),
+                events: vec![StringEvent {
+                    r#type: "transfer".to_string(),
+                    attributes: vec![Attribute {
+                        key: "recipient".to_string(),
+                        value: ours.to_string(),
+                    }],
+                }],
+            }],
+            ..Default::default()
+        };
+        mock.include_tx(included.clone());
+        let accepted = TxResponse {
+            txhash: hash.clone(),
+            ..Default::default()
+        };
+
+        // the simulated fee is bumped to what the node requires
+        mock.push_simulate_result(Ok(GasInfo {
+            gas_wanted: 0,
+            gas_used: 100_000,
+        }));
+        mock.push_broadcast_result(Ok(TxResponse {
+            code: 13,
+            codespace: "sdk".to_string(),
+            raw_log: "insufficient fees; got: required: 100stake: insufficient fee".to_string(),
+            ..Default::default()
+        }));
+        mock.push_broadcast_result(Ok(accepted.clone()));
+        let receipt = contact
+            .send_and_confirm(&messages, key.clone(), SendOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(mock.broadcast_count(), 2);
+        assert_eq!(receipt.tx_hash(), hash);
+        assert_eq!(receipt.height(), 10);
+        assert_eq!(receipt.gas_used, 90_000);
+        assert_eq!(receipt.fee.gas_limit, 130_000);
+        assert_eq!(
+            receipt.fee.amount,
+            vec![Coin {
+                denom: "stake".into(),
+                amount: 100u64.into(),
+            }]
+        );
+        assert_eq!(receipt.events[0].kind, "transfer");
+        assert_eq!(
+            receipt.msg_responses[0].type_url,
+            "/cosmos.bank.v1beta1.MsgSend"
+        );
+        let sent = mock.broadcasts.lock().unwrap()[1].tx_bytes.clone();
+        let tx = TxRaw::decode(sent.as_slice()).unwrap();
+        let auth_info = AuthInfo::decode(tx.auth_info_bytes.as_slice()).unwrap();
+        assert_eq!(auth_info.fee.unwrap().amount[0].amount, "100");
+
+        // a fixed fee is used as is and not simulated
+        let fee = Fee {
+            amount: vec![],
+            gas_limit: 200_000,
+            granter: None,
+            payer: None,
+        };
+        let opts = SendOptions {
+            fee: FeeStrategy::Fixed(fee.clone()),
+            memo: "receipt".into(),
+            ..SendOptions::default()
+        };
+        mock.push_broadcast_result(Ok(accepted.clone()));
+        let receipt = contact
+            .send_and_confirm(&messages, key.clone(), opts.clone())
+            .await
+            .unwrap();
+        assert_eq!(receipt.fee, fee);
+        assert_eq!(mock.broadcast_count(), 3);
+
+        // included but failed
+        mock.include_tx(TxResponse {
+            code: 5,
+            codespace: "sdk".to_string(),
+            raw_log: "insufficient funds".to_string(),
+            ..included
+        });
+        mock.push_broadcast_result(Ok(accepted));
+        match contact.send_and_confirm(&messages, key, opts).await {
+            Err(CosmosGrpcError::ExecutionFailed { tx }) => assert_eq!(tx.code, 5),
+            other => panic!("Expected ExecutionFailed, got {:?}", other),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_fee_denom_preference() {
+        use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+
+        let (mut contact, mock, key) = mock_contact();
+        let address = key.to_address("cosmos").unwrap();
+        *mock.minimum_gas_price.lock().unwrap() =
+            Some("0.100000000000000000uatom,0.200000000000000000ujuno".to_string());
+        mock.balances.lock().unwrap().insert(
+            address.to_string(),
+            vec![
+                ProtoCoin {
+                    denom: "uatom".to_string(),
+                    amount: "1000000".to_string(),
+                },
+                ProtoCoin {
+                    denom: "ujuno".to_string(),
+                    amount: "1000000".to_string(),
+                },
+            ],
+        );
+        // most of the uatom is still locked by vesting
+        mock.spendable_balances.lock().unwrap().insert(
+            address.to_string(),
+            vec![
+                ProtoCoin {
+                    denom: "uatom".to_string(),
+                    amount: "5".to_string(),
+                },
+                ProtoCoin {
+                    denom: "ujuno".to_string(),
+                    amount: "1000000".to_string(),
+                },
+            ],
+        );
+        let gas = GasInfo {
+            gas_wanted: 0,
+            gas_used: 100_000,
+        };
+        // uosmo is not accepted and there is too little uatom for the fee
+        contact.set_fee_denom_preference(vec![
+            "uosmo".to_string(),
+            "uatom".to_string(),
+            "ujuno".to_string(),
+        ]);
+        mock.push_simulate_result(Ok(gas.clone()));
+        let fee = contact.estimate_fee(&[], "", &key).await.unwrap();
+        assert_eq!(fee.gas_limit, 130_000);
+        assert_eq!(fee.amount, vec![Coin::new(26_000u64.into(), "ujuno")]);
+
+        contact.set_fee_denom_preference(vec!["uosmo".to_string(), "uatom".to_string()]);
+        mock.push_simulate_result(Ok(gas));
+        match contact.estimate_fee(&[], "", &key).await {
+            Err(CosmosGrpcError::BadInput(message)) => {
+                assert!(message.contains("uosmo is not accepted"));
+                assert!(message.contains("holds 5uatom of 13000uatom"));
+            }
+            _ => panic!("Expected no payable denom"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_simulate_with_granter() {
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxRaw};
+        use prost::Message;
+
+        let (contact, mock, key) = mock_contact();
+        let granter = "cosmos1granter".to_string();
+        mock.push_simulate_result(Ok(GasInfo {
+            gas_wanted: 0,
+            gas_used: 100_000,
+        }));
+        contact
+            .simulate_and_send_with_granter(&[], Some(granter.clone()), key, None)
+            .await
+            .unwrap();
+        let simulated = mock.simulations.lock().unwrap()[0].tx.clone().unwrap();
+        let simulated_fee = simulated.auth_info.unwrap().fee.unwrap();
+        assert_eq!(simulated_fee.granter, granter);
+        let sent = mock.broadcasts.lock().unwrap()[0].tx_bytes.clone();
+        let tx = TxRaw::decode(sent.as_slice()).unwrap();
+        let fee = AuthInfo::decode(tx.auth_info_bytes.as_slice())
+            .unwrap()
+            .fee
+            .unwrap();
+        assert_eq!(fee.granter, granter);
+        assert_eq!(fee.gas_limit, 130_000);
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_simulation_unsupported() {
+        let (contact, mock, key) = mock_contact();
+        mock.push_simulate_result(Err(tonic::Status::unimplemented(
+            "unknown service cosmos.tx.v1beta1.Service",
+        )));
+        let res = contact.estimate_fee(&[], "", &key).await;
+        assert!(matches!(res, Err(CosmosGrpcError::SimulationUnsupported)));
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_simulate_tx_full() {
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{MsgData, Result, TxMsgData};
+        use prost::Message;
+        use tendermint_proto::abci::{Event, EventAttribute};
+
+        let (contact, mock, key) = mock_contact();
+        let attribute = |key: &str, value: &str| EventAttribute {
+            key: key.as_bytes().to_vec(),
+            value: value.as_bytes().to_vec(),
+            index: true,
+        };
+        let mut data = Vec::new();
+        TxMsgData {
+            data: vec![MsgData {
+                msg_type: "/cosmwasm.wasm.v1.MsgExecuteContract".to_string(),
+                data: vec![1, 2, 3],
+            }],
+        }
+        .encode(&mut data)
+        .unwrap();
+        *mock.simulate_abci_result.lock().unwrap() = Some(Result {
+            data,
+            log: "[]".to_string(),
+            events: vec![
+                Event {
+                    r#type: "tx".to_string(),
+                    attributes: vec![attribute("fee", "100stake")],
+                },
+                Event {
+                    r#type: "wasm".to_string(),
+                    attributes: vec![attribute("action", "swap"), attribute("msg_index", "0")],
+                },
+            ],
+        });
+        mock.push_simulate_result(Ok(GasInfo {
+            gas_wanted: 0,
+            gas_used: 1234,
+        }));
+        let args = crate::MessageArgs {
+            sequence: 0,
+            fee: Fee::default(),
+            timeout_height: 100,
+            chain_id: "testing".to_string(),
+            account_number: 0,
+        };
+        let parts = key.build_tx(&[], args, "").unwrap();
+        let result = contact.simulate_tx_full(parts).await.unwrap();
+        assert_eq!(result.gas_info.gas_used, 1234);
+        assert_eq!(result.msg_responses.len(), 1);
+        assert_eq!(result.msg_responses[0].value, vec![1, 2, 3]);
+        assert_eq!(result.events.len(), 2);
+        assert_eq!(result.events[0].msg_index, None);
+        assert_eq!(result.events[0].attribute("fee"), Some("100stake"));
+        let wasm: Vec<_> = result.events_of_kind("wasm").collect();
+        assert_eq!(wasm[0].msg_index, Some(0));
+        assert_eq!(
+            wasm[0].attributes,
+            vec![("action".to_string(), "swap".to_string())]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_fee_buffer() {
+        use crate::decimal::Decimal;
+
+        let (mut contact, mock, key) = mock_contact();
+        contact.set_gas_price(Some(GasPrice::new(
+            Decimal::from_str_lenient("0.1").unwrap(),
+            "stake".to_string(),
+        )));
+        assert!(contact.set_fee_buffer(0.9).is_err());
+        contact.set_fee_buffer(1.2).unwrap();
+        mock.push_simulate_result(Ok(GasInfo {
+            gas_wanted: 0,
+            gas_used: 100_000,
+        }));
+        let fee = contact.estimate_fee(&[], "", &key).await.unwrap();
+        // the buffer scales the amount but not the limit
+        assert_eq!(fee.gas_limit, 130_000);
+        assert_eq!(fee.amount[0].amount, 15_600u64.into());
+    }
+}
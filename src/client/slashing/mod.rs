@@ -0,0 +1,54 @@
+//! Contains utility functions for interacting with the Cosmos sdk slashing module, mostly
+//! useful for monitoring validator uptime
+
+use crate::error::CosmosGrpcError;
+use crate::Address;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::query_client::QueryClient as SlashingQueryClient;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::Params as SlashingParams;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::QueryParamsRequest;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::QuerySigningInfoRequest;
+use cosmos_sdk_proto::cosmos::slashing::v1beta1::ValidatorSigningInfo;
+use tonic::Code as TonicCode;
+
+impl Contact {
+    /// Gets the signing info for a validator, this includes the missed block counter, the time
+    /// the validator is jailed until and if the validator has been tombstoned. The address here is
+    /// the validator consensus address, not the operator address, it is always encoded with the
+    /// `<chain_prefix>valcons` prefix regardless of the prefix on the provided address. It can
+    /// be derived from the validators consensus pubkey using `Address::from_consensus_pubkey`.
+    /// Returns None if the validator has never signed a block.
+    pub async fn get_signing_info(
+        &self,
+        cons_address: Address,
+    ) -> Result<Option<ValidatorSigningInfo>, CosmosGrpcError> {
+        let cons_address = match cons_address.to_bech32(format!("{}valcons", self.chain_prefix)) {
+            Ok(a) => a,
+            Err(e) => return Err(CosmosGrpcError::BadInput(e.to_string())),
+        };
+        let mut grpc = SlashingQueryClient::new(self.channel().await?);
+        let res = grpc
+            .signing_info(QuerySigningInfoRequest { cons_address })
+            .await;
+        match res {
+            Ok(res) => Ok(res.into_inner().val_signing_info),
+            Err(e) => match e.code() {
+                TonicCode::NotFound => Ok(None),
+                _ => Err(CosmosGrpcError::RequestError { error: e }),
+            },
+        }
+    }
+
+    /// Gets the slashing module params, including the signed blocks window and the minimum
+    /// portion of that window a validator must sign to avoid being jailed
+    pub async fn get_slashing_params(&self) -> Result<SlashingParams, CosmosGrpcError> {
+        let mut grpc = SlashingQueryClient::new(self.channel().await?);
+        let res = grpc.params(QueryParamsRequest {}).await?.into_inner();
+        match res.params {
+            Some(params) => Ok(params),
+            None => Err(CosmosGrpcError::BadResponse(
+                "No slashing params returned".to_string(),
+            )),
+        }
+    }
+}
@@ -1,7 +1,11 @@
 //! Contains utility functions for interacting with and submitting Cosmos governance proposals
 
+use crate::client::types::BondStatus;
+use crate::client::types::ConsensusPubKey;
 use crate::client::MEMO;
+use crate::decimal::Decimal;
 use crate::error::CosmosGrpcError;
+use crate::utils::next_page;
 use crate::Address;
 use crate::Coin;
 use crate::Contact;
@@ -9,33 +13,382 @@ use crate::Fee;
 use crate::Msg;
 use crate::PrivateKey;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::query_client::QueryClient as StakingQueryClient;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::CommissionRates as ProtoCommissionRates;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::DelegationResponse;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::Description;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgCreateValidator;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgDelegate;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgEditValidator;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::Pool;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryDelegatorUnbondingDelegationsRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryParamsRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryPoolRequest;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryValidatorDelegationsRequest;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryValidatorsRequest;
 use cosmos_sdk_proto::cosmos::staking::v1beta1::QueryValidatorsResponse;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
+use num256::Uint256;
+use prost::Message;
+use std::convert::TryFrom;
 use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tonic::Code as TonicCode;
+
+/// The type url of `MsgCancelUnbondingDelegation`
+pub const MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL: &str =
+    "/cosmos.staking.v1beta1.MsgCancelUnbondingDelegation";
+
+/// MsgCancelUnbondingDelegation defines the SDK message for performing a cancel unbonding
+/// delegation for delegator, introduced in Cosmos SDK v0.46 and so not part of the
+/// cosmos-sdk-proto version used by this crate
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgCancelUnbondingDelegation {
+    #[prost(string, tag = "1")]
+    pub delegator_address: String,
+    #[prost(string, tag = "2")]
+    pub validator_address: String,
+    /// amount is always less than or equal to unbonding delegation entry balance
+    #[prost(message, optional, tag = "3")]
+    pub amount: Option<ProtoCoin>,
+    /// creation_height is the height which the unbonding took place.
+    #[prost(int64, tag = "4")]
+    pub creation_height: i64,
+}
+
+/// The type url of `MsgCreateValidator`
+pub const MSG_CREATE_VALIDATOR_TYPE_URL: &str = "/cosmos.staking.v1beta1.MsgCreateValidator";
+/// The type url of `MsgEditValidator`
+pub const MSG_EDIT_VALIDATOR_TYPE_URL: &str = "/cosmos.staking.v1beta1.MsgEditValidator";
+/// The value of a `Description` field that leaves it unchanged in a `MsgEditValidator`
+const DO_NOT_MODIFY: &str = "[do-not-modify]";
+
+/// The commission of a new validator, `rate` is the initial commission, `max_rate` the most
+/// it can ever be raised to and `max_change_rate` the most it can be changed by per day.
+/// Only `rate` can be changed after the validator is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommissionRates {
+    pub rate: Decimal,
+    pub max_rate: Decimal,
+    pub max_change_rate: Decimal,
+}
+
+impl CommissionRates {
+    /// Checks the constraints the staking module places on commission rates, all of the rates
+    /// must be between 0 and 1, with neither `rate` nor `max_change_rate` above `max_rate`
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("rate", self.rate),
+            ("max_rate", self.max_rate),
+            ("max_change_rate", self.max_change_rate),
+        ] {
+            validate_rate(name, value)?;
+        }
+        if self.rate > self.max_rate {
+            return Err(format!(
+                "Commission rate {} is above the max rate {}",
+                self.rate, self.max_rate
+            ));
+        }
+        if self.max_change_rate > self.max_rate {
+            return Err(format!(
+                "Commission max change rate {} is above the max rate {}",
+                self.max_change_rate, self.max_rate
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl From<CommissionRates> for ProtoCommissionRates {
+    fn from(value: CommissionRates) -> Self {
+        ProtoCommissionRates {
+            rate: value.rate.to_sdk_dec_string(),
+            max_rate: value.max_rate.to_sdk_dec_string(),
+            max_change_rate: value.max_change_rate.to_sdk_dec_string(),
+        }
+    }
+}
+
+fn validate_rate(name: &str, value: Decimal) -> Result<(), String> {
+    if value < Decimal::from(0u8) || value > Decimal::from(1u8) {
+        return Err(format!(
+            "Commission {} {} is not between 0 and 1",
+            name, value
+        ));
+    }
+    Ok(())
+}
+
+/// A delegation to a validator, see `Contact::get_validator_delegations`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorDelegation {
+    pub delegator_address: String,
+    /// The shares of the validator held by the delegator, these only equal the tokens
+    /// delegated until the validator is slashed
+    pub shares: Decimal,
+    /// The tokens the shares are currently worth
+    pub balance: Coin,
+}
+
+impl TryFrom<DelegationResponse> for ValidatorDelegation {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: DelegationResponse) -> Result<Self, Self::Error> {
+        let (delegation, balance) = match (value.delegation, value.balance) {
+            (Some(delegation), Some(balance)) => (delegation, balance),
+            _ => {
+                return Err(CosmosGrpcError::BadResponse(
+                    "Delegation without delegation or balance".to_string(),
+                ))
+            }
+        };
+        let shares = match Decimal::from_sdk_dec_str_rounded(&delegation.shares) {
+            Ok(shares) => shares,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadResponse(format!(
+                    "Failed to parse shares {} {}",
+                    delegation.shares, e
+                )))
+            }
+        };
+        Ok(ValidatorDelegation {
+            delegator_address: delegation.delegator_address,
+            shares,
+            balance: Coin::try_from(balance)?,
+        })
+    }
+}
+
+/// The tokens staked on the chain, see `Contact::get_staking_pool`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakingPool {
+    /// The tokens delegated to validators in the active set
+    pub bonded_tokens: Uint256,
+    /// The tokens delegated to validators outside the active set and those unbonding
+    pub not_bonded_tokens: Uint256,
+}
+
+impl StakingPool {
+    /// The share of all staked tokens that is bonded, None if nothing is staked. To get the
+    /// share of the supply that is bonded, as used for inflation, divide by the supply of
+    /// the bond denom instead
+    pub fn bonded_ratio(&self) -> Option<Decimal> {
+        let total = self.bonded_tokens.clone() + self.not_bonded_tokens.clone();
+        if total == 0u8.into() {
+            return None;
+        }
+        let one: Uint256 = 10u128.pow(18).into();
+        let ratio = self.bonded_tokens.clone() * one / total;
+        Decimal::from_sdk_dec_str(&ratio.to_string()).ok()
+    }
+}
+
+impl TryFrom<Pool> for StakingPool {
+    type Error = CosmosGrpcError;
+
+    fn try_from(pool: Pool) -> Result<Self, Self::Error> {
+        Ok(StakingPool {
+            bonded_tokens: parse_pool_amount(&pool.bonded_tokens)?,
+            not_bonded_tokens: parse_pool_amount(&pool.not_bonded_tokens)?,
+        })
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_pool_amount(amount: &str) -> Result<Uint256, CosmosGrpcError> {
+    amount
+        .parse()
+        .map_err(|e| CosmosGrpcError::BadResponse(format!("Invalid pool amount {} {}", amount, e)))
+}
+
+/// A single entry of an unbonding delegation, each undelegation from a validator creates
+/// a new entry that is paid out at it's completion time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnbondingEntry {
+    pub validator_address: String,
+    /// The height the undelegation took place, needed to cancel it
+    pub creation_height: u64,
+    /// When the tokens become available to the delegator
+    pub completion_time: SystemTime,
+    /// The tokens initially scheduled to be received
+    pub initial_balance: Uint256,
+    /// The tokens still to be received, less than `initial_balance` if the validator
+    /// was slashed during the unbonding period
+    pub balance: Uint256,
+}
 
 impl Contact {
+    /// Gets the staking module's unbonding period, the time between undelegating tokens
+    /// and receiving them
+    pub async fn get_unbonding_time(&self) -> Result<Duration, CosmosGrpcError> {
+        let mut grpc = StakingQueryClient::new(self.channel().await?);
+        let params = grpc
+            .params(QueryParamsRequest {})
+            .await?
+            .into_inner()
+            .params;
+        match params.and_then(|p| p.unbonding_time) {
+            Some(time) => Duration::try_from(time)
+                .map_err(|_| CosmosGrpcError::BadResponse("Negative unbonding time".to_string())),
+            None => Err(CosmosGrpcError::BadResponse(
+                "No unbonding time in staking params".to_string(),
+            )),
+        }
+    }
+
+    /// Gets the denom that is staked on this chain, returns `ModuleNotPresent` on chains without
+    /// a staking module such as consumer chains
+    pub async fn get_bond_denom(&self) -> Result<String, CosmosGrpcError> {
+        let mut grpc = StakingQueryClient::new(self.channel().await?);
+        match grpc.params(QueryParamsRequest {}).await {
+            Ok(res) => Ok(res.into_inner().params.unwrap_or_default().bond_denom),
+            Err(e) if e.code() == TonicCode::Unimplemented => Err(
+                CosmosGrpcError::ModuleNotPresent("cosmos.staking".to_string()),
+            ),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Gets the total bonded and not bonded tokens of the staking module, with the inflation
+    /// and supply this gives the staking APR
+    pub async fn get_staking_pool(&self) -> Result<StakingPool, CosmosGrpcError> {
+        let mut grpc = StakingQueryClient::new(self.channel().await?);
+        let pool = grpc.pool(QueryPoolRequest {}).await?.into_inner().pool;
+        match pool {
+            Some(pool) => StakingPool::try_from(pool),
+            None => Err(CosmosGrpcError::BadResponse(
+                "No pool in staking pool response".to_string(),
+            )),
+        }
+    }
+
+    /// Gets every pending unbonding entry of the delegator across all validators. Entries
+    /// without a completion time have it computed from the time of the block they were
+    /// created in plus the unbonding period, this requires the node to still have that block.
+    pub async fn get_unbonding_delegations(
+        &self,
+        delegator: Address,
+    ) -> Result<Vec<UnbondingEntry>, CosmosGrpcError> {
+        let mut grpc = StakingQueryClient::new(self.channel().await?);
+        let mut entries = Vec::new();
+        let mut unbonding_time = None;
+        let mut pagination = None;
+        loop {
+            let res = grpc
+                .delegator_unbonding_delegations(QueryDelegatorUnbondingDelegationsRequest {
+                    delegator_addr: delegator.to_string(),
+                    pagination,
+                })
+                .await?
+                .into_inner();
+            for unbonding in res.unbonding_responses {
+                for entry in unbonding.entries {
+                    let creation_height = entry.creation_height as u64;
+                    let completion_time = match entry.completion_time {
+                        Some(time) => SystemTime::from(time),
+                        None => {
+                            if unbonding_time.is_none() {
+                                unbonding_time = Some(self.get_unbonding_time().await?);
+                            }
+                            self.get_block_time(creation_height).await? + unbonding_time.unwrap()
+                        }
+                    };
+                    entries.push(UnbondingEntry {
+                        validator_address: unbonding.validator_address.clone(),
+                        creation_height,
+                        completion_time,
+                        initial_balance: entry.initial_balance.parse()?,
+                        balance: entry.balance.parse()?,
+                    });
+                }
+            }
+            pagination = next_page(&res.pagination);
+            if pagination.is_none() {
+                return Ok(entries);
+            }
+        }
+    }
+
+    /// Gets the delegations to a validator, page by page, stopping once `max_delegations` have
+    /// been collected if provided. Popular validators have many thousands of delegators and
+    /// each page is a separate query, so set a cap unless every delegation is needed
+    pub async fn get_validator_delegations(
+        &self,
+        validator: Address,
+        max_delegations: Option<usize>,
+    ) -> Result<Vec<ValidatorDelegation>, CosmosGrpcError> {
+        let validator_addr = validator.to_bech32(format!("{}valoper", self.chain_prefix))?;
+        let mut grpc = StakingQueryClient::new(self.channel().await?);
+        let mut delegations = Vec::new();
+        let mut pagination = None;
+        loop {
+            let res = grpc
+                .validator_delegations(QueryValidatorDelegationsRequest {
+                    validator_addr: validator_addr.clone(),
+                    pagination,
+                })
+                .await?
+                .into_inner();
+            for delegation in res.delegation_responses {
+                delegations.push(ValidatorDelegation::try_from(delegation)?);
+            }
+            if let Some(max) = max_delegations {
+                if delegations.len() >= max {
+                    delegations.truncate(max);
+                    return Ok(delegations);
+                }
+            }
+            pagination = next_page(&res.pagination);
+            if pagination.is_none() {
+                return Ok(delegations);
+            }
+        }
+    }
+
+    async fn get_block_time(&self, height: u64) -> Result<SystemTime, CosmosGrpcError> {
+        let time = self
+            .get_block(height)
+            .await?
+            .and_then(|b| b.header)
+            .and_then(|h| h.time);
+        match time {
+            Some(time) if time.seconds >= 0 => {
+                Ok(UNIX_EPOCH + Duration::new(time.seconds as u64, time.nanos as u32))
+            }
+            _ => Err(CosmosGrpcError::NotFound(format!("block {} time", height))),
+        }
+    }
+
     /// Gets a list of validators
     pub async fn get_validators_list(
         &self,
         filters: QueryValidatorsRequest,
     ) -> Result<QueryValidatorsResponse, CosmosGrpcError> {
-        let mut grpc = StakingQueryClient::connect(self.url.clone()).await?;
+        let mut grpc = StakingQueryClient::new(self.channel().await?);
         let res = grpc.validators(filters).await?.into_inner();
         Ok(res)
     }
 
-    /// Gets a list of bonded validators
-    pub async fn get_active_validators(&self) -> Result<QueryValidatorsResponse, CosmosGrpcError> {
+    /// Gets a list of validators with the given bond status
+    pub async fn get_validators_by_status(
+        &self,
+        status: BondStatus,
+    ) -> Result<QueryValidatorsResponse, CosmosGrpcError> {
         let req = QueryValidatorsRequest {
             pagination: None,
-            status: "BOND_STATUS_BONDED".to_string(),
+            status: status.as_str().to_string(),
         };
         self.get_validators_list(req).await
     }
 
+    /// Gets a list of bonded validators
+    pub async fn get_active_validators(&self) -> Result<QueryValidatorsResponse, CosmosGrpcError> {
+        self.get_validators_by_status(BondStatus::Bonded).await
+    }
+
     /// Delegates tokens to a specified bonded validator
     pub async fn delegate_to_validator(
         &self,
@@ -45,7 +398,7 @@ impl Contact {
         private_key: PrivateKey,
         wait_timeout: Option<Duration>,
     ) -> Result<TxResponse, CosmosGrpcError> {
-        let our_address = private_key.to_address(&self.chain_prefix).unwrap();
+        let our_address = private_key.to_address(&self.chain_prefix)?;
         let vote = MsgDelegate {
             amount: Some(amount_to_delegate.into()),
             delegator_address: our_address.to_string(),
@@ -64,17 +417,276 @@ impl Contact {
         let args = self.get_message_args(our_address, fee).await?;
         trace!("got optional tx info");
 
-        let msg_bytes = private_key.sign_std_msg(&[msg], args, MEMO)?;
+        let msg_bytes =
+            private_key.sign_std_msg_with_sign_mode(&[msg], args, MEMO, self.sign_mode)?;
 
         let response = self
             .send_transaction(msg_bytes, BroadcastMode::Sync)
             .await?;
 
         trace!("broadcasted! with response {:?}", response);
-        if let Some(time) = wait_timeout {
+        if let Some(time) = self.wait_timeout(wait_timeout) {
             self.wait_for_tx(response, time).await
         } else {
             Ok(response)
         }
     }
+
+    /// Cancels some or all of an unbonding delegation, returning the tokens to the validator
+    /// they were being unbonded from. The unbonding entry is identified by the height it was
+    /// created at, see the `unbonding_delegation` staking query. Only supported since Cosmos
+    /// SDK v0.46, chains that don't support the message return `UnsupportedMessage`
+    pub async fn cancel_unbonding_delegation(
+        &self,
+        validator_address: Address,
+        amount: Coin,
+        creation_height: u64,
+        fee: Fee,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        // if the node doesn't report it's messages the chain is left to reject the tx
+        if let Ok(info) = self.get_app_version().await {
+            if info.has_msg(MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL) == Some(false) {
+                return Err(CosmosGrpcError::UnsupportedMessage(
+                    MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL.to_string(),
+                ));
+            }
+        }
+        let our_address = private_key.to_address(&self.chain_prefix)?;
+        let cancel = MsgCancelUnbondingDelegation {
+            delegator_address: our_address.to_string(),
+            validator_address: validator_address.to_string(),
+            amount: Some(amount.into()),
+            creation_height: creation_height as i64,
+        };
+        let msg = Msg::new(MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL, cancel);
+        match self
+            .create_and_send(&[msg], fee, MEMO, private_key, wait_timeout)
+            .await
+        {
+            Err(CosmosGrpcError::TransactionFailed { tx, .. })
+                if tx.raw_log.contains("unable to resolve type URL") =>
+            {
+                Err(CosmosGrpcError::UnsupportedMessage(
+                    MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL.to_string(),
+                ))
+            }
+            res => res,
+        }
+    }
+
+    /// Creates a validator operated by `private_key`, self delegating `self_delegation` from
+    /// it's account. `consensus_pubkey` is the key the validator node signs blocks with, for
+    /// a standard node this is the ed25519 key in `priv_validator_key.json`. The commission
+    /// rates are checked locally and `BadInput` is returned if they are invalid or if the self
+    /// delegation is below `min_self_delegation`
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_validator(
+        &self,
+        description: Description,
+        commission: CommissionRates,
+        min_self_delegation: Uint256,
+        consensus_pubkey: ConsensusPubKey,
+        self_delegation: Coin,
+        fee: Fee,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        commission.validate().map_err(CosmosGrpcError::BadInput)?;
+        if self_delegation.amount < min_self_delegation {
+            return Err(CosmosGrpcError::BadInput(format!(
+                "Self delegation {} is below the min self delegation {}",
+                self_delegation, min_self_delegation
+            )));
+        }
+        let our_address = private_key.to_address(&self.chain_prefix)?;
+        let validator_address = private_key.to_valoper_address(&self.chain_prefix)?;
+        let create = MsgCreateValidator {
+            description: Some(description),
+            commission: Some(commission.into()),
+            min_self_delegation: min_self_delegation.to_string(),
+            delegator_address: our_address.to_string(),
+            validator_address: validator_address.to_string(),
+            pubkey: Some(consensus_pubkey.into()),
+            value: Some(self_delegation.into()),
+        };
+        let msg = Msg::new(MSG_CREATE_VALIDATOR_TYPE_URL, create);
+        self.create_and_send(&[msg], fee, MEMO, private_key, wait_timeout)
+            .await
+    }
+
+    /// Edits the validator operated by `private_key`, fields that are None are left unchanged.
+    /// The new `commission_rate` must be between 0 and 1, the chain additionally rejects rates
+    /// above the validators max rate or changes larger than it's max change rate, and allows
+    /// only one change per day. The min self delegation may only be raised
+    pub async fn edit_validator(
+        &self,
+        description: Option<Description>,
+        commission_rate: Option<Decimal>,
+        min_self_delegation: Option<Uint256>,
+        fee: Fee,
+        private_key: PrivateKey,
+        wait_timeout: Option<Duration>,
+    ) -> Result<TxResponse, CosmosGrpcError> {
+        if let Some(rate) = commission_rate {
+            validate_rate("rate", rate).map_err(CosmosGrpcError::BadInput)?;
+        }
+        let validator_address = private_key.to_valoper_address(&self.chain_prefix)?;
+        let description = description.unwrap_or_else(|| Description {
+            moniker: DO_NOT_MODIFY.to_string(),
+            identity: DO_NOT_MODIFY.to_string(),
+            website: DO_NOT_MODIFY.to_string(),
+            security_contact: DO_NOT_MODIFY.to_string(),
+            details: DO_NOT_MODIFY.to_string(),
+        });
+        // empty strings are decoded as nil, leaving the value unchanged
+        let edit = MsgEditValidator {
+            description: Some(description),
+            validator_address: validator_address.to_string(),
+            commission_rate: commission_rate
+                .map(|rate| rate.to_sdk_dec_string())
+                .unwrap_or_default(),
+            min_self_delegation: min_self_delegation
+                .map(|amount| amount.to_string())
+                .unwrap_or_default(),
+        };
+        let msg = Msg::new(MSG_EDIT_VALIDATOR_TYPE_URL, edit);
+        self.create_and_send(&[msg], fee, MEMO, private_key, wait_timeout)
+            .await
+    }
+}
+
+#[test]
+fn test_commission_rates() {
+    let rate = |s: &str| Decimal::from_str_lenient(s).unwrap();
+    let commission = CommissionRates {
+        rate: rate("0.1"),
+        max_rate: rate("0.2"),
+        max_change_rate: rate("0.01"),
+    };
+    assert!(commission.validate().is_ok());
+    let proto = ProtoCommissionRates::from(commission);
+    assert_eq!(proto.rate, "100000000000000000");
+    assert_eq!(proto.max_change_rate, "10000000000000000");
+
+    let invalid = [
+        CommissionRates {
+            rate: rate("0.3"),
+            ..commission
+        },
+        CommissionRates {
+            max_change_rate: rate("0.25"),
+            ..commission
+        },
+        CommissionRates {
+            max_rate: rate("1.5"),
+            ..commission
+        },
+        CommissionRates {
+            rate: rate("-0.1"),
+            ..commission
+        },
+    ];
+    for commission in invalid.iter() {
+        assert!(commission.validate().is_err(), "{:?}", commission);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::mock_contact;
+    use cosmos_sdk_proto::cosmos::staking::v1beta1::Delegation;
+
+    #[test]
+    fn test_validator_delegation_conversion() {
+        let response = DelegationResponse {
+            delegation: Some(Delegation {
+                delegator_address: "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp".to_string(),
+                validator_address: "cosmosvaloper1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96a9l7ld"
+                    .to_string(),
+                // a whale delegation with more digits than a Decimal holds exactly
+                shares: "1500000000000000000000000000001234567".to_string(),
+            }),
+            balance: Some(ProtoCoin {
+                denom: "uatom".to_string(),
+                amount: "1500000000000000000".to_string(),
+            }),
+        };
+        let delegation = ValidatorDelegation::try_from(response.clone()).unwrap();
+        assert_eq!(
+            delegation.shares.to_sdk_dec_string(),
+            format!("15{}", "0".repeat(35))
+        );
+        assert_eq!(
+            delegation.balance.amount,
+            1_500_000_000_000_000_000u64.into()
+        );
+
+        let missing = DelegationResponse {
+            balance: None,
+            ..response
+        };
+        assert!(ValidatorDelegation::try_from(missing).is_err());
+    }
+
+    #[test]
+    fn test_staking_pool() {
+        let pool = StakingPool::try_from(Pool {
+            not_bonded_tokens: "250000000000000000000000000".to_string(),
+            bonded_tokens: "750000000000000000000000000".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            pool.bonded_tokens,
+            "750000000000000000000000000".parse().unwrap()
+        );
+        assert_eq!(
+            pool.bonded_ratio().unwrap(),
+            Decimal::from_str_lenient("0.75").unwrap()
+        );
+        let empty = StakingPool {
+            bonded_tokens: 0u8.into(),
+            not_bonded_tokens: 0u8.into(),
+        };
+        assert_eq!(empty.bonded_ratio(), None);
+        assert!(StakingPool::try_from(Pool {
+            not_bonded_tokens: "1.5".to_string(),
+            bonded_tokens: "1".to_string(),
+        })
+        .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_mock_cancel_unbonding_unsupported() {
+        let (contact, mock, key) = mock_contact();
+        let validator = Address::from_bytes([1; 20], "cosmosvaloper").unwrap();
+        mock.push_broadcast_result(Ok(TxResponse {
+            code: 2,
+            codespace: "sdk".to_string(),
+            raw_log: "unable to resolve type URL /cosmos.staking.v1beta1.MsgCancelUnbondingDelegation: tx parse error".to_string(),
+            ..Default::default()
+        }));
+        let fee = Fee {
+            amount: Vec::new(),
+            gas_limit: 200_000,
+            payer: None,
+            granter: None,
+        };
+        let res = contact
+            .cancel_unbonding_delegation(
+                validator,
+                Coin {
+                    denom: "stake".into(),
+                    amount: 1u64.into(),
+                },
+                8,
+                fee,
+                key,
+                None,
+            )
+            .await;
+        assert!(matches!(res, Err(CosmosGrpcError::UnsupportedMessage(_))));
+    }
 }
@@ -0,0 +1,151 @@
+//! Contains utility functions for paying fees on Osmosis in denoms other than the base fee
+//! denom. The `x/txfees` module accepts any of it's registered fee tokens and converts them
+//! into the base denom at the spot price of a liquidity pool, the minimum gas price of the
+//! node is denominated in the base denom. The module is not part of the Cosmos SDK so it's
+//! types are defined here.
+
+use crate::client::rpc::unary_call;
+use crate::coin::Coin;
+use crate::decimal::Decimal;
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use num_traits::ToPrimitive;
+use prost::Message;
+use tonic::Code as TonicCode;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryBaseDenomRequest {}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryBaseDenomResponse {
+    #[prost(string, tag = "1")]
+    pub base_denom: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryDenomSpotPriceRequest {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryDenomSpotPriceResponse {
+    #[prost(uint64, tag = "1")]
+    pub pool_id: u64,
+    /// The price of one unit of the fee token in the base denom, an `sdk.Dec`
+    #[prost(string, tag = "2")]
+    pub spot_price: String,
+}
+
+impl Contact {
+    /// Gets the denom the minimum gas price of Osmosis nodes is in, returns
+    /// `ModuleNotPresent` on chains without the txfees module
+    pub async fn get_txfees_base_denom(&self) -> Result<String, CosmosGrpcError> {
+        let res: Result<QueryBaseDenomResponse, CosmosGrpcError> = unary_call(
+            self.channel().await?,
+            "/osmosis.txfees.v1beta1.Query/BaseDenom",
+            QueryBaseDenomRequest {},
+        )
+        .await;
+        match res {
+            Ok(res) => Ok(res.base_denom),
+            Err(CosmosGrpcError::RequestError { error })
+                if error.code() == TonicCode::Unimplemented =>
+            {
+                Err(CosmosGrpcError::ModuleNotPresent(
+                    "osmosis.txfees".to_string(),
+                ))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the spot price the txfees module converts `denom` into the base denom at, this is
+    /// the amount of the base denom one unit of `denom` pays for
+    pub async fn get_txfees_spot_price(&self, denom: &str) -> Result<Decimal, CosmosGrpcError> {
+        let res: QueryDenomSpotPriceResponse = unary_call(
+            self.channel().await?,
+            "/osmosis.txfees.v1beta1.Query/DenomSpotPrice",
+            QueryDenomSpotPriceRequest {
+                denom: denom.to_string(),
+            },
+        )
+        .await?;
+        match Decimal::from_sdk_dec_str(&res.spot_price) {
+            Ok(price) => Ok(price),
+            Err(e) => Err(CosmosGrpcError::BadResponse(format!(
+                "Failed to parse spot price {} {}",
+                res.spot_price, e
+            ))),
+        }
+    }
+
+    /// Computes the minimum fee in `denom` for a transaction with `gas_limit` on a chain with
+    /// the txfees module. The minimum fee in the base denom, see `min_fee_for_gas`, is converted
+    /// at the current spot price and rounded up. The spot price moves with every swap so add a
+    /// margin for fees of transactions that may wait before inclusion. On chains without the
+    /// txfees module this is the same as `min_fee_for_gas` in `denom`.
+    pub async fn txfees_min_fee_for_gas(
+        &self,
+        gas_limit: u64,
+        denom: &str,
+    ) -> Result<Vec<Coin>, CosmosGrpcError> {
+        let base_denom = match self.get_txfees_base_denom().await {
+            Ok(base_denom) => base_denom,
+            Err(CosmosGrpcError::ModuleNotPresent(_)) => {
+                return self.min_fee_for_gas(gas_limit, Some(denom)).await
+            }
+            Err(e) => return Err(e),
+        };
+        if base_denom == denom {
+            return self.min_fee_for_gas(gas_limit, Some(denom)).await;
+        }
+        let base_fee = match self
+            .min_fee_for_gas(gas_limit, Some(&base_denom))
+            .await?
+            .pop()
+        {
+            Some(fee) => fee,
+            None => return Ok(Vec::new()),
+        };
+        let price = self.get_txfees_spot_price(denom).await?;
+        match convert_fee(&base_fee, price, denom) {
+            Some(fee) => Ok(vec![fee]),
+            None => Err(CosmosGrpcError::BadInput(format!(
+                "Can not convert {} to {} at {}",
+                base_fee, denom, price
+            ))),
+        }
+    }
+}
+
+/// Converts a fee in the base denom into `denom`, where `price` is the amount of the base
+/// denom one unit of `denom` is worth. Rounds up so the converted fee is never short
+fn convert_fee(base_fee: &Coin, price: Decimal, denom: &str) -> Option<Coin> {
+    let amount = price.checked_div_ceil(base_fee.amount.to_u128()?)?;
+    Some(Coin {
+        amount: amount.into(),
+        denom: denom.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_fee() {
+        let base_fee = Coin {
+            amount: 1_000u64.into(),
+            denom: "uosmo".into(),
+        };
+        // one uatom is worth 8.5 uosmo, spot prices are sdk.Dec encoded
+        let price = Decimal::from_sdk_dec_str("8500000000000000000").unwrap();
+        let fee = convert_fee(&base_fee, price, "uatom").unwrap();
+        // 1000 / 8.5 = 117.6, rounded up
+        assert_eq!(fee.amount, 118u64.into());
+        assert_eq!(fee.denom, "uatom");
+
+        assert!(convert_fee(&base_fee, Decimal::from(0u8), "uatom").is_none());
+    }
+}
@@ -1,6 +1,45 @@
 use crate::address::Address;
+use crate::client::authz::{decode_exec_results, ExecResult, MsgExec, MSG_EXEC_TYPE_URL};
+use crate::client::{DEFAULT_CONFIRM_TIMEOUT, MEMO};
+use crate::coin::Coin;
+use crate::coin::Fee;
+use crate::coin::GasPrices;
+use crate::error::AddressError;
+use crate::error::CosmosGrpcError;
+use crate::memo::Memo;
+use crate::msg::{decode_tx_messages, DecodedMsg};
+use crate::public_key::PublicKey;
+use crate::utils::decode_any;
+use crate::utils::encode_any;
+use crate::utils::hex_str_to_bytes;
+use crate::utils::tx_hash;
+use async_trait::async_trait;
 use cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount as ProtoBaseAccount;
+use cosmos_sdk_proto::cosmos::auth::v1beta1::ModuleAccount;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{AbciMessageLog, GasInfo, MsgData, TxResponse};
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::Module;
+use cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::Validator as TendermintValidator;
+use cosmos_sdk_proto::cosmos::crypto::ed25519::PubKey as Ed25519PubKey;
+use cosmos_sdk_proto::cosmos::crypto::multisig::LegacyAminoPubKey;
+use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as Secp256k1PubKey;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::ProposalStatus as ProtoProposalStatus;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::VoteOption as ProtoVoteOption;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::BondStatus as ProtoBondStatus;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    AuthInfo, BroadcastMode, GetTxResponse, SimulateResponse, Tx, TxBody, TxRaw,
+};
+use cosmos_sdk_proto::cosmos::vesting::v1beta1::DelayedVestingAccount;
+use cosmos_sdk_proto::ibc::core::channel::v1::Order as ProtoChannelOrder;
+use cosmos_sdk_proto::ibc::core::channel::v1::State as ProtoChannelState;
+use cosmos_sdk_proto::ibc::core::connection::v1::State as ProtoConnectionState;
+use prost::Message;
+use prost_types::Any;
 use serde::Deserialize;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tendermint_proto::abci::Event as AbciEvent;
 use tendermint_proto::types::Block;
 
 /// This struct represents the status of a Cosmos chain, instead of just getting the
@@ -36,6 +75,1163 @@ pub enum LatestBlock {
     WaitingToStart,
 }
 
+/// Progress updates provided by `Contact::wait_for_tx_with_progress` while waiting
+/// for a transaction to enter the chain
+pub enum TxWaitProgress<'a> {
+    /// The transaction was not yet found after `attempt` queries
+    Polling { attempt: u32, elapsed: Duration },
+    /// The transaction was found in a block, it still may have failed
+    /// during execution
+    Included {
+        attempt: u32,
+        elapsed: Duration,
+        response: &'a TxResponse,
+    },
+}
+
+/// A vote on a governance proposal, unlike the protobuf enum this does not include the
+/// unspecified value, conversion from the protobuf i32 value returns the value as an
+/// error if it's not a valid vote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VoteOption {
+    Yes,
+    Abstain,
+    No,
+    NoWithVeto,
+}
+
+impl From<VoteOption> for ProtoVoteOption {
+    fn from(value: VoteOption) -> Self {
+        match value {
+            VoteOption::Yes => ProtoVoteOption::Yes,
+            VoteOption::Abstain => ProtoVoteOption::Abstain,
+            VoteOption::No => ProtoVoteOption::No,
+            VoteOption::NoWithVeto => ProtoVoteOption::NoWithVeto,
+        }
+    }
+}
+
+impl From<VoteOption> for i32 {
+    fn from(value: VoteOption) -> Self {
+        ProtoVoteOption::from(value).into()
+    }
+}
+
+impl TryFrom<i32> for VoteOption {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match ProtoVoteOption::from_i32(value) {
+            Some(ProtoVoteOption::Yes) => Ok(VoteOption::Yes),
+            Some(ProtoVoteOption::Abstain) => Ok(VoteOption::Abstain),
+            Some(ProtoVoteOption::No) => Ok(VoteOption::No),
+            Some(ProtoVoteOption::NoWithVeto) => Ok(VoteOption::NoWithVeto),
+            Some(ProtoVoteOption::Unspecified) | None => Err(value),
+        }
+    }
+}
+
+impl fmt::Display for VoteOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VoteOption::Yes => "yes",
+            VoteOption::Abstain => "abstain",
+            VoteOption::No => "no",
+            VoteOption::NoWithVeto => "no_with_veto",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for VoteOption {
+    type Err = String;
+
+    /// Parses the names used by the Cosmos SDK cli, `yes`, `abstain`, `no` and `no_with_veto`
+    /// as well as the protobuf names such as `VOTE_OPTION_YES`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        match lower.trim_start_matches("vote_option_") {
+            "yes" => Ok(VoteOption::Yes),
+            "abstain" => Ok(VoteOption::Abstain),
+            "no" => Ok(VoteOption::No),
+            "no_with_veto" | "nowithveto" => Ok(VoteOption::NoWithVeto),
+            _ => Err(format!("Invalid vote option {}", s)),
+        }
+    }
+}
+
+/// The status of a governance proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProposalStatus {
+    /// The proposal is waiting for it's minimum deposit
+    DepositPeriod,
+    /// The proposal has met it's deposit and is being voted on
+    VotingPeriod,
+    Passed,
+    Rejected,
+    /// The proposal passed but could not be executed
+    Failed,
+}
+
+impl From<ProposalStatus> for ProtoProposalStatus {
+    fn from(value: ProposalStatus) -> Self {
+        match value {
+            ProposalStatus::DepositPeriod => ProtoProposalStatus::DepositPeriod,
+            ProposalStatus::VotingPeriod => ProtoProposalStatus::VotingPeriod,
+            ProposalStatus::Passed => ProtoProposalStatus::Passed,
+            ProposalStatus::Rejected => ProtoProposalStatus::Rejected,
+            ProposalStatus::Failed => ProtoProposalStatus::Failed,
+        }
+    }
+}
+
+impl From<ProposalStatus> for i32 {
+    fn from(value: ProposalStatus) -> Self {
+        ProtoProposalStatus::from(value).into()
+    }
+}
+
+impl TryFrom<i32> for ProposalStatus {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match ProtoProposalStatus::from_i32(value) {
+            Some(ProtoProposalStatus::DepositPeriod) => Ok(ProposalStatus::DepositPeriod),
+            Some(ProtoProposalStatus::VotingPeriod) => Ok(ProposalStatus::VotingPeriod),
+            Some(ProtoProposalStatus::Passed) => Ok(ProposalStatus::Passed),
+            Some(ProtoProposalStatus::Rejected) => Ok(ProposalStatus::Rejected),
+            Some(ProtoProposalStatus::Failed) => Ok(ProposalStatus::Failed),
+            Some(ProtoProposalStatus::Unspecified) | None => Err(value),
+        }
+    }
+}
+
+/// The bond status of a validator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondStatus {
+    /// The validator is not in the active set and is not unbonding
+    Unbonded,
+    /// The validator has left the active set and it's delegations are unbonding
+    Unbonding,
+    /// The validator is in the active set
+    Bonded,
+}
+
+impl BondStatus {
+    /// The name of this status as used by the validators query, for example `BOND_STATUS_BONDED`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BondStatus::Unbonded => "BOND_STATUS_UNBONDED",
+            BondStatus::Unbonding => "BOND_STATUS_UNBONDING",
+            BondStatus::Bonded => "BOND_STATUS_BONDED",
+        }
+    }
+}
+
+impl From<BondStatus> for ProtoBondStatus {
+    fn from(value: BondStatus) -> Self {
+        match value {
+            BondStatus::Unbonded => ProtoBondStatus::Unbonded,
+            BondStatus::Unbonding => ProtoBondStatus::Unbonding,
+            BondStatus::Bonded => ProtoBondStatus::Bonded,
+        }
+    }
+}
+
+impl From<BondStatus> for i32 {
+    fn from(value: BondStatus) -> Self {
+        ProtoBondStatus::from(value).into()
+    }
+}
+
+impl TryFrom<i32> for BondStatus {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match ProtoBondStatus::from_i32(value) {
+            Some(ProtoBondStatus::Unbonded) => Ok(BondStatus::Unbonded),
+            Some(ProtoBondStatus::Unbonding) => Ok(BondStatus::Unbonding),
+            Some(ProtoBondStatus::Bonded) => Ok(BondStatus::Bonded),
+            Some(ProtoBondStatus::Unspecified) | None => Err(value),
+        }
+    }
+}
+
+impl fmt::Display for BondStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The state of an IBC channel end, channels that are not yet initialized
+/// can not be queried so the unspecified value is not included. States added by
+/// newer versions of ibc-go, such as the FLUSHING (5) and FLUSHCOMPLETE (6) states
+/// of channel upgrades, are returned as `Unknown`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelState {
+    /// The opening handshake has started on this chain
+    Init,
+    /// The handshake step has been acknowledged on the counterparty chain
+    TryOpen,
+    /// The handshake is complete and packets can be sent and received
+    Open,
+    /// The channel is closed and can no longer be used
+    Closed,
+    /// A state this crate does not know about
+    Unknown(i32),
+}
+
+impl TryFrom<i32> for ChannelState {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match ProtoChannelState::from_i32(value) {
+            Some(ProtoChannelState::Init) => Ok(ChannelState::Init),
+            Some(ProtoChannelState::Tryopen) => Ok(ChannelState::TryOpen),
+            Some(ProtoChannelState::Open) => Ok(ChannelState::Open),
+            Some(ProtoChannelState::Closed) => Ok(ChannelState::Closed),
+            Some(ProtoChannelState::UninitializedUnspecified) => Err(value),
+            None => Ok(ChannelState::Unknown(value)),
+        }
+    }
+}
+
+impl fmt::Display for ChannelState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ChannelState::Init => "STATE_INIT",
+            ChannelState::TryOpen => "STATE_TRYOPEN",
+            ChannelState::Open => "STATE_OPEN",
+            ChannelState::Closed => "STATE_CLOSED",
+            ChannelState::Unknown(state) => return write!(f, "STATE_UNKNOWN({})", state),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The ordering of packets on an IBC channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelOrder {
+    /// Packets may be delivered in any order
+    Unordered,
+    /// Packets are delivered in the order they were sent
+    Ordered,
+}
+
+impl TryFrom<i32> for ChannelOrder {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match ProtoChannelOrder::from_i32(value) {
+            Some(ProtoChannelOrder::Unordered) => Ok(ChannelOrder::Unordered),
+            Some(ProtoChannelOrder::Ordered) => Ok(ChannelOrder::Ordered),
+            Some(ProtoChannelOrder::NoneUnspecified) | None => Err(value),
+        }
+    }
+}
+
+/// The state of an IBC connection end, unlike channels connections can not be closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// The opening handshake has started on this chain
+    Init,
+    /// The handshake step has been acknowledged on the counterparty chain
+    TryOpen,
+    /// The handshake is complete and channels can be opened on this connection
+    Open,
+}
+
+impl TryFrom<i32> for ConnectionState {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match ProtoConnectionState::from_i32(value) {
+            Some(ProtoConnectionState::Init) => Ok(ConnectionState::Init),
+            Some(ProtoConnectionState::Tryopen) => Ok(ConnectionState::TryOpen),
+            Some(ProtoConnectionState::Open) => Ok(ConnectionState::Open),
+            Some(ProtoConnectionState::UninitializedUnspecified) | None => Err(value),
+        }
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConnectionState::Init => "STATE_INIT",
+            ConnectionState::TryOpen => "STATE_TRYOPEN",
+            ConnectionState::Open => "STATE_OPEN",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A block along with every transaction in it paired with it's execution result,
+/// see `Contact::get_block_with_results`
+#[derive(Debug, Clone)]
+pub struct BlockWithResults {
+    pub block: Block,
+    /// The transactions of the block in the order they were executed
+    pub txs: Vec<BlockTx>,
+}
+
+/// A transaction included in a block
+#[derive(Debug, Clone)]
+pub struct BlockTx {
+    /// The hash of the transaction, computed from the raw bytes in the block
+    pub hash: String,
+    /// The transaction bytes exactly as included in the block
+    pub raw: Vec<u8>,
+    /// The decoded transaction, None if the bytes are not a valid transaction. Proposers
+    /// may include such bytes in a block, they fail without being executed
+    pub tx: Option<Tx>,
+    /// The execution result containing the code, gas used and events, None if the node
+    /// did not index a result for this transaction
+    pub result: Option<TxResponse>,
+}
+
+/// The consensus key a validator signs blocks with, decoded from it's Any encoding
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsensusPubKey {
+    /// The raw 32 byte key, used by almost every chain
+    Ed25519(Vec<u8>),
+    /// The 33 byte compressed key
+    Secp256k1(Vec<u8>),
+    Other(Any),
+}
+
+impl From<Any> for ConsensusPubKey {
+    fn from(value: Any) -> Self {
+        match value.type_url.as_str() {
+            "/cosmos.crypto.ed25519.PubKey" => match decode_any::<Ed25519PubKey>(&value) {
+                Ok(key) => ConsensusPubKey::Ed25519(key.key),
+                Err(_) => ConsensusPubKey::Other(value),
+            },
+            "/cosmos.crypto.secp256k1.PubKey" => match decode_any::<Secp256k1PubKey>(&value) {
+                Ok(key) => ConsensusPubKey::Secp256k1(key.key),
+                Err(_) => ConsensusPubKey::Other(value),
+            },
+            _ => ConsensusPubKey::Other(value),
+        }
+    }
+}
+
+/// Encodes the key the way it's provided in the `pubkey` of a `MsgCreateValidator`
+impl From<ConsensusPubKey> for Any {
+    fn from(value: ConsensusPubKey) -> Self {
+        match value {
+            ConsensusPubKey::Ed25519(key) => {
+                encode_any(Ed25519PubKey { key }, "/cosmos.crypto.ed25519.PubKey")
+            }
+            ConsensusPubKey::Secp256k1(key) => {
+                encode_any(Secp256k1PubKey { key }, "/cosmos.crypto.secp256k1.PubKey")
+            }
+            ConsensusPubKey::Other(any) => any,
+        }
+    }
+}
+
+/// The public key an account signs transactions with, decoded from the `pub_key` of the
+/// account, see `Contact::get_account_pubkey`. Keys that don't decode are left as `Other`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountPubKey {
+    /// A Cosmos secp256k1 key, addresses are derived with `AddressHash::Cosmos`
+    Secp256k1(PublicKey),
+    /// An Ethermint `eth_secp256k1` key, addresses are derived with `AddressHash::Ethermint`
+    EthSecp256k1(PublicKey),
+    /// The raw 32 byte key
+    Ed25519(Vec<u8>),
+    /// A legacy amino multisig, `threshold` of `keys` have to sign
+    Multisig {
+        threshold: u32,
+        keys: Vec<AccountPubKey>,
+    },
+    Other(Any),
+}
+
+impl AccountPubKey {
+    /// Decodes a key, `prefix` is the bech32 prefix of the public key, for example `cosmospub`
+    pub fn from_any(value: Any, prefix: &str) -> AccountPubKey {
+        let secp256k1 = |value: &Any| {
+            decode_any::<Secp256k1PubKey>(value)
+                .ok()
+                .and_then(|key| PublicKey::from_slice(&key.key, prefix).ok())
+        };
+        let decoded = match value.type_url.as_str() {
+            "/cosmos.crypto.secp256k1.PubKey" => secp256k1(&value).map(AccountPubKey::Secp256k1),
+            // each Ethermint based chain registers the key under it's own package
+            url if url.ends_with(".ethsecp256k1.PubKey") => {
+                secp256k1(&value).map(AccountPubKey::EthSecp256k1)
+            }
+            "/cosmos.crypto.ed25519.PubKey" => decode_any::<Ed25519PubKey>(&value)
+                .ok()
+                .map(|key| AccountPubKey::Ed25519(key.key)),
+            "/cosmos.crypto.multisig.LegacyAminoPubKey" => decode_any::<LegacyAminoPubKey>(&value)
+                .ok()
+                .map(|key| AccountPubKey::Multisig {
+                    threshold: key.threshold,
+                    keys: key
+                        .public_keys
+                        .into_iter()
+                        .map(|key| AccountPubKey::from_any(key, prefix))
+                        .collect(),
+                }),
+            _ => None,
+        };
+        decoded.unwrap_or(AccountPubKey::Other(value))
+    }
+
+    /// The name of the signing algorithm as used by the Cosmos SDK keyring, the type url
+    /// for other keys
+    pub fn algorithm(&self) -> &str {
+        match self {
+            AccountPubKey::Secp256k1(_) => "secp256k1",
+            AccountPubKey::EthSecp256k1(_) => "eth_secp256k1",
+            AccountPubKey::Ed25519(_) => "ed25519",
+            AccountPubKey::Multisig { .. } => "multi",
+            AccountPubKey::Other(any) => &any.type_url,
+        }
+    }
+}
+
+/// A member of the consensus validator set at some height, see `Contact::get_validator_set`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsensusValidator {
+    /// The consensus address, `<prefix>valcons1...`
+    pub address: Address,
+    pub pub_key: ConsensusPubKey,
+    pub voting_power: u64,
+    pub proposer_priority: i64,
+}
+
+impl TryFrom<TendermintValidator> for ConsensusValidator {
+    type Error = AddressError;
+
+    fn try_from(value: TendermintValidator) -> Result<Self, Self::Error> {
+        let pub_key = match value.pub_key {
+            Some(key) => ConsensusPubKey::from(key),
+            None => ConsensusPubKey::Other(Any::default()),
+        };
+        Ok(ConsensusValidator {
+            address: value.address.parse()?,
+            pub_key,
+            voting_power: value.voting_power as u64,
+            proposer_priority: value.proposer_priority,
+        })
+    }
+}
+
+/// The consensus validator set at a block height
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorSet {
+    pub block_height: u64,
+    pub validators: Vec<ConsensusValidator>,
+}
+
+/// Totals for a single block, see `Contact::get_block_stats`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockStats {
+    pub height: u64,
+    pub tx_count: usize,
+    /// The gas used by every transaction in the block that has an indexed result
+    pub gas_used: u64,
+    /// The fees of every transaction in the block summed by denom, failed transactions
+    /// still pay their fee
+    pub fees: Vec<Coin>,
+    /// The consensus address of the validator that proposed the block
+    pub proposer: Address,
+}
+
+impl BlockStats {
+    pub(crate) fn new(block: &BlockWithResults, valcons_prefix: &str) -> Result<Self, String> {
+        let header = block.block.header.as_ref().ok_or("Block has no header")?;
+        let proposer = Address::from_slice(&header.proposer_address, valcons_prefix)
+            .map_err(|e| format!("Invalid proposer address {}", e))?;
+        let mut gas_used = 0;
+        let mut fees: Vec<Coin> = Vec::new();
+        for tx in block.txs.iter() {
+            if let Some(result) = &tx.result {
+                gas_used += result.gas_used as u64;
+            }
+            let fee = tx
+                .tx
+                .as_ref()
+                .and_then(|tx| tx.auth_info.as_ref()?.fee.as_ref());
+            for coin in fee.map(|f| f.amount.as_slice()).unwrap_or_default() {
+                let coin = Coin::try_from(coin).map_err(|e| format!("Invalid fee {}", e))?;
+                match fees.iter_mut().find(|c| c.denom == coin.denom) {
+                    Some(total) => total.amount = total.amount.clone() + coin.amount,
+                    None => fees.push(coin),
+                }
+            }
+        }
+        Ok(BlockStats {
+            height: header.height as u64,
+            tx_count: block.txs.len(),
+            gas_used,
+            fees,
+            proposer,
+        })
+    }
+}
+
+/// Information about the node and the application it runs, see `Contact::get_app_version`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// The chain id of the network the node is on
+    pub network: String,
+    pub moniker: String,
+    /// The Tendermint version of the node
+    pub tendermint_version: String,
+    /// The name of the application binary, for example `gaiad`
+    pub app_name: String,
+    /// The version of the application binary
+    pub app_version: String,
+    /// The version of the Cosmos SDK the application was built with, for example `v0.45.1`,
+    /// None if the node does not report it's build dependencies
+    pub cosmos_sdk_version: Option<String>,
+    /// The type url of every message the application accepts, queried through the reflection
+    /// service. None if the node has reflection disabled
+    pub msg_type_urls: Option<Vec<String>>,
+}
+
+impl NodeInfo {
+    /// Returns if the application accepts messages from the protobuf package, for example
+    /// `cosmwasm.wasm.v1` or `cosmos.authz.v1beta1`, omit the version to match any version.
+    /// None if this could not be determined because the node has reflection disabled
+    pub fn has_module(&self, package: &str) -> Option<bool> {
+        let prefix = format!("/{}.", package.trim_start_matches('/'));
+        self.msg_type_urls
+            .as_ref()
+            .map(|urls| urls.iter().any(|url| url.starts_with(&prefix)))
+    }
+
+    /// Returns if the application accepts messages with this type url, for example
+    /// `/cosmos.bank.v1beta1.MsgSend`, None if the node has reflection disabled
+    pub fn has_msg(&self, type_url: &str) -> Option<bool> {
+        self.msg_type_urls
+            .as_ref()
+            .map(|urls| urls.iter().any(|url| url == type_url))
+    }
+}
+
+/// Everything fetched about a chain by `Contact::bootstrap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainInfo {
+    pub chain_id: String,
+    /// The node and application versions, including the Cosmos SDK version and the
+    /// messages the application accepts
+    pub node: NodeInfo,
+    /// None on chains without a staking module
+    pub bond_denom: Option<String>,
+    /// The `minimum-gas-prices` of the node, None if the node does not serve them
+    pub min_gas_prices: Option<GasPrices>,
+    /// The longest memo the chain accepts, in bytes
+    pub max_memo_characters: u64,
+}
+
+/// Finds the version of the Cosmos SDK in the build dependencies of an application, if the
+/// dependency is replaced the version of the replacement is used
+pub(crate) fn cosmos_sdk_version(deps: &[Module]) -> Option<String> {
+    let dep = deps
+        .iter()
+        .find(|dep| dep.path == "github.com/cosmos/cosmos-sdk")?;
+    // replaced dependencies are reported as `<version> => <replacement path> <version>`
+    dep.version
+        .rsplit(' ')
+        .next()
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+/// An account number and sequence used in place of the values on chain when signing,
+/// see `Contact::get_message_args_with_override`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountOverride {
+    pub account_number: u64,
+    pub sequence: u64,
+}
+
+/// A signed transaction that was not broadcast, see `Contact::dry_run_create_and_send`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunResult {
+    pub messages: Vec<DecodedMsg>,
+    pub memo: String,
+    pub fee: Fee,
+    /// The gas limit of the transaction
+    pub gas: u64,
+    /// The hash the transaction would have on chain
+    pub tx_hash: String,
+}
+
+impl DryRunResult {
+    /// Decodes a signed and serialized transaction (TxRaw bytes), returns None if the bytes
+    /// are not a valid transaction
+    pub(crate) fn from_tx_bytes(tx_bytes: &[u8]) -> Option<DryRunResult> {
+        let raw = TxRaw::decode(tx_bytes).ok()?;
+        let body = TxBody::decode(raw.body_bytes.as_slice()).ok()?;
+        let auth_info = AuthInfo::decode(raw.auth_info_bytes.as_slice()).ok()?;
+        let fee = Fee::try_from(auth_info.fee?).ok()?;
+        let messages = body.messages.iter().map(DecodedMsg::decode).collect();
+        Some(DryRunResult {
+            messages,
+            memo: body.memo,
+            gas: fee.gas_limit,
+            fee,
+            tx_hash: tx_hash(tx_bytes),
+        })
+    }
+}
+
+/// Asked to approve every transaction before it's broadcast, see `Contact::set_confirm_callback`.
+/// Interactive wallets can show the decoded transaction, in the same form a dry run returns
+/// it, and wait for the user to accept or reject it
+#[async_trait]
+pub trait ConfirmTx: Send + Sync {
+    /// Returns true to broadcast the transaction, false to abort with `UserRejected`
+    async fn confirm(&self, tx: &DryRunResult) -> bool;
+}
+
+/// An event emitted while executing a message of a transaction, see `TxSummary`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxEvent {
+    /// The index of the message in the transaction that emitted this event
+    pub msg_index: u32,
+    /// The event type, for example `transfer` or `delegate`
+    pub kind: String,
+    /// The attributes of the event as key value pairs in the order they were emitted,
+    /// keys may repeat when a message emits the same event type more than once
+    pub attributes: Vec<(String, String)>,
+}
+
+impl TxEvent {
+    /// Gets the value of the first attribute with this key
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn events_from_logs(logs: Vec<AbciMessageLog>) -> Vec<TxEvent> {
+    let mut events = Vec::new();
+    for log in logs {
+        for event in log.events {
+            events.push(TxEvent {
+                msg_index: log.msg_index,
+                kind: event.r#type,
+                attributes: event
+                    .attributes
+                    .into_iter()
+                    .map(|a| (a.key, a.value))
+                    .collect(),
+            });
+        }
+    }
+    events
+}
+
+/// The events of a `TxResponse` in both of the formats used by the Cosmos SDK. Up to v0.47
+/// the events of each message are grouped by message in `logs`, v0.50 always leaves `logs`
+/// empty and only fills the flat `events` list where each message event has a `msg_index`
+/// attribute. The cosmos-sdk-proto version used by this crate predates `events` so just these
+/// two fields are decoded from the encoded `TxResponse`, see `Contact::get_tx_events`
+#[derive(Clone, PartialEq, Message)]
+pub struct TxResponseEvents {
+    #[prost(message, repeated, tag = "7")]
+    pub logs: Vec<AbciMessageLog>,
+    #[prost(message, repeated, tag = "13")]
+    pub events: Vec<AbciEvent>,
+}
+
+impl TxResponseEvents {
+    /// The events emitted by the messages of the transaction in the same form for every SDK
+    /// version, `logs` is used if it has any entries and `events` otherwise. Events emitted by
+    /// the transaction itself rather than a message, such as fee payment, are left out as they
+    /// are not part of `logs`
+    pub fn tx_events(&self) -> Vec<TxEvent> {
+        if !self.logs.is_empty() {
+            return events_from_logs(self.logs.clone());
+        }
+        let mut events = Vec::new();
+        for event in self.events.iter() {
+            let attributes = event_attributes(event);
+            if let Some(msg_index) = event_msg_index(&attributes) {
+                events.push(TxEvent {
+                    msg_index,
+                    kind: event.r#type.clone(),
+                    attributes: attributes
+                        .into_iter()
+                        .filter(|(k, _)| k != "msg_index")
+                        .collect(),
+                });
+            }
+        }
+        events
+    }
+}
+
+/// The attributes of an abci event as strings, they are bytes up to Tendermint v0.34
+fn event_attributes(event: &AbciEvent) -> Vec<(String, String)> {
+    event
+        .attributes
+        .iter()
+        .map(|a| {
+            (
+                String::from_utf8_lossy(&a.key).into_owned(),
+                String::from_utf8_lossy(&a.value).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// The `msg_index` attribute added to message events from Cosmos SDK v0.50
+fn event_msg_index(attributes: &[(String, String)]) -> Option<u32> {
+    attributes
+        .iter()
+        .find(|(k, _)| k == "msg_index")
+        .and_then(|(_, v)| v.parse().ok())
+}
+
+/// Everything about an executed transaction in decoded form, see `Contact::get_tx_summary`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxSummary {
+    pub hash: String,
+    pub height: u64,
+    /// The time of the block the transaction is in, as reported by the node
+    pub timestamp: String,
+    pub success: bool,
+    pub code: u32,
+    pub codespace: String,
+    /// The reason the transaction failed, None if it succeeded
+    pub error: Option<String>,
+    pub messages: Vec<DecodedMsg>,
+    pub memo: String,
+    /// None if the node did not return the transaction itself
+    pub fee: Option<Fee>,
+    pub gas_wanted: u64,
+    pub gas_used: u64,
+    /// The events of every message, empty for failed transactions as their
+    /// state changes are reverted. Always empty on Cosmos SDK v0.50 and later chains,
+    /// use `Contact::get_tx_events` for those
+    pub events: Vec<TxEvent>,
+}
+
+impl TryFrom<GetTxResponse> for TxSummary {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: GetTxResponse) -> Result<Self, Self::Error> {
+        let response = match value.tx_response {
+            Some(response) => response,
+            None => {
+                return Err(CosmosGrpcError::BadResponse(
+                    "No tx response returned".to_string(),
+                ))
+            }
+        };
+        let (messages, memo, fee) = match value.tx {
+            Some(tx) => {
+                let messages = decode_tx_messages(&tx);
+                let memo = tx.body.map(|b| b.memo).unwrap_or_default();
+                let fee = match tx.auth_info.and_then(|a| a.fee) {
+                    Some(fee) => Some(Fee::try_from(fee)?),
+                    None => None,
+                };
+                (messages, memo, fee)
+            }
+            None => (Vec::new(), String::new(), None),
+        };
+        let events = events_from_logs(response.logs);
+        let success = response.code == 0;
+        Ok(TxSummary {
+            hash: response.txhash,
+            height: response.height as u64,
+            timestamp: response.timestamp,
+            success,
+            code: response.code,
+            codespace: response.codespace,
+            error: if success {
+                None
+            } else {
+                Some(response.raw_log)
+            },
+            messages,
+            memo,
+            fee,
+            gas_wanted: response.gas_wanted as u64,
+            gas_used: response.gas_used as u64,
+            events,
+        })
+    }
+}
+
+/// `TxMsgData` as encoded in the `data` of a `TxResponse`, up to v0.45 each message result
+/// is a `MsgData` in `data`, from v0.46 they are `Any` encoded in `msg_responses`
+#[derive(Clone, PartialEq, Message)]
+struct TxMsgDataCompat {
+    #[prost(message, repeated, tag = "1")]
+    data: Vec<MsgData>,
+    #[prost(message, repeated, tag = "2")]
+    msg_responses: Vec<Any>,
+}
+
+/// `TxResponse` with the fields of every Cosmos SDK version, `events` was added in v0.45.
+/// Decoding tolerates any of them being absent
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct TxResponseCompat {
+    #[prost(int64, tag = "1")]
+    height: i64,
+    #[prost(string, tag = "2")]
+    txhash: String,
+    #[prost(string, tag = "3")]
+    codespace: String,
+    #[prost(uint32, tag = "4")]
+    code: u32,
+    #[prost(string, tag = "5")]
+    data: String,
+    #[prost(string, tag = "6")]
+    raw_log: String,
+    #[prost(message, repeated, tag = "7")]
+    logs: Vec<AbciMessageLog>,
+    #[prost(string, tag = "8")]
+    info: String,
+    #[prost(int64, tag = "9")]
+    gas_wanted: i64,
+    #[prost(int64, tag = "10")]
+    gas_used: i64,
+    #[prost(message, optional, tag = "11")]
+    tx: Option<Any>,
+    #[prost(string, tag = "12")]
+    timestamp: String,
+    #[prost(message, repeated, tag = "13")]
+    events: Vec<AbciEvent>,
+}
+
+/// `GetTxResponse` with the transaction left encoded so that a transaction this crate
+/// can not decode does not fail the whole response
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct GetTxResponseCompat {
+    #[prost(bytes, optional, tag = "1")]
+    tx: Option<Vec<u8>>,
+    #[prost(message, optional, tag = "2")]
+    tx_response: Option<TxResponseCompat>,
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// A transaction and it's result as returned by `Contact::get_tx_by_hash`, the same for
+/// every Cosmos SDK version. Fields that are not returned by every version or node are
+/// None when absent
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxRecord {
+    pub hash: String,
+    pub height: u64,
+    pub code: u32,
+    pub codespace: Option<String>,
+    /// The log of the transaction, v0.50 and later only set this for failed transactions
+    pub raw_log: Option<String>,
+    pub info: Option<String>,
+    pub gas_wanted: u64,
+    pub gas_used: u64,
+    /// The time of the block the transaction is in, as reported by the node
+    pub timestamp: Option<String>,
+    /// The transaction itself, None if the node did not return it or it could not be decoded
+    pub tx: Option<Tx>,
+    /// The result of each message, from v0.46 these are the `Msg*Response` of each message,
+    /// before that the `type_url` is the type of the message and `value` its result
+    pub msg_responses: Vec<Any>,
+    /// The events of every message, see `TxResponseEvents::tx_events`
+    pub events: Vec<TxEvent>,
+}
+
+impl TxRecord {
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+
+    /// The results of the messages executed by the authz MsgExec at `index` in the messages
+    /// of the transaction, see `authz::decode_exec_results`
+    #[allow(clippy::result_large_err)]
+    pub fn exec_results(&self, index: usize) -> Result<Vec<ExecResult>, CosmosGrpcError> {
+        let msg = self
+            .tx
+            .as_ref()
+            .and_then(|tx| tx.body.as_ref())
+            .and_then(|body| body.messages.get(index));
+        let (msg, response) = match (msg, self.msg_responses.get(index)) {
+            (Some(msg), Some(response)) if msg.type_url == MSG_EXEC_TYPE_URL => (msg, response),
+            _ => {
+                return Err(CosmosGrpcError::BadInput(format!(
+                    "No MsgExec with a result at {}",
+                    index
+                )))
+            }
+        };
+        let exec: MsgExec = decode_any(msg)?;
+        decode_exec_results(&exec, response)
+    }
+}
+
+impl TryFrom<GetTxResponseCompat> for TxRecord {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: GetTxResponseCompat) -> Result<Self, Self::Error> {
+        let response = match value.tx_response {
+            Some(response) => response,
+            None => {
+                return Err(CosmosGrpcError::BadResponse(
+                    "No tx response returned".to_string(),
+                ))
+            }
+        };
+        // the tx is in both the response and the tx response on some versions, either may
+        // be missing
+        let tx = value
+            .tx
+            .and_then(|bytes| Tx::decode(bytes.as_slice()).ok())
+            .or_else(|| {
+                response
+                    .tx
+                    .as_ref()
+                    .and_then(|any| decode_any::<Tx>(any).ok())
+            });
+        let msg_responses = match hex_str_to_bytes(&response.data) {
+            Ok(data) => msg_responses_from_data(&data),
+            Err(_) => Vec::new(),
+        };
+        let events = TxResponseEvents {
+            logs: response.logs,
+            events: response.events,
+        }
+        .tx_events();
+        Ok(TxRecord {
+            hash: response.txhash,
+            height: response.height.max(0) as u64,
+            code: response.code,
+            codespace: non_empty(response.codespace),
+            raw_log: non_empty(response.raw_log),
+            info: non_empty(response.info),
+            gas_wanted: response.gas_wanted.max(0) as u64,
+            gas_used: response.gas_used.max(0) as u64,
+            timestamp: non_empty(response.timestamp),
+            tx,
+            msg_responses,
+            events,
+        })
+    }
+}
+
+/// Decodes the message responses from an encoded `TxMsgData`, empty if it doesn't decode
+pub(crate) fn msg_responses_from_data(data: &[u8]) -> Vec<Any> {
+    match TxMsgDataCompat::decode(data) {
+        Ok(data) if !data.msg_responses.is_empty() => data.msg_responses,
+        Ok(data) => data
+            .data
+            .into_iter()
+            .map(|d| Any {
+                type_url: d.msg_type,
+                value: d.data,
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// How `Contact::send_and_confirm` determines the fee of a transaction
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum FeeStrategy {
+    /// Simulate the transaction for it's gas limit and compute the fee like
+    /// `Contact::estimate_fee`, if the chain requires more than that it's resent once with the
+    /// fee the node asks for
+    Simulate,
+    /// `Simulate` with the fee paid by this granter through a fee grant
+    SimulateWithGranter(String),
+    /// Sign with this fee and gas limit as is
+    Fixed(Fee),
+}
+
+/// The options of `Contact::send_and_confirm`, the defaults simulate the fee, broadcast in
+/// sync mode so that CheckTx errors are returned right away and wait up to
+/// `DEFAULT_CONFIRM_TIMEOUT` for the transaction to be included
+#[derive(Debug, Clone, PartialEq)]
+pub struct SendOptions {
+    pub fee: FeeStrategy,
+    pub memo: Memo,
+    /// How long to wait for the transaction to be included and confirmed, in total
+    pub timeout: Duration,
+    /// `Async` returns no CheckTx errors, a transaction the node rejects is then only found
+    /// to be missing once `timeout` has passed or the mempool is checked, see `wait_for_tx`
+    pub mode: BroadcastMode,
+    /// The blocks to wait for on top of the one the transaction is included in, see
+    /// `Contact::wait_for_confirmations`
+    pub confirmations: u64,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        SendOptions {
+            fee: FeeStrategy::Simulate,
+            memo: Memo::text(MEMO),
+            timeout: DEFAULT_CONFIRM_TIMEOUT,
+            mode: BroadcastMode::Sync,
+            confirmations: 0,
+        }
+    }
+}
+
+/// A transaction that was executed successfully, see `Contact::send_and_confirm`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Receipt {
+    pub response: TxResponse,
+    /// The events of every message, see `TxResponseEvents::tx_events`
+    pub events: Vec<TxEvent>,
+    /// The response of each message in order, see `TxRecord::msg_responses`
+    pub msg_responses: Vec<Any>,
+    pub gas_wanted: u64,
+    pub gas_used: u64,
+    /// The fee the transaction was signed with, the whole fee is paid regardless of gas used
+    pub fee: Fee,
+}
+
+impl Receipt {
+    pub(crate) fn new(response: TxResponse, events: Vec<TxEvent>, fee: Fee) -> Receipt {
+        let msg_responses = match hex_str_to_bytes(&response.data) {
+            Ok(data) => msg_responses_from_data(&data),
+            Err(_) => Vec::new(),
+        };
+        Receipt {
+            gas_wanted: response.gas_wanted.max(0) as u64,
+            gas_used: response.gas_used.max(0) as u64,
+            response,
+            events,
+            msg_responses,
+            fee,
+        }
+    }
+
+    pub fn tx_hash(&self) -> &str {
+        &self.response.txhash
+    }
+
+    pub fn height(&self) -> u64 {
+        self.response.height.max(0) as u64
+    }
+}
+
+/// An event emitted by a simulated transaction, see `SimulateResult`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedEvent {
+    /// The event type, for example `transfer` or `wasm`
+    pub kind: String,
+    /// The attributes of the event as key value pairs in the order they were emitted
+    pub attributes: Vec<(String, String)>,
+    /// The index of the message that emitted this event, only reported by Cosmos SDK v0.50
+    /// and later chains, None for events of the transaction itself such as fee payment
+    pub msg_index: Option<u32>,
+}
+
+impl SimulatedEvent {
+    /// Gets the value of the first attribute with this key
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Everything returned by simulating a transaction, see `Contact::simulate_tx_full`. Nothing
+/// here is committed, it's what the transaction would do if it was executed now
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulateResult {
+    pub gas_info: GasInfo,
+    /// The response of each message in order, for example the data returned by a contract
+    pub msg_responses: Vec<Any>,
+    pub log: String,
+    /// Every event the transaction would emit in order, including those of the ante handler
+    pub events: Vec<SimulatedEvent>,
+}
+
+impl SimulateResult {
+    /// The events of this type, in the order they were emitted
+    pub fn events_of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a SimulatedEvent> {
+        self.events.iter().filter(move |e| e.kind == kind)
+    }
+}
+
+impl TryFrom<SimulateResponse> for SimulateResult {
+    type Error = CosmosGrpcError;
+
+    fn try_from(value: SimulateResponse) -> Result<Self, Self::Error> {
+        let gas_info = match value.gas_info {
+            Some(gas_info) => gas_info,
+            None => {
+                return Err(CosmosGrpcError::BadResponse(
+                    "Simulation returned no gas_info".to_string(),
+                ))
+            }
+        };
+        let result = value.result.unwrap_or_default();
+        let events = result
+            .events
+            .iter()
+            .map(|event| {
+                let attributes = event_attributes(event);
+                SimulatedEvent {
+                    kind: event.r#type.clone(),
+                    msg_index: event_msg_index(&attributes),
+                    attributes: attributes
+                        .into_iter()
+                        .filter(|(k, _)| k != "msg_index")
+                        .collect(),
+                }
+            })
+            .collect();
+        Ok(SimulateResult {
+            gas_info,
+            msg_responses: msg_responses_from_data(&result.data),
+            log: result.log,
+            events,
+        })
+    }
+}
+
+/// The gas usage of a simulated transaction, converted from the `GasInfo` returned by
+/// `Contact::simulate_tx`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEstimate {
+    /// The gas limit of the simulated transaction, simulations run with an unlimited
+    /// gas meter so this is usually zero
+    pub gas_wanted: u64,
+    /// The gas consumed by the simulated transaction
+    pub gas_used: u64,
+}
+
+impl GasEstimate {
+    /// The gas limit to use for this transaction, the gas used multiplied by the adjustment
+    /// and rounded up. Simulation is not perfectly accurate so an adjustment above 1.0 is
+    /// recommended, see `DEFAULT_GAS_ADJUSTMENT`
+    pub fn recommended_gas_limit(&self, adjustment: f64) -> u64 {
+        (self.gas_used as f64 * adjustment).ceil() as u64
+    }
+}
+
+impl From<GasInfo> for GasEstimate {
+    fn from(value: GasInfo) -> Self {
+        GasEstimate {
+            gas_wanted: value.gas_wanted,
+            gas_used: value.gas_used,
+        }
+    }
+}
+
 /// This is a parsed and validated version of the Cosmos base account proto
 /// struct
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -47,16 +1243,568 @@ pub struct BaseAccount {
     pub sequence: u64,
 }
 
-impl From<ProtoBaseAccount> for BaseAccount {
-    fn from(value: ProtoBaseAccount) -> Self {
-        BaseAccount {
-            address: value.address.parse().unwrap(),
-            pubkey: value.pub_key.unwrap().value,
+/// Accounts that have never signed a transaction have no pubkey on chain, in which
+/// case `pubkey` is left empty
+impl TryFrom<ProtoBaseAccount> for BaseAccount {
+    type Error = AddressError;
+
+    fn try_from(value: ProtoBaseAccount) -> Result<Self, Self::Error> {
+        Ok(BaseAccount {
+            address: value.address.parse()?,
+            pubkey: value.pub_key.map(|k| k.value).unwrap_or_default(),
             account_number: value.account_number,
             sequence: value.sequence,
-        }
+        })
     }
 }
 
+/// Gets the `pub_key` of an account of any of the account types of the Cosmos SDK or
+/// Ethermint, None if the account has never signed
+#[allow(clippy::result_large_err)]
+pub(crate) fn account_pub_key(account: &Any) -> Result<Option<Any>, CosmosGrpcError> {
+    let base = match account.type_url.as_str() {
+        "/cosmos.auth.v1beta1.BaseAccount" => Some(decode_any::<ProtoBaseAccount>(account)?),
+        // every vesting account holds it's base vesting account in field 1
+        url if url.starts_with("/cosmos.vesting.") => decode_any::<DelayedVestingAccount>(account)?
+            .base_vesting_account
+            .and_then(|vesting| vesting.base_account),
+        // like module accounts, Ethermint accounts hold their base account in field 1
+        "/cosmos.auth.v1beta1.ModuleAccount" | "/ethermint.types.v1.EthAccount" => {
+            decode_any::<ModuleAccount>(account)?.base_account
+        }
+        url => {
+            return Err(CosmosGrpcError::BadResponse(format!(
+                "Unknown account type {}",
+                url
+            )))
+        }
+    };
+    Ok(base.and_then(|base| base.pub_key))
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_pubkey() {
+        use cosmos_sdk_proto::cosmos::vesting::v1beta1::{
+            BaseVestingAccount, ContinuousVestingAccount,
+        };
+
+        let key = crate::PrivateKey::from_secret(b"account pubkey")
+            .to_public_key("cosmospub")
+            .unwrap();
+        let key_any = encode_any(
+            Secp256k1PubKey {
+                key: key.as_bytes().to_vec(),
+            },
+            "/cosmos.crypto.secp256k1.PubKey",
+        );
+        let base = ProtoBaseAccount {
+            address: "cosmos1account".to_string(),
+            pub_key: Some(key_any.clone()),
+            account_number: 1,
+            sequence: 1,
+        };
+        let account = encode_any(base.clone(), "/cosmos.auth.v1beta1.BaseAccount");
+        let decoded =
+            AccountPubKey::from_any(account_pub_key(&account).unwrap().unwrap(), "cosmospub");
+        assert_eq!(decoded, AccountPubKey::Secp256k1(key));
+        assert_eq!(decoded.algorithm(), "secp256k1");
+
+        let vesting = encode_any(
+            ContinuousVestingAccount {
+                base_vesting_account: Some(BaseVestingAccount {
+                    base_account: Some(base.clone()),
+                    ..Default::default()
+                }),
+                start_time: 100,
+            },
+            "/cosmos.vesting.v1beta1.ContinuousVestingAccount",
+        );
+        assert_eq!(account_pub_key(&vesting).unwrap(), Some(key_any.clone()));
+        let eth = Any {
+            type_url: "/ethermint.crypto.v1.ethsecp256k1.PubKey".to_string(),
+            value: key_any.value.clone(),
+        };
+        assert_eq!(
+            AccountPubKey::from_any(eth, "evmospub"),
+            AccountPubKey::EthSecp256k1(PublicKey::from_slice(key.as_bytes(), "evmospub").unwrap())
+        );
+
+        let multisig = encode_any(
+            LegacyAminoPubKey {
+                threshold: 2,
+                public_keys: vec![key_any.clone(), key_any],
+            },
+            "/cosmos.crypto.multisig.LegacyAminoPubKey",
+        );
+        match AccountPubKey::from_any(multisig, "cosmospub") {
+            AccountPubKey::Multisig { threshold, keys } => {
+                assert_eq!(threshold, 2);
+                assert_eq!(keys, vec![AccountPubKey::Secp256k1(key); 2]);
+            }
+            other => panic!("Expected multisig, got {:?}", other),
+        }
+
+        // accounts that never signed have no key
+        let unsigned = encode_any(
+            ProtoBaseAccount {
+                pub_key: None,
+                ..base
+            },
+            "/cosmos.auth.v1beta1.BaseAccount",
+        );
+        assert_eq!(account_pub_key(&unsigned).unwrap(), None);
+        let other = Any {
+            type_url: "/cosmos.crypto.secp256r1.PubKey".to_string(),
+            value: vec![1, 2, 3],
+        };
+        assert_eq!(
+            AccountPubKey::from_any(other, "cosmospub").algorithm(),
+            "/cosmos.crypto.secp256r1.PubKey"
+        );
+    }
+
+    #[test]
+    fn test_tx_response_events() {
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{Attribute, StringEvent};
+        use tendermint_proto::abci::EventAttribute;
+
+        // a v0.47 response, events are grouped by message in logs
+        let legacy = TxResponse {
+            logs: vec![AbciMessageLog {
+                msg_index: 0,
+                log: String::new(),
+                events: vec![StringEvent {
+                    r#type: "transfer".to_string(),
+                    attributes: vec![Attribute {
+                        key: "amount".to_string(),
+                        value: "7stake".to_string(),
+                    }],
+                }],
+            }],
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        legacy.encode(&mut buf).unwrap();
+        let decoded = TxResponseEvents::decode(buf.as_slice()).unwrap();
+        let events = decoded.tx_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].attribute("amount"), Some("7stake"));
+
+        // a v0.50 response, no logs and message events carry their index as an attribute
+        let attribute = |key: &str, value: &str| EventAttribute {
+            key: key.as_bytes().to_vec(),
+            value: value.as_bytes().to_vec(),
+            index: true,
+        };
+        let current = TxResponseEvents {
+            logs: Vec::new(),
+            events: vec![
+                AbciEvent {
+                    r#type: "tx".to_string(),
+                    attributes: vec![attribute("fee", "100stake")],
+                },
+                AbciEvent {
+                    r#type: "transfer".to_string(),
+                    attributes: vec![attribute("amount", "7stake"), attribute("msg_index", "1")],
+                },
+            ],
+        };
+        let mut buf = Vec::new();
+        current.encode(&mut buf).unwrap();
+        let decoded = TxResponseEvents::decode(buf.as_slice()).unwrap();
+        let events = decoded.tx_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].msg_index, 1);
+        assert_eq!(events[0].kind, "transfer");
+        assert_eq!(
+            events[0].attributes,
+            vec![("amount".to_string(), "7stake".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_consensus_validator_conversion() {
+        let address = Address::from_consensus_pubkey(&[1; 32], "cosmosvalcons").unwrap();
+        let validator = ConsensusValidator::try_from(TendermintValidator {
+            address: address.to_string(),
+            pub_key: Some(encode_any(
+                Ed25519PubKey { key: vec![1; 32] },
+                "/cosmos.crypto.ed25519.PubKey",
+            )),
+            voting_power: 100,
+            proposer_priority: -5,
+        })
+        .unwrap();
+        assert_eq!(validator.address, address);
+        assert_eq!(validator.pub_key, ConsensusPubKey::Ed25519(vec![1; 32]));
+        assert_eq!(validator.voting_power, 100);
+        // and back into the Any of a MsgCreateValidator
+        let any = Any::from(validator.pub_key.clone());
+        assert_eq!(any.type_url, "/cosmos.crypto.ed25519.PubKey");
+        assert_eq!(ConsensusPubKey::from(any), validator.pub_key);
+
+        let unknown = Any {
+            type_url: "/cosmos.crypto.bn254.PubKey".to_string(),
+            value: vec![1, 2],
+        };
+        assert_eq!(
+            ConsensusPubKey::from(unknown.clone()),
+            ConsensusPubKey::Other(unknown)
+        );
+    }
+
+    #[test]
+    fn test_block_stats() {
+        use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::Fee as ProtoFee;
+        use tendermint_proto::types::Header;
+
+        let block = Block {
+            header: Some(Header {
+                height: 10,
+                proposer_address: vec![1; 20],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut block = BlockWithResults {
+            block,
+            txs: Vec::new(),
+        };
+        // empty blocks are common
+        let stats = BlockStats::new(&block, "cosmosvalcons").unwrap();
+        assert_eq!(stats.tx_count, 0);
+        assert_eq!(stats.gas_used, 0);
+        assert!(stats.fees.is_empty());
+        assert!(stats.proposer.to_string().starts_with("cosmosvalcons1"));
+
+        let tx = |amount: &str, gas_used| BlockTx {
+            hash: String::new(),
+            raw: Vec::new(),
+            tx: Some(Tx {
+                body: None,
+                auth_info: Some(AuthInfo {
+                    signer_infos: Vec::new(),
+                    fee: Some(ProtoFee {
+                        amount: vec![ProtoCoin {
+                            denom: "stake".to_string(),
+                            amount: amount.to_string(),
+                        }],
+                        ..Default::default()
+                    }),
+                }),
+                signatures: Vec::new(),
+            }),
+            result: Some(TxResponse {
+                gas_used,
+                ..Default::default()
+            }),
+        };
+        block.txs = vec![tx("100", 50_000), tx("250", 70_000)];
+        let stats = BlockStats::new(&block, "cosmosvalcons").unwrap();
+        assert_eq!(stats.height, 10);
+        assert_eq!(stats.tx_count, 2);
+        assert_eq!(stats.gas_used, 120_000);
+        assert_eq!(stats.fees, vec![Coin::new(350u64.into(), "stake")]);
+    }
+
+    #[test]
+    fn test_enum_conversions() {
+        for vote in [
+            VoteOption::Yes,
+            VoteOption::Abstain,
+            VoteOption::No,
+            VoteOption::NoWithVeto,
+        ] {
+            let raw: i32 = vote.into();
+            assert_eq!(VoteOption::try_from(raw), Ok(vote));
+            assert_eq!(vote.to_string().parse::<VoteOption>(), Ok(vote));
+        }
+        assert_eq!(i32::from(VoteOption::NoWithVeto), 4);
+        assert_eq!("VOTE_OPTION_YES".parse::<VoteOption>(), Ok(VoteOption::Yes));
+        assert!("maybe".parse::<VoteOption>().is_err());
+        assert_eq!(VoteOption::try_from(0), Err(0));
+
+        assert_eq!(i32::from(ProposalStatus::VotingPeriod), 2);
+        assert_eq!(ProposalStatus::try_from(5), Ok(ProposalStatus::Failed));
+        assert_eq!(ProposalStatus::try_from(9), Err(9));
+
+        assert_eq!(i32::from(BondStatus::Bonded), 3);
+        assert_eq!(BondStatus::try_from(1), Ok(BondStatus::Unbonded));
+        assert_eq!(BondStatus::Unbonding.to_string(), "BOND_STATUS_UNBONDING");
+    }
+
+    #[test]
+    fn test_node_info_feature_detection() {
+        let dep = |path: &str, version: &str| Module {
+            path: path.to_string(),
+            version: version.to_string(),
+            sum: String::new(),
+        };
+        let deps = vec![
+            dep("github.com/tendermint/tendermint", "v0.34.14"),
+            dep("github.com/cosmos/cosmos-sdk", "v0.44.5"),
+        ];
+        assert_eq!(cosmos_sdk_version(&deps), Some("v0.44.5".to_string()));
+        let replaced = vec![dep(
+            "github.com/cosmos/cosmos-sdk",
+            "v0.44.5 => github.com/someone/cosmos-sdk v0.44.5-patch",
+        )];
+        assert_eq!(
+            cosmos_sdk_version(&replaced),
+            Some("v0.44.5-patch".to_string())
+        );
+        assert_eq!(cosmos_sdk_version(&[]), None);
+
+        let mut info = NodeInfo {
+            network: "cosmoshub-4".to_string(),
+            moniker: "node".to_string(),
+            tendermint_version: "0.34.14".to_string(),
+            app_name: "gaiad".to_string(),
+            app_version: "v6.0.0".to_string(),
+            cosmos_sdk_version: Some("v0.44.5".to_string()),
+            msg_type_urls: Some(vec![
+                "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                "/cosmwasm.wasm.v1.MsgExecuteContract".to_string(),
+            ]),
+        };
+        assert_eq!(info.has_module("cosmwasm.wasm.v1"), Some(true));
+        // any version
+        assert_eq!(info.has_module("cosmwasm.wasm"), Some(true));
+        assert_eq!(info.has_module("cosmwasm.was"), Some(false));
+        assert_eq!(info.has_module("cosmos.authz.v1beta1"), Some(false));
+        assert_eq!(info.has_msg("/cosmos.bank.v1beta1.MsgSend"), Some(true));
+        info.msg_type_urls = None;
+        assert_eq!(info.has_module("cosmwasm.wasm.v1"), None);
+    }
+
+    #[test]
+    fn test_recommended_gas_limit() {
+        let estimate: GasEstimate = GasInfo {
+            gas_wanted: 0,
+            gas_used: 100_001,
+        }
+        .into();
+        assert_eq!(estimate.gas_used, 100_001);
+        assert_eq!(estimate.recommended_gas_limit(1.0), 100_001);
+        // rounded up
+        assert_eq!(estimate.recommended_gas_limit(1.3), 130_002);
+    }
+
+    #[test]
+    fn test_base_account_conversion() {
+        let address = Address::from_bytes([1; 20], "cosmos").unwrap();
+        let proto = ProtoBaseAccount {
+            address: address.to_string(),
+            pub_key: None,
+            account_number: 3,
+            sequence: 7,
+        };
+        let account = BaseAccount::try_from(proto.clone()).unwrap();
+        assert_eq!(account.address, address);
+        assert!(account.pubkey.is_empty());
+        assert_eq!(account.sequence, 7);
+
+        let bad = ProtoBaseAccount {
+            address: "cosmos1notanaddress".to_string(),
+            ..proto
+        };
+        let error: crate::error::CosmosGrpcError = BaseAccount::try_from(bad).unwrap_err().into();
+        assert!(matches!(
+            error,
+            crate::error::CosmosGrpcError::AddressError { .. }
+        ));
+    }
+
+    #[test]
+    fn test_tx_summary() {
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{
+            AbciMessageLog, Attribute, StringEvent,
+        };
+        let response = GetTxResponse {
+            tx: None,
+            tx_response: Some(TxResponse {
+                txhash: "ABCD".to_string(),
+                height: 12,
+                gas_wanted: 200_000,
+                gas_used: 81_000,
+                logs: vec![AbciMessageLog {
+                    msg_index: 0,
+                    log: String::new(),
+                    events: vec![StringEvent {
+                        r#type: "transfer".to_string(),
+                        attributes: vec![
+                            Attribute {
+                                key: "recipient".to_string(),
+                                value: "cosmos1a".to_string(),
+                            },
+                            Attribute {
+                                key: "amount".to_string(),
+                                value: "7stake".to_string(),
+                            },
+                        ],
+                    }],
+                }],
+                ..Default::default()
+            }),
+        };
+        let summary = TxSummary::try_from(response.clone()).unwrap();
+        assert!(summary.success);
+        assert_eq!(summary.error, None);
+        assert_eq!(summary.height, 12);
+        assert_eq!(summary.events[0].kind, "transfer");
+        assert_eq!(summary.events[0].attribute("amount"), Some("7stake"));
+        assert_eq!(summary.fee, None);
+
+        let mut failed = response;
+        let tx_response = failed.tx_response.as_mut().unwrap();
+        tx_response.code = 5;
+        tx_response.raw_log = "insufficient funds".to_string();
+        let summary = TxSummary::try_from(failed).unwrap();
+        assert!(!summary.success);
+        assert_eq!(summary.error.as_deref(), Some("insufficient funds"));
+    }
+
+    #[test]
+    fn test_tx_record_sdk_versions() {
+        use crate::utils::bytes_to_hex_str;
+        use cosmos_sdk_proto::cosmos::base::abci::v1beta1::{Attribute, StringEvent, TxMsgData};
+        use tendermint_proto::abci::EventAttribute;
+
+        let tx = Tx {
+            body: Some(TxBody {
+                memo: "fixture".to_string(),
+                ..Default::default()
+            }),
+            auth_info: None,
+            signatures: vec![vec![1; 64]],
+        };
+        let mut tx_bytes = Vec::new();
+        tx.encode(&mut tx_bytes).unwrap();
+
+        // a v0.43 response, the tx is returned next to the response and message results are
+        // MsgData in data
+        let data = TxMsgData {
+            data: vec![MsgData {
+                msg_type: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                data: vec![],
+            }],
+        };
+        let mut data_bytes = Vec::new();
+        data.encode(&mut data_bytes).unwrap();
+        let legacy = GetTxResponse {
+            tx: Some(tx.clone()),
+            tx_response: Some(TxResponse {
+                height: 12,
+                txhash: "ABCD".to_string(),
+                data: bytes_to_hex_str(&data_bytes).to_uppercase(),
+                raw_log: "[]".to_string(),
+                logs: vec![AbciMessageLog {
+                    msg_index: 0,
+                    log: String::new(),
+                    events: vec![StringEvent {
+                        r#type: "transfer".to_string(),
+                        attributes: vec![Attribute {
+                            key: "amount".to_string(),
+                            value: "7stake".to_string(),
+                        }],
+                    }],
+                }],
+                gas_wanted: 200_000,
+                gas_used: 81_000,
+                timestamp: "2021-08-01T00:00:00Z".to_string(),
+                ..Default::default()
+            }),
+        };
+        let mut buf = Vec::new();
+        legacy.encode(&mut buf).unwrap();
+        let record =
+            TxRecord::try_from(GetTxResponseCompat::decode(buf.as_slice()).unwrap()).unwrap();
+        assert!(record.is_success());
+        assert_eq!(record.height, 12);
+        assert_eq!(record.codespace, None);
+        assert_eq!(record.tx, Some(tx.clone()));
+        assert_eq!(record.msg_responses.len(), 1);
+        assert_eq!(
+            record.msg_responses[0].type_url,
+            "/cosmos.bank.v1beta1.MsgSend"
+        );
+        assert_eq!(record.events[0].attribute("amount"), Some("7stake"));
+        assert_eq!(record.gas_used, 81_000);
+
+        // a v0.50 response, only the tx response carries the tx, message results are in
+        // msg_responses, logs and raw_log are empty and events are flat
+        let data = TxMsgDataCompat {
+            data: Vec::new(),
+            msg_responses: vec![Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSendResponse".to_string(),
+                value: vec![],
+            }],
+        };
+        let mut data_bytes = Vec::new();
+        data.encode(&mut data_bytes).unwrap();
+        let current = GetTxResponseCompat {
+            tx: None,
+            tx_response: Some(TxResponseCompat {
+                height: 900,
+                txhash: "ABCD".to_string(),
+                data: bytes_to_hex_str(&data_bytes).to_uppercase(),
+                gas_wanted: 200_000,
+                gas_used: 90_000,
+                tx: Some(Any {
+                    type_url: "/cosmos.tx.v1beta1.Tx".to_string(),
+                    value: tx_bytes.clone(),
+                }),
+                events: vec![AbciEvent {
+                    r#type: "transfer".to_string(),
+                    attributes: vec![
+                        EventAttribute {
+                            key: b"amount".to_vec(),
+                            value: b"7stake".to_vec(),
+                            index: true,
+                        },
+                        EventAttribute {
+                            key: b"msg_index".to_vec(),
+                            value: b"0".to_vec(),
+                            index: true,
+                        },
+                    ],
+                }],
+                ..Default::default()
+            }),
+        };
+        let mut buf = Vec::new();
+        current.encode(&mut buf).unwrap();
+        // the crate's own protobuf types still decode it, without the new fields
+        let raw = GetTxResponse::decode(buf.as_slice()).unwrap();
+        assert!(raw.tx.is_none());
+        let record =
+            TxRecord::try_from(GetTxResponseCompat::decode(buf.as_slice()).unwrap()).unwrap();
+        assert_eq!(record.raw_log, None);
+        assert_eq!(record.tx, Some(tx));
+        assert_eq!(
+            record.msg_responses[0].type_url,
+            "/cosmos.bank.v1beta1.MsgSendResponse"
+        );
+        assert_eq!(record.events[0].attribute("amount"), Some("7stake"));
+
+        // a node that returns almost nothing, or a tx this crate can't decode
+        let sparse = GetTxResponseCompat {
+            tx: Some(vec![0xff; 4]),
+            tx_response: Some(TxResponseCompat {
+                txhash: "ABCD".to_string(),
+                data: "not hex".to_string(),
+                ..Default::default()
+            }),
+        };
+        let record = TxRecord::try_from(sparse).unwrap();
+        assert_eq!(record.tx, None);
+        assert_eq!(record.timestamp, None);
+        assert!(record.msg_responses.is_empty());
+        assert!(TxRecord::try_from(GetTxResponseCompat::default()).is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Contains utility functions for querying the Cosmos sdk upgrade module, useful for
+//! automation that must stop around a scheduled upgrade, once the upgrade height is reached
+//! the chain halts until the validators have upgraded and transactions sent in the meantime
+//! are stuck
+
+use crate::error::CosmosGrpcError;
+use crate::Contact;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::query_client::QueryClient as UpgradeQueryClient;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::Plan;
+use cosmos_sdk_proto::cosmos::upgrade::v1beta1::QueryCurrentPlanRequest;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tonic::Code as TonicCode;
+
+/// A scheduled upgrade of the chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradePlan {
+    /// The name of the upgrade handler in the new software version
+    pub name: String,
+    /// The height the chain halts at for the upgrade, None for the deprecated time based
+    /// plans of older sdk versions
+    pub height: Option<u64>,
+    /// The time the upgrade is performed after, only set by older sdk versions
+    pub time: Option<SystemTime>,
+    /// Application specific info, commonly a JSON object with the binaries of the new version
+    pub info: String,
+}
+
+impl UpgradePlan {
+    /// The number of blocks left before the chain halts for the upgrade at `current_height`,
+    /// zero once the upgrade height is reached and None for a plan without a height
+    pub fn blocks_until(&self, current_height: u64) -> Option<u64> {
+        Some(self.height?.saturating_sub(current_height))
+    }
+}
+
+impl From<Plan> for UpgradePlan {
+    fn from(plan: Plan) -> Self {
+        let time = match plan.time {
+            // an unset time is encoded as the zero value rather than being omitted
+            Some(time) if time.seconds > 0 && time.nanos >= 0 => {
+                Some(UNIX_EPOCH + Duration::new(time.seconds as u64, time.nanos as u32))
+            }
+            _ => None,
+        };
+        UpgradePlan {
+            name: plan.name,
+            height: if plan.height > 0 {
+                Some(plan.height as u64)
+            } else {
+                None
+            },
+            time,
+            info: plan.info,
+        }
+    }
+}
+
+impl Contact {
+    /// Gets the currently scheduled upgrade of the chain, None if no upgrade is scheduled.
+    /// Chains without the upgrade module return `ModuleNotPresent`
+    pub async fn get_current_upgrade_plan(&self) -> Result<Option<UpgradePlan>, CosmosGrpcError> {
+        let mut grpc = UpgradeQueryClient::new(self.channel().await?);
+        let res = grpc.current_plan(QueryCurrentPlanRequest {}).await;
+        match res {
+            Ok(res) => Ok(res.into_inner().plan.map(UpgradePlan::from)),
+            Err(e) if e.code() == TonicCode::Unimplemented => Err(
+                CosmosGrpcError::ModuleNotPresent("cosmos.upgrade".to_string()),
+            ),
+            Err(e) => Err(CosmosGrpcError::RequestError { error: e }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::Timestamp;
+
+    #[test]
+    fn test_upgrade_plan() {
+        let plan = UpgradePlan::from(Plan {
+            name: "v2".to_string(),
+            time: Some(Timestamp {
+                seconds: 0,
+                nanos: 0,
+            }),
+            height: 1000,
+            info: "{\"binaries\":{}}".to_string(),
+            upgraded_client_state: None,
+        });
+        assert_eq!(plan.name, "v2");
+        assert_eq!(plan.height, Some(1000));
+        assert_eq!(plan.time, None);
+        assert_eq!(plan.blocks_until(990), Some(10));
+        assert_eq!(plan.blocks_until(1200), Some(0));
+
+        let plan = UpgradePlan::from(Plan {
+            name: "v1".to_string(),
+            time: Some(Timestamp {
+                seconds: 100,
+                nanos: 0,
+            }),
+            height: 0,
+            info: String::new(),
+            upgraded_client_state: None,
+        });
+        assert_eq!(plan.height, None);
+        assert_eq!(plan.time, Some(UNIX_EPOCH + Duration::from_secs(100)));
+        assert_eq!(plan.blocks_until(990), None);
+    }
+}
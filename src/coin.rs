@@ -1,11 +1,121 @@
 use crate::address::Address;
+use crate::decimal::Decimal;
+use crate::decimal::DecimalError;
+use cosmos_sdk_proto::cosmos::bank::v1beta1::Metadata;
 use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::base::v1beta1::DecCoin as ProtoDecCoin;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::Fee as ProtoFee;
 use num256::Uint256;
 use std::convert::TryFrom;
 use std::fmt;
 use std::str::FromStr;
 
+/// The denom of a coin, always valid for the default denom format of the Cosmos SDK: a letter
+/// followed by 2 to 127 letters, digits or `/:._-`. IBC denoms must be `ibc/` followed by a 64
+/// character hex hash and token factory denoms `factory/<creator>/<subdenom>`. `Coin` keeps
+/// it's denom as a plain string since chains may configure a different format, use
+/// `Coin::try_new` or `Coin::checked_denom` to check it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Denom(String);
+
+impl Denom {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn validate(denom: &str) -> Result<(), CoinError> {
+        let invalid = || CoinError::InvalidDenom(denom.to_string());
+        let mut chars = denom.chars();
+        match chars.next() {
+            Some(c) if c.is_ascii_alphabetic() => {}
+            _ => return Err(invalid()),
+        }
+        if denom.len() < 3
+            || denom.len() > 128
+            || !chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+        {
+            return Err(invalid());
+        }
+        if let Some(hash) = denom.strip_prefix("ibc/") {
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+        }
+        if let Some(rest) = denom.strip_prefix("factory/") {
+            match rest.split_once('/') {
+                Some((creator, subdenom)) if !creator.is_empty() && !subdenom.is_empty() => {}
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Denom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Denom {
+    type Err = CoinError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Denom::validate(value)?;
+        Ok(Denom(value.to_string()))
+    }
+}
+
+impl TryFrom<String> for Denom {
+    type Error = CoinError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Denom::validate(&value)?;
+        Ok(Denom(value))
+    }
+}
+
+impl TryFrom<&str> for Denom {
+    type Error = CoinError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Denom> for String {
+    fn from(value: Denom) -> Self {
+        value.0
+    }
+}
+
+impl std::ops::Deref for Denom {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Denom {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Denom {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Denom {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
 /// Coin holds some amount of one currency we convert from ProtoCoin to do more
 /// validation and provide a generally nicer interface
 #[derive(Serialize, Debug, Default, Clone, Deserialize, Eq, PartialEq, Hash)]
@@ -52,8 +162,73 @@ impl FromStr for Coin {
 }
 
 impl Coin {
-    pub fn new(amount: Uint256, denom: String) -> Coin {
-        Coin { amount, denom }
+    pub fn new(amount: Uint256, denom: impl Into<String>) -> Coin {
+        Coin {
+            amount,
+            denom: denom.into(),
+        }
+    }
+
+    /// Like `new` but checks the denom against the denom format of the Cosmos SDK, see `Denom`
+    pub fn try_new(amount: Uint256, denom: impl Into<String>) -> Result<Coin, CoinError> {
+        let denom = Denom::try_from(denom.into())?;
+        Ok(Coin {
+            amount,
+            denom: denom.into(),
+        })
+    }
+
+    /// The denom of this coin checked against the denom format of the Cosmos SDK
+    pub fn checked_denom(&self) -> Result<Denom, CoinError> {
+        self.denom.parse()
+    }
+
+    /// Converts an amount in display units, such as `1.5` ATOM, into a coin in base units, so
+    /// `Coin::from_decimal("1.5", "uatom", 6)` is `1500000uatom`. The conversion is exact, an
+    /// amount with more significant fractional digits than `exponent` can't be represented in
+    /// base units and is an error rather than being rounded
+    pub fn from_decimal(amount: &str, denom: &str, exponent: u32) -> Result<Coin, CoinError> {
+        let amount = amount.trim();
+        let (integral, fractional) = match amount.split_once('.') {
+            Some((integral, fractional)) => (integral, fractional),
+            None => (amount, ""),
+        };
+        let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (integral.is_empty() && fractional.is_empty())
+            || !digits(integral)
+            || !digits(fractional)
+        {
+            return Err(CoinError::InvalidAmount(amount.to_string()));
+        }
+        let fractional = fractional.trim_end_matches('0');
+        if fractional.len() > exponent as usize {
+            return Err(CoinError::ExcessivePrecision {
+                amount: amount.to_string(),
+                exponent,
+            });
+        }
+        let base_units = format!(
+            "{}{}{}",
+            integral,
+            fractional,
+            "0".repeat(exponent as usize - fractional.len())
+        );
+        let base_units = base_units.trim_start_matches('0');
+        let base_units = if base_units.is_empty() {
+            "0"
+        } else {
+            base_units
+        };
+        Ok(Coin {
+            amount: parse_amount(base_units)?,
+            denom: denom.to_string(),
+        })
+    }
+
+    /// The inverse of `from_decimal`, returns the amount of this coin in display units as an
+    /// exact decimal string with trailing zeros removed, so `1500000uatom` is `1.5`
+    pub fn to_decimal(&self, exponent: u32) -> String {
+        shift_decimal_point(&self.amount.to_string(), exponent)
     }
 
     /// utility function to display a list of coins
@@ -66,15 +241,112 @@ impl Coin {
     }
 }
 
-impl From<ProtoCoin> for Coin {
-    fn from(value: ProtoCoin) -> Self {
-        Coin {
-            denom: value.denom,
-            amount: value.amount.parse().unwrap(),
+/// Formats a coin for display using the denom metadata of the chain, converting the amount
+/// from base units to the display unit, so `1000000uatom` is displayed as `1 ATOM`. If the
+/// metadata is not for this coin's denom, or does not contain the display unit the coin is
+/// displayed as is.
+pub fn format_coin(coin: &Coin, metadata: &Metadata) -> String {
+    if coin.denom != metadata.base {
+        return coin.to_string();
+    }
+    let display_unit = metadata
+        .denom_units
+        .iter()
+        .find(|unit| unit.denom == metadata.display);
+    match display_unit {
+        Some(unit) => format!(
+            "{} {}",
+            shift_decimal_point(&coin.amount.to_string(), unit.exponent),
+            unit.denom.to_uppercase()
+        ),
+        None => coin.to_string(),
+    }
+}
+
+/// Takes a string of digits representing an integer and divides it by 10^exponent,
+/// returning the exact decimal representation with trailing zeros removed
+fn shift_decimal_point(digits: &str, exponent: u32) -> String {
+    let exponent = exponent as usize;
+    let padded = if digits.len() <= exponent {
+        format!("{}{}", "0".repeat(exponent - digits.len() + 1), digits)
+    } else {
+        digits.to_string()
+    };
+    let (integral, fractional) = padded.split_at(padded.len() - exponent);
+    let fractional = fractional.trim_end_matches('0');
+    if fractional.is_empty() {
+        integral.to_string()
+    } else {
+        format!("{}.{}", integral, fractional)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CoinError {
+    /// The amount of a proto coin is not a base 10 integer that fits in a Uint256
+    InvalidAmount(String),
+    /// A decimal amount has more fractional digits than the exponent of the denom
+    ExcessivePrecision { amount: String, exponent: u32 },
+    /// The denom does not match the denom format of the Cosmos SDK, see `Denom`
+    InvalidDenom(String),
+    /// A list of coins holds more than one coin of this denom
+    DuplicateDenom(String),
+    /// A list of coins that must be positive holds a zero amount of this denom
+    ZeroAmount(String),
+}
+
+impl fmt::Display for CoinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoinError::InvalidAmount(v) => write!(f, "Invalid coin amount {:?}", v),
+            CoinError::ExcessivePrecision { amount, exponent } => write!(
+                f,
+                "Coin amount {:?} has more than {} fractional digits",
+                amount, exponent
+            ),
+            CoinError::InvalidDenom(v) => write!(f, "Invalid denom {:?}", v),
+            CoinError::DuplicateDenom(v) => write!(f, "Duplicate denom {:?}", v),
+            CoinError::ZeroAmount(v) => write!(f, "Zero amount of {:?}", v),
         }
     }
 }
 
+impl std::error::Error for CoinError {}
+
+/// Parses the amount of a proto coin, the sdk always encodes these as base 10
+/// integers, unlike `Uint256::from_str` hex is not accepted
+fn parse_amount(amount: &str) -> Result<Uint256, CoinError> {
+    if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(CoinError::InvalidAmount(amount.to_string()));
+    }
+    match amount.parse::<Uint256>() {
+        Ok(v) if v.0.bits() <= 256 => Ok(v),
+        _ => Err(CoinError::InvalidAmount(amount.to_string())),
+    }
+}
+
+impl TryFrom<ProtoCoin> for Coin {
+    type Error = CoinError;
+
+    fn try_from(value: ProtoCoin) -> Result<Self, Self::Error> {
+        Ok(Coin {
+            amount: parse_amount(&value.amount)?,
+            denom: value.denom,
+        })
+    }
+}
+
+impl TryFrom<&ProtoCoin> for Coin {
+    type Error = CoinError;
+
+    fn try_from(value: &ProtoCoin) -> Result<Self, Self::Error> {
+        Ok(Coin {
+            amount: parse_amount(&value.amount)?,
+            denom: value.denom.clone(),
+        })
+    }
+}
+
 impl From<Coin> for ProtoCoin {
     fn from(value: Coin) -> Self {
         ProtoCoin {
@@ -84,21 +356,195 @@ impl From<Coin> for ProtoCoin {
     }
 }
 
+impl From<&Coin> for ProtoCoin {
+    fn from(value: &Coin) -> Self {
+        ProtoCoin {
+            denom: value.denom.to_string(),
+            amount: value.amount.to_string(),
+        }
+    }
+}
+
+/// GasPrice represents the amount of a given denom paid per unit of gas, like the
+/// `minimum-gas-prices` node setting, these are usually fractions of the base unit
+/// and are represented as a decimal for this reason.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GasPrice {
+    pub amount: Decimal,
+    pub denom: String,
+}
+
+impl GasPrice {
+    pub fn new(amount: Decimal, denom: String) -> GasPrice {
+        GasPrice { amount, denom }
+    }
+
+    /// Computes the fee for the given gas limit at this price, fractional amounts are
+    /// rounded up so that the resulting fee is never below what the node requires.
+    /// Returns None if the gas price is negative or the fee would overflow
+    pub fn fee_for_gas(&self, gas_limit: u64) -> Option<Coin> {
+        let amount = self.amount.checked_mul_ceil(gas_limit)?;
+        Some(Coin {
+            amount: amount.into(),
+            denom: self.denom.clone(),
+        })
+    }
+}
+
+impl fmt::Display for GasPrice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.denom)
+    }
+}
+
+impl FromStr for GasPrice {
+    type Err = CoinError;
+
+    /// Parses a gas price in the format used by the Cosmos CLI, for example `0.025uatom`,
+    /// a price without a denom is rejected
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let split_idx = value
+            .char_indices()
+            .find(|(_, c)| c.is_alphabetic())
+            .map(|(idx, _)| idx)
+            .unwrap_or_else(|| value.len());
+        let (amount, denom) = value.split_at(split_idx);
+        if denom.is_empty() {
+            return Err(CoinError::InvalidDenom(denom.to_string()));
+        }
+        Ok(GasPrice {
+            amount: Decimal::from_str_lenient(amount)
+                .map_err(|_| CoinError::InvalidAmount(amount.to_string()))?,
+            denom: denom.to_string(),
+        })
+    }
+}
+
+/// A list of gas prices in different denoms, parsed from the comma separated format of
+/// the Cosmos CLI `--gas-prices` flag, for example `0.025uatom,0.1uosmo`
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct GasPrices(pub Vec<GasPrice>);
+
+impl GasPrices {
+    /// Gets the price for the given denom
+    pub fn for_denom(&self, denom: &str) -> Option<&GasPrice> {
+        self.0.iter().find(|p| p.denom == denom)
+    }
+
+    /// Selects the first of these prices in a denom the chain accepts for fees, for
+    /// example the denoms of the node's minimum gas prices, for use with
+    /// `Contact::set_gas_price`
+    pub fn select(&self, accepted_denoms: &[String]) -> Option<&GasPrice> {
+        self.0.iter().find(|p| accepted_denoms.contains(&p.denom))
+    }
+}
+
+impl fmt::Display for GasPrices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prices: Vec<String> = self.0.iter().map(|p| p.to_string()).collect();
+        write!(f, "{}", prices.join(","))
+    }
+}
+
+impl FromStr for GasPrices {
+    type Err = String;
+
+    /// Parses a comma separated list of gas prices, whitespace around each price is ignored
+    /// and an empty string is an empty list. Like the CLI every price must have a denom and
+    /// each denom may only appear once
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut prices: Vec<GasPrice> = Vec::new();
+        if value.trim().is_empty() {
+            return Ok(GasPrices(prices));
+        }
+        for entry in value.split(',') {
+            let price: GasPrice = entry
+                .parse()
+                .map_err(|e| format!("Invalid gas price {:?}: {}", entry.trim(), e))?;
+            if prices.iter().any(|p| p.denom == price.denom) {
+                return Err(format!("Duplicate gas price denom {}", price.denom));
+            }
+            prices.push(price);
+        }
+        Ok(GasPrices(prices))
+    }
+}
+
+/// DecCoin is a coin with a decimal amount, these are used by modules such as distribution
+/// where amounts accumulate in fractions of the base denom
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DecCoin {
+    pub amount: Decimal,
+    pub denom: String,
+}
+
+impl DecCoin {
+    pub fn new(amount: Decimal, denom: String) -> DecCoin {
+        DecCoin { amount, denom }
+    }
+}
+
+impl fmt::Display for DecCoin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.denom)
+    }
+}
+
+impl TryFrom<ProtoDecCoin> for DecCoin {
+    type Error = DecimalError;
+
+    fn try_from(value: ProtoDecCoin) -> Result<Self, Self::Error> {
+        Ok(DecCoin {
+            amount: Decimal::from_sdk_dec_str(&value.amount)?,
+            denom: value.denom,
+        })
+    }
+}
+
 /// Fee represents everything about a Cosmos transaction fee, including the gas limit
 /// who pays, and how much of an arbitrary number of Coin structs.
 #[derive(Serialize, Debug, Default, Clone, Deserialize, Eq, PartialEq, Hash)]
 pub struct Fee {
     pub amount: Vec<Coin>,
     pub gas_limit: u64,
+    /// The account paying the fee if not the first signer, the payer must sign the tx
     pub payer: Option<Address>,
+    /// An account that granted the first signer a fee allowance with the feegrant module,
+    /// the granter pays the fee without signing
     pub granter: Option<String>,
 }
 
-impl From<ProtoFee> for Fee {
-    fn from(value: ProtoFee) -> Self {
+impl Fee {
+    /// Creates a fee paying `amount` for `gas_limit`, the coins are sorted by denom as the node
+    /// requires. Returns an error if a denom appears more than once or an amount is zero, the
+    /// node rejects such fees. Prefer this over building the struct directly
+    pub fn new(mut amount: Vec<Coin>, gas_limit: u64) -> Result<Fee, CoinError> {
+        amount.sort_by(|a, b| a.denom.as_str().cmp(b.denom.as_str()));
+        for (index, coin) in amount.iter().enumerate() {
+            if coin.amount == 0u8.into() {
+                return Err(CoinError::ZeroAmount(coin.denom.to_string()));
+            }
+            if index > 0 && amount[index - 1].denom == coin.denom {
+                return Err(CoinError::DuplicateDenom(coin.denom.to_string()));
+            }
+        }
+        Ok(Fee {
+            amount,
+            gas_limit,
+            payer: None,
+            granter: None,
+        })
+    }
+}
+
+impl TryFrom<ProtoFee> for Fee {
+    type Error = CoinError;
+
+    fn try_from(value: ProtoFee) -> Result<Self, Self::Error> {
         let mut converted_coins = Vec::new();
         for coin in value.amount {
-            converted_coins.push(coin.into());
+            converted_coins.push(Coin::try_from(coin)?);
         }
         let payer = if let Ok(addr) = value.payer.parse() {
             Some(addr)
@@ -110,12 +556,12 @@ impl From<ProtoFee> for Fee {
         } else {
             Some(value.granter)
         };
-        Fee {
+        Ok(Fee {
             amount: converted_coins,
             gas_limit: value.gas_limit,
             payer,
             granter,
-        }
+        })
     }
 }
 
@@ -161,4 +607,260 @@ mod tests {
 
         let _res = PrivateKey::from_phrase("swim cereal address police kiwi ship safe raven other place lizard index auction mother arrive sad void real library upgrade chase frequent bike diesel", "").unwrap();
     }
+
+    #[test]
+    fn test_fee_new() {
+        let fee = Fee::new(
+            vec![
+                Coin::new(5u8.into(), "uosmo"),
+                Coin::new(7u8.into(), "uatom"),
+            ],
+            100_000,
+        )
+        .unwrap();
+        assert_eq!(fee.amount[0].denom, "uatom");
+        assert_eq!(fee.amount[1].denom, "uosmo");
+        assert_eq!(fee.gas_limit, 100_000);
+        assert_eq!(Fee::new(Vec::new(), 1).unwrap().amount, vec![]);
+        assert_eq!(
+            Fee::new(
+                vec![
+                    Coin::new(5u8.into(), "uatom"),
+                    Coin::new(1u8.into(), "uosmo"),
+                    Coin::new(7u8.into(), "uatom"),
+                ],
+                1
+            ),
+            Err(CoinError::DuplicateDenom("uatom".to_string()))
+        );
+        assert_eq!(
+            Fee::new(vec![Coin::new(0u8.into(), "uatom")], 1),
+            Err(CoinError::ZeroAmount("uatom".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_denom() {
+        let hash = "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        for valid in [
+            "uatom".to_string(),
+            format!("ibc/{}", hash),
+            "factory/osmo1t0sgxmpxafdfjd3k6kgg50kdgn4muh5t0phml6/sub/denom".to_string(),
+            "gravity0x8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063".to_string(),
+        ] {
+            assert_eq!(valid.parse::<Denom>().unwrap().as_str(), valid);
+        }
+        for invalid in [
+            "",
+            "ua",
+            "1uatom",
+            "uatom!",
+            "u atom",
+            "ibc/1234",
+            "factory/osmo1creator",
+            "factory//sub",
+        ] {
+            assert_eq!(
+                Denom::try_from(invalid.to_string()),
+                Err(CoinError::InvalidDenom(invalid.to_string()))
+            );
+        }
+        assert!(Denom::from_str(&"a".repeat(129)).is_err());
+
+        // try_new checks the denom, new and parsing do not as chains may use another format
+        let custom: Coin = "100u$atom".parse().unwrap();
+        assert_eq!(custom.denom, "u$atom");
+        assert!(custom.checked_denom().is_err());
+        assert_eq!(Denom::try_from("uatom").unwrap(), "uatom");
+        assert!(Denom::try_from("u$atom").is_err());
+        assert_eq!(
+            Coin::try_new(7u8.into(), "uatom"),
+            Ok(Coin::new(7u8.into(), "uatom".to_string()))
+        );
+        assert_eq!(
+            Coin::try_new(7u8.into(), "1x"),
+            Err(CoinError::InvalidDenom("1x".to_string()))
+        );
+        assert_eq!(Coin::new(7u8.into(), "1x").denom, "1x");
+        let denom: Denom = serde_json::from_str(r#""uatom""#).unwrap();
+        assert_eq!(denom, "uatom");
+        assert!(serde_json::from_str::<Denom>(r#""1x""#).is_err());
+    }
+
+    #[test]
+    fn test_coin_from_decimal() {
+        let coin = Coin::from_decimal("1.5", "uatom", 6).unwrap();
+        assert_eq!(coin.to_string(), "1500000uatom");
+        assert_eq!(coin.to_decimal(6), "1.5");
+        assert_eq!(
+            Coin::from_decimal("0.000001", "uatom", 6).unwrap().amount,
+            1u8.into()
+        );
+        assert_eq!(
+            Coin::from_decimal("2", "uatom", 6).unwrap().amount,
+            2_000_000u64.into()
+        );
+        assert_eq!(
+            Coin::from_decimal(".5", "uatom", 1).unwrap().amount,
+            5u8.into()
+        );
+        // trailing zeros don't add precision
+        assert_eq!(Coin::from_decimal("1.50000000", "uatom", 6).unwrap(), coin);
+        assert_eq!(
+            Coin::from_decimal("0", "uatom", 6).unwrap().to_decimal(6),
+            "0"
+        );
+        // 18 decimal denoms are larger than any float can represent exactly
+        let wei = Coin::from_decimal("123456789.123456789123456789", "wei", 18).unwrap();
+        assert_eq!(wei.amount.to_string(), "123456789123456789123456789");
+        assert_eq!(wei.to_decimal(18), "123456789.123456789123456789");
+
+        assert_eq!(
+            Coin::from_decimal("1.0000001", "uatom", 6),
+            Err(CoinError::ExcessivePrecision {
+                amount: "1.0000001".to_string(),
+                exponent: 6
+            })
+        );
+        for invalid in ["", ".", "-1", "1.2.3", "1e6", "1,5", "0x10"] {
+            assert!(
+                Coin::from_decimal(invalid, "uatom", 6).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_coin() {
+        use cosmos_sdk_proto::cosmos::bank::v1beta1::DenomUnit;
+        let metadata = Metadata {
+            description: "The native staking token of the Cosmos Hub.".to_string(),
+            denom_units: vec![
+                DenomUnit {
+                    denom: "uatom".to_string(),
+                    exponent: 0,
+                    aliases: vec!["microatom".to_string()],
+                },
+                DenomUnit {
+                    denom: "atom".to_string(),
+                    exponent: 6,
+                    aliases: vec![],
+                },
+            ],
+            base: "uatom".to_string(),
+            display: "atom".to_string(),
+        };
+        let one: Coin = "1000000uatom".parse().unwrap();
+        assert_eq!(format_coin(&one, &metadata), "1 ATOM");
+        let fraction: Coin = "1500001uatom".parse().unwrap();
+        assert_eq!(format_coin(&fraction, &metadata), "1.500001 ATOM");
+        let small: Coin = "25uatom".parse().unwrap();
+        assert_eq!(format_coin(&small, &metadata), "0.000025 ATOM");
+        let other: Coin = "25uosmo".parse().unwrap();
+        assert_eq!(format_coin(&other, &metadata), "25uosmo");
+    }
+
+    #[test]
+    fn test_gas_price_fee() {
+        let price: GasPrice = "0.025uatom".parse().unwrap();
+        assert_eq!(price.denom, "uatom");
+        assert_eq!(
+            price.fee_for_gas(123_457),
+            Some(Coin {
+                amount: 3087u64.into(),
+                denom: "uatom".into(),
+            })
+        );
+        assert!("notanumber".parse::<GasPrice>().is_err());
+        assert_eq!(
+            "0.025".parse::<GasPrice>(),
+            Err(CoinError::InvalidDenom(String::new()))
+        );
+    }
+
+    #[test]
+    fn test_gas_prices_parse() {
+        let prices: GasPrices = " 0.025uatom, 0.1uosmo ,2ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+            .parse()
+            .unwrap();
+        assert_eq!(prices.0.len(), 3);
+        assert_eq!(prices.0[0], "0.025uatom".parse().unwrap());
+        assert_eq!(
+            prices.for_denom("uosmo"),
+            Some(&"0.1uosmo".parse().unwrap())
+        );
+        assert_eq!(
+            prices.select(&["ujuno".to_string(), "uosmo".to_string()]),
+            prices.for_denom("uosmo")
+        );
+        assert_eq!(
+            prices.0[2].denom,
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+        assert_eq!(prices.to_string().parse::<GasPrices>().unwrap(), prices);
+
+        assert_eq!("".parse::<GasPrices>().unwrap(), GasPrices::default());
+        assert!("0.025".parse::<GasPrices>().is_err());
+        assert!("0.025uatom,".parse::<GasPrices>().is_err());
+        assert!("0.025uatom,1uatom".parse::<GasPrices>().is_err());
+        assert!("abc".parse::<GasPrices>().is_err());
+    }
+
+    #[test]
+    fn test_proto_coin_round_trip() {
+        let coin = Coin {
+            amount: Uint256::from_str(
+                "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+            )
+            .unwrap(),
+            denom: "gravity0x7580bFE88Dd3d07947908FAE12d95872a260F2D8".into(),
+        };
+        let proto = ProtoCoin::from(&coin);
+        assert_eq!(Coin::try_from(&proto).unwrap(), coin);
+        assert_eq!(Coin::try_from(proto).unwrap(), coin);
+
+        let fee = Fee {
+            amount: vec![coin],
+            gas_limit: 200_000,
+            payer: None,
+            granter: Some("cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp".to_string()),
+        };
+        assert_eq!(Fee::try_from(ProtoFee::from(fee.clone())).unwrap(), fee);
+    }
+
+    #[test]
+    fn test_proto_coin_invalid_amount() {
+        let proto = |amount: &str| ProtoCoin {
+            denom: "uatom".to_string(),
+            amount: amount.to_string(),
+        };
+        for amount in [
+            "",
+            "-5",
+            "1.5",
+            "0x10",
+            " 10",
+            // 2^256
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                Coin::try_from(proto(amount)),
+                Err(CoinError::InvalidAmount(amount.to_string()))
+            );
+        }
+        assert_eq!(Coin::try_from(proto("0")).unwrap().amount, 0u8.into());
+    }
+
+    #[test]
+    fn test_dec_coin_from_proto() {
+        let proto = ProtoDecCoin {
+            denom: "uatom".to_string(),
+            amount: "1500000000000000000".to_string(),
+        };
+        let coin = DecCoin::try_from(proto).unwrap();
+        assert_eq!(coin.to_string(), "1.500000000000000000uatom");
+    }
 }
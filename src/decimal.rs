@@ -14,6 +14,7 @@ use std::{
 pub enum DecimalError {
     ExcessivePrecision,
     InvalidPrecision,
+    InvalidEncoding,
     DecimalError(DecimalLibraryError),
 }
 
@@ -26,6 +27,9 @@ impl fmt::Display for DecimalError {
             DecimalError::InvalidPrecision => {
                 write!(f, "Decimal is using an invalid precision must be 0 or 18")
             }
+            DecimalError::InvalidEncoding => {
+                write!(f, "Decimal is not a valid utf8 integer string")
+            }
             DecimalError::DecimalError(v) => {
                 write!(f, "{:?}", v)
             }
@@ -74,6 +78,99 @@ impl Decimal {
         combined_decimal.set_scale(PRECISION)?;
         Ok(Decimal(combined_decimal))
     }
+
+    /// Parses a decimal string with any number of fractional digits up to the
+    /// 18 digits of precision of an `sdk.Dec`, unlike `from_str` which requires the
+    /// input to have either 0 or exactly 18 fractional digits. So "0.025" is accepted.
+    pub fn from_str_lenient(s: &str) -> Result<Self, DecimalError> {
+        let mut value = s.trim().parse::<rust_decimal::Decimal>()?;
+        if value.scale() > PRECISION {
+            return Err(DecimalError::ExcessivePrecision);
+        }
+        value.rescale(PRECISION);
+        value.try_into()
+    }
+
+    /// Parses the encoding used for an `sdk.Dec` in protobuf messages, which is the value
+    /// multiplied by 10^18 and then written as an integer string with no decimal point, for
+    /// example "130000000000000000" is 0.13. Note that this type can only hold around 28
+    /// significant digits, large values such as annual provisions of a micro denom may not fit
+    /// in which case an error is returned.
+    pub fn from_sdk_dec_str(s: &str) -> Result<Self, DecimalError> {
+        let s = s.trim();
+        if s.is_empty() || s.contains('.') {
+            return Err(DecimalError::InvalidEncoding);
+        }
+        let mut value = s.parse::<rust_decimal::Decimal>()?;
+        value.set_scale(PRECISION)?;
+        Ok(Decimal(value))
+    }
+
+    /// Same as `from_sdk_dec_str` except that values with more significant digits than this
+    /// type can hold have their fractional digits rounded instead of being rejected, for values
+    /// such as delegation shares that only need to be approximately right when large. An error
+    /// is still returned if the integer part does not fit
+    pub fn from_sdk_dec_str_rounded(s: &str) -> Result<Self, DecimalError> {
+        if let Ok(value) = Decimal::from_sdk_dec_str(s) {
+            return Ok(value);
+        }
+        let s = s.trim();
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(DecimalError::InvalidEncoding);
+        }
+        let padded = format!("{:0>width$}", s, width = PRECISION as usize + 1);
+        let (integer, fraction) = padded.split_at(padded.len() - PRECISION as usize);
+        Ok(Decimal(format!("{}.{}", integer, fraction).parse()?))
+    }
+
+    /// Same as `from_sdk_dec_str` for responses where the `sdk.Dec` is provided as bytes
+    pub fn from_sdk_dec_bytes(bytes: &[u8]) -> Result<Self, DecimalError> {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Decimal::from_sdk_dec_str(s),
+            Err(_) => Err(DecimalError::InvalidEncoding),
+        }
+    }
+
+    /// Encodes this decimal the way an `sdk.Dec` is encoded in protobuf messages, the inverse
+    /// of `from_sdk_dec_str`, for example 0.7 is "700000000000000000"
+    pub fn to_sdk_dec_string(&self) -> String {
+        let mut value = self.0;
+        value.rescale(PRECISION);
+        // values too large to have 18 fractional digits keep a lower scale, see
+        // `from_sdk_dec_str_rounded`
+        let zeros = (PRECISION - value.scale()) as usize;
+        format!("{}{}", value.mantissa(), "0".repeat(zeros))
+    }
+
+    /// Adds two decimals, returns None on overflow
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        Some(Decimal(self.0.checked_add(other.0)?))
+    }
+
+    /// Multiplies this decimal by an integer and rounds the result up to the next
+    /// whole number. This is the rounding you want when computing a fee from a
+    /// per unit price, the Cosmos SDK also rounds up. Returns None if this decimal
+    /// is negative or the result does not fit in a u128
+    pub fn checked_mul_ceil(&self, value: u64) -> Option<u128> {
+        use rust_decimal::prelude::ToPrimitive;
+        if self.0.is_sign_negative() {
+            return None;
+        }
+        let value: rust_decimal::Decimal = value.into();
+        self.0.checked_mul(value)?.ceil().to_u128()
+    }
+
+    /// Divides an integer by this decimal and rounds the result up to the next whole
+    /// number, for example to convert a fee into another denom at a price. Returns None
+    /// if this decimal is not positive or the result does not fit in a u128
+    pub fn checked_div_ceil(&self, value: u128) -> Option<u128> {
+        use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+        if self.0.is_sign_negative() || self.0.is_zero() {
+            return None;
+        }
+        let value = rust_decimal::Decimal::from_u128(value)?;
+        value.checked_div(self.0)?.ceil().to_u128()
+    }
 }
 
 impl Debug for Decimal {
@@ -135,4 +232,71 @@ mod tests {
         let num = Decimal::from(-1i8);
         assert_eq!(num.to_string(), "-1.000000000000000000")
     }
+
+    #[test]
+    fn sdk_dec_string_test() {
+        let weight = Decimal::from_str_lenient("0.7").unwrap();
+        assert_eq!(weight.to_sdk_dec_string(), "700000000000000000");
+        assert_eq!(
+            Decimal::from_sdk_dec_str(&weight.to_sdk_dec_string()).unwrap(),
+            weight
+        );
+        assert_eq!(
+            weight
+                .checked_add(&Decimal::from_str_lenient("0.3").unwrap())
+                .unwrap(),
+            Decimal::from(1u8)
+        );
+    }
+
+    #[test]
+    fn lenient_parse_and_ceil_test() {
+        let price = Decimal::from_str_lenient("0.025").unwrap();
+        assert_eq!(price.to_string(), "0.025000000000000000");
+        assert_eq!(price.checked_mul_ceil(200_000), Some(5000));
+        // 0.025 * 200_001 = 5000.025 which must be rounded up, not down
+        assert_eq!(price.checked_mul_ceil(200_001), Some(5001));
+
+        // large prices such as those used by 18 decimal chains must not lose precision
+        let price = Decimal::from_str_lenient("25000000000").unwrap();
+        assert_eq!(
+            price.checked_mul_ceil(1_000_007),
+            Some(25_000_175_000_000_000)
+        );
+
+        assert!(Decimal::from_str_lenient("0.0000000000000000001").is_err());
+        assert_eq!(Decimal::from(-1i8).checked_mul_ceil(1), None);
+    }
+
+    #[test]
+    fn sdk_dec_str_test() {
+        let inflation = Decimal::from_sdk_dec_bytes(b"130000000000000000").unwrap();
+        assert_eq!(inflation, Decimal::from_str_lenient("0.13").unwrap());
+        let big = Decimal::from_sdk_dec_str("1000000000000000000000").unwrap();
+        assert_eq!(big, Decimal::from(1000u64));
+        assert!(Decimal::from_sdk_dec_str("0.13").is_err());
+        assert!(Decimal::from_sdk_dec_str("").is_err());
+        assert!(Decimal::from_sdk_dec_bytes(&[0xff, 0xfe]).is_err());
+        // far too large to be held with 18 digits of precision
+        assert!(Decimal::from_sdk_dec_str("100000000000000000000000000000000000000").is_err());
+        let big = "100000000000000000000000000000000000000";
+        assert_eq!(
+            Decimal::from_sdk_dec_str_rounded(big)
+                .unwrap()
+                .to_sdk_dec_string(),
+            big
+        );
+        assert_eq!(
+            Decimal::from_sdk_dec_str_rounded("100000000000000000000000000000000000007")
+                .unwrap()
+                .to_sdk_dec_string(),
+            big
+        );
+        assert_eq!(
+            Decimal::from_sdk_dec_str_rounded("130000000000000000").unwrap(),
+            inflation
+        );
+        assert!(Decimal::from_sdk_dec_str_rounded("0.13").is_err());
+        assert!(Decimal::from_sdk_dec_str_rounded(&"9".repeat(60)).is_err());
+    }
 }
@@ -1,8 +1,11 @@
+use crate::coin::CoinError;
 use crate::mnemonic::Language;
 use crate::utils::FeeInfo;
 use base64::DecodeError as Base64DecodeError;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
 use fmt::Debug;
+use num256::Uint256;
 use num_bigint::ParseBigIntError;
 use prost::DecodeError;
 use prost::EncodeError;
@@ -23,18 +26,95 @@ pub enum CosmosGrpcError {
     NoToken,
     BadResponse(String),
     BadStruct(String),
-    SigningError { error: PrivateKeyError },
-    ConnectionError { error: TonicError },
-    RequestError { error: Status },
-    DecodeError { error: DecodeError },
+    SigningError {
+        error: PrivateKeyError,
+    },
+    ConnectionError {
+        error: TonicError,
+    },
+    RequestError {
+        error: Status,
+    },
+    DecodeError {
+        error: DecodeError,
+    },
+    AddressError {
+        error: AddressError,
+    },
     BadInput(String),
     ChainNotRunning,
     NodeNotSynced,
     InvalidPrefix,
-    NoBlockProduced { time: Duration },
-    TransactionFailed { tx: TxResponse, time: Duration },
-    InsufficientFees { fee_info: FeeInfo },
-    ParseError { error: ParseBigIntError },
+    NoBlockProduced {
+        time: Duration,
+    },
+    TransactionFailed {
+        tx: TxResponse,
+        time: Duration,
+    },
+    InsufficientFees {
+        fee_info: FeeInfo,
+    },
+    ParseError {
+        error: ParseBigIntError,
+    },
+    OutOfGas {
+        gas_wanted: u64,
+        gas_used: u64,
+    },
+    Timeout {
+        time: Duration,
+        tx_hash: Option<String>,
+    },
+    /// The chain did not reach `target` in `time`, `height` is the last height seen if any
+    HeightTimeout {
+        target: u64,
+        height: Option<u64>,
+        time: Duration,
+    },
+    PrefixMismatch {
+        expected: String,
+        found: String,
+    },
+    ModuleNotPresent(String),
+    UnsupportedMessage(String),
+    MemoTooLong {
+        limit: u64,
+    },
+    NotFound(String),
+    InvalidTxHash(String),
+    MissingSigner {
+        address: String,
+    },
+    HeightNotAvailable {
+        height: u64,
+        message: String,
+    },
+    CooldownActive {
+        retry_after: Duration,
+    },
+    SimulationUnsupported,
+    HeightPruned {
+        height: u64,
+        message: String,
+    },
+    InsufficientFunds {
+        denom: String,
+        needed: Uint256,
+        available: Uint256,
+    },
+    /// The transaction is neither on chain nor in the mempool of the node, it will not be
+    /// included unless it's broadcast again
+    TxDropped {
+        tx_hash: String,
+    },
+    /// The transaction was not approved by the confirm callback, see `ConfirmTx`
+    UserRejected,
+    /// The transaction was included in a block but failed during execution, it's state
+    /// changes were reverted but the fee was paid and the sequence consumed
+    ExecutionFailed {
+        tx: TxResponse,
+    },
 }
 
 impl Display for CosmosGrpcError {
@@ -83,9 +163,129 @@ impl Display for CosmosGrpcError {
             CosmosGrpcError::InsufficientFees { fee_info } => {
                 write!(f, "Insufficient fees or gas for transaction {:?}", fee_info)
             }
+            CosmosGrpcError::AddressError { error } => {
+                write!(f, "CosmosGrpc invalid address {}", error)
+            }
             CosmosGrpcError::ParseError { error } => {
                 write!(f, "Failed to Parse BigInt {:?}", error)
             }
+            CosmosGrpcError::OutOfGas {
+                gas_wanted,
+                gas_used,
+            } => {
+                write!(
+                    f,
+                    "CosmosGrpc Transaction ran out of gas, wanted {} used {}",
+                    gas_wanted, gas_used
+                )
+            }
+            CosmosGrpcError::Timeout { time, tx_hash } => match tx_hash {
+                Some(hash) => write!(
+                    f,
+                    "CosmosGrpc Timeout after {}ms for tx {}",
+                    time.as_millis(),
+                    hash
+                ),
+                None => write!(f, "CosmosGrpc Timeout after {}ms", time.as_millis()),
+            },
+            CosmosGrpcError::HeightTimeout {
+                target,
+                height,
+                time,
+            } => match height {
+                Some(height) => write!(
+                    f,
+                    "CosmosGrpc height {} not reached after {}ms, reached {}",
+                    target,
+                    time.as_millis(),
+                    height
+                ),
+                None => write!(
+                    f,
+                    "CosmosGrpc height {} not reached after {}ms",
+                    target,
+                    time.as_millis()
+                ),
+            },
+            CosmosGrpcError::NotFound(val) => write!(f, "CosmosGrpc not found {}", val),
+            CosmosGrpcError::HeightNotAvailable { height, message } => {
+                write!(
+                    f,
+                    "CosmosGrpc node has no state at height {}, it may be pruned {}",
+                    height, message
+                )
+            }
+            CosmosGrpcError::MissingSigner { address } => {
+                write!(f, "CosmosGrpc no signature provided for {}", address)
+            }
+            CosmosGrpcError::MemoTooLong { limit } => {
+                write!(f, "CosmosGrpc memo is longer than the limit of {}", limit)
+            }
+            CosmosGrpcError::ModuleNotPresent(module) => {
+                write!(
+                    f,
+                    "CosmosGrpc the {} module is not present on this chain",
+                    module
+                )
+            }
+            CosmosGrpcError::InvalidTxHash(hash) => {
+                write!(f, "CosmosGrpc {:?} is not a valid tx hash", hash)
+            }
+            CosmosGrpcError::UnsupportedMessage(type_url) => {
+                write!(f, "CosmosGrpc {} is not supported by this chain", type_url)
+            }
+            CosmosGrpcError::PrefixMismatch { expected, found } => {
+                write!(
+                    f,
+                    "CosmosGrpc Address has prefix {} but this chain uses {}",
+                    found, expected
+                )
+            }
+            CosmosGrpcError::CooldownActive { retry_after } => {
+                write!(
+                    f,
+                    "CosmosGrpc cooldown active, retry in {}s",
+                    retry_after.as_secs()
+                )
+            }
+            CosmosGrpcError::SimulationUnsupported => {
+                write!(f, "CosmosGrpc node does not support tx simulation")
+            }
+            CosmosGrpcError::HeightPruned { height, message } => {
+                write!(
+                    f,
+                    "CosmosGrpc node has pruned the state at height {}, query an archive node {}",
+                    height, message
+                )
+            }
+            CosmosGrpcError::InsufficientFunds {
+                denom,
+                needed,
+                available,
+            } => {
+                write!(
+                    f,
+                    "CosmosGrpc insufficient funds, needed {}{} but only {}{} is spendable",
+                    needed, denom, available, denom
+                )
+            }
+            CosmosGrpcError::TxDropped { tx_hash } => {
+                write!(
+                    f,
+                    "CosmosGrpc tx {} was dropped from the mempool without being included",
+                    tx_hash
+                )
+            }
+            CosmosGrpcError::UserRejected => {
+                write!(f, "CosmosGrpc transaction rejected by the confirm callback")
+            }
+            CosmosGrpcError::ExecutionFailed { tx } => {
+                write!(
+                    f,
+                    "CosmosGrpc tx {} failed during execution with code {} {} {}",
+                    tx.txhash, tx.code, tx.codespace, tx.raw_log
+                )
+            }
         }
     }
 }
@@ -116,6 +316,33 @@ impl From<DecodeError> for CosmosGrpcError {
     }
 }
 
+impl From<AddressError> for CosmosGrpcError {
+    fn from(error: AddressError) -> Self {
+        CosmosGrpcError::AddressError { error }
+    }
+}
+
+impl From<bech32::Error> for CosmosGrpcError {
+    fn from(error: bech32::Error) -> Self {
+        CosmosGrpcError::AddressError {
+            error: error.into(),
+        }
+    }
+}
+
+impl From<ParseBigIntError> for CosmosGrpcError {
+    fn from(error: ParseBigIntError) -> Self {
+        CosmosGrpcError::ParseError { error }
+    }
+}
+
+/// Coins are only converted from the sdk representation when parsing responses
+impl From<CoinError> for CosmosGrpcError {
+    fn from(error: CoinError) -> Self {
+        CosmosGrpcError::BadResponse(error.to_string())
+    }
+}
+
 impl From<PrivateKeyError> for CosmosGrpcError {
     fn from(error: PrivateKeyError) -> Self {
         CosmosGrpcError::SigningError { error }
@@ -130,7 +357,10 @@ pub enum AddressError {
     HexDecodeError(ByteDecodeError),
     HexDecodeErrorWrongLength,
     PrefixTooLong(ArrayStringError),
+    PrefixMixedCase,
+    PrefixMismatch { expected: String, found: String },
     BytesDecodeErrorWrongLength,
+    InvalidPublicKey,
 }
 
 impl fmt::Display for AddressError {
@@ -142,7 +372,12 @@ impl fmt::Display for AddressError {
             AddressError::HexDecodeError(val) => write!(f, "HexDecodeError {}", val),
             AddressError::HexDecodeErrorWrongLength => write!(f, "HexDecodeError Wrong Length"),
             AddressError::PrefixTooLong(val) => write!(f, "Prefix too long {}", val),
+            AddressError::PrefixMixedCase => write!(f, "Prefix mixes upper and lower case"),
+            AddressError::PrefixMismatch { expected, found } => {
+                write!(f, "Expected prefix {} found {}", expected, found)
+            }
             AddressError::BytesDecodeErrorWrongLength => write!(f, "BytesDecodeError Wrong Length"),
+            AddressError::InvalidPublicKey => write!(f, "Public key is not a valid curve point"),
         }
     }
 }
@@ -163,7 +398,7 @@ impl From<bech32::Error> for AddressError {
             bech32::Error::InvalidData(_) => AddressError::Bech32InvalidEncoding,
             bech32::Error::InvalidChecksum => AddressError::Bech32InvalidEncoding,
             bech32::Error::InvalidPadding => AddressError::Bech32InvalidEncoding,
-            bech32::Error::MixedCase => AddressError::Bech32InvalidEncoding,
+            bech32::Error::MixedCase => AddressError::PrefixMixedCase,
             bech32::Error::MissingSeparator => AddressError::Bech32InvalidEncoding,
         }
     }
@@ -196,6 +431,8 @@ pub enum PublicKeyError {
     HexDecodeErrorWrongLength,
     BytesDecodeErrorWrongLength,
     PrefixTooLong(ArrayStringError),
+    InvalidMultisigThreshold { threshold: u32, keys: usize },
+    AddressError(AddressError),
 }
 
 impl fmt::Display for PublicKeyError {
@@ -211,6 +448,12 @@ impl fmt::Display for PublicKeyError {
             }
             PublicKeyError::HexDecodeErrorWrongLength => write!(f, "HexDecodeError Wrong Length"),
             PublicKeyError::PrefixTooLong(val) => write!(f, "Prefix too long {}", val),
+            PublicKeyError::InvalidMultisigThreshold { threshold, keys } => write!(
+                f,
+                "Invalid multisig threshold {} for {} keys",
+                threshold, keys
+            ),
+            PublicKeyError::AddressError(val) => write!(f, "AddressError {}", val),
         }
     }
 }
@@ -223,6 +466,12 @@ impl From<ArrayStringError> for PublicKeyError {
     }
 }
 
+impl From<AddressError> for PublicKeyError {
+    fn from(error: AddressError) -> Self {
+        PublicKeyError::AddressError(error)
+    }
+}
+
 impl From<bech32::Error> for PublicKeyError {
     fn from(error: bech32::Error) -> Self {
         match error {
@@ -246,6 +495,12 @@ pub enum PrivateKeyError {
     PublicKeyError(PublicKeyError),
     AddressError(AddressError),
     HdWalletError(HdWalletError),
+    AminoJsonError(String),
+    UnsupportedSignMode(SignMode),
+    InvalidAuxSignerData(String),
+    /// The transaction given to `PrivateKey::sign_partial` can't be decoded or
+    /// this key is not the signer at the given index
+    InvalidPartialTx(String),
 }
 
 impl fmt::Display for PrivateKeyError {
@@ -258,12 +513,151 @@ impl fmt::Display for PrivateKeyError {
             PrivateKeyError::PublicKeyError(val) => write!(f, "{}", val),
             PrivateKeyError::AddressError(val) => write!(f, "{}", val),
             PrivateKeyError::HdWalletError(val) => write!(f, "{}", val),
+            PrivateKeyError::AminoJsonError(val) => write!(f, "Amino JSON error {}", val),
+            PrivateKeyError::InvalidAuxSignerData(val) => {
+                write!(f, "Invalid AuxSignerData {}", val)
+            }
+            PrivateKeyError::InvalidPartialTx(val) => {
+                write!(f, "Invalid partially signed tx {}", val)
+            }
+            PrivateKeyError::UnsupportedSignMode(val) => {
+                write!(f, "Unsupported signing mode {:?}", val)
+            }
         }
     }
 }
 
 impl std::error::Error for PrivateKeyError {}
 
+/// Reasons a signed transaction fails `verify::verify_tx`
+#[derive(Debug)]
+pub enum TxVerifyError {
+    /// The bytes are not a valid `TxRaw` or it's body or auth info can't be decoded
+    DecodeError(String),
+    /// The number of signatures or account numbers does not match the number of signers
+    SignerCountMismatch {
+        signers: usize,
+        signatures: usize,
+        account_numbers: usize,
+    },
+    /// The signer at this index did not include it's public key in the transaction
+    MissingPubKey(usize),
+    /// The public key of the signer at this index is not a secp256k1 key
+    UnsupportedPubKey { index: usize, type_url: String },
+    /// The signer at this index signed with a mode other than `Direct` or `LegacyAminoJson`
+    UnsupportedSignMode { index: usize, mode: i32 },
+    /// The sign doc could not be reconstructed, for example because a message has no
+    /// Amino JSON encoding
+    SignDocError(PrivateKeyError),
+    /// The signature of the signer at this index does not match
+    InvalidSignature(usize),
+    /// The signers of the tx are not the accounts the messages and fee payer require
+    SignerMismatch {
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    /// A message of this type has signers that are not known, so the signers of the tx can't
+    /// be checked
+    UnknownSigners(String),
+}
+
+impl fmt::Display for TxVerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            TxVerifyError::DecodeError(val) => write!(f, "Could not decode tx {}", val),
+            TxVerifyError::SignerCountMismatch {
+                signers,
+                signatures,
+                account_numbers,
+            } => write!(
+                f,
+                "Tx has {} signers, {} signatures and {} account numbers were provided",
+                signers, signatures, account_numbers
+            ),
+            TxVerifyError::MissingPubKey(index) => {
+                write!(f, "Signer {} did not provide a public key", index)
+            }
+            TxVerifyError::UnsupportedPubKey { index, type_url } => {
+                write!(
+                    f,
+                    "Signer {} has unsupported public key {}",
+                    index, type_url
+                )
+            }
+            TxVerifyError::UnsupportedSignMode { index, mode } => {
+                write!(f, "Signer {} used unsupported sign mode {}", index, mode)
+            }
+            TxVerifyError::SignDocError(val) => write!(f, "Could not build sign doc {}", val),
+            TxVerifyError::InvalidSignature(index) => {
+                write!(f, "Signature of signer {} is invalid", index)
+            }
+            TxVerifyError::SignerMismatch { expected, found } => write!(
+                f,
+                "Tx is signed by {} but requires signatures from {}",
+                found.join(", "),
+                expected.join(", ")
+            ),
+            TxVerifyError::UnknownSigners(type_url) => {
+                write!(f, "Signers of message type {} are not known", type_url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TxVerifyError {}
+
+impl From<PrivateKeyError> for TxVerifyError {
+    fn from(error: PrivateKeyError) -> Self {
+        TxVerifyError::SignDocError(error)
+    }
+}
+
+/// Errors storing or loading a key in the OS secret store, see `PrivateKey::from_keyring`
+#[cfg(feature = "keyring")]
+#[derive(Debug)]
+pub enum KeyringError {
+    /// There is no key stored for this service and account
+    NotFound,
+    /// The secret store is locked or the user denied access
+    AccessDenied(String),
+    /// The stored value is not a valid private key
+    InvalidKey(PrivateKeyError),
+    PlatformFailure(String),
+}
+
+#[cfg(feature = "keyring")]
+impl fmt::Display for KeyringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> FormatResult {
+        match self {
+            KeyringError::NotFound => write!(f, "No key found in the keyring"),
+            KeyringError::AccessDenied(val) => write!(f, "Keyring access denied {}", val),
+            KeyringError::InvalidKey(val) => write!(f, "Invalid key in the keyring {}", val),
+            KeyringError::PlatformFailure(val) => write!(f, "Keyring failure {}", val),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl std::error::Error for KeyringError {}
+
+#[cfg(feature = "keyring")]
+impl From<keyring::Error> for KeyringError {
+    fn from(error: keyring::Error) -> Self {
+        match error {
+            keyring::Error::NoEntry => KeyringError::NotFound,
+            keyring::Error::NoStorageAccess(e) => KeyringError::AccessDenied(e.to_string()),
+            e => KeyringError::PlatformFailure(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl From<PrivateKeyError> for KeyringError {
+    fn from(error: PrivateKeyError) -> Self {
+        KeyringError::InvalidKey(error)
+    }
+}
+
 impl From<CurveError> for PrivateKeyError {
     fn from(error: CurveError) -> Self {
         PrivateKeyError::CurveError(error)
@@ -17,27 +17,38 @@ extern crate log;
 extern crate serde_derive;
 
 pub mod address;
+pub mod amino;
 pub mod client;
 pub mod coin;
 pub mod decimal;
 pub mod error;
+pub mod memo;
 pub mod mnemonic;
 pub mod msg;
+pub mod prelude;
 pub mod private_key;
+pub mod proto_json;
 pub mod public_key;
 pub mod signature;
+pub mod tips;
+pub mod type_registry;
 pub mod utils;
+pub mod verify;
 
 pub use address::Address;
 pub use client::Contact;
 pub use coin::Coin;
+pub use coin::Denom;
 pub use coin::Fee;
+pub use memo::Memo;
 pub use mnemonic::Mnemonic;
+pub use msg::supported_msg_types;
 pub use msg::Msg;
 pub use private_key::MessageArgs;
 pub use private_key::PrivateKey;
-pub use public_key::PublicKey;
+pub use public_key::{multisig_address, multisig_pubkey, MultisigPublicKey, PublicKey};
 pub use signature::Signature;
+pub use type_registry::TypeRegistry;
 
 use cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount;
 use cosmos_sdk_proto::cosmos::vesting::v1beta1::{
@@ -0,0 +1,151 @@
+//! Structured transaction memos. Some modules and services read instructions from the memo,
+//! for example the packet forward middleware (PFM) routes an IBC transfer on to another chain
+//! when the memo holds `{"forward":{...}}`. A `Memo` holds either plain text or a JSON value
+//! and serializes to the canonical string included in the transaction, it can be passed to
+//! any of the send helpers that take a memo.
+
+use serde_json::{Map, Value};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Memo {
+    Text(String),
+    Json(Value),
+}
+
+impl Memo {
+    pub fn text(text: impl Into<String>) -> Memo {
+        Memo::Text(text.into())
+    }
+
+    pub fn json(value: Value) -> Memo {
+        Memo::Json(value)
+    }
+
+    /// A packet forward middleware memo forwarding the received tokens to `receiver` over
+    /// `port` and `channel` of the receiving chain, further options such as `timeout`,
+    /// `retries` or a `next` hop can be added with `with_forward_field`
+    pub fn forward(receiver: &str, port: &str, channel: &str) -> Memo {
+        let mut forward = Map::new();
+        forward.insert("receiver".to_string(), Value::from(receiver));
+        forward.insert("port".to_string(), Value::from(port));
+        forward.insert("channel".to_string(), Value::from(channel));
+        let mut memo = Map::new();
+        memo.insert("forward".to_string(), Value::Object(forward));
+        Memo::Json(Value::Object(memo))
+    }
+
+    /// Sets `key` to `value` in the top level object of a JSON memo, a text memo or a JSON
+    /// memo that isn't an object is replaced by an object holding only `key`
+    pub fn with_field(self, key: &str, value: impl Into<Value>) -> Memo {
+        let mut map = match self {
+            Memo::Json(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        map.insert(key.to_string(), value.into());
+        Memo::Json(Value::Object(map))
+    }
+
+    /// Sets `key` to `value` in the `forward` object of a memo built with `Memo::forward`,
+    /// for example `next` to forward the tokens on over multiple hops
+    pub fn with_forward_field(self, key: &str, value: impl Into<Value>) -> Memo {
+        let mut map = match self {
+            Memo::Json(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        let mut forward = match map.remove("forward") {
+            Some(Value::Object(forward)) => forward,
+            _ => Map::new(),
+        };
+        forward.insert(key.to_string(), value.into());
+        map.insert("forward".to_string(), Value::Object(forward));
+        Memo::Json(Value::Object(map))
+    }
+
+    /// The JSON value of the memo, None for a text memo
+    pub fn as_json(&self) -> Option<&Value> {
+        match self {
+            Memo::Text(_) => None,
+            Memo::Json(value) => Some(value),
+        }
+    }
+
+    /// Returns true if the serialized memo is within the `max_memo_characters` param of the
+    /// chain, the limit is in bytes. See `Contact::check_memo` to check against the chain
+    pub fn is_within_limit(&self, limit: u64) -> bool {
+        self.to_string().len() as u64 <= limit
+    }
+}
+
+impl Default for Memo {
+    fn default() -> Self {
+        Memo::Text(String::new())
+    }
+}
+
+/// Serializes the memo as it's included in the transaction, JSON is written without
+/// whitespace and with the keys of objects sorted
+impl fmt::Display for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Memo::Text(text) => write!(f, "{}", text),
+            Memo::Json(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<&str> for Memo {
+    fn from(text: &str) -> Self {
+        Memo::Text(text.to_string())
+    }
+}
+
+impl From<String> for Memo {
+    fn from(text: String) -> Self {
+        Memo::Text(text)
+    }
+}
+
+impl From<Value> for Memo {
+    fn from(value: Value) -> Self {
+        Memo::Json(value)
+    }
+}
+
+impl From<Memo> for String {
+    fn from(memo: Memo) -> Self {
+        memo.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pfm_memo() {
+        let memo = Memo::forward("osmo1receiver", "transfer", "channel-0")
+            .with_forward_field("timeout", "10m")
+            .with_forward_field("retries", 2)
+            .with_forward_field(
+                "next",
+                Memo::forward("juno1receiver", "transfer", "channel-42")
+                    .as_json()
+                    .unwrap()
+                    .clone(),
+            );
+        let expected = r#"{"forward":{"channel":"channel-0","next":{"forward":{"channel":"channel-42","port":"transfer","receiver":"juno1receiver"}},"port":"transfer","receiver":"osmo1receiver","retries":2,"timeout":"10m"}}"#;
+        assert_eq!(memo.to_string(), expected);
+        let value: Value = serde_json::from_str(&String::from(memo.clone())).unwrap();
+        assert_eq!(value["forward"]["next"]["forward"]["channel"], "channel-42");
+        assert!(memo.is_within_limit(expected.len() as u64));
+        assert!(!memo.is_within_limit(expected.len() as u64 - 1));
+
+        assert_eq!(Memo::from("plain").to_string(), "plain");
+        assert_eq!(
+            Memo::text("plain").with_field("id", 7).to_string(),
+            r#"{"id":7}"#
+        );
+        assert_eq!(Memo::default().to_string(), "");
+    }
+}
@@ -1,8 +1,20 @@
 //! Transaction messages
 
+use cosmos_sdk_proto::cosmos::bank::v1beta1::{MsgMultiSend, MsgSend};
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
+    MsgSetWithdrawAddress, MsgWithdrawDelegatorReward,
+};
+use cosmos_sdk_proto::cosmos::gov::v1beta1::{MsgDeposit, MsgSubmitProposal, MsgVote};
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgBeginRedelegate, MsgDelegate, MsgUndelegate};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{GetTxResponse, Tx};
+use cosmos_sdk_proto::cosmwasm::wasm::v1beta1::MsgExecuteContract;
+use cosmos_sdk_proto::ibc::applications::transfer::v1::MsgTransfer;
+use prost::DecodeError;
 use prost_types::Any;
 
-use crate::utils::encode_any;
+use crate::proto_json::any_to_json;
+use crate::utils::{decode_any, encode_any};
 
 /// Transaction messages, encoded to allow arbitrary payloads
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +26,239 @@ impl Msg {
         let any = encode_any(value, type_url);
         Msg(any)
     }
+
+    /// Wraps an already encoded message, useful for message types this crate does not know.
+    /// The value is not checked, only that the type url is not empty
+    pub fn from_any(any: Any) -> Result<Self, String> {
+        if any.type_url.trim().is_empty() {
+            return Err("Message has an empty type url".to_string());
+        }
+        Ok(Msg(any))
+    }
+}
+
+impl Msg {
+    /// The type url of this message, for example `/cosmos.bank.v1beta1.MsgSend`
+    pub fn type_url(&self) -> &str {
+        &self.0.type_url
+    }
+
+    /// Decodes this message into it's concrete type, see `DecodedMsg`
+    pub fn decode(&self) -> DecodedMsg {
+        DecodedMsg::decode(&self.0)
+    }
+
+    /// Encodes this message as proto3 JSON with it's type url in the `@type` field, as printed
+    /// by the Cosmos SDK CLI, see `proto_json::any_to_json` for the types supported
+    pub fn to_json(&self) -> serde_json::Value {
+        any_to_json(&self.0)
+    }
+}
+
+/// A transaction message decoded from it's Any encoding, the type url is always
+/// kept so that messages of unknown types can still be identified by the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedMsg {
+    pub type_url: String,
+    pub value: DecodedMsgValue,
+}
+
+/// The concrete value of a decoded transaction message, messages with a type
+/// url this crate does not know about, or that do not decode as the type of
+/// their type url, are returned as the raw Any
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedMsgValue {
+    Send(MsgSend),
+    MultiSend(MsgMultiSend),
+    Delegate(MsgDelegate),
+    Undelegate(MsgUndelegate),
+    BeginRedelegate(MsgBeginRedelegate),
+    WithdrawDelegatorReward(MsgWithdrawDelegatorReward),
+    SubmitProposal(MsgSubmitProposal),
+    Vote(MsgVote),
+    Deposit(MsgDeposit),
+    IbcTransfer(MsgTransfer),
+    WasmExecute(MsgExecuteContract),
+    Unknown(Any),
+}
+
+impl DecodedMsg {
+    /// Decodes an Any into a known message type, if the type url is unknown or the value
+    /// does not decode as that type the message is kept as `DecodedMsgValue::Unknown`
+    pub fn decode(any: &Any) -> DecodedMsg {
+        DecodedMsg {
+            type_url: any.type_url.clone(),
+            value: Self::decode_known(any)
+                .unwrap_or_else(|_| DecodedMsgValue::Unknown(any.clone())),
+        }
+    }
+
+    fn decode_known(any: &Any) -> Result<DecodedMsgValue, DecodeError> {
+        let value = match any.type_url.as_str() {
+            "/cosmos.bank.v1beta1.MsgSend" => DecodedMsgValue::Send(decode_any(any)?),
+            "/cosmos.bank.v1beta1.MsgMultiSend" => DecodedMsgValue::MultiSend(decode_any(any)?),
+            "/cosmos.staking.v1beta1.MsgDelegate" => DecodedMsgValue::Delegate(decode_any(any)?),
+            "/cosmos.staking.v1beta1.MsgUndelegate" => {
+                DecodedMsgValue::Undelegate(decode_any(any)?)
+            }
+            "/cosmos.staking.v1beta1.MsgBeginRedelegate" => {
+                DecodedMsgValue::BeginRedelegate(decode_any(any)?)
+            }
+            "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward" => {
+                DecodedMsgValue::WithdrawDelegatorReward(decode_any(any)?)
+            }
+            "/cosmos.gov.v1beta1.MsgSubmitProposal" => {
+                DecodedMsgValue::SubmitProposal(decode_any(any)?)
+            }
+            "/cosmos.gov.v1beta1.MsgVote" => DecodedMsgValue::Vote(decode_any(any)?),
+            "/cosmos.gov.v1beta1.MsgDeposit" => DecodedMsgValue::Deposit(decode_any(any)?),
+            "/ibc.applications.transfer.v1.MsgTransfer" => {
+                DecodedMsgValue::IbcTransfer(decode_any(any)?)
+            }
+            // the encoding of MsgExecuteContract is the same in both versions
+            "/cosmwasm.wasm.v1beta1.MsgExecuteContract"
+            | "/cosmwasm.wasm.v1.MsgExecuteContract" => {
+                DecodedMsgValue::WasmExecute(decode_any(any)?)
+            }
+            _ => DecodedMsgValue::Unknown(any.clone()),
+        };
+        Ok(value)
+    }
+
+    /// The addresses that must sign this message, in the order the sdk expects their
+    /// signatures. None for messages of unknown type
+    pub fn signers(&self) -> Option<Vec<String>> {
+        let signers = match &self.value {
+            DecodedMsgValue::Send(msg) => vec![msg.from_address.clone()],
+            DecodedMsgValue::MultiSend(msg) => {
+                msg.inputs.iter().map(|i| i.address.clone()).collect()
+            }
+            DecodedMsgValue::Delegate(msg) => vec![msg.delegator_address.clone()],
+            DecodedMsgValue::Undelegate(msg) => vec![msg.delegator_address.clone()],
+            DecodedMsgValue::BeginRedelegate(msg) => vec![msg.delegator_address.clone()],
+            DecodedMsgValue::WithdrawDelegatorReward(msg) => vec![msg.delegator_address.clone()],
+            DecodedMsgValue::SubmitProposal(msg) => vec![msg.proposer.clone()],
+            DecodedMsgValue::Vote(msg) => vec![msg.voter.clone()],
+            DecodedMsgValue::Deposit(msg) => vec![msg.depositor.clone()],
+            DecodedMsgValue::IbcTransfer(msg) => vec![msg.sender.clone()],
+            DecodedMsgValue::WasmExecute(msg) => vec![msg.sender.clone()],
+            DecodedMsgValue::Unknown(_) => return None,
+        };
+        Some(signers)
+    }
+}
+
+/// Collects the distinct signers of a set of messages in order of first appearance, this is
+/// the order the sdk requires `signer_infos` and signatures to be in for a transaction with
+/// more than one signer. Returns None if any of the messages is of unknown type.
+pub fn required_signers(messages: &[DecodedMsg]) -> Option<Vec<String>> {
+    let mut signers: Vec<String> = Vec::new();
+    for msg in messages {
+        for signer in msg.signers()? {
+            if !signers.contains(&signer) {
+                signers.push(signer);
+            }
+        }
+    }
+    Some(signers)
+}
+
+/// Decodes all the messages in the body of a transaction
+pub fn decode_tx_messages(tx: &Tx) -> Vec<DecodedMsg> {
+    match &tx.body {
+        Some(body) => body.messages.iter().map(DecodedMsg::decode).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Decodes all the messages in a transaction returned by `Contact::get_raw_tx_by_hash`
+pub fn decode_get_tx_response(response: &GetTxResponse) -> Vec<DecodedMsg> {
+    match &response.tx {
+        Some(tx) => decode_tx_messages(tx),
+        None => Vec::new(),
+    }
+}
+
+/// Decodes all the messages in the transaction included in a TxResponse, the tx field
+/// is only populated by queries and not by broadcasting
+pub fn decode_tx_response(response: &TxResponse) -> Result<Vec<DecodedMsg>, DecodeError> {
+    match &response.tx {
+        Some(tx) => Ok(decode_tx_messages(&decode_any(tx)?)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// A message type with a known type url, `Msg` can be created from any of these with `From`
+/// without providing the type url. Implement it for message types of other modules to do the
+/// same with `Msg::from_typed`
+pub trait MsgType: prost::Message + Sized {
+    /// The type url the message is encoded with, for example `/cosmos.bank.v1beta1.MsgSend`
+    const TYPE_URL: &'static str;
+}
+
+impl Msg {
+    /// Create a new transaction message of a type with a known type url
+    pub fn from_typed<V: MsgType>(value: V) -> Self {
+        Msg::new(V::TYPE_URL, value)
+    }
+}
+
+macro_rules! impl_msg_type {
+    ($($msg:ty => $type_url:expr,)*) => {
+        $(
+            impl MsgType for $msg {
+                const TYPE_URL: &'static str = $type_url;
+            }
+
+            impl From<$msg> for Msg {
+                fn from(value: $msg) -> Msg {
+                    Msg::from_typed(value)
+                }
+            }
+        )*
+    };
+}
+
+// MsgExecuteContract is left out as chains disagree on the version of it's type url
+impl_msg_type! {
+    MsgSend => "/cosmos.bank.v1beta1.MsgSend",
+    MsgMultiSend => "/cosmos.bank.v1beta1.MsgMultiSend",
+    MsgDelegate => "/cosmos.staking.v1beta1.MsgDelegate",
+    MsgUndelegate => "/cosmos.staking.v1beta1.MsgUndelegate",
+    MsgBeginRedelegate => "/cosmos.staking.v1beta1.MsgBeginRedelegate",
+    MsgWithdrawDelegatorReward => "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward",
+    MsgSetWithdrawAddress => "/cosmos.distribution.v1beta1.MsgSetWithdrawAddress",
+    MsgSubmitProposal => "/cosmos.gov.v1beta1.MsgSubmitProposal",
+    MsgVote => "/cosmos.gov.v1beta1.MsgVote",
+    MsgDeposit => "/cosmos.gov.v1beta1.MsgDeposit",
+    MsgTransfer => "/ibc.applications.transfer.v1.MsgTransfer",
+}
+
+/// The type urls of every message this crate has a typed helper or decoder for, either a
+/// `MsgType`, a variant of `DecodedMsgValue` or a send helper on `Contact`. Messages of other
+/// types can still be sent as a raw `Any` with `Msg::new` but are not checked locally, for
+/// example their signers are left for the chain to validate
+pub fn supported_msg_types() -> &'static [&'static str] {
+    &[
+        "/cosmos.bank.v1beta1.MsgSend",
+        "/cosmos.bank.v1beta1.MsgMultiSend",
+        "/cosmos.staking.v1beta1.MsgDelegate",
+        "/cosmos.staking.v1beta1.MsgUndelegate",
+        "/cosmos.staking.v1beta1.MsgBeginRedelegate",
+        "/cosmos.staking.v1beta1.MsgCancelUnbondingDelegation",
+        "/cosmos.staking.v1beta1.MsgCreateValidator",
+        "/cosmos.staking.v1beta1.MsgEditValidator",
+        "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward",
+        "/cosmos.distribution.v1beta1.MsgSetWithdrawAddress",
+        "/cosmos.gov.v1beta1.MsgSubmitProposal",
+        "/cosmos.gov.v1beta1.MsgVote",
+        "/cosmos.gov.v1beta1.MsgVoteWeighted",
+        "/cosmos.gov.v1beta1.MsgDeposit",
+        "/cosmos.authz.v1beta1.MsgExec",
+        "/ibc.applications.transfer.v1.MsgTransfer",
+        "/cosmwasm.wasm.v1beta1.MsgExecuteContract",
+        "/cosmwasm.wasm.v1.MsgExecuteContract",
+    ]
 }
 
 impl From<Any> for Msg {
@@ -27,3 +272,190 @@ impl From<Msg> for Any {
         msg.0
     }
 }
+
+#[test]
+fn test_decode_tx_messages() {
+    use crate::coin::Coin;
+    use crate::utils::encode_any;
+    use cosmos_sdk_proto::cosmos::tx::v1beta1::TxBody;
+
+    let send = MsgSend {
+        from_address: "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a".to_string(),
+        to_address: "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp".to_string(),
+        amount: vec![Coin::new(5u8.into(), "uatom").into()],
+    };
+    let unknown = Any {
+        type_url: "/some.module.MsgUnknown".to_string(),
+        value: vec![1, 2, 3],
+    };
+    let tx = Tx {
+        body: Some(TxBody {
+            messages: vec![
+                Msg::new("/cosmos.bank.v1beta1.MsgSend", send.clone()).into(),
+                unknown.clone(),
+            ],
+            memo: String::new(),
+            timeout_height: 0,
+            extension_options: Vec::new(),
+            non_critical_extension_options: Vec::new(),
+        }),
+        auth_info: None,
+        signatures: Vec::new(),
+    };
+    let response = TxResponse {
+        tx: Some(encode_any(tx, "/cosmos.tx.v1beta1.Tx")),
+        ..Default::default()
+    };
+
+    let decoded = decode_tx_response(&response).unwrap();
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoded[0].type_url, "/cosmos.bank.v1beta1.MsgSend");
+    assert_eq!(decoded[0].value, DecodedMsgValue::Send(send));
+    assert_eq!(decoded[1].type_url, "/some.module.MsgUnknown");
+    assert_eq!(decoded[1].value, DecodedMsgValue::Unknown(unknown));
+
+    // a known type url with a bad value is kept as is rather than failing the whole tx
+    let bad = Any {
+        type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+        value: vec![0xff],
+    };
+    let decoded = DecodedMsg::decode(&bad);
+    assert_eq!(decoded.type_url, "/cosmos.bank.v1beta1.MsgSend");
+    assert_eq!(decoded.value, DecodedMsgValue::Unknown(bad));
+}
+
+#[test]
+fn test_required_signers() {
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::Input;
+
+    let a = "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a".to_string();
+    let b = "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp".to_string();
+    let vote = Msg::new(
+        "/cosmos.gov.v1beta1.MsgVote",
+        MsgVote {
+            proposal_id: 1,
+            voter: b.clone(),
+            option: 1,
+        },
+    );
+    let multi = Msg::new(
+        "/cosmos.bank.v1beta1.MsgMultiSend",
+        MsgMultiSend {
+            inputs: vec![
+                Input {
+                    address: a.clone(),
+                    coins: Vec::new(),
+                },
+                Input {
+                    address: b.clone(),
+                    coins: Vec::new(),
+                },
+            ],
+            outputs: Vec::new(),
+        },
+    );
+    let decoded: Vec<DecodedMsg> = [vote, multi].iter().map(|m| m.decode()).collect();
+    // first appearance order with duplicates removed
+    assert_eq!(required_signers(&decoded), Some(vec![b, a]));
+
+    let unknown = DecodedMsg::decode(&Any {
+        type_url: "/some.module.MsgUnknown".to_string(),
+        value: Vec::new(),
+    });
+    assert_eq!(required_signers(&[unknown]), None);
+}
+
+#[test]
+fn test_msg_from_any() {
+    let any = Any {
+        type_url: "/my.module.v1.MsgThing".to_string(),
+        value: vec![1, 2, 3],
+    };
+    let msg = Msg::from_any(any.clone()).unwrap();
+    assert_eq!(msg.type_url(), "/my.module.v1.MsgThing");
+    assert_eq!(msg.0, any);
+    assert!(Msg::from_any(Any {
+        type_url: String::new(),
+        value: vec![1]
+    })
+    .is_err());
+}
+
+#[test]
+fn test_msg_from_typed() {
+    let vote = MsgVote {
+        proposal_id: 1,
+        voter: "cosmos1vlms2r8f6x7yxjh3ynyzc7ckarqd8a96ckjvrp".to_string(),
+        option: 1,
+    };
+    let msg: Msg = vote.clone().into();
+    assert_eq!(msg, Msg::new("/cosmos.gov.v1beta1.MsgVote", vote.clone()));
+    assert_eq!(msg.decode().value, DecodedMsgValue::Vote(vote));
+
+    // every type url must round trip through decoding as the same type
+    let msgs: Vec<Msg> = vec![
+        MsgSend::default().into(),
+        MsgMultiSend::default().into(),
+        MsgDelegate::default().into(),
+        MsgUndelegate::default().into(),
+        MsgBeginRedelegate::default().into(),
+        MsgWithdrawDelegatorReward::default().into(),
+        MsgSubmitProposal::default().into(),
+        MsgDeposit::default().into(),
+        MsgTransfer::default().into(),
+    ];
+    for msg in msgs {
+        assert!(!matches!(msg.decode().value, DecodedMsgValue::Unknown(_)));
+    }
+}
+
+#[test]
+fn test_supported_msg_types() {
+    use crate::client::authz::MSG_EXEC_TYPE_URL;
+    use crate::client::distribution::MSG_SET_WITHDRAW_ADDRESS_TYPE_URL;
+    use crate::client::gov::MSG_VOTE_WEIGHTED_TYPE_URL;
+    use crate::client::staking::{
+        MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL, MSG_CREATE_VALIDATOR_TYPE_URL,
+        MSG_EDIT_VALIDATOR_TYPE_URL,
+    };
+
+    let supported = supported_msg_types();
+    let typed = [
+        MsgSend::TYPE_URL,
+        MsgMultiSend::TYPE_URL,
+        MsgDelegate::TYPE_URL,
+        MsgUndelegate::TYPE_URL,
+        MsgBeginRedelegate::TYPE_URL,
+        MsgWithdrawDelegatorReward::TYPE_URL,
+        MsgSetWithdrawAddress::TYPE_URL,
+        MsgSubmitProposal::TYPE_URL,
+        MsgVote::TYPE_URL,
+        MsgDeposit::TYPE_URL,
+        MsgTransfer::TYPE_URL,
+    ];
+    let helpers = [
+        MSG_EXEC_TYPE_URL,
+        MSG_SET_WITHDRAW_ADDRESS_TYPE_URL,
+        MSG_VOTE_WEIGHTED_TYPE_URL,
+        MSG_CANCEL_UNBONDING_DELEGATION_TYPE_URL,
+        MSG_CREATE_VALIDATOR_TYPE_URL,
+        MSG_EDIT_VALIDATOR_TYPE_URL,
+    ];
+    for type_url in typed.iter().chain(helpers.iter()) {
+        assert!(supported.contains(type_url), "{} is not listed", type_url);
+    }
+    // messages of unlisted types are not decoded
+    let unknown = Any {
+        type_url: "/cosmos.slashing.v1beta1.MsgUnjail".to_string(),
+        value: vec![],
+    };
+    assert!(!supported.contains(&unknown.type_url.as_str()));
+    assert!(matches!(
+        DecodedMsg::decode(&unknown).value,
+        DecodedMsgValue::Unknown(_)
+    ));
+    let mut sorted = supported.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(sorted.len(), supported.len());
+}
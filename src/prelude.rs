@@ -0,0 +1,19 @@
+//! Re-exports the types needed for most uses of this crate, so that they can be
+//! imported with `use deep_space::prelude::*`. Everything here is also available
+//! at it's usual path.
+
+pub use crate::address::Address;
+pub use crate::client::ChainStatus;
+pub use crate::client::Contact;
+pub use crate::client::FeeStrategy;
+pub use crate::client::Receipt;
+pub use crate::client::SendOptions;
+pub use crate::coin::Coin;
+pub use crate::coin::Fee;
+pub use crate::error::CosmosGrpcError;
+pub use crate::mnemonic::Mnemonic;
+pub use crate::msg::Msg;
+pub use crate::private_key::MessageArgs;
+pub use crate::private_key::PrivateKey;
+pub use crate::public_key::PublicKey;
+pub use cosmos_sdk_proto::cosmos::tx::v1beta1::BroadcastMode;
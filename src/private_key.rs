@@ -1,18 +1,23 @@
+use crate::amino;
 use crate::mnemonic::Mnemonic;
 use crate::msg::Msg;
-use crate::public_key::PublicKey;
+use crate::public_key::{AddressHash, PublicKey};
+#[cfg(feature = "keyring")]
 use crate::utils::bytes_to_hex_str;
 use crate::utils::encode_any;
 use crate::utils::hex_str_to_bytes;
+use crate::utils::tx_hash;
 use crate::{coin::Fee, Address};
 use crate::{error::*, utils::contains_non_hex_chars};
 use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as ProtoSecp256k1Pubkey;
+use cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::Tx;
 use cosmos_sdk_proto::cosmos::tx::v1beta1::{
     mode_info, AuthInfo, ModeInfo, SignDoc, SignerInfo, TxBody, TxRaw,
 };
 use num_bigint::BigUint;
 use prost::Message;
+use prost_types::Any;
 use secp256k1::constants::CURVE_ORDER as CurveN;
 use secp256k1::Message as CurveMessage;
 use secp256k1::Secp256k1;
@@ -20,28 +25,105 @@ use secp256k1::{PublicKey as PublicKeyEC, SecretKey};
 use sha2::Sha512;
 use sha2::{Digest, Sha256};
 use std::str::FromStr;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
+/// The account and chain specific values required to sign a transaction,
+/// see `Contact::get_message_args` to fetch these from a node
 #[derive(Debug, PartialEq, Clone)]
 pub struct MessageArgs {
+    /// The sequence of the signing account, this is incremented with every
+    /// transaction the account sends and prevents replays
     pub sequence: u64,
+    /// The fee paid for this transaction, along with the gas limit
     pub fee: Fee,
+    /// The block height after which this transaction is no longer valid,
+    /// zero for no timeout
     pub timeout_height: u64,
+    /// The id of the chain this transaction is for, for example `cosmoshub-4`
     pub chain_id: String,
+    /// The account number of the signing account, assigned when the account
+    /// is first created on chain
     pub account_number: u64,
 }
 
+/// Extension options for the `TxBody` of a transaction, these are required by some chains
+/// such as Ethermint for EIP-712 signed transactions or app specific tx types. A node rejects
+/// a tx with `extension_options` it does not understand, unknown
+/// `non_critical_extension_options` are ignored. Both are empty by default
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct TxExtensions {
+    pub extension_options: Vec<Any>,
+    pub non_critical_extension_options: Vec<Any>,
+}
+
 pub struct TxParts {
     pub body: TxBody,
     pub body_buf: Vec<u8>,
     pub auth_info: AuthInfo,
     pub auth_buf: Vec<u8>,
     pub signatures: Vec<Vec<u8>>,
+    /// The exact bytes each signature was made over, in the same order as `signatures`.
+    /// The protobuf `SignDoc` for `SignMode::Direct` and the Amino JSON `StdSignDoc` for
+    /// `SignMode::LegacyAminoJson`, the signature is over the sha256 hash of these bytes
+    pub sign_docs: Vec<Vec<u8>>,
+}
+
+/// Everything produced by signing a transaction, see `PrivateKey::sign_std_msg_detailed`
+pub struct SignedTx {
+    /// The serialized TxRaw, ready to be broadcast
+    pub tx_raw_bytes: Vec<u8>,
+    /// The hash of `tx_raw_bytes`, this is the hash the node will report for the tx
+    pub tx_hash: String,
+    /// The parts the transaction was built from
+    pub tx_parts: TxParts,
+}
+
+impl SignedTx {
+    /// The compact 64 byte secp256k1 signature of the first signer included in the
+    /// transaction, None if the tx parts hold no signature
+    pub fn signature_bytes(&self) -> Option<&[u8]> {
+        self.tx_parts.signatures.first().map(Vec::as_slice)
+    }
+
+    /// The sign doc bytes the first signature was made over, together with `signature_bytes`
+    /// and the signer's public key this is everything needed to verify the signature
+    /// independently, exactly as the node does. None if the tx parts hold no sign doc
+    pub fn sign_doc_bytes(&self) -> Option<&[u8]> {
+        self.tx_parts.sign_docs.first().map(Vec::as_slice)
+    }
+}
+
+/// One of the signers of a transaction with more than one signer, see `build_multi_signer_tx`
+#[derive(Debug, Clone)]
+pub struct TxSigner {
+    pub key: PrivateKey,
+    /// The account number of the signing account
+    pub account_number: u64,
+    /// The current sequence of the signing account
+    pub sequence: u64,
 }
 
 /// This structure represents a private key of a Cosmos Network.
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+/// The secret bytes are wiped from memory when the key is dropped, for
+/// this reason the key is intentionally not `Copy`, use `clone()` if you
+/// need more than one instance.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct PrivateKey([u8; 32]);
 
+impl Zeroize for PrivateKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for PrivateKey {}
+
 impl PrivateKey {
     /// Create a private key using an arbitrary slice of bytes. This function is not resistant to side
     /// channel attacks and may reveal your secret and private key. It is on the other hand more compact
@@ -67,7 +149,10 @@ impl PrivateKey {
             i_bytes.push(0);
         }
         result.copy_from_slice(&i_bytes);
-        PrivateKey(result)
+        i_bytes.zeroize();
+        let key = PrivateKey(result);
+        result.zeroize();
+        key
     }
 
     /// This function will take the key_import phrase provided by CosmosCLI
@@ -99,8 +184,9 @@ impl PrivateKey {
         let _ = iterator.next();
 
         let key_import = Mnemonic::from_str(phrase).unwrap();
-        let seed_bytes = key_import.to_seed(passphrase);
+        let mut seed_bytes = key_import.to_seed(passphrase);
         let (master_secret_key, master_chain_code) = master_key_from_seed(&seed_bytes);
+        seed_bytes.zeroize();
         let mut secret_key = master_secret_key;
         let mut chain_code = master_chain_code;
 
@@ -112,13 +198,20 @@ impl PrivateKey {
             }
             if let Ok(parsed_int) = val.parse() {
                 let (s, c) = get_child_key(secret_key, chain_code, parsed_int, hardened);
+                secret_key.zeroize();
+                chain_code.zeroize();
                 secret_key = s;
                 chain_code = c;
             } else {
+                secret_key.zeroize();
+                chain_code.zeroize();
                 return Err(HdWalletError::InvalidPathSpec(path.to_string()).into());
             }
         }
-        Ok(PrivateKey(secret_key))
+        chain_code.zeroize();
+        let key = PrivateKey(secret_key);
+        secret_key.zeroize();
+        Ok(key)
     }
 
     /// Obtain a public key for a given private key
@@ -137,6 +230,23 @@ impl PrivateKey {
         Ok(address)
     }
 
+    /// Obtain an Address for a given private key derived with the address `hash` of the chain,
+    /// addresses on Ethermint chains such as Evmos differ from those of other Cosmos chains
+    pub fn to_address_with_hash(
+        &self,
+        prefix: &str,
+        hash: AddressHash,
+    ) -> Result<Address, PrivateKeyError> {
+        let pubkey = self.to_public_key("")?;
+        Ok(pubkey.to_address_with_hash(prefix, hash)?)
+    }
+
+    /// Obtain the validator operator address for a given private key, `base_prefix` is the
+    /// account prefix of the chain such as `cosmos`, producing a `cosmosvaloper` address
+    pub fn to_valoper_address(&self, base_prefix: &str) -> Result<Address, PrivateKeyError> {
+        Ok(self.to_address(base_prefix)?.to_valoper(base_prefix)?)
+    }
+
     /// Internal function that that handles building a single message to sign
     /// returns an internal struct containing the parts of the built transaction
     /// in a way that's easy to mix and match for various uses and output types.
@@ -146,15 +256,53 @@ impl PrivateKey {
         args: MessageArgs,
         memo: impl Into<String>,
     ) -> Result<TxParts, PrivateKeyError> {
+        self.build_tx_with_sign_mode(messages, args, memo, SignMode::Direct)
+    }
+
+    /// Identical to `build_tx` but allows selecting the signing mode, `Direct` and
+    /// `LegacyAminoJson` are supported. Amino JSON signing is only possible for the
+    /// messages supported by the `amino` module.
+    pub fn build_tx_with_sign_mode(
+        &self,
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+        sign_mode: SignMode,
+    ) -> Result<TxParts, PrivateKeyError> {
+        self.build_tx_with_extensions(messages, args, memo, sign_mode, TxExtensions::default())
+    }
+
+    /// Identical to `build_tx_with_sign_mode` but also sets the extension options of the tx
+    /// body. Amino JSON can't represent extension options, so `LegacyAminoJson` is rejected
+    /// with `UnsupportedSignMode` if any are provided
+    pub fn build_tx_with_extensions(
+        &self,
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+        sign_mode: SignMode,
+        extensions: TxExtensions,
+    ) -> Result<TxParts, PrivateKeyError> {
+        if sign_mode == SignMode::LegacyAminoJson && extensions != TxExtensions::default() {
+            return Err(PrivateKeyError::UnsupportedSignMode(sign_mode));
+        }
+        let memo = memo.into();
+        // amino json is produced before the fee is moved into the auth info
+        // so that both signing modes sign the same transaction
+        let amino_sign_doc = match sign_mode {
+            SignMode::Direct => None,
+            SignMode::LegacyAminoJson => Some(amino::std_sign_doc(messages, &args, &memo)?),
+            other => return Err(PrivateKeyError::UnsupportedSignMode(other)),
+        };
         // prefix does not matter in this case, you could use a blank string
         let our_pubkey = self.to_public_key(PublicKey::DEFAULT_PREFIX)?;
         // Create TxBody
         let body = TxBody {
             messages: messages.iter().map(|msg| msg.0.clone()).collect(),
-            memo: memo.into(),
+            memo,
             timeout_height: args.timeout_height,
-            extension_options: Default::default(),
-            non_critical_extension_options: Default::default(),
+            extension_options: extensions.extension_options,
+            non_critical_extension_options: extensions.non_critical_extension_options,
         };
 
         // A protobuf serialization of a TxBody
@@ -167,7 +315,9 @@ impl PrivateKey {
 
         let pk_any = encode_any(key, "/cosmos.crypto.secp256k1.PubKey".to_string());
 
-        let single = mode_info::Single { mode: 1 };
+        let single = mode_info::Single {
+            mode: sign_mode.into(),
+        };
 
         let mode = Some(ModeInfo {
             sum: Some(mode_info::Sum::Single(single)),
@@ -188,24 +338,24 @@ impl PrivateKey {
         let mut auth_buf = Vec::new();
         auth_info.encode(&mut auth_buf).unwrap();
 
-        let sign_doc = SignDoc {
-            body_bytes: body_buf.clone(),
-            auth_info_bytes: auth_buf.clone(),
-            chain_id: args.chain_id.to_string(),
-            account_number: args.account_number,
+        let signdoc_buf = match amino_sign_doc {
+            Some(json) => json.into_bytes(),
+            None => {
+                let sign_doc = SignDoc {
+                    body_bytes: body_buf.clone(),
+                    auth_info_bytes: auth_buf.clone(),
+                    chain_id: args.chain_id.to_string(),
+                    account_number: args.account_number,
+                };
+
+                // Protobuf serialization of `SignDoc`
+                let mut signdoc_buf = Vec::new();
+                sign_doc.encode(&mut signdoc_buf).unwrap();
+                signdoc_buf
+            }
         };
 
-        // Protobuf serialization of `SignDoc`
-        let mut signdoc_buf = Vec::new();
-        sign_doc.encode(&mut signdoc_buf).unwrap();
-
-        let secp256k1 = Secp256k1::new();
-        let sk = SecretKey::from_slice(&self.0)?;
-        let digest = Sha256::digest(&signdoc_buf);
-        let msg = CurveMessage::from_slice(&digest)?;
-        // Sign the signdoc
-        let signed = secp256k1.sign(&msg, &sk);
-        let compact = signed.serialize_compact().to_vec();
+        let compact = self.sign_bytes(&signdoc_buf)?;
 
         Ok(TxParts {
             body,
@@ -213,9 +363,22 @@ impl PrivateKey {
             auth_info,
             auth_buf,
             signatures: vec![compact],
+            sign_docs: vec![signdoc_buf],
         })
     }
 
+    /// Signs the sha256 hash of the provided sign doc bytes, returning the compact
+    /// 64 byte signature used in Cosmos transactions
+    pub(crate) fn sign_bytes(&self, signdoc_buf: &[u8]) -> Result<Vec<u8>, PrivateKeyError> {
+        let secp256k1 = Secp256k1::new();
+        let sk = SecretKey::from_slice(&self.0)?;
+        let digest = Sha256::digest(signdoc_buf);
+        let msg = CurveMessage::from_slice(&digest)?;
+        // Sign the signdoc
+        let signed = secp256k1.sign(&msg, &sk);
+        Ok(signed.serialize_compact().to_vec())
+    }
+
     /// Signs a transaction that contains at least one message using a single
     /// private key, returns the standard Tx type, useful for simulations
     pub fn get_signed_tx(
@@ -240,7 +403,32 @@ impl PrivateKey {
         args: MessageArgs,
         memo: impl Into<String>,
     ) -> Result<Vec<u8>, PrivateKeyError> {
-        let parts = self.build_tx(messages, args, memo)?;
+        self.sign_std_msg_with_sign_mode(messages, args, memo, SignMode::Direct)
+    }
+
+    /// Identical to `sign_std_msg` but allows selecting the signing mode, see
+    /// `build_tx_with_sign_mode`
+    pub fn sign_std_msg_with_sign_mode(
+        &self,
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+        sign_mode: SignMode,
+    ) -> Result<Vec<u8>, PrivateKeyError> {
+        self.sign_std_msg_with_extensions(messages, args, memo, sign_mode, TxExtensions::default())
+    }
+
+    /// Identical to `sign_std_msg_with_sign_mode` but also sets the extension options of the
+    /// tx body, see `build_tx_with_extensions`
+    pub fn sign_std_msg_with_extensions(
+        &self,
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+        sign_mode: SignMode,
+        extensions: TxExtensions,
+    ) -> Result<Vec<u8>, PrivateKeyError> {
+        let parts = self.build_tx_with_extensions(messages, args, memo, sign_mode, extensions)?;
 
         let tx_raw = TxRaw {
             body_bytes: parts.body_buf,
@@ -250,23 +438,224 @@ impl PrivateKey {
 
         let mut txraw_buf = Vec::new();
         tx_raw.encode(&mut txraw_buf).unwrap();
-        let digest = Sha256::digest(&txraw_buf);
-        trace!("TXID {}", bytes_to_hex_str(&digest));
+        trace!("TXID {}", tx_hash(&txraw_buf));
 
         Ok(txraw_buf)
     }
+
+    /// Identical to `sign_std_msg` but also returns the hash of the signed transaction and
+    /// the parts it was built from, useful for tracking a transaction before it's broadcast
+    pub fn sign_std_msg_detailed(
+        &self,
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+    ) -> Result<SignedTx, PrivateKeyError> {
+        let parts = self.build_tx(messages, args, memo)?;
+
+        let tx_raw = TxRaw {
+            body_bytes: parts.body_buf.clone(),
+            auth_info_bytes: parts.auth_buf.clone(),
+            signatures: parts.signatures.clone(),
+        };
+
+        let mut txraw_buf = Vec::new();
+        tx_raw.encode(&mut txraw_buf).unwrap();
+
+        Ok(SignedTx {
+            tx_hash: tx_hash(&txraw_buf),
+            tx_raw_bytes: txraw_buf,
+            tx_parts: parts,
+        })
+    }
+
+    /// Returns the canonical Amino JSON `StdSignDoc` for a transaction, this is exactly
+    /// what is hashed and signed when using `SignMode::LegacyAminoJson`. Useful for debugging
+    /// signature verification failures by comparing against what a reference wallet produces.
+    pub fn sign_doc_amino_json(
+        &self,
+        messages: &[Msg],
+        args: MessageArgs,
+        memo: impl Into<String>,
+    ) -> Result<String, PrivateKeyError> {
+        amino::std_sign_doc(messages, &args, &memo.into())
+    }
+}
+
+/// Builds and signs a transaction that requires signatures from more than one account using
+/// `SignMode::Direct`, the first signer pays the fee. Signers must be provided in the order the
+/// sdk expects which is the order of first appearance in the messages, see `msg::required_signers`,
+/// otherwise the transaction is rejected as unauthorized. Every signer signs the same body and
+/// auth info, only the account number in the sign doc differs.
+pub fn build_multi_signer_tx(
+    messages: &[Msg],
+    signers: &[TxSigner],
+    fee: Fee,
+    timeout_height: u64,
+    chain_id: &str,
+    memo: impl Into<String>,
+) -> Result<TxParts, PrivateKeyError> {
+    let body = TxBody {
+        messages: messages.iter().map(|msg| msg.0.clone()).collect(),
+        memo: memo.into(),
+        timeout_height,
+        extension_options: Default::default(),
+        non_critical_extension_options: Default::default(),
+    };
+    let mut body_buf = Vec::new();
+    body.encode(&mut body_buf).unwrap();
+
+    let mut signer_infos = Vec::new();
+    for signer in signers {
+        signer_infos.push(signer.key.signer_info(signer.sequence)?);
+    }
+    let auth_info = AuthInfo {
+        signer_infos,
+        fee: Some(fee.into()),
+    };
+    let mut auth_buf = Vec::new();
+    auth_info.encode(&mut auth_buf).unwrap();
+
+    let mut signatures = Vec::new();
+    let mut sign_docs = Vec::new();
+    for signer in signers {
+        let sign_doc = SignDoc {
+            body_bytes: body_buf.clone(),
+            auth_info_bytes: auth_buf.clone(),
+            chain_id: chain_id.to_string(),
+            account_number: signer.account_number,
+        };
+        let mut signdoc_buf = Vec::new();
+        sign_doc.encode(&mut signdoc_buf).unwrap();
+        signatures.push(signer.key.sign_bytes(&signdoc_buf)?);
+        sign_docs.push(signdoc_buf);
+    }
+
+    Ok(TxParts {
+        body,
+        body_buf,
+        auth_info,
+        auth_buf,
+        signatures,
+        sign_docs,
+    })
+}
+
+/// Signing a single signer's part of a transaction with more than one signer, for when the
+/// keys are held by different parties. Assembling such a transaction works in three steps:
+///
+/// 1. Every signer provides their `signer_info` for their account's current sequence.
+/// 2. A coordinator builds the `TxBody` and an `AuthInfo` holding the fee and every
+///    `SignerInfo` in the order the sdk expects, see `msg::required_signers`, and sends the
+///    encoded body and auth info to every signer.
+/// 3. Each signer checks the transaction and calls `sign_partial` with their index in
+///    `signer_infos` and returns the signature. The coordinator puts the signatures into a
+///    `TxRaw` with the same body and auth info bytes, in `signer_infos` order, and broadcasts it.
+///
+/// The bytes must not be re-encoded between the steps as the signatures are over the exact
+/// bytes. `build_multi_signer_tx` does all of this when a single party holds every key.
+impl PrivateKey {
+    /// The `SignerInfo` of this key for a transaction signed with `SignMode::Direct`
+    /// at the given sequence
+    pub fn signer_info(&self, sequence: u64) -> Result<SignerInfo, PrivateKeyError> {
+        let pubkey = self.to_public_key(PublicKey::DEFAULT_PREFIX)?;
+        let key = ProtoSecp256k1Pubkey {
+            key: pubkey.to_vec(),
+        };
+        Ok(SignerInfo {
+            public_key: Some(encode_any(key, "/cosmos.crypto.secp256k1.PubKey")),
+            mode_info: Some(ModeInfo {
+                sum: Some(mode_info::Sum::Single(mode_info::Single {
+                    mode: SignMode::Direct.into(),
+                })),
+            }),
+            sequence,
+        })
+    }
+
+    /// Signs the transaction made of `body_bytes` and `auth_info_bytes` as the signer at
+    /// `signer_index` of the auth info, returning that signer's `SignerInfo` from the auth
+    /// info and the signature. Returns `InvalidPartialTx` if the auth info does not decode,
+    /// or the signer at that index is not this key or does not sign with `SignMode::Direct`
+    pub fn sign_partial(
+        &self,
+        body_bytes: &[u8],
+        auth_info_bytes: &[u8],
+        chain_id: &str,
+        account_number: u64,
+        signer_index: usize,
+    ) -> Result<(SignerInfo, Vec<u8>), PrivateKeyError> {
+        let auth_info = AuthInfo::decode(auth_info_bytes)
+            .map_err(|e| PrivateKeyError::InvalidPartialTx(e.to_string()))?;
+        let signer_info = match auth_info.signer_infos.get(signer_index) {
+            Some(info) => info.clone(),
+            None => {
+                return Err(PrivateKeyError::InvalidPartialTx(format!(
+                    "No signer at index {}, the tx has {}",
+                    signer_index,
+                    auth_info.signer_infos.len()
+                )))
+            }
+        };
+        let expected = self.signer_info(signer_info.sequence)?;
+        if signer_info != expected {
+            return Err(PrivateKeyError::InvalidPartialTx(format!(
+                "Signer {} is not this key or does not use direct signing",
+                signer_index
+            )));
+        }
+        let sign_doc = SignDoc {
+            body_bytes: body_bytes.to_vec(),
+            auth_info_bytes: auth_info_bytes.to_vec(),
+            chain_id: chain_id.to_string(),
+            account_number,
+        };
+        let mut signdoc_buf = Vec::new();
+        sign_doc.encode(&mut signdoc_buf).unwrap();
+        let signature = self.sign_bytes(&signdoc_buf)?;
+        Ok((signer_info, signature))
+    }
+}
+
+/// Storage of keys in the OS secret store, the Secret Service on Linux, the Keychain on macOS
+/// and the Credential Manager on Windows. Keys are stored as hex
+#[cfg(feature = "keyring")]
+impl PrivateKey {
+    /// Stores this key in the OS secret store under the given service and account names,
+    /// replacing any key already stored there
+    pub fn store_in_keyring(&self, service: &str, account: &str) -> Result<(), KeyringError> {
+        let entry = keyring::Entry::new(service, account)?;
+        let mut hex = bytes_to_hex_str(&self.0);
+        let res = entry.set_password(&hex);
+        hex.zeroize();
+        Ok(res?)
+    }
+
+    /// Loads a key stored with `store_in_keyring`, returns `KeyringError::NotFound` if
+    /// there is no key stored under these names
+    pub fn from_keyring(service: &str, account: &str) -> Result<PrivateKey, KeyringError> {
+        let entry = keyring::Entry::new(service, account)?;
+        let mut hex = entry.get_password()?;
+        let key = hex.parse();
+        hex.zeroize();
+        Ok(key?)
+    }
 }
 
 impl FromStr for PrivateKey {
     type Err = PrivateKeyError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match hex_str_to_bytes(s) {
-            Ok(bytes) => {
+            Ok(mut bytes) => {
                 if bytes.len() == 32 {
                     let mut inner = [0; 32];
                     inner.copy_from_slice(&bytes[0..32]);
-                    Ok(PrivateKey(inner))
+                    bytes.zeroize();
+                    let key = PrivateKey(inner);
+                    inner.zeroize();
+                    Ok(key)
                 } else {
+                    bytes.zeroize();
                     Err(PrivateKeyError::HexDecodeErrorWrongLength)
                 }
             }
@@ -291,11 +680,12 @@ fn master_key_from_seed(seed_bytes: &[u8]) -> ([u8; 32], [u8; 32]) {
 
     let mut hasher = HmacSha512::new_from_slice(b"Bitcoin seed").unwrap();
     hasher.update(seed_bytes);
-    let hash = hasher.finalize().into_bytes();
+    let mut hash = hasher.finalize().into_bytes();
     let mut master_secret_key: [u8; 32] = [0; 32];
     let mut master_chain_code: [u8; 32] = [0; 32];
     master_secret_key.copy_from_slice(&hash[0..32]);
     master_chain_code.copy_from_slice(&hash[32..64]);
+    hash.zeroize();
 
     // key check
     let _ = SecretKey::from_slice(&master_secret_key).unwrap();
@@ -330,7 +720,7 @@ fn get_child_key(
     }
     hasher.update(&i.to_be_bytes());
 
-    let l_param = hasher.finalize().into_bytes();
+    let mut l_param = hasher.finalize().into_bytes();
 
     // If you wanted to do this on your own (without add_assign)
     // it would go like this
@@ -355,10 +745,15 @@ fn get_child_key(
     parse_i_l.add_assign(&k_parent).unwrap();
     let child_key = parse_i_l;
 
+    let mut child_key_hex = format!("{:x}", child_key);
+    let mut child_key_bytes = hex_str_to_bytes(&child_key_hex).unwrap();
     let mut child_key_res: [u8; 32] = [0; 32];
-    child_key_res.copy_from_slice(&hex_str_to_bytes(&format!("{:x}", child_key)).unwrap());
+    child_key_res.copy_from_slice(&child_key_bytes);
+    child_key_hex.zeroize();
+    child_key_bytes.zeroize();
     let mut chain_code_res: [u8; 32] = [0; 32];
     chain_code_res.copy_from_slice(&l_param[32..64]);
+    l_param.zeroize();
     (child_key_res, chain_code_res)
 }
 
@@ -419,6 +814,13 @@ fn test_cosmos_key_derivation_manual() {
         public_key.to_bech32("cosmospub").unwrap(),
         "cosmospub1addwnpepqfn2xmm5g2uackkn62ew309n3paf0xzhug6xshv4a4yq4algm9ksugt2dx6",
     );
+    assert_eq!(
+        private_key
+            .to_valoper_address("cosmos")
+            .unwrap()
+            .to_string(),
+        "cosmosvaloper1t0sgxmpxafdfjd3k6kgg50kdgn4muh5t24rwnf",
+    );
 }
 
 #[test]
@@ -542,6 +944,199 @@ fn test_vector_unhardened() {
     assert_eq!(c0.to_vec(), correct_m0_chaincode);
 }
 
+#[test]
+fn test_amino_sign_doc_matches_signer() {
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+    use secp256k1::Signature as CurveSignature;
+    let private_key = PrivateKey::from_secret(b"mySecret");
+    let address = private_key.to_address("cosmos").unwrap();
+    let send = MsgSend {
+        amount: vec![],
+        from_address: address.to_string(),
+        to_address: address.to_string(),
+    };
+    let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+    let args = MessageArgs {
+        sequence: 0,
+        fee: Fee::default(),
+        timeout_height: 100,
+        chain_id: "mychainid".to_string(),
+        account_number: 0,
+    };
+    let doc = private_key
+        .sign_doc_amino_json(std::slice::from_ref(&msg), args.clone(), "memo")
+        .unwrap();
+    let parts = private_key
+        .build_tx_with_sign_mode(&[msg], args, "memo", SignMode::LegacyAminoJson)
+        .unwrap();
+
+    let secp256k1 = Secp256k1::new();
+    let digest = Sha256::digest(doc.as_bytes());
+    let message = CurveMessage::from_slice(&digest).unwrap();
+    let signature = CurveSignature::from_compact(&parts.signatures[0]).unwrap();
+    let sk = SecretKey::from_slice(&private_key.0).unwrap();
+    let pk = PublicKeyEC::from_secret_key(&secp256k1, &sk);
+    secp256k1.verify(&message, &signature, &pk).unwrap();
+}
+
+#[test]
+fn test_sign_std_msg_detailed() {
+    let private_key = PrivateKey::from_secret(b"mySecret");
+    let args = MessageArgs {
+        sequence: 3,
+        fee: Fee::default(),
+        timeout_height: 100,
+        chain_id: "mychainid".to_string(),
+        account_number: 1,
+    };
+    let signed = private_key
+        .sign_std_msg_detailed(&[], args.clone(), "memo")
+        .unwrap();
+    // signing is deterministic so this must match the plain signing path
+    let raw = private_key.sign_std_msg(&[], args, "memo").unwrap();
+    assert_eq!(signed.tx_raw_bytes, raw);
+    assert_eq!(signed.tx_hash, tx_hash(&raw));
+    let decoded = TxRaw::decode(raw.as_slice()).unwrap();
+    assert_eq!(decoded.body_bytes, signed.tx_parts.body_buf);
+    assert_eq!(decoded.signatures, signed.tx_parts.signatures);
+
+    // the signature must verify against the exported sign doc
+    let sign_doc = SignDoc::decode(signed.sign_doc_bytes().unwrap()).unwrap();
+    assert_eq!(sign_doc.chain_id, "mychainid");
+    assert_eq!(sign_doc.body_bytes, signed.tx_parts.body_buf);
+    let secp256k1 = Secp256k1::new();
+    let digest = Sha256::digest(signed.sign_doc_bytes().unwrap());
+    let msg = CurveMessage::from_slice(&digest).unwrap();
+    let sig = secp256k1::Signature::from_compact(signed.signature_bytes().unwrap()).unwrap();
+    let key =
+        PublicKeyEC::from_secret_key(&secp256k1, &SecretKey::from_slice(&private_key.0).unwrap());
+    secp256k1.verify(&msg, &sig, &key).unwrap();
+
+    let mut unsigned = signed;
+    unsigned.tx_parts.signatures.clear();
+    unsigned.tx_parts.sign_docs.clear();
+    assert_eq!(unsigned.signature_bytes(), None);
+    assert_eq!(unsigned.sign_doc_bytes(), None);
+}
+
+#[test]
+fn test_tx_extensions() {
+    let private_key = PrivateKey::from_secret(b"mySecret");
+    let args = MessageArgs {
+        sequence: 0,
+        fee: Fee::default(),
+        timeout_height: 0,
+        chain_id: "mychainid".to_string(),
+        account_number: 0,
+    };
+    let extensions = TxExtensions {
+        extension_options: vec![Any {
+            type_url: "/ethermint.types.v1.ExtensionOptionsWeb3Tx".to_string(),
+            value: vec![8, 9],
+        }],
+        non_critical_extension_options: Vec::new(),
+    };
+    let raw = private_key
+        .sign_std_msg_with_extensions(
+            &[],
+            args.clone(),
+            "memo",
+            SignMode::Direct,
+            extensions.clone(),
+        )
+        .unwrap();
+    let body =
+        TxBody::decode(TxRaw::decode(raw.as_slice()).unwrap().body_bytes.as_slice()).unwrap();
+    assert_eq!(body.extension_options, extensions.extension_options);
+    assert!(body.non_critical_extension_options.is_empty());
+
+    // without extensions the tx is unchanged
+    assert_eq!(
+        private_key
+            .sign_std_msg_with_extensions(
+                &[],
+                args.clone(),
+                "memo",
+                SignMode::Direct,
+                TxExtensions::default()
+            )
+            .unwrap(),
+        private_key.sign_std_msg(&[], args.clone(), "memo").unwrap()
+    );
+    assert!(private_key
+        .build_tx_with_extensions(&[], args, "memo", SignMode::LegacyAminoJson, extensions)
+        .is_err());
+}
+
+#[test]
+fn test_sign_partial() {
+    use crate::verify::verify_tx;
+    let first = PrivateKey::from_secret(b"partial first");
+    let second = PrivateKey::from_secret(b"partial second");
+    let auth_info = AuthInfo {
+        signer_infos: vec![
+            first.signer_info(4).unwrap(),
+            second.signer_info(0).unwrap(),
+        ],
+        fee: Some(Fee::default().into()),
+    };
+    let mut auth_buf = Vec::new();
+    auth_info.encode(&mut auth_buf).unwrap();
+    // each signer sends one message so that both are required signers
+    let send = |from: &PrivateKey| {
+        let address = from.to_address("cosmos").unwrap().to_string();
+        crate::utils::encode_any(
+            cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend {
+                from_address: address.clone(),
+                to_address: address,
+                amount: vec![],
+            },
+            "/cosmos.bank.v1beta1.MsgSend",
+        )
+    };
+    let body = TxBody {
+        messages: vec![send(&first), send(&second)],
+        ..Default::default()
+    };
+    let mut body_buf = Vec::new();
+    body.encode(&mut body_buf).unwrap();
+
+    let (info, first_sig) = first
+        .sign_partial(&body_buf, &auth_buf, "mychainid", 10, 0)
+        .unwrap();
+    assert_eq!(info.sequence, 4);
+    let (_, second_sig) = second
+        .sign_partial(&body_buf, &auth_buf, "mychainid", 11, 1)
+        .unwrap();
+    // the wrong index or a missing signer is rejected
+    assert!(second
+        .sign_partial(&body_buf, &auth_buf, "mychainid", 11, 0)
+        .is_err());
+    assert!(second
+        .sign_partial(&body_buf, &auth_buf, "mychainid", 11, 2)
+        .is_err());
+
+    let tx = TxRaw {
+        body_bytes: body_buf,
+        auth_info_bytes: auth_buf,
+        signatures: vec![first_sig, second_sig],
+    };
+    let mut tx_bytes = Vec::new();
+    tx.encode(&mut tx_bytes).unwrap();
+    let signers = verify_tx(&tx_bytes, "mychainid", &[10, 11], "cosmos").unwrap();
+    assert_eq!(signers[1], second.to_address("cosmos").unwrap());
+}
+
+#[test]
+fn test_zeroize_on_drop() {
+    fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+    assert_zeroize_on_drop::<PrivateKey>();
+
+    let mut private_key = PrivateKey::from_secret(b"mySecret");
+    private_key.zeroize();
+    assert_eq!(private_key.0, [0u8; 32]);
+}
+
 #[test]
 // this tests generating many thousands of private keys
 fn test_many_key_generation() {
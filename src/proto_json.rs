@@ -0,0 +1,248 @@
+//! Proto3 JSON encoding of messages, the form printed by the Cosmos SDK CLI and used for the
+//! messages of governance proposals, `{"@type": "/cosmos.bank.v1beta1.MsgSend", ...}`. Field
+//! names are kept in snake case as the SDK does. There is no reflection in prost so only the
+//! message types known to `DecodedMsg` and authz `MsgExec` have a JSON representation, see
+//! `any_to_json` for what is produced for other types, and `type_registry` to add the types
+//! of a chain's own modules.
+
+use crate::client::authz::{MsgExec, MSG_EXEC_TYPE_URL};
+use crate::msg::{DecodedMsg, DecodedMsgValue};
+use crate::type_registry::TypeRegistry;
+use crate::utils::decode_any;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::gov::v1beta1::TextProposal;
+use prost_types::Any;
+use serde_json::{json, Map, Value};
+
+/// The type url of `TextProposal`, the only proposal content with a JSON representation
+const TEXT_PROPOSAL_TYPE_URL: &str = "/cosmos.gov.v1beta1.TextProposal";
+
+/// Encodes an Any as proto3 JSON with it's type url in the `@type` field, Anys nested inside
+/// the message such as the messages of an authz `MsgExec` are encoded the same way. Types
+/// without a JSON representation, or values that fail to decode, are encoded with their
+/// protobuf bytes in base64 as `{"@type": ..., "value": ...}`
+pub fn any_to_json(any: &Any) -> Value {
+    any_to_json_with(any, None)
+}
+
+pub(crate) fn any_to_json_with(any: &Any, registry: Option<&TypeRegistry>) -> Value {
+    let (_, fields) = decode_any_readable_with(any, registry);
+    let mut object = Map::new();
+    object.insert("@type".to_string(), Value::String(any.type_url.clone()));
+    if let Value::Object(fields) = fields {
+        object.extend(fields);
+    }
+    Value::Object(object)
+}
+
+/// A best effort human readable form of any Any for display, for example by an explorer that
+/// shows messages of every type. Returns the type url and the proto3 JSON fields of the value,
+/// as in `any_to_json` without the `@type` field. Unknown types, and values that fail to decode
+/// as their type, are returned as `{"value": <base64 of the bytes>}`. See `Msg::decode` to
+/// decode into the concrete types instead
+pub fn decode_any_readable(any: &Any) -> (String, Value) {
+    decode_any_readable_with(any, None)
+}
+
+pub(crate) fn decode_any_readable_with(
+    any: &Any,
+    registry: Option<&TypeRegistry>,
+) -> (String, Value) {
+    let fields =
+        known_json(any, registry).unwrap_or_else(|| json!({ "value": base64::encode(&any.value) }));
+    (any.type_url.clone(), fields)
+}
+
+fn known_json(any: &Any, registry: Option<&TypeRegistry>) -> Option<Value> {
+    if let Some(fields) = registry.and_then(|registry| registry.decode_fields(any)) {
+        return Some(fields);
+    }
+    match any.type_url.as_str() {
+        MSG_EXEC_TYPE_URL => {
+            let msg: MsgExec = decode_any(any).ok()?;
+            let msgs: Vec<Value> = msg
+                .msgs
+                .iter()
+                .map(|msg| any_to_json_with(msg, registry))
+                .collect();
+            return Some(json!({ "grantee": msg.grantee, "msgs": msgs }));
+        }
+        TEXT_PROPOSAL_TYPE_URL => {
+            let content: TextProposal = decode_any(any).ok()?;
+            return Some(json!({ "title": content.title, "description": content.description }));
+        }
+        _ => {}
+    }
+    let value = match DecodedMsg::decode(any).value {
+        DecodedMsgValue::Send(msg) => json!({
+            "from_address": msg.from_address,
+            "to_address": msg.to_address,
+            "amount": coins_json(&msg.amount),
+        }),
+        DecodedMsgValue::MultiSend(msg) => {
+            let io = |address: &str, coins: &[ProtoCoin]| json!({ "address": address, "coins": coins_json(coins) });
+            json!({
+                "inputs": msg.inputs.iter().map(|i| io(&i.address, &i.coins)).collect::<Vec<_>>(),
+                "outputs": msg.outputs.iter().map(|o| io(&o.address, &o.coins)).collect::<Vec<_>>(),
+            })
+        }
+        DecodedMsgValue::Delegate(msg) => json!({
+            "delegator_address": msg.delegator_address,
+            "validator_address": msg.validator_address,
+            "amount": coin_json(&msg.amount),
+        }),
+        DecodedMsgValue::Undelegate(msg) => json!({
+            "delegator_address": msg.delegator_address,
+            "validator_address": msg.validator_address,
+            "amount": coin_json(&msg.amount),
+        }),
+        DecodedMsgValue::BeginRedelegate(msg) => json!({
+            "delegator_address": msg.delegator_address,
+            "validator_src_address": msg.validator_src_address,
+            "validator_dst_address": msg.validator_dst_address,
+            "amount": coin_json(&msg.amount),
+        }),
+        DecodedMsgValue::WithdrawDelegatorReward(msg) => json!({
+            "delegator_address": msg.delegator_address,
+            "validator_address": msg.validator_address,
+        }),
+        DecodedMsgValue::SubmitProposal(msg) => json!({
+            "content": msg.content.as_ref().map(|content| any_to_json_with(content, registry)),
+            "initial_deposit": coins_json(&msg.initial_deposit),
+            "proposer": msg.proposer,
+        }),
+        DecodedMsgValue::Vote(msg) => json!({
+            "proposal_id": msg.proposal_id.to_string(),
+            "voter": msg.voter,
+            "option": vote_option_name(msg.option),
+        }),
+        DecodedMsgValue::Deposit(msg) => json!({
+            "proposal_id": msg.proposal_id.to_string(),
+            "depositor": msg.depositor,
+            "amount": coins_json(&msg.amount),
+        }),
+        DecodedMsgValue::IbcTransfer(msg) => json!({
+            "source_port": msg.source_port,
+            "source_channel": msg.source_channel,
+            "token": coin_json(&msg.token),
+            "sender": msg.sender,
+            "receiver": msg.receiver,
+            "timeout_height": msg.timeout_height.map(|h| json!({
+                "revision_number": h.revision_number.to_string(),
+                "revision_height": h.revision_height.to_string(),
+            })),
+            "timeout_timestamp": msg.timeout_timestamp.to_string(),
+        }),
+        // wasmd prints the contract message as the JSON object it is
+        DecodedMsgValue::WasmExecute(msg) => json!({
+            "sender": msg.sender,
+            "contract": msg.contract,
+            "msg": serde_json::from_slice::<Value>(&msg.msg)
+                .unwrap_or_else(|_| Value::String(base64::encode(&msg.msg))),
+            "funds": coins_json(&msg.funds),
+        }),
+        DecodedMsgValue::Unknown(_) => return None,
+    };
+    Some(value)
+}
+
+fn vote_option_name(option: i32) -> &'static str {
+    match option {
+        1 => "VOTE_OPTION_YES",
+        2 => "VOTE_OPTION_ABSTAIN",
+        3 => "VOTE_OPTION_NO",
+        4 => "VOTE_OPTION_NO_WITH_VETO",
+        _ => "VOTE_OPTION_UNSPECIFIED",
+    }
+}
+
+fn coin_json(coin: &Option<ProtoCoin>) -> Value {
+    match coin {
+        Some(c) => json!({"denom": c.denom, "amount": c.amount}),
+        None => Value::Null,
+    }
+}
+
+fn coins_json(coins: &[ProtoCoin]) -> Value {
+    Value::Array(coins.iter().map(|c| coin_json(&Some(c.clone()))).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encode_any;
+    use crate::Msg;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+
+    #[test]
+    fn test_msg_to_json() {
+        let send = MsgSend {
+            from_address: "cosmos1from".to_string(),
+            to_address: "cosmos1to".to_string(),
+            amount: vec![ProtoCoin {
+                denom: "stake".to_string(),
+                amount: "7".to_string(),
+            }],
+        };
+        let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send.clone());
+        let expected = json!({
+            "@type": "/cosmos.bank.v1beta1.MsgSend",
+            "from_address": "cosmos1from",
+            "to_address": "cosmos1to",
+            "amount": [{"denom": "stake", "amount": "7"}],
+        });
+        assert_eq!(msg.to_json(), expected);
+
+        // nested messages are encoded recursively
+        let exec = MsgExec {
+            grantee: "cosmos1grantee".to_string(),
+            msgs: vec![encode_any(send, "/cosmos.bank.v1beta1.MsgSend")],
+        };
+        let exec = Msg::new(MSG_EXEC_TYPE_URL, exec).to_json();
+        assert_eq!(exec["grantee"], "cosmos1grantee");
+        assert_eq!(exec["msgs"][0], expected);
+
+        let unknown = Msg(Any {
+            type_url: "/my.module.MsgThing".to_string(),
+            value: vec![1, 2, 3],
+        });
+        assert_eq!(
+            unknown.to_json(),
+            json!({"@type": "/my.module.MsgThing", "value": "AQID"})
+        );
+    }
+
+    #[test]
+    fn test_decode_any_readable() {
+        let send = encode_any(
+            MsgSend {
+                from_address: "cosmos1from".to_string(),
+                to_address: "cosmos1to".to_string(),
+                amount: Vec::new(),
+            },
+            "/cosmos.bank.v1beta1.MsgSend",
+        );
+        let (type_url, fields) = decode_any_readable(&send);
+        assert_eq!(type_url, "/cosmos.bank.v1beta1.MsgSend");
+        assert_eq!(fields["to_address"], "cosmos1to");
+        assert!(fields.get("@type").is_none());
+
+        // a known type with a value that does not decode as it
+        let malformed = Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+            value: vec![0xff, 0xff],
+        };
+        assert_eq!(
+            decode_any_readable(&malformed),
+            (
+                "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                json!({"value": "//8="})
+            )
+        );
+        let empty = Any::default();
+        assert_eq!(
+            decode_any_readable(&empty),
+            (String::new(), json!({"value": ""}))
+        );
+    }
+}
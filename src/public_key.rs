@@ -1,14 +1,34 @@
 use crate::error::*;
-use crate::utils::hex_str_to_bytes;
+use crate::utils::{encode_any, hex_str_to_bytes};
 use crate::{address::Address, utils::ArrayString};
 use bech32::Variant;
 use bech32::{self, FromBase32, ToBase32};
+use cosmos_sdk_proto::cosmos::crypto::multisig::LegacyAminoPubKey;
+use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as ProtoSecp256k1Pubkey;
+use prost_types::Any;
 use ripemd160::Ripemd160;
+use secp256k1::PublicKey as PublicKeyEC;
 use sha2::{Digest, Sha256};
+use sha3::Keccak256;
 use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
 use std::str::FromStr;
 
+/// How a chain derives account addresses from secp256k1 public keys. Deriving an address with
+/// the wrong hash produces a valid looking address that no key controls, funds sent to it are
+/// lost, so use the hash of the chain. This only derives addresses, transactions built by
+/// this crate are signed as Cosmos secp256k1 keys which Ethermint chains don't accept for
+/// accounts with `AddressHash::Ethermint` addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AddressHash {
+    /// RIPEMD160(SHA256(compressed key)), used by the Cosmos Hub and most Cosmos SDK chains
+    #[default]
+    Cosmos,
+    /// The last 20 bytes of Keccak256(uncompressed key), the Ethereum address of the key,
+    /// used by Ethermint based chains such as Evmos for their `eth_secp256k1` keys
+    Ethermint,
+}
+
 /// Represents a public key of a given private key in the Cosmos Network.
 #[derive(PartialEq, Eq, Copy, Clone, Hash)]
 pub struct PublicKey {
@@ -83,10 +103,32 @@ impl PublicKey {
     /// provided as a utility for one step creation and change of prefix if the conventions
     /// in `to_address()` are incorrect
     pub fn to_address_with_prefix(&self, prefix: &str) -> Result<Address, AddressError> {
-        let sha256 = Sha256::digest(&self.bytes);
-        let ripemd160 = Ripemd160::digest(&sha256);
+        self.to_address_with_hash(prefix, AddressHash::Cosmos)
+    }
+
+    /// Create an address object with the given prefix using the address derivation `hash`
+    /// of the chain, returns `InvalidPublicKey` if an Ethermint address is requested for
+    /// bytes that are not a point on the curve
+    pub fn to_address_with_hash(
+        &self,
+        prefix: &str,
+        hash: AddressHash,
+    ) -> Result<Address, AddressError> {
         let mut bytes: [u8; 20] = Default::default();
-        bytes.copy_from_slice(&ripemd160[..]);
+        match hash {
+            AddressHash::Cosmos => {
+                let sha256 = Sha256::digest(&self.bytes);
+                let ripemd160 = Ripemd160::digest(&sha256);
+                bytes.copy_from_slice(&ripemd160[..]);
+            }
+            AddressHash::Ethermint => {
+                let key = PublicKeyEC::from_slice(&self.bytes)
+                    .map_err(|_| AddressError::InvalidPublicKey)?;
+                // the uncompressed key without it's 0x04 tag byte
+                let keccak = Keccak256::digest(&key.serialize_uncompressed()[1..]);
+                bytes.copy_from_slice(&keccak[12..]);
+            }
+        }
         Address::from_bytes(bytes, prefix)
     }
 
@@ -179,6 +221,104 @@ impl fmt::Debug for PublicKey {
     }
 }
 
+/// A legacy amino multisig public key, `threshold` of `keys` have to sign for the account.
+/// The address of the account is the truncated sha256 hash of the amino encoding of the
+/// key, so it depends on the order of the keys as well as the threshold. Use
+/// `multisig_pubkey` to get the key `gaiad keys add --multisig` creates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MultisigPublicKey {
+    threshold: u32,
+    keys: Vec<PublicKey>,
+}
+
+impl MultisigPublicKey {
+    /// Creates a multisig key with `keys` in the given order, this is the key created by
+    /// `gaiad keys add --multisig --nosort`
+    pub fn new(keys: Vec<PublicKey>, threshold: u32) -> Result<Self, PublicKeyError> {
+        if threshold == 0 || threshold as usize > keys.len() {
+            return Err(PublicKeyError::InvalidMultisigThreshold {
+                threshold,
+                keys: keys.len(),
+            });
+        }
+        Ok(MultisigPublicKey { threshold, keys })
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    /// Creates the amino representation of the key, `PubKeyMultisigThreshold` with the
+    /// threshold as field 1 and the amino encoded member keys as field 2
+    pub fn to_amino_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0x22, 0xC1, 0xF7, 0xE2, 0x08];
+        prost::encoding::encode_varint(self.threshold as u64, &mut bytes);
+        for key in self.keys.iter() {
+            let key_bytes = key.to_amino_bytes();
+            bytes.push(0x12);
+            prost::encoding::encode_varint(key_bytes.len() as u64, &mut bytes);
+            bytes.extend(key_bytes);
+        }
+        bytes
+    }
+
+    /// The address of the multisig account with the given prefix
+    pub fn to_address(&self, prefix: &str) -> Result<Address, AddressError> {
+        let mut bytes: [u8; 20] = Default::default();
+        bytes.copy_from_slice(&Sha256::digest(&self.to_amino_bytes())[..20]);
+        Address::from_bytes(bytes, prefix)
+    }
+
+    /// The key as a `LegacyAminoPubKey`, as it's included in the signer infos of a
+    /// transaction signed by the multisig
+    pub fn to_any(&self) -> Any {
+        let public_keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                encode_any(
+                    ProtoSecp256k1Pubkey { key: key.to_vec() },
+                    "/cosmos.crypto.secp256k1.PubKey",
+                )
+            })
+            .collect();
+        encode_any(
+            LegacyAminoPubKey {
+                threshold: self.threshold,
+                public_keys,
+            },
+            "/cosmos.crypto.multisig.LegacyAminoPubKey",
+        )
+    }
+}
+
+/// Creates the multisig key `gaiad keys add --multisig` creates from `pubkeys`, the keys are
+/// sorted by their address bytes before they are combined so the same set of keys always
+/// gives the same multisig account regardless of the order they are passed in
+pub fn multisig_pubkey(
+    pubkeys: &[PublicKey],
+    threshold: u32,
+) -> Result<MultisigPublicKey, PublicKeyError> {
+    let mut keys = pubkeys.to_vec();
+    // the prefix does not change the address bytes
+    keys.sort_by_key(|key| key.to_address().to_vec());
+    MultisigPublicKey::new(keys, threshold)
+}
+
+/// The address of the multisig account created from `pubkeys` with `threshold`, see
+/// `multisig_pubkey`
+pub fn multisig_address(
+    pubkeys: &[PublicKey],
+    threshold: u32,
+    prefix: &str,
+) -> Result<Address, PublicKeyError> {
+    Ok(multisig_pubkey(pubkeys, threshold)?.to_address(prefix)?)
+}
+
 #[test]
 fn check_bech32() {
     let raw_bytes = [
@@ -228,3 +368,102 @@ fn parse_base64_pubkey() {
 fn test_default_prefix() {
     PublicKey::from_bytes([0; 33], PublicKey::DEFAULT_PREFIX).unwrap();
 }
+
+#[test]
+fn test_address_hash() {
+    // the public key of the private key 1, the generator point
+    let key = PublicKey::from_slice(
+        &hex_str_to_bytes("0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798")
+            .unwrap(),
+        PublicKey::DEFAULT_PREFIX,
+    )
+    .unwrap();
+    let cosmos = key
+        .to_address_with_hash("cosmos", AddressHash::Cosmos)
+        .unwrap();
+    assert_eq!(cosmos, key.to_address_with_prefix("cosmos").unwrap());
+    assert_eq!(
+        cosmos.as_bytes(),
+        hex_str_to_bytes("751E76E8199196D454941C45D1B3A323F1433BD6").unwrap()
+    );
+    // the Ethereum address of the key
+    let evmos = key
+        .to_address_with_hash("evmos", AddressHash::Ethermint)
+        .unwrap();
+    assert_eq!(evmos.get_prefix(), "evmos");
+    assert_eq!(
+        evmos.as_bytes(),
+        hex_str_to_bytes("7E5F4552091A69125D5DFCB7B8C2659029395BDF").unwrap()
+    );
+
+    let private_key: crate::PrivateKey =
+        "0000000000000000000000000000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+    assert_eq!(
+        private_key
+            .to_address_with_hash("evmos", AddressHash::Ethermint)
+            .unwrap(),
+        evmos
+    );
+    assert!(matches!(
+        PublicKey::from_bytes([4; 33], PublicKey::DEFAULT_PREFIX)
+            .unwrap()
+            .to_address_with_hash("evmos", AddressHash::Ethermint),
+        Err(AddressError::InvalidPublicKey)
+    ));
+}
+
+#[test]
+fn test_multisig_address() {
+    // the test1, test2 and test3 keys of the wasmd development chain
+    let keys: Vec<PublicKey> = [
+        "038cb598ee54130d34f8e0818e7787aa06139a0e2d0026cadb662b55cf16859a67",
+        "02906f1bd9516c8cd3b52639322c801bf8724c1fa5e878c0e32b9bd6c0bb8b0f68",
+        "03cc93519d61b686da6f0e8cff9431e356b45f91063ed6f81f79ddd898858800f3",
+    ]
+    .iter()
+    .map(|key| PublicKey::from_slice(&hex_str_to_bytes(key).unwrap(), "wasmpub").unwrap())
+    .collect();
+    assert_eq!(
+        keys[0].to_address_with_prefix("wasm").unwrap().to_string(),
+        "wasm1jq59w7y34msq69g4w3zvq6d5h3stcajd8g62xm"
+    );
+
+    let reversed: Vec<PublicKey> = keys.iter().rev().cloned().collect();
+    let multisig = multisig_pubkey(&reversed, 2).unwrap();
+    assert_eq!(multisig.keys(), &keys[..]);
+    assert_eq!(multisig.threshold(), 2);
+    assert_eq!(
+        multisig_address(&keys, 2, "wasm").unwrap().to_string(),
+        "wasm1pzf2wlat97n7rykrk7e8g8nxste6hde0r8jqsy"
+    );
+    assert_eq!(
+        multisig_address(&reversed, 1, "wasm").unwrap().to_string(),
+        "wasm1hez3rmcu96cqv8puvk034yj27vmtgtkzha2ypg"
+    );
+
+    // without sorting the order of the keys changes the address
+    let unsorted = MultisigPublicKey::new(vec![keys[2], keys[0]], 2).unwrap();
+    assert_eq!(
+        unsorted.to_address("wasm").unwrap().as_bytes(),
+        hex_str_to_bytes("6BD32226EAC8C689E3FED19F3533D601AE7B8392").unwrap()
+    );
+    let any = unsorted.to_any();
+    let decoded = crate::utils::decode_any::<LegacyAminoPubKey>(&any).unwrap();
+    assert_eq!(decoded.threshold, 2);
+    assert_eq!(decoded.public_keys.len(), 2);
+
+    assert!(matches!(
+        multisig_pubkey(&keys, 0),
+        Err(PublicKeyError::InvalidMultisigThreshold { .. })
+    ));
+    assert!(matches!(
+        multisig_pubkey(&keys, 4),
+        Err(PublicKeyError::InvalidMultisigThreshold {
+            threshold: 4,
+            keys: 3
+        })
+    ));
+    assert!(multisig_pubkey(&[], 1).is_err());
+}
@@ -0,0 +1,373 @@
+//! Support for transaction tips using `SIGN_MODE_DIRECT_AUX`, introduced in Cosmos SDK v0.46.
+//!
+//! With tips a tipper who may not hold the fee denom signs only the transaction body along
+//! with a tip paid in any denom they do hold, producing an `AuxSignerData`. A separate fee payer
+//! then adds the fee, signs the complete transaction with `SIGN_MODE_DIRECT` and broadcasts it.
+//! The fee payer receives the tip. The protobuf types required are not part of the
+//! cosmos-sdk-proto version used by this crate so they are defined here, note that tips were
+//! removed again in Cosmos SDK v0.50 so check that the chain you are targeting supports them.
+
+use crate::coin::{Coin, Fee};
+use crate::error::PrivateKeyError;
+use crate::msg::Msg;
+use crate::private_key::MessageArgs;
+use crate::public_key::PublicKey;
+use crate::utils::{decode_any, encode_any, tx_hash};
+use crate::PrivateKey;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as ProtoSecp256k1Pubkey;
+use cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    mode_info, Fee as ProtoFee, ModeInfo, SignDoc, SignerInfo, TxBody, TxRaw,
+};
+use prost::Message;
+use prost_types::Any;
+
+/// The value of `SIGN_MODE_DIRECT_AUX` in the `SignMode` enum, not present in
+/// the `SignMode` type of the protobuf version used by this crate
+pub const SIGN_MODE_DIRECT_AUX: i32 = 3;
+
+/// Tip is the tip used for meta-transactions.
+#[derive(Clone, PartialEq, Message)]
+pub struct Tip {
+    /// amount is the amount of the tip
+    #[prost(message, repeated, tag = "1")]
+    pub amount: Vec<ProtoCoin>,
+    /// tipper is the address of the account paying for the tip
+    #[prost(string, tag = "2")]
+    pub tipper: String,
+}
+
+/// SignDocDirectAux is the type used for generating sign bytes for
+/// SIGN_MODE_DIRECT_AUX.
+#[derive(Clone, PartialEq, Message)]
+pub struct SignDocDirectAux {
+    /// body_bytes is protobuf serialization of a TxBody that matches the
+    /// representation in TxRaw.
+    #[prost(bytes = "vec", tag = "1")]
+    pub body_bytes: Vec<u8>,
+    /// public_key is the public key of the signing account.
+    #[prost(message, optional, tag = "2")]
+    pub public_key: Option<Any>,
+    /// chain_id is the identifier of the chain this transaction targets.
+    #[prost(string, tag = "3")]
+    pub chain_id: String,
+    /// account_number is the account number of the account in state.
+    #[prost(uint64, tag = "4")]
+    pub account_number: u64,
+    /// sequence is the sequence number of the signing account.
+    #[prost(uint64, tag = "5")]
+    pub sequence: u64,
+    /// Tip is the optional tip used for meta-transactions.
+    #[prost(message, optional, tag = "6")]
+    pub tip: Option<Tip>,
+}
+
+/// AuxSignerData is the intermediary format that an auxiliary signer (e.g. a
+/// tipper) builds and sends to the fee payer (who will build and broadcast the
+/// actual tx).
+#[derive(Clone, PartialEq, Message)]
+pub struct AuxSignerData {
+    /// address is the bech32-encoded address of the auxiliary signer.
+    #[prost(string, tag = "1")]
+    pub address: String,
+    /// sign_doc is the SIGN_MODE_DIRECT_AUX sign doc that the auxiliary signer
+    /// signs.
+    #[prost(message, optional, tag = "2")]
+    pub sign_doc: Option<SignDocDirectAux>,
+    /// mode is the signing mode of the auxiliary signer.
+    #[prost(int32, tag = "3")]
+    pub mode: i32,
+    /// sig is the signature of the sign doc.
+    #[prost(bytes = "vec", tag = "4")]
+    pub sig: Vec<u8>,
+}
+
+/// AuthInfo as of Cosmos SDK v0.46, which adds the tip field
+#[derive(Clone, PartialEq, Message)]
+pub struct AuthInfo {
+    #[prost(message, repeated, tag = "1")]
+    pub signer_infos: Vec<SignerInfo>,
+    #[prost(message, optional, tag = "2")]
+    pub fee: Option<ProtoFee>,
+    #[prost(message, optional, tag = "3")]
+    pub tip: Option<Tip>,
+}
+
+/// The account details required by the tipper to produce an `AuxSignerData`, the fee
+/// is not included as it's chosen by the fee payer
+#[derive(Debug, PartialEq, Clone)]
+pub struct AuxArgs {
+    pub sequence: u64,
+    pub timeout_height: u64,
+    pub chain_id: String,
+    pub account_number: u64,
+}
+
+impl PrivateKey {
+    /// Signs the provided messages with `SIGN_MODE_DIRECT_AUX` as the tipper, producing
+    /// an `AuxSignerData` to be handed to a fee payer who completes the transaction using
+    /// `sign_tx_as_fee_payer`. The tipper must be the signer of all the provided messages.
+    pub fn sign_aux(
+        &self,
+        messages: &[Msg],
+        args: AuxArgs,
+        memo: impl Into<String>,
+        tip: Vec<Coin>,
+        prefix: &str,
+    ) -> Result<AuxSignerData, PrivateKeyError> {
+        let our_address = self.to_address(prefix)?;
+        let our_pubkey = self.to_public_key(PublicKey::DEFAULT_PREFIX)?;
+        let body = TxBody {
+            messages: messages.iter().map(|msg| msg.clone().into()).collect(),
+            memo: memo.into(),
+            timeout_height: args.timeout_height,
+            extension_options: Default::default(),
+            non_critical_extension_options: Default::default(),
+        };
+        let mut body_bytes = Vec::new();
+        body.encode(&mut body_bytes).unwrap();
+
+        let key = ProtoSecp256k1Pubkey {
+            key: our_pubkey.to_vec(),
+        };
+        let sign_doc = SignDocDirectAux {
+            body_bytes,
+            public_key: Some(encode_any(key, "/cosmos.crypto.secp256k1.PubKey")),
+            chain_id: args.chain_id,
+            account_number: args.account_number,
+            sequence: args.sequence,
+            tip: Some(Tip {
+                amount: tip.into_iter().map(|c| c.into()).collect(),
+                tipper: our_address.to_string(),
+            }),
+        };
+        let mut sign_doc_buf = Vec::new();
+        sign_doc.encode(&mut sign_doc_buf).unwrap();
+        let sig = self.sign_bytes(&sign_doc_buf)?;
+
+        Ok(AuxSignerData {
+            address: our_address.to_string(),
+            sign_doc: Some(sign_doc),
+            mode: SIGN_MODE_DIRECT_AUX,
+            sig,
+        })
+    }
+
+    /// Completes a transaction signed by a tipper with `sign_aux`, this key pays the fee
+    /// in `args` and receives the tip. The fee payer of `args.fee` is always set to this key
+    /// and `args.timeout_height` is ignored, the transaction body is exactly what the tipper
+    /// signed. Returns the encoded transaction ready for broadcast.
+    pub fn sign_tx_as_fee_payer(
+        &self,
+        aux: &AuxSignerData,
+        args: MessageArgs,
+        prefix: &str,
+    ) -> Result<Vec<u8>, PrivateKeyError> {
+        let sign_doc = match &aux.sign_doc {
+            Some(sign_doc) => sign_doc,
+            None => {
+                return Err(PrivateKeyError::InvalidAuxSignerData(
+                    "No sign doc".to_string(),
+                ))
+            }
+        };
+        if aux.mode != SIGN_MODE_DIRECT_AUX {
+            return Err(PrivateKeyError::InvalidAuxSignerData(format!(
+                "Unsupported mode {}",
+                aux.mode
+            )));
+        }
+        if sign_doc.chain_id != args.chain_id {
+            return Err(PrivateKeyError::InvalidAuxSignerData(format!(
+                "Signed for chain {} not {}",
+                sign_doc.chain_id, args.chain_id
+            )));
+        }
+
+        let our_address = self.to_address(prefix)?;
+        let our_pubkey = self.to_public_key(PublicKey::DEFAULT_PREFIX)?;
+        let key = ProtoSecp256k1Pubkey {
+            key: our_pubkey.to_vec(),
+        };
+
+        let tipper_info = SignerInfo {
+            public_key: sign_doc.public_key.clone(),
+            mode_info: Some(ModeInfo {
+                sum: Some(mode_info::Sum::Single(mode_info::Single {
+                    mode: SIGN_MODE_DIRECT_AUX,
+                })),
+            }),
+            sequence: sign_doc.sequence,
+        };
+        // the fee payer is always the last signer
+        let payer_info = SignerInfo {
+            public_key: Some(encode_any(key, "/cosmos.crypto.secp256k1.PubKey")),
+            mode_info: Some(ModeInfo {
+                sum: Some(mode_info::Sum::Single(mode_info::Single {
+                    mode: SignMode::Direct.into(),
+                })),
+            }),
+            sequence: args.sequence,
+        };
+        let fee = Fee {
+            payer: Some(our_address),
+            ..args.fee
+        };
+        let auth_info = AuthInfo {
+            signer_infos: vec![tipper_info, payer_info],
+            fee: Some(fee.into()),
+            tip: sign_doc.tip.clone(),
+        };
+        let mut auth_info_bytes = Vec::new();
+        auth_info.encode(&mut auth_info_bytes).unwrap();
+
+        let payer_sign_doc = SignDoc {
+            body_bytes: sign_doc.body_bytes.clone(),
+            auth_info_bytes: auth_info_bytes.clone(),
+            chain_id: args.chain_id,
+            account_number: args.account_number,
+        };
+        let mut payer_sign_doc_buf = Vec::new();
+        payer_sign_doc.encode(&mut payer_sign_doc_buf).unwrap();
+        let payer_sig = self.sign_bytes(&payer_sign_doc_buf)?;
+
+        let tx_raw = TxRaw {
+            body_bytes: sign_doc.body_bytes.clone(),
+            auth_info_bytes,
+            signatures: vec![aux.sig.clone(), payer_sig],
+        };
+        let mut txraw_buf = Vec::new();
+        tx_raw.encode(&mut txraw_buf).unwrap();
+        trace!("TXID {}", tx_hash(&txraw_buf));
+        Ok(txraw_buf)
+    }
+}
+
+/// Returns the tipper public key from an `AuxSignerData`, useful for checking who is
+/// tipping before agreeing to pay the fee
+pub fn aux_signer_pubkey(aux: &AuxSignerData) -> Result<Option<PublicKey>, PrivateKeyError> {
+    let any = match aux.sign_doc.as_ref().and_then(|d| d.public_key.as_ref()) {
+        Some(any) => any,
+        None => return Ok(None),
+    };
+    let key: ProtoSecp256k1Pubkey = match decode_any(any) {
+        Ok(k) => k,
+        Err(e) => return Err(PrivateKeyError::InvalidAuxSignerData(e.to_string())),
+    };
+    Ok(Some(PublicKey::from_slice(
+        &key.key,
+        PublicKey::DEFAULT_PREFIX,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+    use secp256k1::{Message as CurveMessage, PublicKey as PublicKeyEC, Secp256k1, Signature};
+    use sha2::{Digest, Sha256};
+
+    fn verify(pubkey: &PublicKey, data: &[u8], sig: &[u8]) {
+        let secp256k1 = Secp256k1::new();
+        let digest = Sha256::digest(data);
+        let msg = CurveMessage::from_slice(&digest).unwrap();
+        let sig = Signature::from_compact(sig).unwrap();
+        let key = PublicKeyEC::from_slice(pubkey.as_bytes()).unwrap();
+        secp256k1.verify(&msg, &sig, &key).unwrap();
+    }
+
+    #[test]
+    fn test_assemble_tipped_tx() {
+        let tipper = PrivateKey::from_secret(b"tipper");
+        let payer = PrivateKey::from_secret(b"payer");
+        let tipper_address = tipper.to_address("cosmos").unwrap();
+        let send = MsgSend {
+            from_address: tipper_address.to_string(),
+            to_address: payer.to_address("cosmos").unwrap().to_string(),
+            amount: vec![Coin::new(10u8.into(), "ujuno".to_string()).into()],
+        };
+        let msg = Msg::new("/cosmos.bank.v1beta1.MsgSend", send);
+        let aux_args = AuxArgs {
+            sequence: 3,
+            timeout_height: 100,
+            chain_id: "testing".to_string(),
+            account_number: 7,
+        };
+        let tip = vec![Coin::new(1u8.into(), "ujuno".to_string())];
+        let aux = tipper
+            .sign_aux(&[msg], aux_args, "tipped", tip, "cosmos")
+            .unwrap();
+        assert_eq!(aux.address, tipper_address.to_string());
+        // the aux data must survive the trip to the fee payer
+        let mut aux_buf = Vec::new();
+        aux.encode(&mut aux_buf).unwrap();
+        let aux = AuxSignerData::decode(aux_buf.as_slice()).unwrap();
+
+        let payer_args = MessageArgs {
+            sequence: 1,
+            fee: Fee {
+                amount: vec![Coin::new(500u16.into(), "uatom".to_string())],
+                gas_limit: 200_000,
+                payer: None,
+                granter: None,
+            },
+            timeout_height: 0,
+            chain_id: "testing".to_string(),
+            account_number: 9,
+        };
+        let tx_bytes = payer
+            .sign_tx_as_fee_payer(&aux, payer_args.clone(), "cosmos")
+            .unwrap();
+        let tx = TxRaw::decode(tx_bytes.as_slice()).unwrap();
+        assert_eq!(tx.signatures.len(), 2);
+
+        let auth_info = AuthInfo::decode(tx.auth_info_bytes.as_slice()).unwrap();
+        assert_eq!(auth_info.signer_infos.len(), 2);
+        assert_eq!(auth_info.signer_infos[0].sequence, 3);
+        assert_eq!(auth_info.signer_infos[1].sequence, 1);
+        assert_eq!(
+            auth_info.tip.as_ref().unwrap().tipper,
+            tipper_address.to_string()
+        );
+        assert_eq!(
+            auth_info.fee.unwrap().payer,
+            payer.to_address("cosmos").unwrap().to_string()
+        );
+
+        // the tipper signature covers the aux sign doc
+        let tipper_pubkey = aux_signer_pubkey(&aux).unwrap().unwrap();
+        assert_eq!(tipper_pubkey, tipper.to_public_key("cosmospub").unwrap());
+        let sign_doc = aux.sign_doc.unwrap();
+        assert_eq!(sign_doc.body_bytes, tx.body_bytes);
+        let mut sign_doc_buf = Vec::new();
+        sign_doc.encode(&mut sign_doc_buf).unwrap();
+        verify(&tipper_pubkey, &sign_doc_buf, &tx.signatures[0]);
+
+        // the fee payer signature covers the full sign doc
+        let payer_sign_doc = SignDoc {
+            body_bytes: tx.body_bytes.clone(),
+            auth_info_bytes: tx.auth_info_bytes.clone(),
+            chain_id: "testing".to_string(),
+            account_number: 9,
+        };
+        let mut payer_sign_doc_buf = Vec::new();
+        payer_sign_doc.encode(&mut payer_sign_doc_buf).unwrap();
+        verify(
+            &payer.to_public_key("cosmospub").unwrap(),
+            &payer_sign_doc_buf,
+            &tx.signatures[1],
+        );
+
+        // signed for another chain
+        let mut wrong_chain = payer_args;
+        wrong_chain.chain_id = "other".to_string();
+        let aux = AuxSignerData {
+            sign_doc: Some(sign_doc),
+            ..aux
+        };
+        assert!(payer
+            .sign_tx_as_fee_payer(&aux, wrong_chain, "cosmos")
+            .is_err());
+    }
+}
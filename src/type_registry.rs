@@ -0,0 +1,251 @@
+//! A registry of message types defined by the modules of a particular chain, so that the
+//! proto3 JSON helpers of `proto_json` can show them. There is no reflection in prost so this
+//! crate only knows the types it has decoders for, anything else is shown as it's protobuf
+//! bytes in base64. Registering a type url with a decoder makes `TypeRegistry::any_to_json`
+//! and `TypeRegistry::decode_any_readable` show the fields of values of that type, including
+//! when they are nested inside an authz `MsgExec` or are the response of a message.
+//!
+//! The simplest way to wire in the types of a chain is to generate them with prost-build and
+//! derive serde for them, then register each generated type with it's type url
+//!
+//! ```ignore
+//! // build.rs
+//! prost_build::Config::new()
+//!     .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+//!     .compile_protos(&["proto/mychain/tx.proto"], &["proto"])?;
+//!
+//! // when setting up the Contact
+//! let mut registry = TypeRegistry::new();
+//! registry.register::<mychain::MsgDoThing>("/mychain.v1.MsgDoThing");
+//! registry.register::<mychain::MsgDoThingResponse>("/mychain.v1.MsgDoThingResponse");
+//! contact.set_type_registry(registry);
+//! ```
+//!
+//! Note that serde uses the field names of the Rust types, which are the snake case proto
+//! field names as the SDK prints them, but encodes 64 bit integers as numbers and bytes as
+//! arrays rather than the strings of proto3 JSON. Use `register_fn` with your own closures
+//! where the exact proto3 JSON form matters.
+
+use crate::proto_json::{any_to_json_with, decode_any_readable_with};
+use prost_types::Any;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Decodes the protobuf bytes of a value into it's JSON fields
+type DecodeFn = dyn Fn(&[u8]) -> Result<Value, String> + Send + Sync;
+/// Encodes the JSON fields of a value into it's protobuf bytes
+type EncodeFn = dyn Fn(&Value) -> Result<Vec<u8>, String> + Send + Sync;
+
+#[derive(Clone)]
+struct RegisteredType {
+    decode: Arc<DecodeFn>,
+    encode: Arc<EncodeFn>,
+}
+
+/// Type urls mapped to the closures that convert their values between protobuf and JSON, see
+/// the module docs. Registered types take precedence over the types this crate knows, so a
+/// chain that encodes a known type url differently can override it. Cheap to clone, clones
+/// share the registered closures
+#[derive(Clone, Default)]
+pub struct TypeRegistry {
+    types: HashMap<String, RegisteredType>,
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        TypeRegistry::default()
+    }
+
+    /// Registers a prost generated type that also implements serde, it's decoded from protobuf
+    /// and serialized to JSON, or deserialized from JSON and encoded to protobuf
+    pub fn register<T>(&mut self, type_url: impl Into<String>)
+    where
+        T: prost::Message + Default + Serialize + DeserializeOwned + 'static,
+    {
+        self.register_fn(
+            type_url,
+            |bytes| {
+                let value = T::decode(bytes).map_err(|e| e.to_string())?;
+                serde_json::to_value(value).map_err(|e| e.to_string())
+            },
+            |fields| {
+                let value: T = serde_json::from_value(fields.clone()).map_err(|e| e.to_string())?;
+                let mut bytes = Vec::new();
+                value.encode(&mut bytes).map_err(|e| e.to_string())?;
+                Ok(bytes)
+            },
+        )
+    }
+
+    /// Registers a type with closures that `decode` protobuf bytes into the JSON fields of the
+    /// value and `encode` them back, replacing any earlier registration of the type url
+    pub fn register_fn(
+        &mut self,
+        type_url: impl Into<String>,
+        decode: impl Fn(&[u8]) -> Result<Value, String> + Send + Sync + 'static,
+        encode: impl Fn(&Value) -> Result<Vec<u8>, String> + Send + Sync + 'static,
+    ) {
+        self.types.insert(
+            type_url.into(),
+            RegisteredType {
+                decode: Arc::new(decode),
+                encode: Arc::new(encode),
+            },
+        );
+    }
+
+    pub fn contains(&self, type_url: &str) -> bool {
+        self.types.contains_key(type_url)
+    }
+
+    /// The type urls registered, in no particular order
+    pub fn type_urls(&self) -> impl Iterator<Item = &str> {
+        self.types.keys().map(|type_url| type_url.as_str())
+    }
+
+    /// Decodes the JSON fields of a value of a registered type, None if the type is not
+    /// registered or the value does not decode as it
+    pub fn decode_fields(&self, any: &Any) -> Option<Value> {
+        let registered = self.types.get(&any.type_url)?;
+        (registered.decode)(&any.value).ok()
+    }
+
+    /// Encodes the JSON `fields` of a value of a registered type into an Any
+    pub fn encode_fields(&self, type_url: &str, fields: &Value) -> Result<Any, String> {
+        let registered = match self.types.get(type_url) {
+            Some(registered) => registered,
+            None => return Err(format!("Type {} is not registered", type_url)),
+        };
+        Ok(Any {
+            type_url: type_url.to_string(),
+            value: (registered.encode)(fields)?,
+        })
+    }
+
+    /// Encodes a JSON value of a registered type with it's type url in the `@type` field, the
+    /// form produced by `any_to_json`, into an Any
+    pub fn json_to_any(&self, json: &Value) -> Result<Any, String> {
+        let mut fields = match json {
+            Value::Object(fields) => fields.clone(),
+            _ => return Err(format!("Expected a JSON object, got {}", json)),
+        };
+        let type_url = match fields.remove("@type") {
+            Some(Value::String(type_url)) => type_url,
+            _ => return Err("No @type in the JSON object".to_string()),
+        };
+        self.encode_fields(&type_url, &Value::Object(fields))
+    }
+
+    /// `proto_json::any_to_json` that also shows the fields of registered types
+    pub fn any_to_json(&self, any: &Any) -> Value {
+        any_to_json_with(any, Some(self))
+    }
+
+    /// `proto_json::decode_any_readable` that also shows the fields of registered types
+    pub fn decode_any_readable(&self, any: &Any) -> (String, Value) {
+        decode_any_readable_with(any, Some(self))
+    }
+}
+
+impl fmt::Debug for TypeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut type_urls: Vec<&str> = self.type_urls().collect();
+        type_urls.sort_unstable();
+        f.debug_struct("TypeRegistry")
+            .field("types", &type_urls)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::authz::{MsgExec, MSG_EXEC_TYPE_URL};
+    use crate::utils::encode_any;
+    use serde_derive::{Deserialize, Serialize};
+    use serde_json::json;
+
+    /// A message of a custom module, as prost-build generates it with serde derived
+    #[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+    struct MsgDoThing {
+        #[prost(string, tag = "1")]
+        sender: String,
+        #[prost(uint32, tag = "2")]
+        times: u32,
+    }
+
+    #[test]
+    fn test_type_registry() {
+        let thing = MsgDoThing {
+            sender: "cosmos1sender".to_string(),
+            times: 3,
+        };
+        let any = encode_any(thing, "/mychain.v1.MsgDoThing");
+        let exec = encode_any(
+            MsgExec {
+                grantee: "cosmos1grantee".to_string(),
+                msgs: vec![any.clone()],
+            },
+            MSG_EXEC_TYPE_URL,
+        );
+
+        // unknown without the registry
+        assert_eq!(
+            crate::proto_json::any_to_json(&exec)["msgs"][0],
+            json!({"@type": "/mychain.v1.MsgDoThing", "value": base64::encode(&any.value)})
+        );
+
+        let mut registry = TypeRegistry::new();
+        registry.register::<MsgDoThing>("/mychain.v1.MsgDoThing");
+        assert!(registry.contains("/mychain.v1.MsgDoThing"));
+        let expected = json!({
+            "@type": "/mychain.v1.MsgDoThing",
+            "sender": "cosmos1sender",
+            "times": 3,
+        });
+        assert_eq!(registry.any_to_json(&any), expected);
+        assert_eq!(registry.any_to_json(&exec)["msgs"][0], expected);
+        assert_eq!(
+            registry.decode_any_readable(&any),
+            (
+                "/mychain.v1.MsgDoThing".to_string(),
+                json!({"sender": "cosmos1sender", "times": 3})
+            )
+        );
+        assert_eq!(registry.json_to_any(&expected).unwrap(), any);
+        assert!(registry.json_to_any(&json!({"sender": "x"})).is_err());
+        assert!(registry
+            .encode_fields("/mychain.v1.MsgOther", &json!({}))
+            .is_err());
+
+        // a value that does not decode falls back to it's bytes
+        let malformed = Any {
+            type_url: "/mychain.v1.MsgDoThing".to_string(),
+            value: vec![0xff, 0xff],
+        };
+        assert_eq!(
+            registry.decode_any_readable(&malformed).1,
+            json!({"value": "//8="})
+        );
+
+        // closures replace the registration
+        registry.register_fn(
+            "/mychain.v1.MsgDoThing",
+            |bytes| Ok(json!({ "length": bytes.len() })),
+            |_| Err("read only".to_string()),
+        );
+        assert_eq!(
+            registry.decode_fields(&any).unwrap()["length"],
+            any.value.len()
+        );
+        assert!(registry.json_to_any(&expected).is_err());
+        assert_eq!(
+            format!("{:?}", registry),
+            r#"TypeRegistry { types: ["/mychain.v1.MsgDoThing"] }"#
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use crate::error::{ArrayStringError, ByteDecodeError};
 use crate::Coin;
 use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
 use prost_types::Any;
+use sha2::{Digest, Sha256};
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
@@ -32,44 +34,108 @@ pub fn bytes_to_hex_str(bytes: &[u8]) -> String {
         .fold(String::new(), |acc, x| acc + &x)
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Hash, Deserialize, Serialize)]
+/// Computes the hash of a signed and serialized transaction (TxRaw bytes), this is the
+/// same uppercase hex hash the node will report for the transaction, so it can be used to
+/// look up a transaction even if the broadcast response never arrives
+pub fn tx_hash(tx_bytes: &[u8]) -> String {
+    bytes_to_hex_str(&Sha256::digest(tx_bytes)).to_uppercase()
+}
+
+/// Normalizes a transaction hash entered by a user to the form the node expects, an optional
+/// `0x` prefix is removed and the hash is uppercased. Returns None if the result is not 64 hex
+/// characters
+pub fn normalize_tx_hash(hash: &str) -> Option<String> {
+    let hash = hash.trim();
+    let hash = hash
+        .strip_prefix("0x")
+        .or_else(|| hash.strip_prefix("0X"))
+        .unwrap_or(hash);
+    if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Some(hash.to_uppercase())
+    } else {
+        None
+    }
+}
+
+/// Computes the denom of an IBC voucher, `ibc/<HASH>`, from the path it was transferred
+/// over, for example `transfer/channel-0`, and it's base denom on the source chain. To go
+/// the other way use `Contact::get_denom_trace`
+pub fn ibc_denom_hash(path: &str, base_denom: &str) -> String {
+    let full_path = if path.is_empty() {
+        base_denom.to_string()
+    } else {
+        format!("{}/{}", path, base_denom)
+    };
+    format!(
+        "ibc/{}",
+        bytes_to_hex_str(&Sha256::digest(full_path.as_bytes())).to_uppercase()
+    )
+}
+
+/// A short string stored inline so that types holding it, such as `Address`, stay `Copy`.
+/// Kept as utf-8 bytes, up to `MAX_LEN` bytes long
+#[derive(PartialEq, Eq, Copy, Clone, Hash)]
 pub struct ArrayString {
-    chars: [Option<char>; ArrayString::MAX_LEN],
-    used: usize,
+    bytes: [u8; ArrayString::MAX_LEN],
+    used: u8,
 }
 
 impl ArrayString {
-    const MAX_LEN: usize = 32;
+    /// The longest human readable part allowed in a bech32 string by BIP-173
+    const MAX_LEN: usize = 83;
 
     pub fn new(input: &str) -> Result<Self, ArrayStringError> {
         if input.len() > ArrayString::MAX_LEN {
             Err(ArrayStringError::TooLong)
         } else {
-            let mut ret: [Option<char>; ArrayString::MAX_LEN] = [None; ArrayString::MAX_LEN];
-            let mut counter = 0;
-            for char in input.chars() {
-                ret[counter] = Some(char);
-                counter += 1;
-            }
+            let mut bytes = [0u8; ArrayString::MAX_LEN];
+            bytes[..input.len()].copy_from_slice(input.as_bytes());
             Ok(ArrayString {
-                chars: ret,
-                used: counter,
+                bytes,
+                used: input.len() as u8,
             })
         }
     }
+
+    pub fn as_str(&self) -> &str {
+        // only ever copied from a str in new
+        str::from_utf8(&self.bytes[..self.used as usize]).unwrap()
+    }
 }
 
 impl Display for ArrayString {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        let mut str = String::new();
-        for c in self.chars.iter() {
-            if let Some(v) = c {
-                str.push(*v)
-            } else {
-                break;
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Serialized as a plain string, versions up to 2.4.7 serialized the characters as a struct
+/// of `chars` and `used` which is still accepted when deserializing
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArrayStringRepr {
+    String(String),
+    Legacy {
+        chars: Vec<Option<char>>,
+        used: usize,
+    },
+}
+
+impl serde::Serialize for ArrayString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ArrayString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = match ArrayStringRepr::deserialize(deserializer)? {
+            ArrayStringRepr::String(s) => s,
+            ArrayStringRepr::Legacy { chars, used } => {
+                chars.into_iter().take(used).flatten().collect()
             }
-        }
-        write!(f, "{}", str)
+        };
+        ArrayString::new(&s).map_err(serde::de::Error::custom)
     }
 }
 
@@ -82,11 +148,13 @@ pub fn contains_non_hex_chars(input: &str) -> bool {
     false
 }
 
-/// An enum
-#[derive(PartialEq, Eq, Clone, Hash, Deserialize, Serialize, Debug)]
-pub enum FeeInfo {
-    InsufficientFees { min_fees: Vec<Coin> },
-    InsufficientGas { amount: u64 },
+/// The fee and gas requirements reported by a node that rejected a transaction, or by the
+/// simulator. `required` is the minimum fee amount, empty if the fee was not the problem or
+/// the node didn't say, `gas` is the gas the transaction used when it ran out of gas
+#[derive(PartialEq, Eq, Clone, Hash, Deserialize, Serialize, Debug, Default)]
+pub struct FeeInfo {
+    pub required: Vec<Coin>,
+    pub gas: Option<u64>,
 }
 
 /// Returns what fee related problem is keeping your tx from running, you may need
@@ -95,25 +163,62 @@ pub enum FeeInfo {
 /// This is more brittle than it needs to be because the simulate endpoint (A) returns only one
 /// problem at a time and (B) returns insufficient fee messages as a memo, not an error type
 pub fn determine_min_fees_and_gas(input: &TxResponse) -> Option<FeeInfo> {
-    if input.raw_log.contains("insufficient_fees") || input.raw_log.contains("insufficient fee") {
-        let parts = input.raw_log.split(':').nth(2);
-        if let Some(amounts) = parts {
-            let mut coins = Vec::new();
-            for item in amounts.split(',') {
-                if let Ok(coin) = item.parse() {
-                    coins.push(coin);
-                }
+    let log = &input.raw_log;
+    let gas = if input.gas_used > input.gas_wanted {
+        Some(input.gas_used as u64)
+    } else {
+        parse_gas_used(log)
+    };
+    if log.contains("insufficient_fees") || log.contains("insufficient fee") {
+        match parse_required_fees(log) {
+            Some(required) => Some(FeeInfo { required, gas }),
+            None => {
+                error!("Failed parsing insufficient fee error, probably changed gRPC error message response");
+                None
             }
-            dbg!(&input);
-            Some(FeeInfo::InsufficientFees { min_fees: coins })
-        } else {
-            error!("Failed parsing insufficient fee error, probably changed gRPC error message response");
-            None
         }
-    } else if input.gas_used > input.gas_wanted {
-        Some(FeeInfo::InsufficientGas {
-            amount: input.gas_used as u64,
+    } else {
+        gas.map(|gas| FeeInfo {
+            required: Vec::new(),
+            gas: Some(gas),
         })
+    }
+}
+
+/// Parses the coins following `required:` in an insufficient fee error, which ends at the
+/// next ':' or at a field that isn't a coin such as the `minGasPrice` of feemarket errors
+fn parse_required_fees(log: &str) -> Option<Vec<Coin>> {
+    let amounts = log.split("required:").nth(1)?.split(':').next()?;
+    Some(
+        amounts
+            .split(',')
+            .filter_map(|item| item.trim().parse().ok())
+            .collect(),
+    )
+}
+
+/// Parses the `gasUsed: <amount>` of an out of gas error, for responses where the node
+/// didn't fill in the gas fields
+fn parse_gas_used(log: &str) -> Option<u64> {
+    let rest = log.split("gasUsed:").nth(1)?.trim_start();
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// The codespace used by the Cosmos SDK for it's own error codes
+pub const SDK_CODESPACE: &str = "sdk";
+/// The Cosmos SDK error code for a transaction that has run out of gas
+pub const OUT_OF_GAS_CODE: u32 = 11;
+/// The Cosmos SDK error code for a transaction that is already in the mempool
+pub const TX_IN_MEMPOOL_CACHE_CODE: u32 = 19;
+
+/// Returns the gas wanted and gas used values of a transaction if it failed because
+/// it ran out of gas, this can happen either during CheckTx (in which case the sync mode
+/// broadcast will return it) or during execution, in which case only the final result of
+/// the transaction will contain it. Returns None if the tx did not run out of gas.
+pub fn determine_out_of_gas(input: &TxResponse) -> Option<(u64, u64)> {
+    if input.codespace == SDK_CODESPACE && input.code == OUT_OF_GAS_CODE {
+        Some((input.gas_wanted as u64, input.gas_used as u64))
     } else {
         None
     }
@@ -149,6 +254,25 @@ pub fn encode_any(input: impl prost::Message, type_url: impl Into<String>) -> An
     }
 }
 
+/// Returns the request for the next page of a paginated query given the pagination of
+/// the previous response, None if the previous response was the last page
+pub fn next_page(pagination: &Option<PageResponse>) -> Option<PageRequest> {
+    match pagination {
+        Some(page) if !page.next_key.is_empty() => Some(PageRequest {
+            key: page.next_key.clone(),
+            offset: 0,
+            limit: 0,
+            count_total: false,
+        }),
+        _ => None,
+    }
+}
+
+/// Decodes the value of an Any into the given type, note that the type url is not checked
+pub fn decode_any<T: prost::Message + Default>(input: &Any) -> Result<T, prost::DecodeError> {
+    T::decode(input.value.as_slice())
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -170,8 +294,8 @@ mod tests {
             tx: None,
             timestamp: String::new(),
         };
-        let correct_output = Some(FeeInfo::InsufficientFees {
-            min_fees: vec![
+        let correct_output = Some(FeeInfo {
+            required: vec![
                 Coin {
                     denom: "ualtg".to_string(),
                     amount: 50000u64.into(),
@@ -181,10 +305,131 @@ mod tests {
                     amount: 250000u64.into(),
                 },
             ],
+            gas: None,
         });
         assert_eq!(
             determine_min_fees_and_gas(&below_min_fees_tx_response),
             correct_output
         );
     }
+
+    #[test]
+    fn test_determine_fees_formats() {
+        let response = |raw_log: &str, gas_wanted: i64, gas_used: i64| TxResponse {
+            code: 13,
+            raw_log: raw_log.to_string(),
+            gas_wanted,
+            gas_used,
+            ..Default::default()
+        };
+        let stake = |amount: u64| Coin {
+            denom: "stake".to_string(),
+            amount: amount.into(),
+        };
+
+        // simulation of a tx with no fee at all
+        let res = response(
+            "insufficient fees; got:  required: 200stake: insufficient fee",
+            0,
+            0,
+        );
+        assert_eq!(
+            determine_min_fees_and_gas(&res),
+            Some(FeeInfo {
+                required: vec![stake(200)],
+                gas: None
+            })
+        );
+
+        // feemarket style error, the min gas price is not part of the required amount
+        let res = response(
+            "error checking fee: got: 1stake required: 300stake, minGasPrice: 0.003stake: insufficient fee",
+            100000,
+            0,
+        );
+        assert_eq!(
+            determine_min_fees_and_gas(&res),
+            Some(FeeInfo {
+                required: vec![stake(300)],
+                gas: None
+            })
+        );
+
+        // older nodes use the error code name
+        let res = response(
+            "insufficient_fees: got: 1stake required: 5stake,7uatom",
+            0,
+            0,
+        );
+        let info = determine_min_fees_and_gas(&res).unwrap();
+        assert_eq!(info.required.len(), 2);
+        assert_eq!(info.required[1].denom, "uatom");
+
+        // out of gas, with and without the gas fields filled in
+        let log =
+            "out of gas in location: ReadFlat; gasWanted: 100000, gasUsed: 100345: out of gas";
+        let expected = Some(FeeInfo {
+            required: Vec::new(),
+            gas: Some(100345),
+        });
+        assert_eq!(
+            determine_min_fees_and_gas(&response(log, 100000, 100345)),
+            expected
+        );
+        assert_eq!(determine_min_fees_and_gas(&response(log, 0, 0)), expected);
+
+        assert_eq!(
+            determine_min_fees_and_gas(&response("", 100000, 5000)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_determine_out_of_gas() {
+        let mut out_of_gas_tx_response = TxResponse {
+            height: 100,
+            txhash: "3B07E4A68F2260717E45F4469CC197DBC2637858C33B4790B83F4AE9FC058570".to_string(),
+            codespace: "sdk".to_string(),
+            code: 11,
+            data: String::new(),
+            raw_log:
+                "out of gas in location: ReadFlat; gasWanted: 100000, gasUsed: 100345: out of gas"
+                    .to_string(),
+            logs: Vec::new(),
+            info: String::new(),
+            gas_used: 100345,
+            gas_wanted: 100000,
+            tx: None,
+            timestamp: String::new(),
+        };
+        assert_eq!(
+            determine_out_of_gas(&out_of_gas_tx_response),
+            Some((100000, 100345))
+        );
+
+        // same code with another codespace belongs to a different module
+        out_of_gas_tx_response.codespace = "wasm".to_string();
+        assert_eq!(determine_out_of_gas(&out_of_gas_tx_response), None);
+    }
+
+    #[test]
+    fn test_normalize_tx_hash() {
+        let hash = "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        assert_eq!(normalize_tx_hash(hash).as_deref(), Some(hash));
+        assert_eq!(
+            normalize_tx_hash(&format!("0x{}", hash.to_lowercase())).as_deref(),
+            Some(hash)
+        );
+        assert_eq!(normalize_tx_hash(&hash[1..]), None);
+        assert_eq!(normalize_tx_hash(&hash.replace('F', "G")), None);
+    }
+
+    #[test]
+    fn test_ibc_denom_hash() {
+        // ATOM on Osmosis
+        assert_eq!(
+            ibc_denom_hash("transfer/channel-0", "uatom"),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+    }
 }
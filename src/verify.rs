@@ -0,0 +1,346 @@
+//! Verification of signed transactions received from elsewhere, for example by a relayer or
+//! meta transaction service that broadcasts transactions on behalf of their signers and must
+//! check who signed them first.
+//!
+//! The sign doc of every signer is reconstructed from the transaction exactly as the node does
+//! and checked against the public key in the transaction, and the signers are checked against
+//! the accounts the messages require signatures from. Only single secp256k1 signers using
+//! `SignMode::Direct` or `SignMode::LegacyAminoJson` and messages of types known to
+//! `DecodedMsg` are supported.
+
+use crate::address::Address;
+use crate::amino;
+use crate::coin::Fee;
+use crate::error::TxVerifyError;
+use crate::msg::{required_signers, DecodedMsg, Msg};
+use crate::private_key::MessageArgs;
+use crate::public_key::PublicKey;
+use crate::utils::decode_any;
+use cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as ProtoSecp256k1Pubkey;
+use cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    mode_info, AuthInfo, SignDoc, SignerInfo, TxBody, TxRaw,
+};
+use prost::Message;
+use secp256k1::Message as CurveMessage;
+use secp256k1::Secp256k1;
+use secp256k1::{PublicKey as PublicKeyEC, Signature as CurveSignature};
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+
+/// Verifies every signature of the serialized `TxRaw` in `tx_bytes` for `chain_id`, returns
+/// the addresses of the signers with `prefix` in the order they signed. The signers must be
+/// the accounts returned by `msg::required_signers` for the messages, followed by the fee
+/// payer if it's not one of them, `SignerMismatch` is returned otherwise. `account_numbers`
+/// must hold the on chain account number of each signer in the same order. Note that the
+/// sequences still have to match the accounts on chain for the node to accept the tx
+pub fn verify_tx(
+    tx_bytes: &[u8],
+    chain_id: &str,
+    account_numbers: &[u64],
+    prefix: &str,
+) -> Result<Vec<Address>, TxVerifyError> {
+    let raw = decode::<TxRaw>(tx_bytes)?;
+    let body = decode::<TxBody>(&raw.body_bytes)?;
+    let auth_info = decode::<AuthInfo>(&raw.auth_info_bytes)?;
+    let signers = &auth_info.signer_infos;
+    if signers.len() != raw.signatures.len() || signers.len() != account_numbers.len() {
+        return Err(TxVerifyError::SignerCountMismatch {
+            signers: signers.len(),
+            signatures: raw.signatures.len(),
+            account_numbers: account_numbers.len(),
+        });
+    }
+
+    let secp256k1 = Secp256k1::verification_only();
+    let mut addresses = Vec::new();
+    for (index, (signer, account_number)) in signers.iter().zip(account_numbers).enumerate() {
+        let pubkey = signer_pubkey(index, signer)?;
+        let sign_doc = match sign_mode(index, signer)? {
+            SignMode::Direct => {
+                let sign_doc = SignDoc {
+                    body_bytes: raw.body_bytes.clone(),
+                    auth_info_bytes: raw.auth_info_bytes.clone(),
+                    chain_id: chain_id.to_string(),
+                    account_number: *account_number,
+                };
+                let mut buf = Vec::new();
+                sign_doc.encode(&mut buf).unwrap();
+                buf
+            }
+            _ => {
+                let fee = match auth_info.fee.clone().map(Fee::try_from) {
+                    Some(Ok(fee)) => fee,
+                    Some(Err(e)) => return Err(TxVerifyError::DecodeError(e.to_string())),
+                    None => return Err(TxVerifyError::DecodeError("No fee".to_string())),
+                };
+                let messages: Vec<Msg> = body.messages.iter().cloned().map(Msg).collect();
+                let args = MessageArgs {
+                    sequence: signer.sequence,
+                    fee,
+                    timeout_height: body.timeout_height,
+                    chain_id: chain_id.to_string(),
+                    account_number: *account_number,
+                };
+                amino::std_sign_doc(&messages, &args, &body.memo)?.into_bytes()
+            }
+        };
+
+        let digest = Sha256::digest(&sign_doc);
+        let message = CurveMessage::from_slice(&digest).unwrap();
+        let valid = match (
+            CurveSignature::from_compact(&raw.signatures[index]),
+            PublicKeyEC::from_slice(pubkey.as_bytes()),
+        ) {
+            (Ok(signature), Ok(key)) => secp256k1.verify(&message, &signature, &key).is_ok(),
+            _ => false,
+        };
+        if !valid {
+            return Err(TxVerifyError::InvalidSignature(index));
+        }
+        match pubkey.to_address_with_prefix(prefix) {
+            Ok(address) => addresses.push(address),
+            Err(e) => return Err(TxVerifyError::DecodeError(e.to_string())),
+        }
+    }
+    check_signers(&body, &auth_info, &addresses)?;
+    Ok(addresses)
+}
+
+/// Checks that `addresses` are the signers the messages of the tx require, in order, followed
+/// by the fee payer if it's set and not already one of them
+fn check_signers(
+    body: &TxBody,
+    auth_info: &AuthInfo,
+    addresses: &[Address],
+) -> Result<(), TxVerifyError> {
+    let messages = body
+        .messages
+        .iter()
+        .map(DecodedMsg::decode)
+        .collect::<Vec<_>>();
+    let mut expected = match required_signers(&messages) {
+        Some(signers) => signers,
+        None => {
+            let unknown = messages.iter().find(|msg| msg.signers().is_none());
+            return Err(TxVerifyError::UnknownSigners(
+                unknown.map(|msg| msg.type_url.clone()).unwrap_or_default(),
+            ));
+        }
+    };
+    let payer = auth_info.fee.as_ref().map(|fee| fee.payer.as_str());
+    if let Some(payer) = payer.filter(|payer| !payer.is_empty()) {
+        if !expected.iter().any(|signer| same_address(signer, payer)) {
+            expected.push(payer.to_string());
+        }
+    }
+    let matches = expected.len() == addresses.len()
+        && expected
+            .iter()
+            .zip(addresses)
+            .all(|(signer, address)| same_address(signer, &address.to_string()));
+    if !matches {
+        return Err(TxVerifyError::SignerMismatch {
+            expected,
+            found: addresses.iter().map(|a| a.to_string()).collect(),
+        });
+    }
+    Ok(())
+}
+
+/// Compares two bech32 addresses by their bytes, so the prefix does not matter
+fn same_address(a: &str, b: &str) -> bool {
+    match (a.parse::<Address>(), b.parse::<Address>()) {
+        (Ok(a), Ok(b)) => a.as_bytes() == b.as_bytes(),
+        _ => false,
+    }
+}
+
+fn decode<T: Message + Default>(bytes: &[u8]) -> Result<T, TxVerifyError> {
+    T::decode(bytes).map_err(|e| TxVerifyError::DecodeError(e.to_string()))
+}
+
+fn signer_pubkey(index: usize, signer: &SignerInfo) -> Result<PublicKey, TxVerifyError> {
+    let any = match &signer.public_key {
+        Some(any) => any,
+        None => return Err(TxVerifyError::MissingPubKey(index)),
+    };
+    if any.type_url != "/cosmos.crypto.secp256k1.PubKey" {
+        return Err(TxVerifyError::UnsupportedPubKey {
+            index,
+            type_url: any.type_url.clone(),
+        });
+    }
+    let key: ProtoSecp256k1Pubkey = decode_any(any)
+        .map_err(|e| TxVerifyError::DecodeError(format!("public key of signer {} {}", index, e)))?;
+    PublicKey::from_slice(&key.key, PublicKey::DEFAULT_PREFIX)
+        .map_err(|e| TxVerifyError::DecodeError(format!("public key of signer {} {}", index, e)))
+}
+
+fn sign_mode(index: usize, signer: &SignerInfo) -> Result<SignMode, TxVerifyError> {
+    let mode = match signer.mode_info.as_ref().and_then(|m| m.sum.as_ref()) {
+        Some(mode_info::Sum::Single(single)) => single.mode,
+        // multisig signers are not supported, report them with the unspecified mode
+        _ => SignMode::Unspecified as i32,
+    };
+    match SignMode::from_i32(mode) {
+        Some(SignMode::Direct) => Ok(SignMode::Direct),
+        Some(SignMode::LegacyAminoJson) => Ok(SignMode::LegacyAminoJson),
+        _ => Err(TxVerifyError::UnsupportedSignMode { index, mode }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private_key::{build_multi_signer_tx, TxSigner};
+    use crate::PrivateKey;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+
+    fn send(from: &Address, to: &Address) -> Msg {
+        Msg::new(
+            "/cosmos.bank.v1beta1.MsgSend",
+            MsgSend {
+                from_address: from.to_string(),
+                to_address: to.to_string(),
+                amount: vec![],
+            },
+        )
+    }
+
+    fn args(account_number: u64) -> MessageArgs {
+        MessageArgs {
+            sequence: 2,
+            fee: Fee::default(),
+            timeout_height: 100,
+            chain_id: "mychainid".to_string(),
+            account_number,
+        }
+    }
+
+    #[test]
+    fn test_verify_tx() {
+        let key = PrivateKey::from_secret(b"verify signer");
+        let address = key.to_address("cosmos").unwrap();
+        let msg = send(&address, &address);
+
+        for mode in [SignMode::Direct, SignMode::LegacyAminoJson] {
+            let tx = key
+                .sign_std_msg_with_sign_mode(std::slice::from_ref(&msg), args(7), "memo", mode)
+                .unwrap();
+            assert_eq!(
+                verify_tx(&tx, "mychainid", &[7], "cosmos").unwrap(),
+                vec![address]
+            );
+            // the account number and chain id are part of what is signed
+            assert!(matches!(
+                verify_tx(&tx, "mychainid", &[8], "cosmos"),
+                Err(TxVerifyError::InvalidSignature(0))
+            ));
+            assert!(matches!(
+                verify_tx(&tx, "otherchain", &[7], "cosmos"),
+                Err(TxVerifyError::InvalidSignature(0))
+            ));
+        }
+
+        // a signature taken from another tx
+        let tx = key
+            .sign_std_msg(std::slice::from_ref(&msg), args(7), "memo")
+            .unwrap();
+        let other = key.sign_std_msg(&[msg], args(7), "other memo").unwrap();
+        let mut raw = TxRaw::decode(tx.as_slice()).unwrap();
+        raw.signatures = TxRaw::decode(other.as_slice()).unwrap().signatures;
+        let mut tampered = Vec::new();
+        raw.encode(&mut tampered).unwrap();
+        assert!(matches!(
+            verify_tx(&tampered, "mychainid", &[7], "cosmos"),
+            Err(TxVerifyError::InvalidSignature(0))
+        ));
+        assert!(matches!(
+            verify_tx(&tx, "mychainid", &[], "cosmos"),
+            Err(TxVerifyError::SignerCountMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_tx(&[0xff; 8], "mychainid", &[7], "cosmos"),
+            Err(TxVerifyError::DecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_tx_signer_mismatch() {
+        let key = PrivateKey::from_secret(b"verify signer");
+        let address = key.to_address("cosmos").unwrap();
+        let other = PrivateKey::from_secret(b"verify other")
+            .to_address("cosmos")
+            .unwrap();
+        // a valid signature by key, on a message spending from another account
+        let tx = key
+            .sign_std_msg(&[send(&other, &address)], args(7), "memo")
+            .unwrap();
+        match verify_tx(&tx, "mychainid", &[7], "cosmos") {
+            Err(TxVerifyError::SignerMismatch { expected, found }) => {
+                assert_eq!(expected, vec![other.to_string()]);
+                assert_eq!(found, vec![address.to_string()]);
+            }
+            res => panic!("Expected SignerMismatch, got {:?}", res),
+        }
+
+        let unknown = Msg::new(
+            "/my.module.MsgThing",
+            MsgSend {
+                from_address: address.to_string(),
+                to_address: address.to_string(),
+                amount: vec![],
+            },
+        );
+        let tx = key.sign_std_msg(&[unknown], args(7), "memo").unwrap();
+        assert!(matches!(
+            verify_tx(&tx, "mychainid", &[7], "cosmos"),
+            Err(TxVerifyError::UnknownSigners(type_url)) if type_url == "/my.module.MsgThing"
+        ));
+    }
+
+    #[test]
+    fn test_verify_multi_signer_tx() {
+        let first = PrivateKey::from_secret(b"verify first");
+        let second = PrivateKey::from_secret(b"verify second");
+        let a = first.to_address("cosmos").unwrap();
+        let b = second.to_address("cosmos").unwrap();
+        let signers = [
+            TxSigner {
+                key: first,
+                account_number: 3,
+                sequence: 0,
+            },
+            TxSigner {
+                key: second,
+                account_number: 4,
+                sequence: 9,
+            },
+        ];
+        let parts = build_multi_signer_tx(
+            &[send(&a, &b), send(&b, &a)],
+            &signers,
+            Fee::default(),
+            0,
+            "mychainid",
+            "",
+        )
+        .unwrap();
+        let raw = TxRaw {
+            body_bytes: parts.body_buf,
+            auth_info_bytes: parts.auth_buf,
+            signatures: parts.signatures,
+        };
+        let mut tx = Vec::new();
+        raw.encode(&mut tx).unwrap();
+        assert_eq!(
+            verify_tx(&tx, "mychainid", &[3, 4], "cosmos").unwrap(),
+            vec![a, b]
+        );
+        assert!(matches!(
+            verify_tx(&tx, "mychainid", &[3, 5], "cosmos"),
+            Err(TxVerifyError::InvalidSignature(1))
+        ));
+    }
+}